@@ -0,0 +1,388 @@
+use macroquad::prelude::*;
+
+use crate::{
+    graph_drawer::arrow_head,
+    input::InputEvents,
+    svg_writer::{escape_xml, SVGItem, SVGNumber},
+};
+
+const FONT_SIZE: f32 = 24.0;
+/// Rough width of a character relative to the font size, for hit-testing text.
+const CHARACTER_WIDTH_FACTOR: f32 = 0.5;
+const ARROW_WIDTH: f32 = 3.0;
+const ARROW_HEAD_SIZE: f32 = 15.0;
+/// Shorter drags don't create an arrow.
+const MIN_ARROW_LENGTH: f32 = 10.0;
+const HIT_TOLERANCE: f32 = 6.0;
+const ARROW_MARKER_ID: &str = "annotation-arrow";
+
+/// Free text or an arrow drawn on top of the graph, in canvas coordinates.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Annotation {
+    /// `position` is the start of the baseline.
+    Text {
+        position: Vec2,
+        text: String,
+    },
+    Arrow {
+        start: Vec2,
+        end: Vec2,
+    },
+}
+
+impl Annotation {
+    pub fn contains(&self, point: Vec2) -> bool {
+        match self {
+            Annotation::Text { position, text } => {
+                let width = text.chars().count() as f32 * FONT_SIZE * CHARACTER_WIDTH_FACTOR;
+                Rect::new(position.x, position.y - FONT_SIZE, width, FONT_SIZE).contains(point)
+            }
+            Annotation::Arrow { start, end } => {
+                distance_to_segment(*start, *end, point) <= HIT_TOLERANCE
+            }
+        }
+    }
+
+    pub fn translate(&mut self, delta: Vec2) {
+        match self {
+            Annotation::Text { position, .. } => *position += delta,
+            Annotation::Arrow { start, end } => {
+                *start += delta;
+                *end += delta;
+            }
+        }
+    }
+
+    fn draw(&self, color: Color) {
+        match self {
+            Annotation::Text { position, text } => {
+                draw_text(text, position.x, position.y, FONT_SIZE, color);
+            }
+            Annotation::Arrow { start, end } => {
+                if let Some([tip, left, right]) = arrow_head(*start, *end, 0.0, ARROW_HEAD_SIZE) {
+                    let base = (left + right) / 2.0;
+                    draw_line(start.x, start.y, base.x, base.y, ARROW_WIDTH, color);
+                    draw_triangle(tip, left, right, color);
+                }
+            }
+        }
+    }
+
+    fn to_svg_string(&self, color: Color) -> String {
+        match self {
+            Annotation::Text { position, text } => format!(
+                r#"<text x="{}" y="{}" fill="{}" font-size="{}">{}</text>"#,
+                SVGNumber(position.x),
+                SVGNumber(position.y),
+                color.to_svg_string(),
+                SVGNumber(FONT_SIZE),
+                escape_xml(text)
+            ),
+            Annotation::Arrow { start, end } => format!(
+                r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}" stroke-width="{}" marker-end="url(#{})"/>"#,
+                SVGNumber(start.x),
+                SVGNumber(start.y),
+                SVGNumber(end.x),
+                SVGNumber(end.y),
+                color.to_svg_string(),
+                SVGNumber(ARROW_WIDTH),
+                ARROW_MARKER_ID
+            ),
+        }
+    }
+}
+
+pub fn distance_to_segment(start: Vec2, end: Vec2, point: Vec2) -> f32 {
+    let segment = end - start;
+    let length_squared = segment.length_squared();
+    if length_squared == 0.0 {
+        return start.distance(point);
+    }
+
+    let t = ((point - start).dot(segment) / length_squared).clamp(0.0, 1.0);
+    (start + segment * t).distance(point)
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum AnnotationTool {
+    #[default]
+    Text,
+    Arrow,
+}
+
+impl AnnotationTool {
+    pub const ALL: [AnnotationTool; 2] = [AnnotationTool::Text, AnnotationTool::Arrow];
+    pub const NAMES: [&'static str; 2] = ["Text", "Arrow"];
+
+    pub fn index(&self) -> usize {
+        Self::ALL.iter().position(|tool| tool == self).unwrap()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum AnnotationDrag {
+    Move { annotation: usize, last: Vec2 },
+    Arrow { start: Vec2, end: Vec2 },
+}
+
+/// The annotations of the figure. While `editing` is set the mouse places and moves
+/// annotations instead of interacting with the graph.
+pub struct AnnotationLayer {
+    pub annotations: Vec<Annotation>,
+    pub editing: bool,
+    pub tool: AnnotationTool,
+    /// Content of the next text annotation.
+    pub text: String,
+    pub color: Color,
+    drag: Option<AnnotationDrag>,
+}
+
+impl AnnotationLayer {
+    pub fn new() -> Self {
+        Self {
+            annotations: Vec::new(),
+            editing: false,
+            tool: AnnotationTool::default(),
+            text: String::new(),
+            color: BLACK,
+            drag: None,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.annotations.is_empty()
+    }
+
+    /// Topmost annotation at `point`.
+    pub fn annotation_at(&self, point: Vec2) -> Option<usize> {
+        self.annotations
+            .iter()
+            .rposition(|annotation| annotation.contains(point))
+    }
+
+    /// Clicking an annotation starts moving it, clicking elsewhere places text or starts an
+    /// arrow, depending on the tool.
+    fn press(&mut self, point: Vec2) {
+        if let Some(annotation) = self.annotation_at(point) {
+            self.drag = Some(AnnotationDrag::Move {
+                annotation,
+                last: point,
+            });
+            return;
+        }
+
+        match self.tool {
+            AnnotationTool::Text if !self.text.is_empty() => {
+                self.annotations.push(Annotation::Text {
+                    position: point,
+                    text: self.text.clone(),
+                });
+            }
+            AnnotationTool::Text => {}
+            AnnotationTool::Arrow => {
+                self.drag = Some(AnnotationDrag::Arrow {
+                    start: point,
+                    end: point,
+                });
+            }
+        }
+    }
+
+    fn drag_to(&mut self, point: Vec2) {
+        match &mut self.drag {
+            Some(AnnotationDrag::Move { annotation, last }) => {
+                if let Some(annotation) = self.annotations.get_mut(*annotation) {
+                    annotation.translate(point - *last);
+                }
+                *last = point;
+            }
+            Some(AnnotationDrag::Arrow { end, .. }) => *end = point,
+            None => {}
+        }
+    }
+
+    fn release(&mut self) {
+        if let Some(AnnotationDrag::Arrow { start, end }) = self.drag.take() {
+            if start.distance(end) >= MIN_ARROW_LENGTH {
+                self.annotations.push(Annotation::Arrow { start, end });
+            }
+        }
+    }
+
+    /// Only reacts to presses left of `canvas_width`, so the settings panel stays usable.
+    pub fn handle_mouse_input(&mut self, input: &InputEvents, canvas_width: f32) {
+        let point = input.position;
+        let left = input.button(MouseButton::Left);
+
+        if left.pressed && point.x <= canvas_width {
+            self.press(point);
+        } else if left.down {
+            self.drag_to(point);
+        } else if left.released {
+            self.release();
+        }
+    }
+
+    pub fn draw(&self) {
+        for annotation in &self.annotations {
+            annotation.draw(self.color);
+        }
+
+        if let Some(AnnotationDrag::Arrow { start, end }) = self.drag {
+            Annotation::Arrow { start, end }.draw(self.color);
+        }
+    }
+}
+
+impl SVGItem for AnnotationLayer {
+    fn to_svg_string(&self) -> String {
+        let mut string = String::new();
+
+        let has_arrows = self
+            .annotations
+            .iter()
+            .any(|annotation| matches!(annotation, Annotation::Arrow { .. }));
+        if has_arrows {
+            string.push_str("<defs>\n");
+            string.push_str(&format!(
+                r#"    <marker id="{}" viewBox="0 0 10 10" refX="10" refY="5" markerWidth="{}" markerHeight="{}" orient="auto">"#,
+                ARROW_MARKER_ID,
+                SVGNumber(ARROW_HEAD_SIZE / ARROW_WIDTH),
+                SVGNumber(ARROW_HEAD_SIZE / ARROW_WIDTH)
+            ));
+            string.push('\n');
+            string.push_str(&format!(
+                r#"        <path d="M 0 0 L 10 5 L 0 10 z" fill="{}"/>"#,
+                self.color.to_svg_string()
+            ));
+            string.push_str("\n    </marker>\n</defs>\n");
+        }
+
+        for annotation in &self.annotations {
+            string.push_str(&annotation.to_svg_string(self.color));
+            string.push('\n');
+        }
+
+        string
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_place_text_and_arrow() {
+        let mut layer = AnnotationLayer::new();
+
+        // no text entered, nothing to place
+        layer.press(vec2(10.0, 50.0));
+        layer.release();
+        assert!(layer.is_empty());
+
+        layer.text = "hub".to_string();
+        layer.press(vec2(10.0, 50.0));
+        layer.release();
+        assert_eq!(
+            layer.annotations,
+            vec![Annotation::Text {
+                position: vec2(10.0, 50.0),
+                text: "hub".to_string()
+            }]
+        );
+
+        layer.tool = AnnotationTool::Arrow;
+        layer.press(vec2(100.0, 100.0));
+        layer.drag_to(vec2(103.0, 100.0));
+        layer.release();
+        assert_eq!(layer.annotations.len(), 1, "too short for an arrow");
+
+        layer.press(vec2(100.0, 100.0));
+        layer.drag_to(vec2(150.0, 120.0));
+        layer.drag_to(vec2(200.0, 100.0));
+        layer.release();
+        assert_eq!(
+            layer.annotations[1],
+            Annotation::Arrow {
+                start: vec2(100.0, 100.0),
+                end: vec2(200.0, 100.0)
+            }
+        );
+    }
+
+    #[test]
+    fn test_drag_moves_annotation() {
+        let mut layer = AnnotationLayer::new();
+        layer.tool = AnnotationTool::Arrow;
+        layer.annotations.push(Annotation::Arrow {
+            start: vec2(0.0, 0.0),
+            end: vec2(100.0, 0.0),
+        });
+
+        // grabbing the arrow moves it instead of starting a new one
+        layer.press(vec2(50.0, 2.0));
+        layer.drag_to(vec2(60.0, 12.0));
+        layer.drag_to(vec2(70.0, 22.0));
+        layer.release();
+
+        assert_eq!(
+            layer.annotations,
+            vec![Annotation::Arrow {
+                start: vec2(20.0, 20.0),
+                end: vec2(120.0, 20.0)
+            }]
+        );
+    }
+
+    #[test]
+    fn test_annotation_hit_testing() {
+        let text = Annotation::Text {
+            position: vec2(100.0, 100.0),
+            text: "abcd".to_string(),
+        };
+        assert!(text.contains(vec2(101.0, 99.0)));
+        assert!(text.contains(vec2(147.0, 80.0)));
+        assert!(!text.contains(vec2(150.0, 90.0)));
+        assert!(!text.contains(vec2(120.0, 105.0)));
+
+        let arrow = Annotation::Arrow {
+            start: vec2(0.0, 0.0),
+            end: vec2(100.0, 100.0),
+        };
+        assert!(arrow.contains(vec2(52.0, 48.0)));
+        assert!(!arrow.contains(vec2(60.0, 40.0)));
+        // past the end of the segment
+        assert!(!arrow.contains(vec2(108.0, 108.0)));
+
+        let mut layer = AnnotationLayer::new();
+        layer.annotations.extend([arrow.clone(), arrow]);
+        assert_eq!(layer.annotation_at(vec2(50.0, 50.0)), Some(1));
+        assert_eq!(layer.annotation_at(vec2(0.0, 50.0)), None);
+    }
+
+    #[test]
+    fn test_svg_output() {
+        let mut layer = AnnotationLayer::new();
+        layer.annotations.push(Annotation::Text {
+            position: vec2(10.0, 20.0),
+            text: "a < b".to_string(),
+        });
+        assert_eq!(
+            layer.to_svg_string(),
+            "<text x=\"10\" y=\"20\" fill=\"#000000\" font-size=\"24\">a &lt; b</text>\n"
+        );
+
+        layer.annotations.push(Annotation::Arrow {
+            start: vec2(0.0, 0.0),
+            end: vec2(30.0, 40.0),
+        });
+        let svg = layer.to_svg_string();
+
+        assert!(svg.starts_with("<defs>\n    <marker id=\"annotation-arrow\""));
+        assert_eq!(svg.matches("<marker").count(), 1);
+        assert!(svg.ends_with(
+            "<line x1=\"0\" y1=\"0\" x2=\"30\" y2=\"40\" stroke=\"#000000\" stroke-width=\"3\" \
+             marker-end=\"url(#annotation-arrow)\"/>\n"
+        ));
+    }
+}
@@ -0,0 +1,447 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, Sender},
+    thread::JoinHandle,
+    time::Duration,
+};
+
+use macroquad::prelude::Vec2;
+use thiserror::Error;
+
+use crate::{
+    graph::{Graph, Orientation},
+    indexing::Indexing,
+    positions::{format_positions, parse_positions, PositionsParseError},
+};
+
+const HEADER: &str = "# graphdrs autosave";
+
+/// The graph and its layout, the part of a session that is expensive to recreate.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Snapshot {
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+    pub graph: Graph,
+    pub positions: Vec<Vec2>,
+}
+
+impl Snapshot {
+    /// A header with the timestamp, the zero-indexed adjacency list, the directed edges as
+    /// `tail -> head` and the positions, one vertex per line. Unlike graph6, this has no limit
+    /// on the size of the graph.
+    pub fn to_text(&self) -> String {
+        let directions: String = self
+            .graph
+            .edges
+            .iter()
+            .filter_map(|&(a, b)| self.graph.orientation(a, b).direction((a, b)))
+            .map(|(tail, head)| format!("{} -> {}\n", tail, head))
+            .collect();
+
+        format!(
+            "{} {}\n{}{}{}",
+            HEADER,
+            self.timestamp,
+            self.graph.to_adjacency_list_string(Indexing::Zero),
+            directions,
+            format_positions(&self.positions)
+        )
+    }
+
+    pub fn parse(text: &str) -> Result<Self, SnapshotError> {
+        let (header, body) = text.split_once('\n').unwrap_or((text, ""));
+
+        let timestamp = header
+            .strip_prefix(HEADER)
+            .and_then(|timestamp| timestamp.trim().parse().ok())
+            .ok_or(SnapshotError::InvalidHeader)?;
+
+        // adjacency lines have a colon, direction lines an arrow, position lines neither
+        let mut graph = Graph::new(0);
+        let mut neighbours = Vec::new();
+        let mut directions = Vec::new();
+        let mut positions = String::new();
+        for (line_index, line) in body.lines().enumerate() {
+            if let Some((tail, head)) = line.split_once("->") {
+                let invalid = || SnapshotError::InvalidDirection {
+                    line: line_index + 2,
+                };
+                let tail: usize = tail.trim().parse().map_err(|_| invalid())?;
+                let head: usize = head.trim().parse().map_err(|_| invalid())?;
+                directions.push((tail, head, line_index + 2));
+                continue;
+            }
+            let Some((vertex, adjacent)) = line.split_once(':') else {
+                positions.push_str(line);
+                positions.push('\n');
+                continue;
+            };
+
+            let invalid = || SnapshotError::InvalidAdjacency {
+                line: line_index + 2,
+            };
+            if vertex.trim().parse() != Ok(graph.vertices) {
+                return Err(invalid());
+            }
+            for neighbour in adjacent.split_whitespace() {
+                let neighbour: usize = neighbour.parse().map_err(|_| invalid())?;
+                neighbours.push((graph.vertices, neighbour, line_index + 2));
+            }
+            graph.vertices += 1;
+        }
+
+        for (vertex, neighbour, line) in neighbours {
+            if neighbour >= graph.vertices {
+                return Err(SnapshotError::InvalidAdjacency { line });
+            }
+            if vertex < neighbour {
+                graph.edges.push((vertex, neighbour));
+            }
+        }
+        // the two edges of an anti-parallel pair are listed twice, the directions restore them
+        graph.dedup_edges();
+
+        for (tail, head, line) in directions {
+            if !graph.has_edge(tail, head) {
+                return Err(SnapshotError::InvalidDirection { line });
+            }
+            match graph.orientation(tail, head) {
+                Orientation::Undirected => {
+                    let (a, b) = (tail.min(head), tail.max(head));
+                    let orientation = if a == tail {
+                        Orientation::Forward
+                    } else {
+                        Orientation::Backward
+                    };
+                    graph.set_orientation(a, b, orientation);
+                }
+                // the edge already points the other way
+                Orientation::Backward => {
+                    graph.add_reverse_edge(tail, head);
+                }
+                Orientation::Forward => {}
+            }
+        }
+
+        let positions = parse_positions(&positions)?;
+        if positions.len() != graph.vertices {
+            return Err(SnapshotError::VertexCountMismatch {
+                graph: graph.vertices,
+                positions: positions.len(),
+            });
+        }
+
+        Ok(Self {
+            timestamp,
+            graph,
+            positions,
+        })
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum SnapshotError {
+    #[error("Not an autosave")]
+    InvalidHeader,
+    #[error("Invalid adjacency list on line {line}")]
+    InvalidAdjacency { line: usize },
+    #[error("Invalid edge direction on line {line}")]
+    InvalidDirection { line: usize },
+    #[error("Invalid positions: {0}")]
+    Positions(#[from] PositionsParseError),
+    #[error("The graph has {graph} vertices but there are {positions} positions")]
+    VertexCountMismatch { graph: usize, positions: usize },
+}
+
+/// Where the autosave and the time of the last explicit save are kept.
+#[derive(Clone, Debug)]
+pub struct AutosavePaths {
+    pub snapshot: PathBuf,
+    pub last_save: PathBuf,
+}
+
+impl AutosavePaths {
+    pub fn in_directory(directory: &Path) -> Self {
+        Self {
+            snapshot: directory.join("autosave.txt"),
+            last_save: directory.join("last_save.txt"),
+        }
+    }
+
+    /// A directory in the system's temporary directory.
+    pub fn default_location() -> Self {
+        Self::in_directory(&std::env::temp_dir().join("graphdrs"))
+    }
+
+    /// The autosave, when there is one that is newer than the last explicit save.
+    pub fn restorable(&self) -> Option<Snapshot> {
+        let text = std::fs::read_to_string(&self.snapshot).ok()?;
+        let snapshot = Snapshot::parse(&text).ok()?;
+
+        let last_save = std::fs::read_to_string(&self.last_save)
+            .ok()
+            .and_then(|text| text.trim().parse().ok());
+
+        is_newer(snapshot.timestamp, last_save).then_some(snapshot)
+    }
+
+    /// Records an explicit save, which makes the current autosave stale.
+    pub fn mark_saved(&self, timestamp: u64) -> io::Result<()> {
+        if let Some(directory) = self.last_save.parent() {
+            std::fs::create_dir_all(directory)?;
+        }
+
+        std::fs::write(&self.last_save, timestamp.to_string())
+    }
+}
+
+/// Whether an autosave made at `autosave` holds work that was not saved explicitly.
+pub fn is_newer(autosave: u64, last_save: Option<u64>) -> bool {
+    last_save.is_none_or(|last_save| autosave > last_save)
+}
+
+/// Writes snapshots on a background thread, so saving doesn't stall the frame.
+pub struct Autosaver {
+    last_save: Duration,
+    sender: Option<Sender<Snapshot>>,
+    error_receiver: Receiver<String>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl Autosaver {
+    pub fn start(path: PathBuf) -> Self {
+        let (sender, receiver) = mpsc::channel::<Snapshot>();
+        let (error_sender, error_receiver) = mpsc::channel();
+
+        let worker = std::thread::spawn(move || {
+            for snapshot in receiver {
+                if let Err(error) = write_snapshot(&path, &snapshot) {
+                    let message = format!("Autosave to {} failed: {}", path.display(), error);
+                    if error_sender.send(message).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+
+        Self {
+            last_save: Duration::ZERO,
+            sender: Some(sender),
+            error_receiver,
+            worker: Some(worker),
+        }
+    }
+
+    /// Saves a snapshot when `interval` has passed since the last one. `elapsed` is the time
+    /// since the start of the program. The snapshot is only taken when it is saved.
+    pub fn poll<F: FnOnce() -> Snapshot>(
+        &mut self,
+        elapsed: Duration,
+        interval: Duration,
+        snapshot: F,
+    ) {
+        if interval.is_zero() || elapsed.saturating_sub(self.last_save) < interval {
+            return;
+        }
+
+        self.last_save = elapsed;
+        self.save(snapshot());
+    }
+
+    pub fn save(&mut self, snapshot: Snapshot) {
+        let Some(sender) = &self.sender else {
+            return;
+        };
+
+        if sender.send(snapshot).is_err() {
+            self.sender = None;
+        }
+    }
+
+    /// Stops accepting snapshots and waits for the pending ones to be written.
+    pub fn stop(&mut self) {
+        self.sender = None;
+
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+
+    pub fn drain_errors(&self) -> Vec<String> {
+        self.error_receiver.try_iter().collect()
+    }
+}
+
+impl Drop for Autosaver {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Writes to a temporary file first, so a crash while writing keeps the previous autosave.
+fn write_snapshot(path: &Path, snapshot: &Snapshot) -> Result<(), String> {
+    let text = snapshot.to_text();
+    let temporary = path.with_extension("tmp");
+
+    if let Some(directory) = path.parent() {
+        std::fs::create_dir_all(directory).map_err(|error| error.to_string())?;
+    }
+    std::fs::write(&temporary, text).map_err(|error| error.to_string())?;
+    std::fs::rename(&temporary, path).map_err(|error| error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use macroquad::prelude::vec2;
+
+    use super::*;
+
+    fn test_directory(name: &str) -> PathBuf {
+        let directory =
+            std::env::temp_dir().join(format!("graphdrs_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&directory);
+        directory
+    }
+
+    fn snapshot(timestamp: u64) -> Snapshot {
+        Snapshot {
+            timestamp,
+            graph: Graph::path(3),
+            positions: vec![vec2(1.0, 2.0), vec2(3.5, 4.0), vec2(-5.0, 6.0)],
+        }
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let text = snapshot(1700000000).to_text();
+
+        assert!(text.starts_with("# graphdrs autosave 1700000000\n0: 1\n1: 0 2\n2: 1\n1 2\n"));
+        assert_eq!(Snapshot::parse(&text).unwrap(), snapshot(1700000000));
+        assert!(matches!(
+            Snapshot::parse("0: 1\n1: 0\n1 2\n"),
+            Err(SnapshotError::InvalidHeader)
+        ));
+        assert!(matches!(
+            Snapshot::parse("# graphdrs autosave 5\n0: 1\n1 2\n"),
+            Err(SnapshotError::InvalidAdjacency { line: 2 })
+        ));
+        assert!(matches!(
+            Snapshot::parse("# graphdrs autosave 5\n0:\n1 2\n3 4\n"),
+            Err(SnapshotError::VertexCountMismatch {
+                graph: 1,
+                positions: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn test_edge_directions_round_trip() {
+        let mut graph = Graph::path(4);
+        graph.set_orientation(0, 1, Orientation::Forward);
+        graph.set_orientation(1, 2, Orientation::Backward);
+        graph.add_reverse_edge(1, 2);
+        let snapshot = Snapshot {
+            timestamp: 1,
+            graph,
+            positions: vec![vec2(0.0, 0.0); 4],
+        };
+
+        let text = snapshot.to_text();
+
+        assert!(text.contains("0 -> 1\n2 -> 1\n1 -> 2\n"));
+        assert_eq!(Snapshot::parse(&text).unwrap(), snapshot);
+        assert!(matches!(
+            Snapshot::parse("# graphdrs autosave 5\n0: 1\n1: 0\n0 -> 2\n1 2\n3 4\n"),
+            Err(SnapshotError::InvalidDirection { line: 4 })
+        ));
+    }
+
+    #[test]
+    fn test_large_graphs_round_trip() {
+        // too large for graph6
+        let graph = Graph::complete(70);
+        let snapshot = Snapshot {
+            timestamp: 1,
+            positions: (0..graph.vertices)
+                .map(|vertex| vec2(vertex as f32, 0.5))
+                .collect(),
+            graph,
+        };
+
+        assert_eq!(Snapshot::parse(&snapshot.to_text()).unwrap(), snapshot);
+    }
+
+    #[test]
+    fn test_staleness() {
+        assert!(is_newer(100, None));
+        assert!(is_newer(100, Some(99)));
+        assert!(!is_newer(100, Some(100)));
+        assert!(!is_newer(100, Some(150)));
+    }
+
+    #[test]
+    fn test_restorable_after_unsaved_changes() {
+        let directory = test_directory("restorable");
+        let paths = AutosavePaths::in_directory(&directory);
+        assert!(paths.restorable().is_none());
+
+        write_snapshot(&paths.snapshot, &snapshot(100)).unwrap();
+        assert_eq!(paths.restorable(), Some(snapshot(100)));
+
+        paths.mark_saved(50).unwrap();
+        assert_eq!(paths.restorable(), Some(snapshot(100)));
+
+        paths.mark_saved(150).unwrap();
+        assert_eq!(paths.restorable(), None);
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_autosaver_interval() {
+        let directory = test_directory("interval");
+        let path = directory.join("autosave.txt");
+        let interval = Duration::from_secs(60);
+        let mut autosaver = Autosaver::start(path.clone());
+        let mut taken = Vec::new();
+
+        for (seconds, timestamp) in [(30, 1), (60, 2), (100, 3), (125, 4)] {
+            autosaver.poll(Duration::from_secs(seconds), interval, || {
+                taken.push(timestamp);
+                snapshot(timestamp)
+            });
+        }
+        autosaver.stop();
+
+        assert_eq!(taken, vec![2, 4]);
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(Snapshot::parse(&text).unwrap().timestamp, 4);
+        assert!(autosaver.drain_errors().is_empty());
+
+        // no autosave with a zero interval
+        let mut autosaver = Autosaver::start(path);
+        autosaver.poll(Duration::from_secs(1000), Duration::ZERO, || unreachable!());
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_write_failures_are_reported() {
+        let directory = test_directory("failure");
+        std::fs::create_dir_all(&directory).unwrap();
+        // a file where the autosave directory should be
+        let blocker = directory.join("blocker");
+        std::fs::write(&blocker, "").unwrap();
+
+        let mut autosaver = Autosaver::start(blocker.join("autosave.txt"));
+        autosaver.save(snapshot(1));
+        autosaver.stop();
+
+        let errors = autosaver.drain_errors();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("Autosave to"));
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
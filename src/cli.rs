@@ -0,0 +1,139 @@
+use std::io::{IsTerminal, Read};
+
+use thiserror::Error;
+
+use crate::graph::{parse_graph6_string, Graph, Graph6ParseError};
+
+pub const USAGE: &str = "usage: graphdrs [--g6 <graph6 string> | --file <path>]";
+
+/// A graph given on startup, together with the text it was read from.
+pub struct StartupGraph {
+    pub graph: Graph,
+    pub g6_string: String,
+}
+
+/// Resolves the startup graph from `--g6 <string>`, `--file <path>` or piped stdin, in that
+/// order of precedence. Returns `None` when no graph was given.
+pub fn resolve_startup_graph(
+    args: &[String],
+    stdin: Option<String>,
+) -> Result<Option<StartupGraph>, CliError> {
+    let mut g6_string = None;
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--g6" => {
+                let value = args.next().ok_or(CliError::MissingValue("--g6"))?;
+                g6_string = Some(value.clone());
+            }
+            "--file" => {
+                let path = args.next().ok_or(CliError::MissingValue("--file"))?;
+                g6_string = Some(std::fs::read_to_string(path)?);
+            }
+            _ => return Err(CliError::UnknownArgument(arg.clone())),
+        }
+    }
+
+    let g6_string = match g6_string.or(stdin) {
+        Some(text) => first_line(&text),
+        None => return Ok(None),
+    };
+
+    if g6_string.is_empty() {
+        return Ok(None);
+    }
+
+    let graph = parse_graph6_string(&g6_string)?;
+
+    Ok(Some(StartupGraph { graph, g6_string }))
+}
+
+/// Reads stdin when it is piped, so an interactive terminal does not block startup.
+pub fn read_piped_stdin() -> Option<String> {
+    let mut stdin = std::io::stdin();
+
+    if stdin.is_terminal() {
+        return None;
+    }
+
+    let mut text = String::new();
+    stdin.read_to_string(&mut text).ok()?;
+    Some(text)
+}
+
+fn first_line(text: &str) -> String {
+    text.lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or("")
+        .to_string()
+}
+
+#[derive(Error, Debug)]
+pub enum CliError {
+    #[error("Missing value for {0}")]
+    MissingValue(&'static str),
+    #[error("Unknown argument: {0}")]
+    UnknownArgument(String),
+    #[error("Could not read graph file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid graph6 string: {0}")]
+    Graph6(#[from] Graph6ParseError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_no_input_falls_back() {
+        assert!(resolve_startup_graph(&[], None).unwrap().is_none());
+        assert!(resolve_startup_graph(&[], Some("\n".to_string()))
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_g6_argument() {
+        let startup = resolve_startup_graph(&args(&["--g6", "DQc"]), None)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(startup.graph.vertices, 5);
+        assert_eq!(startup.g6_string, "DQc");
+    }
+
+    #[test]
+    fn test_argument_wins_over_stdin() {
+        let startup = resolve_startup_graph(&args(&["--g6", "A_"]), Some("DQc\n".to_string()))
+            .unwrap()
+            .unwrap();
+        assert_eq!(startup.graph.vertices, 2);
+
+        let startup = resolve_startup_graph(&[], Some("\nDQc\nA_\n".to_string()))
+            .unwrap()
+            .unwrap();
+        assert_eq!(startup.graph.vertices, 5);
+    }
+
+    #[test]
+    fn test_invalid_arguments() {
+        assert!(matches!(
+            resolve_startup_graph(&args(&["--g6"]), None),
+            Err(CliError::MissingValue("--g6"))
+        ));
+        assert!(matches!(
+            resolve_startup_graph(&args(&["--bogus"]), None),
+            Err(CliError::UnknownArgument(_))
+        ));
+        assert!(matches!(
+            resolve_startup_graph(&args(&["--g6", ""]), None),
+            Ok(None)
+        ));
+    }
+}
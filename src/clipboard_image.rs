@@ -0,0 +1,76 @@
+use std::borrow::Cow;
+
+use arboard::{Clipboard, ImageData};
+use macroquad::prelude::*;
+use thiserror::Error;
+
+use crate::{
+    annotations::AnnotationLayer,
+    graph_drawer::DrawableGraph,
+    png_export::{render_image, PNGExportError},
+};
+
+#[derive(Error, Debug)]
+pub enum ClipboardImageError {
+    #[error("Clipboard unavailable: {0}")]
+    Clipboard(#[from] arboard::Error),
+    #[error("Nothing to copy, the canvas is empty")]
+    EmptyCanvas,
+    #[error("Could not render the image: {0}")]
+    Render(PNGExportError),
+}
+
+impl From<PNGExportError> for ClipboardImageError {
+    fn from(error: PNGExportError) -> Self {
+        match error {
+            PNGExportError::EmptyImage => Self::EmptyCanvas,
+            error => Self::Render(error),
+        }
+    }
+}
+
+/// Copies the graph and the annotations to the clipboard as an image of `canvas_size`. It is
+/// rendered offscreen like a PNG export, so the overlays of the window are left out.
+pub fn copy_canvas_to_clipboard(
+    graph: &DrawableGraph,
+    annotations: &AnnotationLayer,
+    canvas_size: Vec2,
+    background: Option<Color>,
+) -> Result<(), ClipboardImageError> {
+    let image = render_image(graph, annotations, canvas_size, 1.0, background)?;
+
+    Clipboard::new()?.set_image(clipboard_image(image))?;
+    Ok(())
+}
+
+fn clipboard_image(image: Image) -> ImageData<'static> {
+    ImageData {
+        width: image.width(),
+        height: image.height(),
+        bytes: Cow::Owned(image.bytes),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clipboard_image_keeps_the_pixels() {
+        let mut image = Image::gen_image_color(3, 2, WHITE);
+        image.set_pixel(1, 1, BLUE);
+
+        let image = clipboard_image(image);
+
+        assert_eq!((image.width, image.height), (3, 2));
+        let bytes: [u8; 4] = BLUE.into();
+        assert_eq!(&image.bytes[16..20], &bytes);
+    }
+
+    #[test]
+    fn test_empty_render_is_an_empty_canvas() {
+        let error = ClipboardImageError::from(PNGExportError::EmptyImage);
+
+        assert!(matches!(error, ClipboardImageError::EmptyCanvas));
+    }
+}
@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+use crate::indexing::Indexing;
+
+/// Parses lines of the form `u v label text` into edge labels.
+/// Empty lines are skipped. When a pair occurs more than once, the last line wins.
+pub fn parse_edge_labels(
+    text: &str,
+    indexing: Indexing,
+) -> Result<HashMap<(usize, usize), String>, EdgeLabelParseError> {
+    let mut labels = HashMap::new();
+
+    for (line_index, line) in text.lines().enumerate() {
+        let line_number = line_index + 1;
+        let line = line.trim();
+
+        if line.is_empty() {
+            continue;
+        }
+
+        let mut parts = line.splitn(3, char::is_whitespace);
+
+        let mut parse_vertex = || -> Result<usize, EdgeLabelParseError> {
+            parts
+                .next()
+                .and_then(|part| indexing.parse(part))
+                .ok_or(EdgeLabelParseError::InvalidVertex { line: line_number })
+        };
+
+        let a = parse_vertex()?;
+        let b = parse_vertex()?;
+        let label = parts.next().unwrap_or("").trim().to_string();
+
+        labels.insert(canonical_edge(a, b), label);
+    }
+
+    Ok(labels)
+}
+
+pub fn canonical_edge(a: usize, b: usize) -> (usize, usize) {
+    (a.min(b), a.max(b))
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum EdgeLabelParseError {
+    #[error("Invalid vertex on line {line}")]
+    InvalidVertex { line: usize },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pair_lookup_is_order_insensitive() {
+        let labels = parse_edge_labels("2 1 weight 3\n0 3 x", Indexing::Zero).unwrap();
+
+        assert_eq!(labels.get(&(1, 2)), Some(&"weight 3".to_string()));
+        assert_eq!(labels.get(&(0, 3)), Some(&"x".to_string()));
+    }
+
+    #[test]
+    fn test_duplicate_lines_last_wins() {
+        let labels = parse_edge_labels("0 1 first\n\n1 0 second\n", Indexing::Zero).unwrap();
+
+        assert_eq!(labels.len(), 1);
+        assert_eq!(labels.get(&(0, 1)), Some(&"second".to_string()));
+    }
+
+    #[test]
+    fn test_one_indexed_input() {
+        let labels = parse_edge_labels("1 2 a", Indexing::One).unwrap();
+        assert_eq!(labels.get(&(0, 1)), Some(&"a".to_string()));
+
+        assert_eq!(
+            parse_edge_labels("0 2 a", Indexing::One),
+            Err(EdgeLabelParseError::InvalidVertex { line: 1 })
+        );
+    }
+
+    #[test]
+    fn test_invalid_vertex() {
+        assert_eq!(
+            parse_edge_labels("0 1 a\nx 1 b", Indexing::Zero),
+            Err(EdgeLabelParseError::InvalidVertex { line: 2 })
+        );
+    }
+}
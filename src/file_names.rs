@@ -0,0 +1,188 @@
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+use crate::report::UtcTime;
+
+const INVALID_CHARACTERS: &[char] = &['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
+
+/// Replaces characters that are invalid in file names on common platforms.
+pub fn sanitize_file_name(name: &str) -> String {
+    let sanitized: String = name
+        .trim()
+        .chars()
+        .map(|character| {
+            if INVALID_CHARACTERS.contains(&character) || character.is_control() {
+                '_'
+            } else {
+                character
+            }
+        })
+        .collect();
+
+    let sanitized = sanitized.trim_matches('.');
+
+    if sanitized.is_empty() {
+        "graph".to_string()
+    } else {
+        sanitized.to_string()
+    }
+}
+
+/// Returns `file_name`, or a name derived from the graph name when it is empty.
+pub fn file_name_or_default(file_name: &str, graph_name: &str, extension: &str) -> String {
+    if !file_name.trim().is_empty() {
+        return file_name.to_string();
+    }
+
+    format!("{}.{}", sanitize_file_name(graph_name), extension)
+}
+
+/// A file in `directory` named after the time, like `graphdrs_2024-06-01_14-30-00.svg`, so
+/// that repeated exports don't overwrite each other. An empty directory is the working
+/// directory.
+pub fn timestamped_file_name(directory: &str, timestamp: u64, extension: &str) -> PathBuf {
+    let time = UtcTime::from_timestamp(timestamp);
+    let file_name = format!(
+        "graphdrs_{:04}-{:02}-{:02}_{:02}-{:02}-{:02}.{}",
+        time.year, time.month, time.day, time.hour, time.minute, time.second, extension
+    );
+
+    Path::new(directory.trim()).join(file_name)
+}
+
+#[derive(Error, Debug)]
+pub enum ExportPathError {
+    #[error("{} is a directory, not a file", .0.display())]
+    IsDirectory(PathBuf),
+    #[error("The directory {} does not exist", .0.display())]
+    MissingDirectory(PathBuf),
+    #[error("Could not create the directory {}: {source}", .path.display())]
+    CreateFailed {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+}
+
+/// The absolute path an export to `path` is written to, with `extension` appended when the
+/// file name has none. Missing parent directories are created when `create_directories` is set.
+pub fn resolve_export_path(
+    path: &Path,
+    extension: &str,
+    create_directories: bool,
+) -> Result<PathBuf, ExportPathError> {
+    // a trailing separator names a directory, even one that doesn't exist yet
+    let names_directory = path.as_os_str().to_string_lossy().ends_with(['/', '\\']);
+    if names_directory || path.is_dir() {
+        return Err(ExportPathError::IsDirectory(path.to_path_buf()));
+    }
+
+    let mut path = path.to_path_buf();
+    if path.extension().is_none() {
+        path.set_extension(extension);
+    }
+    if path.is_dir() {
+        return Err(ExportPathError::IsDirectory(path));
+    }
+
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        if !parent.is_dir() {
+            if !create_directories {
+                return Err(ExportPathError::MissingDirectory(parent.to_path_buf()));
+            }
+            std::fs::create_dir_all(parent).map_err(|source| ExportPathError::CreateFailed {
+                path: parent.to_path_buf(),
+                source,
+            })?;
+        }
+    }
+
+    Ok(std::path::absolute(&path).unwrap_or(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invalid_characters_are_replaced() {
+        assert_eq!(sanitize_file_name("G?AB/c:d"), "G_AB_c_d");
+        assert_eq!(sanitize_file_name("a\tb|<c>"), "a_b__c_");
+    }
+
+    #[test]
+    fn test_empty_names_fall_back() {
+        assert_eq!(sanitize_file_name(""), "graph");
+        assert_eq!(sanitize_file_name(" .. "), "graph");
+    }
+
+    #[test]
+    fn test_timestamped_file_name() {
+        // 2024-06-01 14:30:00 UTC
+        let timestamp = 1717252200;
+
+        assert_eq!(
+            timestamped_file_name("", timestamp, "svg"),
+            PathBuf::from("graphdrs_2024-06-01_14-30-00.svg")
+        );
+        assert_eq!(
+            timestamped_file_name("exports", timestamp + 61, "svg"),
+            Path::new("exports").join("graphdrs_2024-06-01_14-31-01.svg")
+        );
+    }
+
+    #[test]
+    fn test_file_name_or_default() {
+        assert_eq!(file_name_or_default("out.svg", "C~", "svg"), "out.svg");
+        assert_eq!(file_name_or_default("", "C~", "svg"), "C~.svg");
+        assert_eq!(file_name_or_default(" ", "a/b", "txt"), "a_b.txt");
+    }
+
+    #[test]
+    fn test_resolve_export_path() {
+        let directory =
+            std::env::temp_dir().join(format!("graphdrs_export_paths_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir_all(directory.join("folder")).unwrap();
+
+        // relative paths resolve against the working directory
+        let resolved = resolve_export_path(Path::new("out.svg"), "svg", false).unwrap();
+        assert!(resolved.is_absolute());
+        assert_eq!(resolved, std::env::current_dir().unwrap().join("out.svg"));
+
+        // a missing extension is appended, an existing one is kept
+        assert_eq!(
+            resolve_export_path(&directory.join("graph"), "svg", false).unwrap(),
+            directory.join("graph.svg")
+        );
+        assert_eq!(
+            resolve_export_path(&directory.join("graph.txt"), "svg", false).unwrap(),
+            directory.join("graph.txt")
+        );
+
+        // directories are not files
+        for target in [directory.join("folder"), directory.join("new_folder/")] {
+            assert!(matches!(
+                resolve_export_path(&target, "svg", true),
+                Err(ExportPathError::IsDirectory(_))
+            ));
+        }
+
+        // missing parents are only created when asked to
+        let nested = directory.join("a").join("b").join("graph");
+        assert!(matches!(
+            resolve_export_path(&nested, "png", false),
+            Err(ExportPathError::MissingDirectory(parent)) if parent == directory.join("a/b")
+        ));
+        assert_eq!(
+            resolve_export_path(&nested, "png", true).unwrap(),
+            directory.join("a/b/graph.png")
+        );
+        assert!(directory.join("a/b").is_dir());
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,87 @@
+use crate::lanes::LaneConfig;
+
+/// Largest repulsion between two vertices, so that the distance based models stay bounded
+/// when vertices nearly coincide.
+const MAX_REPULSION: f32 = 50.0;
+
+/// How the repulsion between two non-adjacent vertices falls off with their distance.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum RepulsionModel {
+    /// Halves every 20 pixels, leaving distant vertices practically unaffected.
+    #[default]
+    Exponential,
+    InverseSquare,
+    InverseLinear,
+}
+
+impl RepulsionModel {
+    pub const ALL: [RepulsionModel; 3] = [
+        RepulsionModel::Exponential,
+        RepulsionModel::InverseSquare,
+        RepulsionModel::InverseLinear,
+    ];
+    pub const NAMES: [&'static str; 3] = ["Exponential", "Inverse square", "Inverse linear"];
+
+    pub fn index(&self) -> usize {
+        Self::ALL.iter().position(|model| model == self).unwrap()
+    }
+
+    /// Strength of the repulsion at `distance`.
+    pub fn magnitude(&self, distance: f32) -> f32 {
+        let magnitude = match self {
+            RepulsionModel::Exponential => 50.0 * 0.5f32.powf(distance / 20.0),
+            RepulsionModel::InverseSquare => 20000.0 / (distance * distance),
+            RepulsionModel::InverseLinear => 300.0 / distance,
+        };
+
+        magnitude.min(MAX_REPULSION)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ForceConfig {
+    pub repulsion: RepulsionModel,
+    /// Nudge the vertices slightly at the start of a force run, so that symmetric layouts
+    /// whose forces cancel exactly don't stay stuck.
+    pub break_symmetry: bool,
+    pub symmetry_seed: u64,
+    /// Keeps vertices that are assigned to a lane inside it.
+    pub lanes: LaneConfig,
+    /// While vertices are selected, only they move and the others act as pinned.
+    pub selected_only: bool,
+}
+
+impl Default for ForceConfig {
+    fn default() -> Self {
+        Self {
+            repulsion: RepulsionModel::default(),
+            break_symmetry: true,
+            symmetry_seed: 0,
+            lanes: LaneConfig::default(),
+            selected_only: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repulsion_is_bounded_and_decreasing() {
+        for model in RepulsionModel::ALL {
+            assert_eq!(model.magnitude(0.0), MAX_REPULSION);
+
+            let magnitudes: Vec<f32> = [10.0, 50.0, 100.0, 400.0]
+                .iter()
+                .map(|&distance| model.magnitude(distance))
+                .collect();
+            assert!(magnitudes.windows(2).all(|pair| pair[0] > pair[1]));
+        }
+
+        // the distance based models keep pushing where the exponential has died out
+        let exponential = RepulsionModel::Exponential.magnitude(200.0);
+        assert!(RepulsionModel::InverseSquare.magnitude(200.0) > 10.0 * exponential);
+        assert!(RepulsionModel::InverseLinear.magnitude(200.0) > 10.0 * exponential);
+    }
+}
@@ -0,0 +1,145 @@
+use std::{collections::VecDeque, time::Duration};
+
+use macroquad::prelude::*;
+
+const GRAPH_WIDTH: f32 = 120.0;
+const GRAPH_HEIGHT: f32 = 40.0;
+const GRAPH_MARGIN: f32 = 10.0;
+const FONT_SIZE: f32 = 20.0;
+const TARGET_FRAME_TIME: Duration = Duration::from_micros(16666);
+
+/// Rolling window of the most recent frame times.
+pub struct FrameTimes {
+    samples: VecDeque<Duration>,
+    capacity: usize,
+}
+
+impl FrameTimes {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    pub fn push(&mut self, frame_time: Duration) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+
+        self.samples.push_back(frame_time);
+    }
+
+    pub fn samples(&self) -> impl Iterator<Item = &Duration> {
+        self.samples.iter()
+    }
+
+    pub fn average(&self) -> Option<Duration> {
+        if self.samples.is_empty() {
+            return None;
+        }
+
+        Some(self.samples.iter().sum::<Duration>() / self.samples.len() as u32)
+    }
+
+    pub fn max(&self) -> Option<Duration> {
+        self.samples.iter().max().copied()
+    }
+
+    /// Draws the frame rate and the graph of the frame times in the top right of the canvas.
+    pub fn draw(&self, canvas_width: f32) {
+        let x = canvas_width - GRAPH_WIDTH - GRAPH_MARGIN;
+        let y = 30.0;
+
+        let average = self.average().unwrap_or_default().as_secs_f32() * 1000.0;
+        let text = format!("{} fps {:.1} ms", get_fps(), average);
+        // right aligned with the graph, so that long values don't run into the settings panel
+        let text_width = measure_text(&text, None, FONT_SIZE as u16, 1.0).width;
+        draw_text(
+            &text,
+            canvas_width - GRAPH_MARGIN - text_width,
+            20.0,
+            FONT_SIZE,
+            WHITE,
+        );
+        draw_rectangle(
+            x,
+            y,
+            GRAPH_WIDTH,
+            GRAPH_HEIGHT,
+            Color::new(0.0, 0.0, 0.0, 0.3),
+        );
+
+        let scale = self
+            .max()
+            .unwrap_or(TARGET_FRAME_TIME)
+            .max(TARGET_FRAME_TIME)
+            .as_secs_f32();
+        let bar_width = GRAPH_WIDTH / self.capacity.max(1) as f32;
+
+        for (i, sample) in self.samples().enumerate() {
+            let height = sample.as_secs_f32() / scale * GRAPH_HEIGHT;
+            let color = if *sample > TARGET_FRAME_TIME {
+                RED
+            } else {
+                LIME
+            };
+
+            draw_rectangle(
+                x + i as f32 * bar_width,
+                y + GRAPH_HEIGHT - height,
+                bar_width,
+                height,
+                color,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rolling_window_drops_oldest() {
+        let mut frame_times = FrameTimes::new(3);
+
+        for millis in 1..=5 {
+            frame_times.push(Duration::from_millis(millis));
+        }
+
+        let samples: Vec<Duration> = frame_times.samples().copied().collect();
+        assert_eq!(
+            samples,
+            vec![
+                Duration::from_millis(3),
+                Duration::from_millis(4),
+                Duration::from_millis(5)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_average_and_max() {
+        let mut frame_times = FrameTimes::new(4);
+        assert_eq!(frame_times.average(), None);
+        assert_eq!(frame_times.max(), None);
+
+        frame_times.push(Duration::from_millis(10));
+        frame_times.push(Duration::from_millis(20));
+
+        assert_eq!(frame_times.average(), Some(Duration::from_millis(15)));
+        assert_eq!(frame_times.max(), Some(Duration::from_millis(20)));
+    }
+
+    #[test]
+    fn test_zero_capacity() {
+        let mut frame_times = FrameTimes::new(0);
+        frame_times.push(Duration::from_millis(10));
+        assert_eq!(frame_times.samples().count(), 0);
+    }
+}
@@ -0,0 +1,265 @@
+use crate::graph::Graph;
+
+/// Standard graph families. Every constructor takes the total number of vertices,
+/// except [`Graph::complete_bipartite`] and [`Graph::grid`] which take both dimensions.
+impl Graph {
+    pub fn complete(n: usize) -> Self {
+        let mut graph = Graph::new(n);
+        for a in 0..n {
+            for b in a + 1..n {
+                graph.edges.push((a, b));
+            }
+        }
+        graph
+    }
+
+    pub fn path(n: usize) -> Self {
+        let mut graph = Graph::new(n);
+        for a in 1..n {
+            graph.edges.push((a - 1, a));
+        }
+        graph
+    }
+
+    /// Falls back to [`Self::path`] below three vertices, since a simple graph has no shorter cycle.
+    pub fn cycle(n: usize) -> Self {
+        let mut graph = Graph::path(n);
+        if n >= 3 {
+            graph.edges.push((0, n - 1));
+        }
+        graph
+    }
+
+    /// Vertex 0 is the centre.
+    pub fn star(n: usize) -> Self {
+        let mut graph = Graph::new(n);
+        for a in 1..n {
+            graph.edges.push((0, a));
+        }
+        graph
+    }
+
+    /// The first `m` vertices form one side.
+    pub fn complete_bipartite(m: usize, n: usize) -> Self {
+        let mut graph = Graph::new(m + n);
+        for a in 0..m {
+            for b in m..m + n {
+                graph.edges.push((a, b));
+            }
+        }
+        graph
+    }
+
+    /// Vertex `(row, col)` has index `row * cols + col`.
+    pub fn grid(rows: usize, cols: usize) -> Self {
+        let index = |row: usize, col: usize| row * cols + col;
+        let mut graph = Graph::new(rows * cols);
+
+        for row in 0..rows {
+            for col in 0..cols {
+                if col + 1 < cols {
+                    graph.edges.push((index(row, col), index(row, col + 1)));
+                }
+                if row + 1 < rows {
+                    graph.edges.push((index(row, col), index(row + 1, col)));
+                }
+            }
+        }
+
+        graph
+    }
+
+    /// Vertex 0 is the hub, the other vertices form the rim.
+    pub fn wheel(n: usize) -> Self {
+        let mut graph = Graph::star(n);
+        for a in 2..n {
+            graph.edges.push((a - 1, a));
+        }
+        if n >= 4 {
+            graph.edges.push((1, n - 1));
+        }
+        graph
+    }
+
+    pub fn k5() -> Self {
+        Graph::complete(5)
+    }
+
+    pub fn k33() -> Self {
+        Graph::complete_bipartite(3, 3)
+    }
+
+    /// Outer cycle 0..5, inner pentagram 5..10, spoke `i - (i + 5)`.
+    pub fn petersen() -> Self {
+        let mut graph = Graph::new(10);
+        for i in 0..5 {
+            for (a, b) in [(i, (i + 1) % 5), (i, i + 5), (5 + i, 5 + (i + 2) % 5)] {
+                graph.edges.push((a.min(b), a.max(b)));
+            }
+        }
+        graph
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Generator {
+    #[default]
+    Complete,
+    Cycle,
+    Path,
+    Star,
+    CompleteBipartite,
+    Grid,
+    Wheel,
+    Petersen,
+    K5,
+    K33,
+}
+
+impl Generator {
+    pub const ALL: [Generator; 10] = [
+        Generator::Complete,
+        Generator::Cycle,
+        Generator::Path,
+        Generator::Star,
+        Generator::CompleteBipartite,
+        Generator::Grid,
+        Generator::Wheel,
+        Generator::Petersen,
+        Generator::K5,
+        Generator::K33,
+    ];
+    pub const NAMES: [&'static str; 10] = [
+        "Complete",
+        "Cycle",
+        "Path",
+        "Star",
+        "Complete bipartite",
+        "Grid",
+        "Wheel",
+        "Petersen",
+        "K5",
+        "K3,3",
+    ];
+
+    pub fn index(&self) -> usize {
+        Self::ALL
+            .iter()
+            .position(|generator| generator == self)
+            .unwrap()
+    }
+
+    /// Whether [`Self::generate`] uses its sizes, named graphs have a fixed size.
+    pub fn has_size(&self) -> bool {
+        !matches!(self, Generator::Petersen | Generator::K5 | Generator::K33)
+    }
+
+    /// Whether [`Self::generate`] uses its second size.
+    pub fn has_second_size(&self) -> bool {
+        matches!(self, Generator::CompleteBipartite | Generator::Grid)
+    }
+
+    /// The graph of the family, with its edges in canonical order, see [`Graph::sort_edges`].
+    pub fn generate(&self, n: usize, m: usize) -> Graph {
+        let mut graph = match self {
+            Generator::Complete => Graph::complete(n),
+            Generator::Cycle => Graph::cycle(n),
+            Generator::Path => Graph::path(n),
+            Generator::Star => Graph::star(n),
+            Generator::CompleteBipartite => Graph::complete_bipartite(n, m),
+            Generator::Grid => Graph::grid(n, m),
+            Generator::Wheel => Graph::wheel(n),
+            Generator::Petersen => Graph::petersen(),
+            Generator::K5 => Graph::k5(),
+            Generator::K33 => Graph::k33(),
+        };
+        graph.sort_edges();
+
+        graph
+    }
+
+    pub fn graph_name(&self, n: usize, m: usize) -> String {
+        match self {
+            Generator::Complete => format!("K{}", n),
+            Generator::Cycle => format!("C{}", n),
+            Generator::Path => format!("P{}", n),
+            Generator::Star => format!("star_{}", n),
+            Generator::CompleteBipartite => format!("K{},{}", n, m),
+            Generator::Grid => format!("grid_{}x{}", n, m),
+            Generator::Wheel => format!("W{}", n),
+            Generator::Petersen => "petersen".to_string(),
+            Generator::K5 => "K5".to_string(),
+            Generator::K33 => "K3,3".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edge_counts() {
+        assert_eq!(Graph::complete(6).edges.len(), 6 * 5 / 2);
+        assert_eq!(Graph::cycle(6).edges.len(), 6);
+        assert_eq!(Graph::path(6).edges.len(), 5);
+        assert_eq!(Graph::star(6).edges.len(), 5);
+        assert_eq!(Graph::complete_bipartite(3, 4).edges.len(), 12);
+        assert_eq!(Graph::grid(3, 4).edges.len(), 3 * 3 + 4 * 2);
+    }
+
+    #[test]
+    fn test_small_sizes() {
+        assert!(Graph::complete(1).edges.is_empty());
+        assert!(Graph::path(0).edges.is_empty());
+        assert_eq!(Graph::cycle(2).edges, vec![(0, 1)]);
+        assert!(Graph::star(1).edges.is_empty());
+        assert_eq!(Graph::grid(0, 5).vertices, 0);
+    }
+
+    #[test]
+    fn test_generated_edges_are_valid() {
+        for generator in Generator::ALL {
+            let graph = generator.generate(5, 3);
+            for &(a, b) in &graph.edges {
+                assert!(
+                    a < b && b < graph.vertices,
+                    "{:?}: {} - {}",
+                    generator,
+                    a,
+                    b
+                );
+            }
+        }
+    }
+
+    fn degrees(graph: &Graph) -> Vec<usize> {
+        (0..graph.vertices)
+            .map(|vertex| graph.neighbours(vertex).len())
+            .collect()
+    }
+
+    #[test]
+    fn test_named_graphs() {
+        let petersen = Graph::petersen();
+        assert_eq!((petersen.vertices, petersen.edges.len()), (10, 15));
+        assert!(degrees(&petersen).iter().all(|&degree| degree == 3));
+
+        let k5 = Graph::k5();
+        assert_eq!((k5.vertices, k5.edges.len()), (5, 10));
+        assert!(degrees(&k5).iter().all(|&degree| degree == 4));
+
+        let k33 = Graph::k33();
+        assert_eq!((k33.vertices, k33.edges.len()), (6, 9));
+        assert!(k33.edges.iter().all(|&(a, b)| a < 3 && b >= 3));
+    }
+
+    #[test]
+    fn test_wheel() {
+        let wheel = Graph::wheel(6);
+        assert_eq!(wheel.edges.len(), 10);
+        assert_eq!(degrees(&wheel), vec![5, 3, 3, 3, 3, 3]);
+
+        assert_eq!(Graph::wheel(3).edges.len(), 3);
+    }
+}
@@ -1,6 +1,15 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use thiserror::Error;
+
+use crate::indexing::Indexing;
+
+#[derive(Clone, Debug, PartialEq)]
 pub struct Graph {
     pub vertices: usize,
     pub edges: Vec<(usize, usize)>,
+    /// Directed edges, keyed like [`Self::edges`]. Missing edges are undirected.
+    pub orientations: HashMap<(usize, usize), Orientation>,
 }
 
 impl Graph {
@@ -8,11 +17,676 @@ impl Graph {
         Graph {
             vertices,
             edges: Vec::new(),
+            orientations: HashMap::new(),
+        }
+    }
+
+    /// Orientation of the edge as seen from `a` to `b`.
+    pub fn orientation(&self, a: usize, b: usize) -> Orientation {
+        match self.orientations.get(&(a, b)) {
+            Some(&orientation) => orientation,
+            None => self
+                .orientations
+                .get(&(b, a))
+                .map(|orientation| orientation.reversed())
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn set_orientation(&mut self, a: usize, b: usize, orientation: Orientation) {
+        // the edges of an anti-parallel pair keep their own orientation
+        if !(self.edges.contains(&(a, b)) && self.edges.contains(&(b, a))) {
+            self.orientations.remove(&(b, a));
+        }
+        if orientation == Orientation::Undirected {
+            self.orientations.remove(&(a, b));
+        } else {
+            self.orientations.insert((a, b), orientation);
+        }
+    }
+
+    pub fn neighbours(&self, vertex: usize) -> Vec<usize> {
+        let mut neighbours: Vec<usize> = self
+            .edges
+            .iter()
+            .filter_map(|&(a, b)| {
+                if a == vertex {
+                    Some(b)
+                } else if b == vertex {
+                    Some(a)
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        neighbours.sort_unstable();
+        neighbours
+    }
+
+    pub fn shortest_path(&self, source: usize, target: usize) -> Option<Vec<usize>> {
+        if source >= self.vertices || target >= self.vertices {
+            return None;
+        }
+
+        let search = breadth_first_search(&self.adjacency_lists(), source);
+
+        search.distance[target]?;
+
+        let mut path = vec![target];
+        let mut current = target;
+
+        while let Some(vertex) = search.previous[current] {
+            path.push(vertex);
+            current = vertex;
+        }
+
+        path.reverse();
+        Some(path)
+    }
+
+    fn adjacency_lists(&self) -> Vec<Vec<usize>> {
+        let mut adjacency = vec![Vec::new(); self.vertices];
+        for &(a, b) in &self.edges {
+            if a < self.vertices && b < self.vertices {
+                adjacency[a].push(b);
+                adjacency[b].push(a);
+            }
+        }
+        for neighbours in adjacency.iter_mut() {
+            neighbours.sort_unstable();
+        }
+        adjacency
+    }
+
+    /// Largest distance from every vertex, `None` for empty or disconnected graphs.
+    pub fn eccentricities(&self) -> Option<Vec<usize>> {
+        let adjacency = self.adjacency_lists();
+
+        (0..self.vertices)
+            .map(|vertex| {
+                breadth_first_search(&adjacency, vertex)
+                    .distance
+                    .into_iter()
+                    .try_fold(0, |eccentricity, distance| {
+                        Some(eccentricity.max(distance?))
+                    })
+            })
+            .collect::<Option<Vec<usize>>>()
+            .filter(|eccentricities| !eccentricities.is_empty())
+    }
+
+    /// Distance of every vertex from `source`, `None` when it can't be reached.
+    pub fn depths(&self, source: usize) -> Vec<Option<usize>> {
+        if source >= self.vertices {
+            return vec![None; self.vertices];
+        }
+
+        breadth_first_search(&self.adjacency_lists(), source).distance
+    }
+
+    pub fn diameter(&self) -> Option<usize> {
+        self.eccentricities()?.into_iter().max()
+    }
+
+    pub fn radius(&self) -> Option<usize> {
+        self.eccentricities()?.into_iter().min()
+    }
+
+    /// Connected component of every vertex, numbered in order of their smallest vertex.
+    pub fn components(&self) -> Vec<usize> {
+        let adjacency = self.adjacency_lists();
+        let mut components = vec![None; self.vertices];
+        let mut count = 0;
+
+        for vertex in 0..self.vertices {
+            if components[vertex].is_some() {
+                continue;
+            }
+
+            let search = breadth_first_search(&adjacency, vertex);
+            for (other, distance) in search.distance.iter().enumerate() {
+                if distance.is_some() {
+                    components[other] = Some(count);
+                }
+            }
+            count += 1;
+        }
+
+        components.into_iter().flatten().collect()
+    }
+
+    /// Fraction of neighbour pairs that are adjacent, 0 for vertices of degree below 2.
+    pub fn local_clustering(&self) -> Vec<f32> {
+        let adjacency = self.adjacency_lists();
+
+        adjacency
+            .iter()
+            .map(|neighbours| {
+                let degree = neighbours.len();
+                if degree < 2 {
+                    return 0.0;
+                }
+
+                // every triangle through the vertex is seen from both other corners
+                let links: usize = neighbours
+                    .iter()
+                    .map(|&neighbour| sorted_intersection_size(neighbours, &adjacency[neighbour]))
+                    .sum();
+
+                links as f32 / (degree * (degree - 1)) as f32
+            })
+            .collect()
+    }
+
+    /// Average of the local clustering coefficients.
+    pub fn clustering_coefficient(&self) -> f32 {
+        if self.vertices == 0 {
+            return 0.0;
+        }
+
+        self.local_clustering().iter().sum::<f32>() / self.vertices as f32
+    }
+
+    /// Vertices of minimal eccentricity.
+    pub fn center(&self) -> Vec<usize> {
+        let Some(eccentricities) = self.eccentricities() else {
+            return Vec::new();
+        };
+        let radius = eccentricities.iter().copied().min().unwrap_or(0);
+
+        (0..self.vertices)
+            .filter(|&vertex| eccentricities[vertex] == radius)
+            .collect()
+    }
+
+    /// Fraction of the possible edges that are present, 0 below two vertices.
+    pub fn density(&self) -> f32 {
+        if self.vertices < 2 {
+            return 0.0;
+        }
+
+        2.0 * self.edges.len() as f32 / (self.vertices * (self.vertices - 1)) as f32
+    }
+
+    /// Vertex degrees in non-increasing order.
+    pub fn degree_sequence(&self) -> Vec<usize> {
+        let mut degrees: Vec<usize> = self
+            .adjacency_lists()
+            .iter()
+            .map(|neighbours| neighbours.len())
+            .collect();
+
+        degrees.sort_unstable_by(|a, b| b.cmp(a));
+        degrees
+    }
+
+    pub fn is_bipartite(&self) -> bool {
+        let adjacency = self.adjacency_lists();
+        let mut distance = vec![None; self.vertices];
+
+        for vertex in 0..self.vertices {
+            if distance[vertex].is_some() {
+                continue;
+            }
+
+            let search = breadth_first_search(&adjacency, vertex);
+            for (other, other_distance) in search.distance.into_iter().enumerate() {
+                if other_distance.is_some() {
+                    distance[other] = other_distance;
+                }
+            }
+        }
+
+        // breadth first search layers are independent exactly when the graph is bipartite
+        self.edges.iter().all(|&(a, b)| distance[a] != distance[b])
+    }
+
+    /// Length of the shortest cycle, `None` for forests.
+    pub fn girth(&self) -> Option<usize> {
+        let adjacency = self.adjacency_lists();
+
+        (0..self.vertices)
+            .flat_map(|source| {
+                let search = breadth_first_search(&adjacency, source);
+                self.edges
+                    .iter()
+                    .filter(|&&(a, b)| {
+                        search.previous[a] != Some(b) && search.previous[b] != Some(a)
+                    })
+                    .filter_map(|&(a, b)| Some(search.distance[a]? + search.distance[b]? + 1))
+                    .collect::<Vec<usize>>()
+            })
+            .min()
+    }
+
+    /// Replaces the edge `a - b` by a path through a new vertex and returns that vertex,
+    /// or `None` when the edge doesn't exist.
+    pub fn subdivide_edge(&mut self, a: usize, b: usize) -> Option<usize> {
+        let index = self
+            .edges
+            .iter()
+            .position(|&edge| edge == (a, b) || edge == (b, a))?;
+        self.edges.remove(index);
+        let orientation = self.orientation(a, b);
+        self.set_orientation(a, b, Orientation::Undirected);
+
+        let c = self.vertices;
+        self.vertices += 1;
+        self.edges.push((a, c));
+        self.edges.push((b, c));
+        self.set_orientation(a, c, orientation);
+        self.set_orientation(b, c, orientation.reversed());
+        self.sort_edges();
+
+        Some(c)
+    }
+
+    /// Cartesian product, vertex `(i, j)` has index `i * other.vertices + j`.
+    pub fn cartesian_product(&self, other: &Graph) -> Graph {
+        let index = |i: usize, j: usize| i * other.vertices + j;
+        let mut product = Graph::new(self.vertices * other.vertices);
+
+        for &(a, b) in &self.edges {
+            for j in 0..other.vertices {
+                product.push_edge(index(a, j), index(b, j));
+            }
+        }
+
+        for i in 0..self.vertices {
+            for &(c, d) in &other.edges {
+                product.push_edge(index(i, c), index(i, d));
+            }
+        }
+        product.sort_edges();
+
+        product
+    }
+
+    /// Tensor (categorical) product, with the same vertex indexing as [`Self::cartesian_product`].
+    pub fn tensor_product(&self, other: &Graph) -> Graph {
+        let index = |i: usize, j: usize| i * other.vertices + j;
+        let mut product = Graph::new(self.vertices * other.vertices);
+
+        for &(a, b) in &self.edges {
+            for &(c, d) in &other.edges {
+                product.push_edge(index(a, c), index(b, d));
+                product.push_edge(index(a, d), index(b, c));
+            }
+        }
+        product.sort_edges();
+
+        product
+    }
+
+    fn push_edge(&mut self, a: usize, b: usize) {
+        self.edges.push((a.min(b), a.max(b)));
+    }
+
+    /// Subgraph formed by the given edges and their endpoints, renumbered in increasing order
+    /// of the original vertex indices.
+    pub fn edge_subgraph(&self, edges: &[(usize, usize)]) -> Subgraph {
+        let mut original_vertices: Vec<usize> = edges.iter().flat_map(|&(a, b)| [a, b]).collect();
+        original_vertices.sort_unstable();
+        original_vertices.dedup();
+
+        let new_index = |vertex: usize| original_vertices.binary_search(&vertex).unwrap();
+
+        let mut graph = Graph::new(original_vertices.len());
+        for &(a, b) in edges {
+            graph.edges.push((new_index(a), new_index(b)));
+            graph.set_orientation(new_index(a), new_index(b), self.orientation(a, b));
+        }
+        graph.sort_edges();
+
+        Subgraph {
+            graph,
+            original_vertices,
+        }
+    }
+
+    /// The graph6 encoding, `None` for graphs with more than 62 vertices.
+    pub fn to_graph6_string(&self) -> Option<String> {
+        if self.vertices > 62 {
+            return None;
+        }
+
+        let mut bits = Vec::new();
+        for b in 1..self.vertices {
+            for a in 0..b {
+                bits.push(self.has_edge(a, b));
+            }
+        }
+
+        let mut g6_string = String::new();
+        g6_string.push((self.vertices as u8 + 63) as char);
+        for chunk in bits.chunks(6) {
+            let mut value = 0u8;
+            for (i, &bit) in chunk.iter().enumerate() {
+                if bit {
+                    value |= 1 << (5 - i);
+                }
+            }
+            g6_string.push((value + 63) as char);
+        }
+
+        Some(g6_string)
+    }
+
+    /// Puts the edges in the order of [`edge_order`], so that equal graphs number their edges
+    /// the same however they were built.
+    pub fn sort_edges(&mut self) {
+        self.edges.sort_unstable_by_key(|&edge| edge_order(edge));
+    }
+
+    /// Stores every edge as `(min, max)`, removes repeated edges, keeping the first one, and
+    /// sorts the rest, see [`Self::sort_edges`]. Returns the number of edges removed.
+    pub fn dedup_edges(&mut self) -> usize {
+        let count = self.edges.len();
+        let mut seen = HashSet::with_capacity(count);
+
+        self.edges = self
+            .edges
+            .iter()
+            .map(|&(a, b)| (a.min(b), a.max(b)))
+            .filter(|&edge| seen.insert(edge))
+            .collect();
+        self.sort_edges();
+
+        count - self.edges.len()
+    }
+
+    /// Adds an edge from the head to the tail of the directed edge between `a` and `b`, so that
+    /// both directions are present. Returns the new edge, with its endpoints swapped relative to
+    /// the existing one, or `None` when there is no single directed edge between the two.
+    pub fn add_reverse_edge(&mut self, a: usize, b: usize) -> Option<(usize, usize)> {
+        let between: Vec<(usize, usize)> = self
+            .edges
+            .iter()
+            .copied()
+            .filter(|&edge| edge == (a, b) || edge == (b, a))
+            .collect();
+        let [edge] = between[..] else {
+            return None;
+        };
+
+        let orientation = self.orientation(edge.0, edge.1);
+        if orientation == Orientation::Undirected {
+            return None;
+        }
+
+        // from here on both keys are in use, one for each edge, and the same orientation as
+        // seen from the swapped endpoints is the opposite direction
+        let reverse = (edge.1, edge.0);
+        self.edges.push(reverse);
+        self.orientations.insert(edge, orientation);
+        self.orientations.insert(reverse, orientation);
+        self.sort_edges();
+
+        Some(reverse)
+    }
+
+    /// Moves the edge stored as `old` to the endpoints `new`, keeping its orientation as seen
+    /// from the first to the second endpoint. Returns whether it moved, it doesn't when `old`
+    /// doesn't exist or `new` would be a loop or a repeated edge.
+    pub fn reconnect_edge(&mut self, old: (usize, usize), new: (usize, usize)) -> bool {
+        let (c, d) = new;
+        if c == d || c >= self.vertices || d >= self.vertices || self.has_edge(c, d) {
+            return false;
+        }
+        let Some(index) = self.edges.iter().position(|&edge| edge == old) else {
+            return false;
+        };
+
+        let orientation = self.orientation(old.0, old.1);
+        self.set_orientation(old.0, old.1, Orientation::Undirected);
+        self.edges[index] = new;
+        self.set_orientation(c, d, orientation);
+        self.sort_edges();
+
+        true
+    }
+
+    pub fn has_edge(&self, a: usize, b: usize) -> bool {
+        self.edges.contains(&(a, b)) || self.edges.contains(&(b, a))
+    }
+
+    /// Index in [`Self::edges`] of the edge between the two vertices of `text`, which are
+    /// separated like in [`parse_vertex_list`].
+    pub fn find_edge(&self, text: &str, indexing: Indexing) -> Result<usize, EdgeInputError> {
+        let vertices = parse_vertex_list(text, indexing)?;
+        let [a, b] = vertices[..] else {
+            return Err(EdgeInputError::NotAPair(vertices.len()));
+        };
+
+        self.edges
+            .iter()
+            .position(|&edge| edge == (a, b) || edge == (b, a))
+            .ok_or(EdgeInputError::NoSuchEdge)
+    }
+
+    /// Splits the edges between consecutive vertices of a sequence into the edges present in
+    /// the graph and the missing ones. Edges are returned as `(min, max)` without duplicates.
+    pub fn sequence_edges(&self, sequence: &[usize]) -> SequenceEdges {
+        let mut present = Vec::new();
+        let mut missing = Vec::new();
+
+        for (a, b) in path_edges(sequence) {
+            let edge = (a.min(b), a.max(b));
+
+            let target = if self.has_edge(a, b) {
+                &mut present
+            } else {
+                &mut missing
+            };
+
+            if !target.contains(&edge) {
+                target.push(edge);
+            }
+        }
+
+        SequenceEdges { present, missing }
+    }
+
+    /// What keeps `self` from being a subgraph of `supergraph` on the same vertices. Edges are
+    /// compared without their direction.
+    pub fn subgraph_mismatch(&self, supergraph: &Graph) -> SubgraphMismatch {
+        let canonical = |&(a, b): &(usize, usize)| (a.min(b), a.max(b));
+        let present: HashSet<(usize, usize)> = supergraph.edges.iter().map(canonical).collect();
+
+        let mut missing_edges: Vec<(usize, usize)> = self
+            .edges
+            .iter()
+            .map(canonical)
+            .filter(|edge| !present.contains(edge))
+            .collect();
+        missing_edges.sort_unstable();
+        missing_edges.dedup();
+
+        SubgraphMismatch {
+            vertex_counts: (self.vertices != supergraph.vertices)
+                .then_some((self.vertices, supergraph.vertices)),
+            missing_edges,
+        }
+    }
+
+    pub fn to_adjacency_list_string(&self, indexing: Indexing) -> String {
+        let mut string = String::new();
+
+        for vertex in 0..self.vertices {
+            string.push_str(&format!("{}:", indexing.display(vertex)));
+
+            for neighbour in self.neighbours(vertex) {
+                string.push_str(&format!(" {}", indexing.display(neighbour)));
+            }
+
+            string.push('\n');
+        }
+
+        string
+    }
+}
+
+/// Direction of an edge `(a, b)`, `Forward` points from `a` to `b`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Orientation {
+    #[default]
+    Undirected,
+    Forward,
+    Backward,
+}
+
+impl Orientation {
+    /// Cycles undirected, forward, backward and back to undirected.
+    pub fn next(&self) -> Orientation {
+        match self {
+            Orientation::Undirected => Orientation::Forward,
+            Orientation::Forward => Orientation::Backward,
+            Orientation::Backward => Orientation::Undirected,
+        }
+    }
+
+    /// The same orientation for the edge with its endpoints swapped.
+    pub fn reversed(&self) -> Orientation {
+        match self {
+            Orientation::Undirected => Orientation::Undirected,
+            Orientation::Forward => Orientation::Backward,
+            Orientation::Backward => Orientation::Forward,
+        }
+    }
+
+    /// `(tail, head)` of a directed edge.
+    pub fn direction(&self, (a, b): (usize, usize)) -> Option<(usize, usize)> {
+        match self {
+            Orientation::Undirected => None,
+            Orientation::Forward => Some((a, b)),
+            Orientation::Backward => Some((b, a)),
+        }
+    }
+}
+
+/// Which edges, given as `(tail, head)` or `None` when undirected, have an edge in the opposite
+/// direction. Only directed edges are anti-parallel.
+pub fn anti_parallel_edges(directions: &[Option<(usize, usize)>]) -> Vec<bool> {
+    let directed: HashSet<(usize, usize)> = directions.iter().flatten().copied().collect();
+
+    directions
+        .iter()
+        .map(|direction| direction.is_some_and(|(tail, head)| directed.contains(&(head, tail))))
+        .collect()
+}
+
+/// A graph cut out of a larger one, vertex `i` was vertex `original_vertices[i]`.
+pub struct Subgraph {
+    pub graph: Graph,
+    pub original_vertices: Vec<usize>,
+}
+
+/// Edges between consecutive vertices of a sequence, split by whether the graph contains them.
+pub struct SequenceEdges {
+    pub present: Vec<(usize, usize)>,
+    pub missing: Vec<(usize, usize)>,
+}
+
+/// See [`Graph::subgraph_mismatch`].
+#[derive(Debug, PartialEq)]
+pub struct SubgraphMismatch {
+    /// Vertex counts of the graph and the supergraph, when they differ.
+    pub vertex_counts: Option<(usize, usize)>,
+    /// Edges as `(min, max)` in increasing order, without duplicates.
+    pub missing_edges: Vec<(usize, usize)>,
+}
+
+struct BreadthFirstSearch {
+    distance: Vec<Option<usize>>,
+    previous: Vec<Option<usize>>,
+}
+
+fn breadth_first_search(adjacency: &[Vec<usize>], source: usize) -> BreadthFirstSearch {
+    let mut distance = vec![None; adjacency.len()];
+    let mut previous = vec![None; adjacency.len()];
+    let mut queue = VecDeque::new();
+
+    distance[source] = Some(0);
+    queue.push_back((source, 0));
+
+    while let Some((vertex, vertex_distance)) = queue.pop_front() {
+        for &neighbour in &adjacency[vertex] {
+            if distance[neighbour].is_none() {
+                distance[neighbour] = Some(vertex_distance + 1);
+                previous[neighbour] = Some(vertex);
+                queue.push_back((neighbour, vertex_distance + 1));
+            }
+        }
+    }
+
+    BreadthFirstSearch { distance, previous }
+}
+
+fn sorted_intersection_size(a: &[usize], b: &[usize]) -> usize {
+    let (mut i, mut j, mut count) = (0, 0, 0);
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                count += 1;
+                i += 1;
+                j += 1;
+            }
         }
     }
+    count
+}
+
+/// Sort key of an edge: by its lower and then its higher endpoint, with `(a, b)` before `(b, a)`
+/// for `a < b`.
+pub fn edge_order((a, b): (usize, usize)) -> (usize, usize, usize) {
+    (a.min(b), a.max(b), a)
 }
 
+/// Oriented edges along a path, in traversal order.
+pub fn path_edges(path: &[usize]) -> Vec<(usize, usize)> {
+    path.windows(2).map(|pair| (pair[0], pair[1])).collect()
+}
+
+/// Parses a single graph6 string, ignoring surrounding whitespace.
 pub fn parse_graph6_string(g6_string: &str) -> Result<Graph, Graph6ParseError> {
+    let mut edges = Vec::new();
+    let vertices = decode_graph6_edges(g6_string, |a, b| edges.push((a, b)))?;
+
+    let mut graph = Graph::new(vertices);
+    graph.edges = edges;
+    graph.sort_edges();
+    Ok(graph)
+}
+
+/// Degrees of the graph in a graph6 string, in the order of [`Graph::degree_sequence`], without
+/// building the graph.
+pub fn graph6_degree_sequence(g6_string: &str) -> Result<Vec<usize>, Graph6ParseError> {
+    let mut degrees = Vec::new();
+    decode_graph6_edges(g6_string, |a, b| {
+        let needed = b + 1;
+        if degrees.len() < needed {
+            degrees.resize(needed, 0);
+        }
+        degrees[a] += 1;
+        degrees[b] += 1;
+    })
+    .map(|vertices| {
+        degrees.resize(vertices, 0);
+        degrees.sort_unstable_by(|a, b| b.cmp(a));
+        degrees
+    })
+}
+
+/// Calls `visit` for every edge of a graph6 string, in the order they are encoded, and returns
+/// the number of vertices.
+fn decode_graph6_edges(
+    g6_string: &str,
+    mut visit: impl FnMut(usize, usize),
+) -> Result<usize, Graph6ParseError> {
+    let g6_string = g6_string.trim();
     let g6_bytes = g6_string.as_bytes();
 
     let vertices = graph6_number_of_vertices(g6_string)?;
@@ -30,19 +704,20 @@ pub fn parse_graph6_string(g6_string: &str) -> Result<Graph, Graph6ParseError> {
         return Err(Graph6ParseError::UnsupportedGraphSize { supported_size: 64 });
     }
 
-    let mut graph = Graph::new(vertices);
-
     let mut current_vertex = 1;
     let mut current_neighbour = 0;
 
     let mut index = start_index;
     'outer: while g6_bytes.get(index).is_some() {
         let mut current_bit = 1 << 5;
-        let current_bits = g6_bytes[index] - 63;
+        let current_bits = match g6_bytes[index] {
+            byte @ 63..=126 => byte - 63,
+            byte => return Err(Graph6ParseError::InvalidCharacter(byte as char)),
+        };
 
         while current_bit != 0 {
             if (current_bits & current_bit) != 0 {
-                graph.edges.push((current_neighbour, current_vertex));
+                visit(current_neighbour, current_vertex);
             }
 
             current_neighbour += 1;
@@ -61,7 +736,17 @@ pub fn parse_graph6_string(g6_string: &str) -> Result<Graph, Graph6ParseError> {
         index += 1;
     }
 
-    Ok(graph)
+    // the string ended before the last row of the upper triangle
+    if current_vertex < vertices {
+        return Err(Graph6ParseError::UnexpectedStringEnd);
+    }
+
+    Ok(vertices)
+}
+
+/// Parses every whitespace separated graph6 string, in order of appearance.
+pub fn parse_graph6_multi(text: &str) -> Vec<Result<Graph, Graph6ParseError>> {
+    text.split_whitespace().map(parse_graph6_string).collect()
 }
 
 fn graph6_number_of_vertices(g6_string: &str) -> Result<usize, Graph6ParseError> {
@@ -93,9 +778,632 @@ fn graph6_number_of_vertices(g6_string: &str) -> Result<usize, Graph6ParseError>
     }
 }
 
+/// Parses a whitespace or comma separated list of vertices.
+pub fn parse_vertex_list(
+    text: &str,
+    indexing: Indexing,
+) -> Result<Vec<usize>, VertexListParseError> {
+    text.split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|token| !token.is_empty())
+        .map(|token| {
+            indexing
+                .parse(token)
+                .ok_or_else(|| VertexListParseError::InvalidVertex(token.to_string()))
+        })
+        .collect()
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum VertexListParseError {
+    #[error("Invalid vertex: {0}")]
+    InvalidVertex(String),
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum EdgeInputError {
+    #[error(transparent)]
+    InvalidVertex(#[from] VertexListParseError),
+    #[error("Expected two vertices, got {0}")]
+    NotAPair(usize),
+    #[error("No such edge")]
+    NoSuchEdge,
+}
+
+#[derive(Error, Debug)]
 pub enum Graph6ParseError {
+    #[error("Empty graph6 string")]
     EmptyString,
+    #[error("Invalid start character: {0}")]
     InvalidStartCharacter(char),
+    #[error("Invalid character: {0}")]
+    InvalidCharacter(char),
+    #[error("Unexpected end of graph6 string")]
     UnexpectedStringEnd,
+    #[error("Unsupported graph size. Supported size: {supported_size}")]
     UnsupportedGraphSize { supported_size: u32 },
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::Generator;
+
+    #[test]
+    fn test_empty_graph() {
+        let graph = Graph::new(5);
+        assert_eq!(graph.vertices, 5);
+        assert!(graph.edges.is_empty());
+    }
+
+    fn default_graph() -> Graph {
+        let mut graph = Graph::new(4);
+        graph.edges.push((0, 1));
+        graph.edges.push((1, 2));
+        graph.edges.push((2, 3));
+        graph.edges.push((0, 3));
+        graph.edges.push((1, 3));
+        graph
+    }
+
+    #[test]
+    fn test_shortest_path() {
+        let graph = default_graph();
+        assert_eq!(graph.shortest_path(0, 2), Some(vec![0, 1, 2]));
+        assert_eq!(graph.shortest_path(2, 2), Some(vec![2]));
+
+        let disconnected = Graph::new(3);
+        assert_eq!(disconnected.shortest_path(0, 2), None);
+    }
+
+    #[test]
+    fn test_path_edges_oriented_from_source_to_target() {
+        let graph = default_graph();
+        let path = graph.shortest_path(2, 0).unwrap();
+        let edges = path_edges(&path);
+
+        assert_eq!(edges.first().unwrap().0, 2);
+        assert_eq!(edges.last().unwrap().1, 0);
+        for pair in edges.windows(2) {
+            assert_eq!(pair[0].1, pair[1].0);
+        }
+    }
+
+    #[test]
+    fn test_parse_vertex_list() {
+        assert_eq!(
+            parse_vertex_list("0 2, 4", Indexing::Zero),
+            Ok(vec![0, 2, 4])
+        );
+        assert_eq!(parse_vertex_list("1 3", Indexing::One), Ok(vec![0, 2]));
+        assert_eq!(
+            parse_vertex_list("0 1", Indexing::One),
+            Err(VertexListParseError::InvalidVertex("0".to_string()))
+        );
+        assert_eq!(
+            parse_vertex_list("a", Indexing::Zero),
+            Err(VertexListParseError::InvalidVertex("a".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_find_edge() {
+        let graph = default_graph();
+
+        assert_eq!(graph.find_edge("1 2", Indexing::Zero), Ok(1));
+        assert_eq!(graph.find_edge("2, 1", Indexing::Zero), Ok(1));
+        // the same edge, one-indexed
+        assert_eq!(graph.find_edge("2 3", Indexing::One), Ok(1));
+        assert_eq!(graph.find_edge("4,2", Indexing::One), Ok(4));
+
+        assert_eq!(
+            graph.find_edge("0 2", Indexing::Zero),
+            Err(EdgeInputError::NoSuchEdge)
+        );
+        assert_eq!(
+            graph.find_edge("0 1", Indexing::One),
+            Err(EdgeInputError::InvalidVertex(
+                VertexListParseError::InvalidVertex("0".to_string())
+            ))
+        );
+        assert_eq!(
+            graph.find_edge("1 2 3", Indexing::Zero),
+            Err(EdgeInputError::NotAPair(3))
+        );
+        assert_eq!(
+            graph.find_edge("", Indexing::Zero),
+            Err(EdgeInputError::NotAPair(0))
+        );
+    }
+
+    #[test]
+    fn test_subdivide_edge() {
+        let mut graph = default_graph();
+        let edge_count = graph.edges.len();
+
+        assert_eq!(graph.subdivide_edge(3, 1), Some(4));
+        assert_eq!(graph.vertices, 5);
+        assert_eq!(graph.edges.len(), edge_count + 1);
+        assert!(!graph.has_edge(1, 3));
+        assert!(graph.has_edge(1, 4) && graph.has_edge(3, 4));
+
+        assert_eq!(graph.subdivide_edge(0, 2), None);
+        assert_eq!(graph.vertices, 5);
+    }
+
+    fn single_edge() -> Graph {
+        let mut graph = Graph::new(2);
+        graph.edges.push((0, 1));
+        graph
+    }
+
+    #[test]
+    fn test_cartesian_product_of_edges_is_square() {
+        let product = single_edge().cartesian_product(&single_edge());
+
+        assert_eq!(product.vertices, 4);
+        assert_eq!(product.edges.len(), 4);
+        for vertex in 0..4 {
+            assert_eq!(product.neighbours(vertex).len(), 2);
+        }
+        assert_eq!(product.shortest_path(0, 3).map(|path| path.len()), Some(3));
+    }
+
+    #[test]
+    fn test_tensor_product_of_edges_is_two_edges() {
+        let product = single_edge().tensor_product(&single_edge());
+
+        assert_eq!(product.vertices, 4);
+        assert_eq!(product.edges, vec![(0, 3), (1, 2)]);
+    }
+
+    #[test]
+    fn test_sequence_edges_repeated_vertices() {
+        let graph = default_graph();
+        let SequenceEdges { present, missing } = graph.sequence_edges(&[0, 1, 2, 3, 0, 1]);
+
+        assert_eq!(present, vec![(0, 1), (1, 2), (2, 3), (0, 3)]);
+        assert!(missing.is_empty());
+    }
+
+    #[test]
+    fn test_sequence_edges_nonexistent_edges() {
+        let graph = default_graph();
+        let SequenceEdges { present, missing } = graph.sequence_edges(&[0, 2, 3, 3, 7]);
+
+        assert_eq!(present, vec![(2, 3)]);
+        assert_eq!(missing, vec![(0, 2), (3, 3), (3, 7)]);
+    }
+
+    #[test]
+    fn test_sequence_edges_single_vertex() {
+        let graph = default_graph();
+        let SequenceEdges { present, missing } = graph.sequence_edges(&[2]);
+
+        assert!(present.is_empty());
+        assert!(missing.is_empty());
+    }
+
+    #[test]
+    fn test_subgraph_mismatch_partial_overlap() {
+        let graph = default_graph();
+        let mut highlight = Graph::new(4);
+        highlight.edges.extend([(1, 0), (0, 2), (3, 2), (2, 0)]);
+
+        let mismatch = highlight.subgraph_mismatch(&graph);
+
+        assert_eq!(mismatch.vertex_counts, None);
+        assert_eq!(mismatch.missing_edges, vec![(0, 2)]);
+
+        highlight
+            .edges
+            .retain(|&edge| edge != (0, 2) && edge != (2, 0));
+        assert_eq!(
+            highlight.subgraph_mismatch(&graph),
+            SubgraphMismatch {
+                vertex_counts: None,
+                missing_edges: Vec::new(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_subgraph_mismatch_disjoint_graphs() {
+        let mut graph = Graph::new(4);
+        graph.edges.extend([(0, 1), (2, 3)]);
+        let mut other = Graph::new(4);
+        other.edges.extend([(3, 1), (0, 2), (0, 3)]);
+
+        let mismatch = other.subgraph_mismatch(&graph);
+
+        assert_eq!(mismatch.missing_edges, vec![(0, 2), (0, 3), (1, 3)]);
+        assert_eq!(mismatch.vertex_counts, None);
+    }
+
+    #[test]
+    fn test_subgraph_mismatch_vertex_counts() {
+        let graph = default_graph();
+        let mut larger = Graph::new(6);
+        larger.edges.extend([(0, 1), (4, 5)]);
+
+        let mismatch = larger.subgraph_mismatch(&graph);
+        assert_eq!(mismatch.vertex_counts, Some((6, 4)));
+        assert_eq!(mismatch.missing_edges, vec![(4, 5)]);
+
+        // fewer vertices with only existing edges still differ
+        let mut smaller = Graph::new(2);
+        smaller.edges.push((0, 1));
+        let mismatch = smaller.subgraph_mismatch(&graph);
+        assert_eq!(mismatch.vertex_counts, Some((2, 4)));
+        assert!(mismatch.missing_edges.is_empty());
+    }
+
+    #[test]
+    fn test_adjacency_list_zero_indexed() {
+        let graph = default_graph();
+        assert_eq!(
+            graph.to_adjacency_list_string(Indexing::Zero),
+            "0: 1 3\n1: 0 2 3\n2: 1 3\n3: 0 1 2\n"
+        );
+    }
+
+    #[test]
+    fn test_adjacency_list_one_indexed() {
+        let graph = default_graph();
+        assert_eq!(
+            graph.to_adjacency_list_string(Indexing::One),
+            "1: 2 4\n2: 1 3 4\n3: 2 4\n4: 1 2 3\n"
+        );
+    }
+
+    #[test]
+    fn test_dedup_edges() {
+        let mut graph = Graph::new(4);
+        graph
+            .edges
+            .extend([(0, 1), (2, 1), (1, 0), (1, 2), (3, 2), (0, 1)]);
+        graph.set_orientation(2, 1, Orientation::Forward);
+
+        assert_eq!(graph.dedup_edges(), 3);
+        assert_eq!(graph.edges, vec![(0, 1), (1, 2), (2, 3)]);
+        assert_eq!(graph.orientation(2, 1), Orientation::Forward);
+
+        assert_eq!(graph.dedup_edges(), 0);
+        assert_eq!(graph.edges.len(), 3);
+    }
+
+    #[test]
+    fn test_sorted_edges_do_not_depend_on_construction() {
+        let mut parsed = parse_graph6_string("DPs").unwrap();
+
+        let mut built = Graph::new(5);
+        built.edges.extend([(4, 3), (2, 0), (1, 4), (0, 4), (3, 2)]);
+        built.sort_edges();
+        let mut reversed = Graph::new(5);
+        reversed.edges = built.edges.iter().rev().copied().collect();
+        reversed.sort_edges();
+
+        assert_eq!(built.edges, vec![(2, 0), (0, 4), (1, 4), (3, 2), (4, 3)]);
+        assert_eq!(reversed.edges, built.edges);
+
+        parsed.dedup_edges();
+        built.dedup_edges();
+        assert_eq!(parsed.edges, built.edges);
+    }
+
+    #[test]
+    fn test_generated_graphs_number_edges_like_parsed_ones() {
+        for generator in Generator::ALL {
+            let generated = generator.generate(5, 3);
+            let parsed = parse_graph6_string(&generated.to_graph6_string().unwrap()).unwrap();
+
+            assert_eq!(generated.edges, parsed.edges, "{:?}", generator);
+        }
+
+        let product = Graph::cycle(5).cartesian_product(&Graph::path(2));
+        let parsed = parse_graph6_string(&product.to_graph6_string().unwrap()).unwrap();
+        assert_eq!(product.edges, parsed.edges);
+    }
+
+    #[test]
+    fn test_edge_subgraph_mapping() {
+        let graph = default_graph();
+        let subgraph = graph.edge_subgraph(&[(1, 3), (2, 3)]);
+
+        assert_eq!(subgraph.original_vertices, vec![1, 2, 3]);
+        assert_eq!(subgraph.graph.vertices, 3);
+        assert_eq!(subgraph.graph.edges, vec![(0, 2), (1, 2)]);
+    }
+
+    #[test]
+    fn test_graph6_round_trip() {
+        let graph = default_graph();
+        let g6_string = graph.to_graph6_string().unwrap();
+        let parsed = parse_graph6_string(&g6_string).unwrap();
+
+        assert_eq!(parsed.vertices, graph.vertices);
+        assert_eq!(parsed.edges.len(), graph.edges.len());
+        for &(a, b) in &graph.edges {
+            assert!(parsed.has_edge(a, b));
+        }
+
+        assert_eq!(Graph::new(63).to_graph6_string(), None);
+    }
+
+    #[test]
+    fn test_depths() {
+        let mut graph = default_graph();
+        graph.vertices = 5;
+
+        assert_eq!(
+            graph.depths(0),
+            vec![Some(0), Some(1), Some(2), Some(1), None]
+        );
+        assert_eq!(graph.depths(5), vec![None; 5]);
+    }
+
+    #[test]
+    fn test_diameter_and_radius_of_path() {
+        let mut graph = Graph::new(5);
+        graph.edges.extend([(0, 1), (1, 2), (2, 3), (3, 4)]);
+
+        assert_eq!(graph.diameter(), Some(4));
+        assert_eq!(graph.radius(), Some(2));
+        assert_eq!(graph.center(), vec![2]);
+    }
+
+    #[test]
+    fn test_diameter_of_complete_graph() {
+        let mut graph = Graph::new(4);
+        graph
+            .edges
+            .extend([(0, 1), (0, 2), (0, 3), (1, 2), (1, 3), (2, 3)]);
+
+        assert_eq!(graph.diameter(), Some(1));
+        assert_eq!(graph.radius(), Some(1));
+        assert_eq!(graph.center(), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_diameter_of_disconnected_graph() {
+        let mut graph = Graph::new(3);
+        graph.edges.push((0, 1));
+
+        assert_eq!(graph.diameter(), None);
+        assert_eq!(Graph::new(0).radius(), None);
+        assert!(graph.center().is_empty());
+    }
+
+    #[test]
+    fn test_clustering_of_triangle() {
+        let mut graph = Graph::new(3);
+        graph.edges.extend([(0, 1), (1, 2), (0, 2)]);
+
+        assert_eq!(graph.local_clustering(), vec![1.0, 1.0, 1.0]);
+        assert_eq!(graph.clustering_coefficient(), 1.0);
+    }
+
+    #[test]
+    fn test_clustering_of_path() {
+        let mut graph = Graph::new(4);
+        graph.edges.extend([(0, 1), (1, 2), (2, 3)]);
+
+        assert_eq!(graph.clustering_coefficient(), 0.0);
+    }
+
+    #[test]
+    fn test_local_clustering() {
+        // vertex 1 has neighbours 0, 2 and 3, two of the three pairs are adjacent
+        let graph = default_graph();
+
+        let clustering = graph.local_clustering();
+        assert!((clustering[1] - 2.0 / 3.0).abs() < 1e-6);
+        assert_eq!(clustering[0], 1.0);
+        assert_eq!(clustering[2], 1.0);
+    }
+
+    #[test]
+    fn test_components() {
+        let mut graph = Graph::new(5);
+        graph.edges.extend([(0, 3), (1, 2)]);
+
+        assert_eq!(graph.components(), vec![0, 1, 1, 0, 2]);
+    }
+
+    #[test]
+    fn test_graph6_surrounding_whitespace() {
+        let expected = parse_graph6_string("DQc").unwrap();
+
+        assert_eq!(parse_graph6_string("DQc\n").unwrap(), expected);
+        assert_eq!(parse_graph6_string("  DQc\r\n").unwrap(), expected);
+        assert!(matches!(
+            parse_graph6_string(" \r\n"),
+            Err(Graph6ParseError::EmptyString)
+        ));
+    }
+
+    #[test]
+    fn test_truncated_graph6_string() {
+        // C5 needs two data characters
+        assert_eq!(parse_graph6_string("Dhc").unwrap().edges.len(), 5);
+        assert!(matches!(
+            parse_graph6_string("Dh"),
+            Err(Graph6ParseError::UnexpectedStringEnd)
+        ));
+        assert!(matches!(
+            parse_graph6_string("D"),
+            Err(Graph6ParseError::UnexpectedStringEnd)
+        ));
+        // no data needed for a single vertex
+        assert_eq!(parse_graph6_string("@").unwrap().vertices, 1);
+    }
+
+    #[test]
+    fn test_parse_graph6_multi() {
+        let results = parse_graph6_multi("DQc Bw\r\nD?{ hello! C~\n");
+
+        assert_eq!(results.len(), 5);
+        assert_eq!(results[0].as_ref().unwrap().edges.len(), 4);
+        assert_eq!(results[1].as_ref().unwrap().edges.len(), 3);
+        assert_eq!(results[2].as_ref().unwrap().edges.len(), 4);
+        assert!(matches!(
+            results[3],
+            Err(Graph6ParseError::InvalidCharacter('!'))
+        ));
+        assert_eq!(results[4].as_ref().unwrap().edges.len(), 6);
+
+        assert!(parse_graph6_multi("\n \r\n").is_empty());
+    }
+
+    #[test]
+    fn test_graph6_degree_sequence_matches_full_parse() {
+        let mut g6_strings: Vec<String> = ["@", "A_", "Bw", "DQc", "D?{", "C~", "E?~o"]
+            .map(String::from)
+            .to_vec();
+        g6_strings.extend(
+            [Graph::new(4), Graph::path(30), Graph::path(62)]
+                .iter()
+                .map(|graph| graph.to_graph6_string().unwrap()),
+        );
+
+        for g6_string in &g6_strings {
+            assert_eq!(
+                graph6_degree_sequence(g6_string).unwrap(),
+                parse_graph6_string(g6_string).unwrap().degree_sequence(),
+                "{}",
+                g6_string
+            );
+        }
+
+        assert!(matches!(
+            graph6_degree_sequence("D?"),
+            Err(Graph6ParseError::UnexpectedStringEnd)
+        ));
+        assert!(matches!(
+            graph6_degree_sequence("D?!"),
+            Err(Graph6ParseError::InvalidCharacter('!'))
+        ));
+    }
+
+    #[test]
+    fn test_density_and_degree_sequence() {
+        let graph = default_graph();
+
+        assert!((graph.density() - 5.0 / 6.0).abs() < 1e-6);
+        assert_eq!(graph.degree_sequence(), vec![3, 3, 2, 2]);
+        assert_eq!(Graph::new(1).density(), 0.0);
+    }
+
+    #[test]
+    fn test_bipartite_and_girth() {
+        assert!(Graph::cycle(6).is_bipartite());
+        assert!(!Graph::cycle(5).is_bipartite());
+        assert!(Graph::path(4).is_bipartite());
+
+        assert_eq!(Graph::cycle(5).girth(), Some(5));
+        assert_eq!(Graph::petersen().girth(), Some(5));
+        assert_eq!(Graph::k33().girth(), Some(4));
+        assert_eq!(default_graph().girth(), Some(3));
+        assert_eq!(Graph::star(5).girth(), None);
+    }
+
+    #[test]
+    fn test_orientation_state_machine() {
+        let mut orientation = Orientation::default();
+        let mut seen = Vec::new();
+        for _ in 0..3 {
+            seen.push(orientation);
+            orientation = orientation.next();
+        }
+
+        assert_eq!(orientation, Orientation::Undirected);
+        assert_eq!(
+            seen,
+            vec![
+                Orientation::Undirected,
+                Orientation::Forward,
+                Orientation::Backward
+            ]
+        );
+        assert_eq!(Orientation::Forward.direction((2, 5)), Some((2, 5)));
+        assert_eq!(Orientation::Backward.direction((2, 5)), Some((5, 2)));
+        assert_eq!(Orientation::Undirected.direction((2, 5)), None);
+    }
+
+    #[test]
+    fn test_orientation_lookup_in_both_directions() {
+        let mut graph = default_graph();
+        graph.set_orientation(1, 2, Orientation::Forward);
+
+        assert_eq!(graph.orientation(1, 2), Orientation::Forward);
+        assert_eq!(graph.orientation(2, 1), Orientation::Backward);
+
+        graph.set_orientation(2, 1, Orientation::Undirected);
+        assert!(graph.orientations.is_empty());
+    }
+
+    #[test]
+    fn test_reverse_edge_makes_anti_parallel_pair() {
+        let mut graph = Graph::path(3);
+        assert_eq!(graph.add_reverse_edge(0, 1), None, "undirected");
+
+        graph.set_orientation(1, 0, Orientation::Forward);
+        assert_eq!(graph.add_reverse_edge(0, 1), Some((1, 0)));
+        // next to the edge it reverses
+        assert_eq!(graph.edges, vec![(0, 1), (1, 0), (1, 2)]);
+        assert_eq!(graph.add_reverse_edge(1, 0), None, "already both ways");
+
+        let direction = |edge: (usize, usize)| graph.orientation(edge.0, edge.1).direction(edge);
+        assert_eq!(direction((0, 1)), Some((1, 0)));
+        assert_eq!(direction((1, 0)), Some((0, 1)));
+
+        // changing one edge of the pair leaves the other
+        graph.set_orientation(1, 0, Orientation::Forward);
+        let direction = |edge: (usize, usize)| graph.orientation(edge.0, edge.1).direction(edge);
+        assert_eq!(direction((0, 1)), Some((1, 0)));
+        assert_eq!(direction((1, 0)), Some((1, 0)));
+    }
+
+    #[test]
+    fn test_reconnect_edge_keeps_orientation() {
+        let mut graph = Graph::path(4);
+        graph.set_orientation(0, 1, Orientation::Forward);
+
+        assert!(!graph.reconnect_edge((0, 1), (2, 2)), "loop");
+        assert!(!graph.reconnect_edge((0, 1), (2, 1)), "repeated edge");
+        assert!(!graph.reconnect_edge((0, 1), (0, 4)), "no such vertex");
+        assert!(!graph.reconnect_edge((0, 3), (0, 2)), "no such edge");
+
+        assert!(graph.reconnect_edge((0, 1), (3, 0)));
+        assert_eq!(graph.edges, vec![(3, 0), (1, 2), (2, 3)]);
+        assert_eq!(graph.orientation(3, 0), Orientation::Forward);
+        assert_eq!(graph.orientation(0, 1), Orientation::Undirected);
+    }
+
+    #[test]
+    fn test_anti_parallel_edges() {
+        let directions = [
+            Some((0, 1)),
+            Some((1, 0)),
+            Some((1, 2)),
+            None,
+            Some((2, 3)),
+            Some((2, 3)),
+        ];
+
+        assert_eq!(
+            anti_parallel_edges(&directions),
+            vec![true, true, false, false, false, false]
+        );
+        assert!(anti_parallel_edges(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_subdivide_directed_edge() {
+        let mut graph = default_graph();
+        graph.set_orientation(1, 2, Orientation::Forward);
+        let c = graph.subdivide_edge(1, 2).unwrap();
+
+        assert_eq!(graph.orientation(1, c), Orientation::Forward);
+        assert_eq!(graph.orientation(c, 2), Orientation::Forward);
+    }
+}
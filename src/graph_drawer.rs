@@ -2,7 +2,16 @@ use std::f32::consts::PI;
 
 use macroquad::prelude::*;
 
-use crate::graph_interface::{DrawState, GraphInterface};
+use crate::{
+    graph::{anti_parallel_edges, Graph},
+    graph_interface::{
+        DrawState, EdgeStyle, GraphInterface, HiddenVertexEdges, HighlightEntry, InducedEdgeState,
+        Selection,
+    },
+    indexing::Indexing,
+    label_placement::{candidate_rect, fitted_label_size, label_size},
+    palette::Palette,
+};
 
 pub trait Drawable {
     fn draw(&self);
@@ -14,53 +23,37 @@ pub struct DrawableGraph {
 }
 
 impl DrawableGraph {
+    /// Composes the graph in full detail, as used for exports.
     pub fn compose(embedding: &GraphInterface, config: &DrawConfig) -> Self {
+        Self::compose_with_detail(embedding, config, LevelOfDetail::Full)
+    }
+
+    /// Composes the graph at `detail`, see [`DrawConfig::level_of_detail`].
+    pub fn compose_with_detail(
+        embedding: &GraphInterface,
+        config: &DrawConfig,
+        detail: LevelOfDetail,
+    ) -> Self {
+        let reduced = detail != LevelOfDetail::Full;
         let mut vertices = Vec::with_capacity(embedding.vertex_properties.len());
         let vertex_config = &config.vertex_config;
 
         for (index, vertex_properties) in embedding.vertex_properties.iter().enumerate().rev() {
             let position = vertex_properties.position;
 
-            let mut main_radius = vertex_config.main_size;
-            let mut border_radius = vertex_config.border_size + main_radius;
-
-            let (mut main_color, mut border_color) = {
-                match vertex_properties.draw_state {
-                    DrawState::Default => (vertex_config.main_color, vertex_config.border_color),
-                    DrawState::Highlighted => {
-                        (vertex_config.highlight_color, vertex_config.highlight_color)
-                    }
-                    DrawState::Unhighlighted => (
-                        vertex_config.unhighlight_color,
-                        vertex_config.unhighlight_color,
-                    ),
-                    DrawState::Hidden => (
-                        Color::new(0.0, 0.0, 0.0, 0.0),
-                        Color::new(0.0, 0.0, 0.0, 0.0),
-                    ),
-                }
-            };
-
-            let mut is_interacted = false;
-
-            if let Some(hovered) = embedding.hovered_vertex {
-                if hovered == index {
-                    main_radius += 2.0;
-                    border_radius += 2.0;
-
-                    main_color = vertex_config.highlight_color;
-                    border_color = vertex_config.highlight_color;
+            let border_radius = vertex_properties.radius;
+            let main_radius = (border_radius - vertex_config.border_size).max(0.0);
 
-                    is_interacted = true;
-                }
-            }
+            let is_interacted = embedding.hovered_vertex == Some(index)
+                || embedding.dragged_vertex == Some(index)
+                || embedding.selected == Some(Selection::Vertex(index))
+                || embedding.selected_vertices.binary_search(&index).is_ok();
 
-            if let Some(dragged) = embedding.dragged_vertex {
-                if dragged == index {
-                    main_color = vertex_config.drag_color;
-                    border_color = vertex_config.drag_color;
+            let mut style = vertex_style(vertex_properties.draw_state, vertex_config);
 
-                    is_interacted = true;
+            if let Some(color) = vertex_properties.color_override {
+                if vertex_properties.draw_state == DrawState::Default {
+                    style.main_color = color;
                 }
             }
 
@@ -68,38 +61,41 @@ impl DrawableGraph {
                 continue;
             }
 
-            let label = if vertex_config.draw_index {
-                let character_width = vertex_config.label_size;
-                let mut string_width = character_width;
-
-                if index >= 10 {
-                    string_width += character_width;
-                }
-
-                let x_offset = -string_width / 2.0 + 9.0;
-                let y_offset = character_width / 2.0 - 10.0;
-
-                Some(DrawableLabel {
-                    content: if vertex_config.zero_indexed {
-                        index.to_string()
-                    } else {
-                        (index + 1).to_string()
-                    },
-                    position: position + vec2(x_offset, y_offset),
-                    size: vertex_config.label_size,
-                    color: vertex_config.label_color,
-                })
-            } else {
-                None
+            let content = match &vertex_properties.label {
+                Some(label) => Some(label.clone()),
+                None if vertex_config.draw_index => Some(index_label(index, config.indexing)),
+                None => None,
             };
 
+            let label = content.filter(|_| !reduced).map(|content| {
+                let color = vertex_config.label_color_on(style.main_color, style.label_color);
+                let radii = (main_radius, border_radius);
+                let colors = (color, config.outside_label_color());
+                vertex_label(content, position, radii, vertex_config, colors)
+            });
+
             let composed_vertex = DrawableVertex {
+                index,
                 position,
                 main_radius,
                 border_radius,
-                main_color,
-                border_color,
+                main_color: style.main_color,
+                border_color: style.border_color,
                 label,
+                svg_stroke: vertex_config.svg_stroke,
+                dashed_border: vertex_config.dash_isolated
+                    && embedding.incident_edges(index).is_empty(),
+                outline: is_interacted.then_some(Outline {
+                    color: config.interaction_color,
+                    width: vertex_config.outline_width,
+                }),
+                title: config.svg_tooltips.then(|| {
+                    vertex_title(
+                        index,
+                        embedding.incident_edges(index).len(),
+                        config.indexing,
+                    )
+                }),
             };
 
             vertices.push(composed_vertex);
@@ -108,70 +104,111 @@ impl DrawableGraph {
         let mut edges = Vec::new();
         let edge_config = &config.edge_config;
 
+        if let LevelOfDetail::Reduced { edge_step } = detail {
+            // plain thin lines, skipping the per edge state
+            for edge in embedding.edge_properties.iter().step_by(edge_step.max(1)) {
+                edges.push(DrawableEdge {
+                    start: embedding.get_position(edge.vertices.0),
+                    end: embedding.get_position(edge.vertices.1),
+                    width: REDUCED_EDGE_WIDTH,
+                    color: edge_config.color,
+                    endpoints: Some(edge.vertices),
+                    ..Default::default()
+                });
+            }
+
+            return Self { vertices, edges };
+        }
+
         for (index, edge) in embedding.edge_properties.iter().enumerate() {
-            let verices = edge.vertices;
+            // a highlighted path overrides the orientation of the edge while it is shown
+            let direction = match edge.path_direction {
+                Some(direction) if edge_config.draw_path_arrows => Some(direction),
+                _ => edge.orientation.direction(edge.vertices),
+            };
+            let verices = direction.or(edge.path_direction).unwrap_or(edge.vertices);
             let start = embedding.get_position(verices.0);
             let end = embedding.get_position(verices.1);
 
-            let mut width = edge_config.width;
-
-            let mut color = match edge.draw_state {
-                crate::graph_interface::DrawState::Default => edge_config.color,
-                crate::graph_interface::DrawState::Highlighted => edge_config.highlight_color,
+            let color = match edge.draw_state {
+                crate::graph_interface::DrawState::Default => {
+                    edge.color_override.unwrap_or(edge_config.color)
+                }
+                crate::graph_interface::DrawState::Highlighted => edge
+                    .highlight_color_override
+                    .unwrap_or_else(|| config.edge_highlight_color(edge.highlight_color_index)),
                 crate::graph_interface::DrawState::Unhighlighted => edge_config.unhighlight_color,
                 crate::graph_interface::DrawState::Hidden => Color::new(0.0, 0.0, 0.0, 0.0),
             };
 
-            let mut is_hovered = false;
-
-            if let Some(hovered) = embedding.hovered_edge {
-                if hovered == index {
-                    width += 2.0;
+            let induced = embedding.induced_edge_state(
+                index,
+                edge_config.hidden_vertex_edges,
+                edge_config.emphasize_highlighted_vertex_edges,
+            );
+            let color = match induced {
+                Some(InducedEdgeState::Faded) => Color {
+                    a: color.a * FADED_EDGE_ALPHA,
+                    ..color
+                },
+                Some(InducedEdgeState::Emphasized) => Color::from_vec(
+                    color
+                        .to_vec()
+                        .lerp(edge_config.highlight_color.to_vec(), EMPHASIS_AMOUNT),
+                ),
+                _ => color,
+            };
 
-                    color = edge_config.highlight_color;
+            let off_target = edge_config.warn_length_deviation
+                && edge
+                    .target_length
+                    .zip(embedding.edge_length(index))
+                    .is_some_and(|(target, length)| {
+                        (length - target).abs() > edge_config.length_tolerance
+                    });
+            let color = if off_target {
+                edge_config.length_warning_color
+            } else {
+                color
+            };
 
-                    is_hovered = true;
-                }
-            }
+            let is_interacted = embedding.hovered_edge == Some(index)
+                || embedding.selected == Some(Selection::Edge(index));
+            let is_hidden =
+                edge.draw_state == DrawState::Hidden || induced == Some(InducedEdgeState::Hidden);
 
-            if edge.draw_state == DrawState::Hidden && !is_hovered {
+            if is_hidden && !is_interacted {
                 continue;
             }
 
-            let label = if edge_config.draw_index {
-                let min_vertex = edge.vertices.0.min(edge.vertices.1);
-                let max_vertex = edge.vertices.0.max(edge.vertices.1);
-
-                let label_index = max_vertex * (max_vertex - 1) / 2 + min_vertex;
+            let content = match &edge.label {
+                Some(label) => Some(label.clone()),
+                None if edge_config.draw_index => {
+                    Some(edge_index_label(edge.vertices, config.indexing))
+                }
+                None => None,
+            };
 
-                let offset = {
-                    let diff = end - start;
-                    let angle = (diff.y.atan2(diff.x) + PI) % PI - 0.3;
+            // the same bend as the hit test, whichever way the edge is directed
+            let control = embedding.edge_control(index, edge_config.curvature);
 
-                    let x_offset = 10.0;
-                    let y_offset = -10.0;
+            let label =
+                content.map(|content| edge_label(content, start, end, control, edge_config));
 
-                    if angle < PI / 4.0 {
-                        vec2(-x_offset, y_offset)
-                    } else if angle < PI / 2.0 {
-                        vec2(x_offset, y_offset)
-                    } else if angle < 3.0 * PI / 4.0 {
-                        vec2(-x_offset, y_offset)
-                    } else {
-                        vec2(x_offset, y_offset)
-                    }
-                };
+            let arrow = if direction.is_some() {
+                let target_radius = embedding
+                    .vertex_properties
+                    .get(verices.1)
+                    .map(|vertex| vertex.radius)
+                    .unwrap_or(0.0);
 
-                Some(DrawableLabel {
-                    content: if edge_config.zero_indexed {
-                        label_index.to_string()
-                    } else {
-                        (label_index + 1).to_string()
-                    },
-                    position: (start + end) / 2.0 + offset,
-                    size: edge_config.label_size,
-                    color: edge_config.label_color,
-                })
+                // the curve ends in the direction of its control point
+                arrow_head(
+                    control.unwrap_or(start),
+                    end,
+                    target_radius,
+                    edge_config.arrow_size,
+                )
             } else {
                 None
             };
@@ -179,9 +216,20 @@ impl DrawableGraph {
             let composed_edge = DrawableEdge {
                 start,
                 end,
-                width,
+                endpoints: Some(verices),
+                width: edge.width,
                 color,
                 label,
+                arrow,
+                style: edge.style,
+                outline: is_interacted.then_some(Outline {
+                    color: config.interaction_color,
+                    width: edge_config.outline_width,
+                }),
+                control,
+                title: config
+                    .svg_tooltips
+                    .then(|| edge_title(edge.vertices, edge.weight, config.indexing)),
             };
 
             edges.push(composed_edge);
@@ -189,6 +237,330 @@ impl DrawableGraph {
 
         Self { vertices, edges }
     }
+
+    /// Composes a graph from explicit positions, without any interaction or highlight state.
+    pub fn from_positions(graph: &Graph, positions: &[Vec2], config: &DrawConfig) -> Self {
+        let vertex_config = &config.vertex_config;
+        let edge_config = &config.edge_config;
+
+        let get_position = |vertex: usize| positions.get(vertex).copied().unwrap_or(Vec2::ZERO);
+
+        let mut degrees = vec![0; graph.vertices];
+        for &(a, b) in &graph.edges {
+            for vertex in [a, b] {
+                if let Some(degree) = degrees.get_mut(vertex) {
+                    *degree += 1;
+                }
+            }
+        }
+
+        let style = vertex_style(DrawState::Default, vertex_config);
+        let radii = (vertex_config.main_size, vertex_config.radius());
+
+        let vertices = (0..graph.vertices)
+            .rev()
+            .map(|index| {
+                let position = get_position(index);
+
+                let label = if vertex_config.draw_index {
+                    let content = index_label(index, config.indexing);
+                    let color = vertex_config.label_color_on(style.main_color, style.label_color);
+                    let colors = (color, config.outside_label_color());
+                    Some(vertex_label(
+                        content,
+                        position,
+                        radii,
+                        vertex_config,
+                        colors,
+                    ))
+                } else {
+                    None
+                };
+
+                DrawableVertex {
+                    index,
+                    position,
+                    main_radius: radii.0,
+                    border_radius: radii.1,
+                    main_color: style.main_color,
+                    border_color: style.border_color,
+                    label,
+                    svg_stroke: vertex_config.svg_stroke,
+                    dashed_border: vertex_config.dash_isolated && degrees[index] == 0,
+                    outline: None,
+                    title: config
+                        .svg_tooltips
+                        .then(|| vertex_title(index, degrees[index], config.indexing)),
+                }
+            })
+            .collect();
+
+        let directions: Vec<_> = graph
+            .edges
+            .iter()
+            .map(|&vertices| {
+                graph
+                    .orientation(vertices.0, vertices.1)
+                    .direction(vertices)
+            })
+            .collect();
+        let anti_parallel = anti_parallel_edges(&directions);
+
+        let edges = graph
+            .edges
+            .iter()
+            .zip(directions)
+            .zip(anti_parallel)
+            .map(|((&vertices, direction), anti_parallel)| {
+                let endpoints = direction.unwrap_or(vertices);
+                let (start, end) = (get_position(endpoints.0), get_position(endpoints.1));
+                // bent relative to their direction, the edges of an anti-parallel pair bend apart
+                let control = if anti_parallel {
+                    curve_control(start, end, ANTI_PARALLEL_CURVATURE)
+                } else {
+                    curve_control(
+                        get_position(vertices.0),
+                        get_position(vertices.1),
+                        edge_config.curvature,
+                    )
+                };
+                let arrow = direction.and_then(|_| {
+                    arrow_head(
+                        control.unwrap_or(start),
+                        end,
+                        radii.1,
+                        edge_config.arrow_size,
+                    )
+                });
+
+                let label = if edge_config.draw_index {
+                    let content = edge_index_label(vertices, config.indexing);
+                    Some(edge_label(content, start, end, control, edge_config))
+                } else {
+                    None
+                };
+
+                DrawableEdge {
+                    start,
+                    end,
+                    endpoints: Some(endpoints),
+                    width: edge_config.width,
+                    color: edge_config.color,
+                    label,
+                    arrow,
+                    style: EdgeStyle::Solid,
+                    outline: None,
+                    control,
+                    title: config
+                        .svg_tooltips
+                        .then(|| edge_title(vertices, None, config.indexing)),
+                }
+            })
+            .collect();
+
+        Self { vertices, edges }
+    }
+}
+
+fn vertex_title(index: usize, degree: usize, indexing: Indexing) -> String {
+    format!("Vertex {}, degree {}", indexing.display(index), degree)
+}
+
+fn edge_title(vertices: (usize, usize), weight: Option<f32>, indexing: Indexing) -> String {
+    let mut title = format!(
+        "Edge {}: {} - {}",
+        edge_index_label(vertices, indexing),
+        indexing.display(vertices.0),
+        indexing.display(vertices.1)
+    );
+    if let Some(weight) = weight {
+        title.push_str(&format!(", weight {}", weight));
+    }
+
+    title
+}
+
+fn index_label(index: usize, indexing: Indexing) -> String {
+    indexing.display(index).to_string()
+}
+
+/// Black or white, whichever is more legible on `background`.
+pub fn contrasting_label_color(background: Color) -> Color {
+    // relative luminance as defined by WCAG, on linearised sRGB channels
+    let linear = |channel: f32| {
+        if channel <= 0.04045 {
+            channel / 12.92
+        } else {
+            ((channel + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    let luminance = 0.2126 * linear(background.r)
+        + 0.7152 * linear(background.g)
+        + 0.0722 * linear(background.b);
+
+    // equal contrast against black and white
+    if luminance > 0.179 {
+        BLACK
+    } else {
+        WHITE
+    }
+}
+
+fn vertex_label(
+    content: String,
+    position: Vec2,
+    (main_radius, border_radius): (f32, f32),
+    config: &VertexDrawConfig,
+    (inside_color, outside_color): (Color, Color),
+) -> DrawableLabel {
+    let character_width = config.label_size;
+    let string_width = character_width * content.chars().count().clamp(1, 2) as f32;
+
+    let x_offset = -string_width / 2.0 + 9.0;
+    let y_offset = character_width / 2.0 - 10.0;
+
+    let mut label = DrawableLabel {
+        content,
+        position: position + vec2(x_offset, y_offset),
+        size: config.label_size,
+        color: inside_color,
+        halo: config.label_halo.then_some(config.label_halo_color),
+    };
+
+    if config.fit_labels && config.label_placement == VertexLabelPlacement::Inside {
+        let size = fitted_label_size(&label.content, config.label_size, 2.0 * border_radius);
+        if size < config.label_size {
+            label.size = size;
+            let centered = label_size(&label) * vec2(-0.5, 0.5);
+            label.position = position + centered;
+        }
+    }
+
+    if !config
+        .label_placement
+        .is_inside(label_size(&label), main_radius)
+    {
+        let direction = config.outside_label_direction();
+        let radius = border_radius + config.outside_label_offset;
+        let rect = candidate_rect(&label, position, radius, direction);
+        label.position = vec2(rect.x, rect.y + rect.h);
+        // next to the vertex the label is read against the background
+        label.color = outside_color;
+    }
+
+    label
+}
+
+fn edge_index_label(vertices: (usize, usize), indexing: Indexing) -> String {
+    let min_vertex = vertices.0.min(vertices.1);
+    let max_vertex = vertices.0.max(vertices.1);
+
+    let label_index = max_vertex * (max_vertex - 1) / 2 + min_vertex;
+
+    index_label(label_index, indexing)
+}
+
+/// Space between an edge and its label.
+const EDGE_LABEL_GAP: f32 = 2.0;
+
+/// Label next to the middle of the edge, which is curved when `control` is set. It is moved
+/// along the normal of the edge, on the upper side, until its box clears the edge line.
+fn edge_label(
+    content: String,
+    start: Vec2,
+    end: Vec2,
+    control: Option<Vec2>,
+    config: &EdgeDrawConfig,
+) -> DrawableLabel {
+    let middle = control.map_or((start + end) / 2.0, |control| {
+        (start + end) / 4.0 + control / 2.0
+    });
+    // also the tangent in the middle of a curved edge
+    let normal = (end - start).perp().try_normalize().unwrap_or(Vec2::Y);
+    let normal = if normal.y > 0.0 { -normal } else { normal };
+
+    let mut label = DrawableLabel {
+        content,
+        position: middle,
+        size: config.label_size,
+        color: config.label_color,
+        halo: None,
+    };
+
+    let half_size = label_size(&label) / 2.0;
+    let distance = normal.abs().dot(half_size) + config.width / 2.0 + EDGE_LABEL_GAP;
+    let center = middle + normal * distance;
+    label.position = center + half_size * vec2(-1.0, 1.0);
+
+    label
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VertexStyle {
+    pub main_color: Color,
+    pub border_color: Color,
+    pub label_color: Color,
+}
+
+/// Colors of a vertex in the given state. Interaction doesn't change them, it adds an
+/// [`Outline`] instead.
+pub fn vertex_style(draw_state: DrawState, config: &VertexDrawConfig) -> VertexStyle {
+    match draw_state {
+        DrawState::Default => VertexStyle {
+            main_color: config.main_color,
+            border_color: config.border_color,
+            label_color: config.label_color,
+        },
+        DrawState::Highlighted => VertexStyle {
+            main_color: config.highlight_color,
+            border_color: config.highlight_border_color,
+            label_color: config.highlight_label_color,
+        },
+        DrawState::Unhighlighted => VertexStyle {
+            main_color: config.unhighlight_color,
+            border_color: config.unhighlight_border_color,
+            label_color: config.unhighlight_label_color,
+        },
+        DrawState::Hidden => VertexStyle {
+            main_color: Color::new(0.0, 0.0, 0.0, 0.0),
+            border_color: Color::new(0.0, 0.0, 0.0, 0.0),
+            label_color: Color::new(0.0, 0.0, 0.0, 0.0),
+        },
+    }
+}
+
+const REDUCED_EDGE_WIDTH: f32 = 1.0;
+
+/// Control point that bends the edge from `start` to `end` to its left by `curvature` times its
+/// length. `None` for straight edges.
+pub fn curve_control(start: Vec2, end: Vec2, curvature: f32) -> Option<Vec2> {
+    if curvature == 0.0 || start == end {
+        return None;
+    }
+
+    let offset = (end - start).perp() * curvature;
+    Some((start + end) / 2.0 + offset)
+}
+
+/// Points on the quadratic Bézier curve, the first and last are `start` and `end`.
+pub fn curve_points(start: Vec2, control: Vec2, end: Vec2) -> Vec<Vec2> {
+    (0..=CURVE_SEGMENTS)
+        .map(|segment| {
+            let t = segment as f32 / CURVE_SEGMENTS as f32;
+            start * (1.0 - t) * (1.0 - t) + control * 2.0 * (1.0 - t) * t + end * t * t
+        })
+        .collect()
+}
+
+/// Triangle pointing at `end`, with its tip on the border of the target vertex.
+pub fn arrow_head(start: Vec2, end: Vec2, target_radius: f32, size: f32) -> Option<[Vec2; 3]> {
+    let direction = (end - start).try_normalize()?;
+    let normal = direction.perp();
+
+    let tip = end - direction * target_radius;
+    let base = tip - direction * size;
+
+    Some([tip, base + normal * size * 0.5, base - normal * size * 0.5])
 }
 
 impl Drawable for DrawableGraph {
@@ -203,13 +575,28 @@ impl Drawable for DrawableGraph {
     }
 }
 
+/// Ring around a vertex or band around an edge marking hover, drag or selection. Only drawn
+/// on screen, never exported.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Outline {
+    pub color: Color,
+    pub width: f32,
+}
+
 pub struct DrawableVertex {
+    pub index: usize,
     pub position: Vec2,
     pub main_radius: f32,
     pub border_radius: f32,
     pub main_color: Color,
     pub border_color: Color,
     pub label: Option<DrawableLabel>,
+    /// Export as a single stroked circle instead of a border circle below the main circle.
+    pub svg_stroke: bool,
+    pub dashed_border: bool,
+    pub outline: Option<Outline>,
+    /// Tooltip in SVG exports.
+    pub title: Option<String>,
 }
 
 impl DrawableVertex {}
@@ -219,24 +606,47 @@ impl Default for DrawableVertex {
         let config = VertexDrawConfig::default();
 
         Self {
+            index: 0,
             position: Vec2::ZERO,
             main_radius: config.main_size,
-            border_radius: config.main_size + config.border_size,
+            border_radius: config.radius(),
             main_color: config.main_color,
             border_color: config.border_color,
             label: None,
+            svg_stroke: config.svg_stroke,
+            dashed_border: false,
+            outline: None,
+            title: None,
         }
     }
 }
 
 impl Drawable for DrawableVertex {
     fn draw(&self) {
-        draw_circle(
-            self.position.x,
-            self.position.y,
-            self.border_radius,
-            self.border_color,
-        );
+        if let Some(outline) = self.outline {
+            draw_circle(
+                self.position.x,
+                self.position.y,
+                self.border_radius + outline.width,
+                outline.color,
+            );
+        }
+
+        if self.dashed_border {
+            let thickness = self.border_radius - self.main_radius;
+            let radius = self.main_radius + thickness / 2.0;
+
+            for (start, end) in border_dashes(self.position, radius) {
+                draw_line(start.x, start.y, end.x, end.y, thickness, self.border_color);
+            }
+        } else {
+            draw_circle(
+                self.position.x,
+                self.position.y,
+                self.border_radius,
+                self.border_color,
+            );
+        }
 
         draw_circle(
             self.position.x,
@@ -254,12 +664,29 @@ impl Drawable for DrawableVertex {
 pub struct DrawableEdge {
     pub start: Vec2,
     pub end: Vec2,
+    /// Indices of the vertices at the start and the end, when the edge is drawn between two.
+    pub endpoints: Option<(usize, usize)>,
     pub width: f32,
     pub color: Color,
     pub label: Option<DrawableLabel>,
+    pub arrow: Option<[Vec2; 3]>,
+    pub style: EdgeStyle,
+    pub outline: Option<Outline>,
+    /// Control point of a quadratic Bézier curve, straight when `None`.
+    pub control: Option<Vec2>,
+    /// Tooltip in SVG exports.
+    pub title: Option<String>,
 }
 
-impl DrawableEdge {}
+impl DrawableEdge {
+    /// Points along the edge, just the endpoints when it is straight.
+    fn points(&self) -> Vec<Vec2> {
+        match self.control {
+            Some(control) => curve_points(self.start, control, self.end),
+            None => vec![self.start, self.end],
+        }
+    }
+}
 
 impl Default for DrawableEdge {
     fn default() -> Self {
@@ -268,23 +695,53 @@ impl Default for DrawableEdge {
         Self {
             start: Vec2::ZERO,
             end: Vec2::ZERO,
+            endpoints: None,
             width: config.width,
             color: config.color,
             label: None,
+            arrow: None,
+            style: EdgeStyle::Solid,
+            outline: None,
+            control: None,
+            title: None,
         }
     }
 }
 
 impl Drawable for DrawableEdge {
     fn draw(&self) {
-        draw_line(
-            self.start.x,
-            self.start.y,
-            self.end.x,
-            self.end.y,
-            self.width,
-            self.color,
-        );
+        let points = self.points();
+        let segments = points.windows(2).map(|pair| (pair[0], pair[1]));
+
+        if let Some(outline) = self.outline {
+            for (start, end) in segments.clone() {
+                draw_line(
+                    start.x,
+                    start.y,
+                    end.x,
+                    end.y,
+                    self.width + 2.0 * outline.width,
+                    outline.color,
+                );
+            }
+        }
+
+        for (start, end) in segments {
+            match self.style {
+                EdgeStyle::Solid => {
+                    draw_line(start.x, start.y, end.x, end.y, self.width, self.color)
+                }
+                EdgeStyle::Dashed => {
+                    for (start, end) in dash_segments(start, end, DASH_LENGTH, DASH_LENGTH) {
+                        draw_line(start.x, start.y, end.x, end.y, self.width, self.color);
+                    }
+                }
+            }
+        }
+
+        if let Some([tip, left, right]) = self.arrow {
+            draw_triangle(tip, left, right, self.color);
+        }
 
         if let Some(label) = &self.label {
             label.draw();
@@ -292,11 +749,83 @@ impl Drawable for DrawableEdge {
     }
 }
 
+/// Opacity of edges faded because an endpoint is hidden.
+const FADED_EDGE_ALPHA: f32 = 0.25;
+/// How far edges of highlighted vertices move towards the highlight color.
+const EMPHASIS_AMOUNT: f32 = 0.4;
+
+pub const DASH_LENGTH: f32 = 10.0;
+/// Line segments a curved edge is drawn with.
+const CURVE_SEGMENTS: usize = 16;
+/// Bend of the two edges of an anti-parallel pair, see [`curve_control`].
+pub const ANTI_PARALLEL_CURVATURE: f32 = 0.15;
+
+/// Number of dashes in a dashed vertex border.
+pub const BORDER_DASHES: usize = 8;
+
+/// Bounds of [`VertexDrawConfig::scale_size`].
+const MIN_VERTEX_SIZE: f32 = 2.0;
+const MAX_VERTEX_SIZE: f32 = 60.0;
+/// Bounds of [`EdgeDrawConfig::scale_width`].
+const MIN_EDGE_WIDTH: f32 = 0.5;
+const MAX_EDGE_WIDTH: f32 = 20.0;
+
+/// Side of a square of the checkerboard that shows a transparent background.
+const CHECKER_SIZE: f32 = 12.0;
+const CHECKER_COLORS: [Color; 2] = [
+    Color::new(0.8, 0.8, 0.8, 1.0),
+    Color::new(0.95, 0.95, 0.95, 1.0),
+];
+
+/// Chords approximating the dashes of a dashed circle, each dash is followed by an equal gap.
+fn border_dashes(center: Vec2, radius: f32) -> Vec<(Vec2, Vec2)> {
+    const CHORDS_PER_DASH: usize = 3;
+    let dash_angle = PI / BORDER_DASHES as f32;
+    let point = |angle: f32| center + radius * vec2(angle.cos(), angle.sin());
+
+    (0..BORDER_DASHES)
+        .flat_map(|dash| {
+            let start_angle = 2.0 * dash as f32 * dash_angle;
+            (0..CHORDS_PER_DASH).map(move |chord| {
+                let step = dash_angle / CHORDS_PER_DASH as f32;
+                let angle = start_angle + chord as f32 * step;
+                (angle, angle + step)
+            })
+        })
+        .map(|(from, to)| (point(from), point(to)))
+        .collect()
+}
+
+pub fn dash_segments(start: Vec2, end: Vec2, dash: f32, gap: f32) -> Vec<(Vec2, Vec2)> {
+    let length = start.distance(end);
+    let mut segments = Vec::new();
+
+    if length == 0.0 || dash <= 0.0 {
+        return segments;
+    }
+
+    let direction = (end - start) / length;
+    let mut distance = 0.0;
+
+    while distance < length {
+        let segment_end = (distance + dash).min(length);
+        segments.push((
+            start + direction * distance,
+            start + direction * segment_end,
+        ));
+        distance += dash + gap;
+    }
+
+    segments
+}
+
 pub struct DrawableLabel {
     pub content: String,
     pub position: Vec2,
     pub size: f32,
     pub color: Color,
+    /// Outline that keeps the label readable over edges. Only drawn in SVG exports.
+    pub halo: Option<Color>,
 }
 
 impl Drawable for DrawableLabel {
@@ -311,10 +840,85 @@ impl Drawable for DrawableLabel {
     }
 }
 
+#[derive(Clone, PartialEq)]
 pub struct DrawConfig {
     pub vertex_config: VertexDrawConfig,
     pub edge_config: EdgeDrawConfig,
     pub background_color: Color,
+    pub background_mode: BackgroundMode,
+    /// Used for every index shown to or read from the user, not only for labels.
+    pub indexing: Indexing,
+    /// Last palette applied with [`Palette::apply`], also used for generated class colors.
+    pub palette: Palette,
+    /// Outline of hovered, dragged and selected elements.
+    pub interaction_color: Color,
+    pub quality: DrawQuality,
+    pub lod_edge_threshold: u32,
+    /// Draw only part of the edges while the layout moves, see [`Self::level_of_detail`].
+    pub lod_subsample: bool,
+    /// Give every vertex and edge a tooltip in SVG exports.
+    pub svg_tooltips: bool,
+}
+
+impl DrawConfig {
+    /// Detail to draw a graph with `edge_count` edges at. Edges are only subsampled while the
+    /// layout moves, a layout that stands `still` shows every edge.
+    pub fn level_of_detail(&self, edge_count: usize, still: bool) -> LevelOfDetail {
+        let threshold = self.lod_edge_threshold as usize;
+        let large = edge_count > threshold;
+        let reduce = match self.quality {
+            DrawQuality::Full => false,
+            DrawQuality::Automatic => large,
+            DrawQuality::Fast => true,
+        };
+
+        if !reduce {
+            return LevelOfDetail::Full;
+        }
+
+        let edge_step = if self.lod_subsample && large && !still {
+            edge_count.div_ceil(threshold.max(1))
+        } else {
+            1
+        };
+
+        LevelOfDetail::Reduced { edge_step }
+    }
+
+    /// Color of the labels placed outside their vertex, readable on the background.
+    pub fn outside_label_color(&self) -> Color {
+        contrasting_label_color(self.background_color)
+    }
+
+    /// Fill behind the graph in exports, none for a transparent background.
+    pub fn export_background(&self) -> Option<Color> {
+        match self.background_mode {
+            BackgroundMode::Color => Some(self.background_color),
+            BackgroundMode::Transparent | BackgroundMode::Checkerboard => None,
+        }
+    }
+
+    /// Highlight color of the edges of history entry `index`. The first entry uses the
+    /// configured highlight color, later ones the palette colors that differ from it.
+    pub fn edge_highlight_color(&self, index: usize) -> Color {
+        let highlight_color = self.edge_config.highlight_color;
+        if index == 0 {
+            return highlight_color;
+        }
+
+        (0..)
+            .map(|i| self.palette.color(i))
+            .filter(|&color| color != highlight_color)
+            .nth(index - 1)
+            .unwrap_or(highlight_color)
+    }
+
+    /// Color of the edges of a history entry, its override or its palette color.
+    pub fn history_entry_color(&self, entry: &HighlightEntry) -> Color {
+        entry
+            .color_override
+            .unwrap_or_else(|| self.edge_highlight_color(entry.color_index))
+    }
 }
 
 impl Default for DrawConfig {
@@ -323,22 +927,192 @@ impl Default for DrawConfig {
             vertex_config: Default::default(),
             edge_config: Default::default(),
             background_color: Color::new(0.91, 0.91, 0.91, 1.00),
+            background_mode: BackgroundMode::default(),
+            indexing: Indexing::default(),
+            palette: Palette::default(),
+            interaction_color: Color::new(1.0, 0.63, 0.0, 0.8),
+            quality: DrawQuality::default(),
+            lod_edge_threshold: 10000,
+            lod_subsample: false,
+            svg_tooltips: false,
         }
     }
 }
 
+/// Trade-off between drawing detail and frame rate on the screen. Exports are always drawn in
+/// full detail.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum DrawQuality {
+    Full,
+    /// Reduced detail above [`DrawConfig::lod_edge_threshold`] edges.
+    #[default]
+    Automatic,
+    Fast,
+}
+
+impl DrawQuality {
+    pub const ALL: [DrawQuality; 3] =
+        [DrawQuality::Full, DrawQuality::Automatic, DrawQuality::Fast];
+    pub const NAMES: [&'static str; 3] = ["Full", "Automatic", "Fast"];
+
+    pub fn index(&self) -> usize {
+        Self::ALL
+            .iter()
+            .position(|quality| quality == self)
+            .unwrap()
+    }
+}
+
+/// What exports put behind the graph. Exports are transparent unless a fill is chosen, so a
+/// default SVG has no background `<rect>`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum BackgroundMode {
+    /// Filled with [`DrawConfig::background_color`].
+    Color,
+    #[default]
+    Transparent,
+    /// Transparent, shown as a checkerboard on the canvas.
+    Checkerboard,
+}
+
+impl BackgroundMode {
+    pub const ALL: [BackgroundMode; 3] = [
+        BackgroundMode::Color,
+        BackgroundMode::Transparent,
+        BackgroundMode::Checkerboard,
+    ];
+    pub const NAMES: [&'static str; 3] = ["Color", "Transparent", "Checkerboard"];
+
+    pub fn index(&self) -> usize {
+        Self::ALL.iter().position(|mode| mode == self).unwrap()
+    }
+}
+
+/// Covers `size` from the origin with the checkerboard of a transparent background.
+pub fn draw_checkerboard(size: Vec2) {
+    let columns = (size.x / CHECKER_SIZE).ceil() as usize;
+    let rows = (size.y / CHECKER_SIZE).ceil() as usize;
+
+    for row in 0..rows {
+        for column in 0..columns {
+            draw_rectangle(
+                column as f32 * CHECKER_SIZE,
+                row as f32 * CHECKER_SIZE,
+                CHECKER_SIZE,
+                CHECKER_SIZE,
+                CHECKER_COLORS[(row + column) % 2],
+            );
+        }
+    }
+}
+
+/// Where vertex labels go relative to their circle.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum VertexLabelPlacement {
+    #[default]
+    Inside,
+    /// Beyond the border, in [`VertexDrawConfig::outside_label_direction`].
+    Outside,
+    /// Inside when the label fits in the main circle, outside otherwise.
+    Auto,
+}
+
+impl VertexLabelPlacement {
+    pub const ALL: [VertexLabelPlacement; 3] = [
+        VertexLabelPlacement::Inside,
+        VertexLabelPlacement::Outside,
+        VertexLabelPlacement::Auto,
+    ];
+    pub const NAMES: [&'static str; 3] = ["Inside", "Outside", "Auto"];
+
+    pub fn index(&self) -> usize {
+        Self::ALL
+            .iter()
+            .position(|placement| placement == self)
+            .unwrap()
+    }
+
+    /// Whether a label with a bounding box of `size` goes inside a vertex with `main_radius`.
+    pub fn is_inside(&self, size: Vec2, main_radius: f32) -> bool {
+        match self {
+            VertexLabelPlacement::Inside => true,
+            VertexLabelPlacement::Outside => false,
+            VertexLabelPlacement::Auto => (size / 2.0).length() <= main_radius,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LevelOfDetail {
+    Full,
+    /// No labels, thin edges without highlighting, and only every `edge_step`th edge.
+    Reduced {
+        edge_step: usize,
+    },
+}
+
+#[derive(Clone, PartialEq)]
 pub struct VertexDrawConfig {
     pub main_color: Color,
     pub border_color: Color,
     pub main_size: f32,
     pub border_size: f32,
     pub highlight_color: Color,
+    pub highlight_border_color: Color,
     pub unhighlight_color: Color,
-    pub drag_color: Color,
+    pub unhighlight_border_color: Color,
+    pub outline_width: f32,
     pub draw_index: bool,
-    pub zero_indexed: bool,
+    pub avoid_label_overlap: bool,
+    pub svg_stroke: bool,
+    /// Draw the border of vertices without edges dashed.
+    pub dash_isolated: bool,
     pub label_color: Color,
+    pub highlight_label_color: Color,
+    pub unhighlight_label_color: Color,
+    /// Choose black or white labels from the brightness of the vertex instead of the colors above.
+    pub auto_label_contrast: bool,
     pub label_size: f32,
+    /// Outline labels in SVG exports so they stand out over edges.
+    pub label_halo: bool,
+    pub label_halo_color: Color,
+    pub label_placement: VertexLabelPlacement,
+    /// Shrink labels inside their vertex until they are no wider than it.
+    pub fit_labels: bool,
+    /// Direction of labels outside their vertex, in degrees counterclockwise from the right.
+    pub outside_label_angle: f32,
+    /// Extra distance between a label outside its vertex and the border.
+    pub outside_label_offset: f32,
+}
+
+impl VertexDrawConfig {
+    /// Radius of a vertex without a radius of its own.
+    pub fn radius(&self) -> f32 {
+        self.main_size + self.border_size
+    }
+
+    /// Scales the main and the border size together, as far as the main size stays in bounds.
+    pub fn scale_size(&mut self, factor: f32) {
+        let main_size = (self.main_size * factor).clamp(MIN_VERTEX_SIZE, MAX_VERTEX_SIZE);
+        self.border_size *= main_size / self.main_size;
+        self.main_size = main_size;
+    }
+
+    pub fn outside_label_direction(&self) -> Vec2 {
+        let angle = self.outside_label_angle.to_radians();
+        // the y axis points down
+        vec2(angle.cos(), -angle.sin())
+    }
+
+    /// Label color on a vertex filled with `background`, `label_color` unless automatic
+    /// contrast is enabled.
+    pub fn label_color_on(&self, background: Color, label_color: Color) -> Color {
+        if self.auto_label_contrast {
+            contrasting_label_color(background)
+        } else {
+            label_color
+        }
+    }
 }
 
 impl Default for VertexDrawConfig {
@@ -349,25 +1123,59 @@ impl Default for VertexDrawConfig {
             main_size: 12.0,
             border_size: 5.0,
             highlight_color: LIME,
+            highlight_border_color: DARKGREEN,
             unhighlight_color: MAROON,
-            drag_color: DARKBLUE,
+            unhighlight_border_color: DARKBROWN,
+            outline_width: 4.0,
             draw_index: true,
-            zero_indexed: false,
+            avoid_label_overlap: false,
+            svg_stroke: false,
+            dash_isolated: false,
             label_color: BLACK,
+            highlight_label_color: BLACK,
+            unhighlight_label_color: WHITE,
+            auto_label_contrast: false,
             label_size: 35.0,
+            label_halo: false,
+            label_halo_color: WHITE,
+            label_placement: VertexLabelPlacement::default(),
+            fit_labels: true,
+            outside_label_angle: 45.0,
+            outside_label_offset: 0.0,
         }
     }
 }
 
+#[derive(Clone, PartialEq)]
 pub struct EdgeDrawConfig {
     pub width: f32,
     pub color: Color,
     pub highlight_color: Color,
     pub unhighlight_color: Color,
+    pub outline_width: f32,
     pub draw_index: bool,
-    pub zero_indexed: bool,
     pub label_color: Color,
     pub label_size: f32,
+    pub draw_path_arrows: bool,
+    pub arrow_size: f32,
+    pub width_by_weight: bool,
+    pub min_weight_width: f32,
+    pub max_weight_width: f32,
+    pub hidden_vertex_edges: HiddenVertexEdges,
+    pub emphasize_highlighted_vertex_edges: bool,
+    /// Draw edges that are further than `length_tolerance` from their target length in
+    /// `length_warning_color`.
+    pub warn_length_deviation: bool,
+    pub length_tolerance: f32,
+    pub length_warning_color: Color,
+    /// How far edges bend to the side, relative to their length. Straight when zero.
+    pub curvature: f32,
+}
+
+impl EdgeDrawConfig {
+    pub fn scale_width(&mut self, factor: f32) {
+        self.width = (self.width * factor).clamp(MIN_EDGE_WIDTH, MAX_EDGE_WIDTH);
+    }
 }
 
 impl Default for EdgeDrawConfig {
@@ -377,10 +1185,643 @@ impl Default for EdgeDrawConfig {
             color: BLACK,
             highlight_color: MAROON,
             unhighlight_color: LIGHTGRAY,
+            outline_width: 3.0,
             draw_index: false,
-            zero_indexed: false,
             label_color: BLUE,
             label_size: 40.0,
+            draw_path_arrows: true,
+            arrow_size: 15.0,
+            width_by_weight: false,
+            min_weight_width: 1.0,
+            max_weight_width: 10.0,
+            hidden_vertex_edges: HiddenVertexEdges::default(),
+            emphasize_highlighted_vertex_edges: false,
+            warn_length_deviation: false,
+            length_tolerance: 1.0,
+            length_warning_color: RED,
+            curvature: 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vertex_style_all_states() {
+        let config = VertexDrawConfig::default();
+
+        let default_style = VertexStyle {
+            main_color: config.main_color,
+            border_color: config.border_color,
+            label_color: config.label_color,
+        };
+        let highlight_style = VertexStyle {
+            main_color: config.highlight_color,
+            border_color: config.highlight_border_color,
+            label_color: config.highlight_label_color,
+        };
+        let unhighlight_style = VertexStyle {
+            main_color: config.unhighlight_color,
+            border_color: config.unhighlight_border_color,
+            label_color: config.unhighlight_label_color,
+        };
+        let hidden_style = VertexStyle {
+            main_color: Color::new(0.0, 0.0, 0.0, 0.0),
+            border_color: Color::new(0.0, 0.0, 0.0, 0.0),
+            label_color: Color::new(0.0, 0.0, 0.0, 0.0),
+        };
+
+        let cases = [
+            (DrawState::Default, default_style),
+            (DrawState::Highlighted, highlight_style),
+            (DrawState::Unhighlighted, unhighlight_style),
+            (DrawState::Hidden, hidden_style),
+        ];
+
+        for (state, style) in cases {
+            assert_eq!(vertex_style(state, &config), style);
+        }
+    }
+
+    #[test]
+    fn test_interaction_adds_outline_and_keeps_colors() {
+        let mut graph = Graph::new(3);
+        graph.edges.extend([(0, 1), (1, 2)]);
+
+        let config = DrawConfig::default();
+        let vertex_outline = Some(Outline {
+            color: config.interaction_color,
+            width: config.vertex_config.outline_width,
+        });
+        let edge_outline = Some(Outline {
+            color: config.interaction_color,
+            width: config.edge_config.outline_width,
+        });
+
+        for state in DrawState::ALL {
+            // none, hover, drag, vertex selection, edge selection
+            for interaction in 0..5 {
+                let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+                embedding.vertex_properties[1].draw_state = state;
+                embedding.edge_properties[0].draw_state = state;
+                match interaction {
+                    1 => {
+                        embedding.hovered_vertex = Some(1);
+                        embedding.hovered_edge = Some(0);
+                    }
+                    2 => embedding.dragged_vertex = Some(1),
+                    3 => embedding.selected = Some(Selection::Vertex(1)),
+                    4 => embedding.selected = Some(Selection::Edge(0)),
+                    _ => {}
+                }
+
+                let drawable = DrawableGraph::compose(&embedding, &config);
+                let vertex = drawable.vertices.iter().find(|vertex| vertex.index == 1);
+                let edge = drawable
+                    .edges
+                    .iter()
+                    .find(|edge| edge.start == embedding.get_position(0));
+
+                let vertex_interacted = matches!(interaction, 1..=3);
+                let edge_interacted = matches!(interaction, 1 | 4);
+
+                let expected_style = vertex_style(state, &config.vertex_config);
+                match vertex {
+                    Some(vertex) => {
+                        assert_eq!(vertex.main_color, expected_style.main_color);
+                        assert_eq!(vertex.border_color, expected_style.border_color);
+                        let outline = if vertex_interacted {
+                            vertex_outline
+                        } else {
+                            None
+                        };
+                        assert_eq!(vertex.outline, outline, "{:?} {}", state, interaction);
+                    }
+                    None => assert!(state == DrawState::Hidden && !vertex_interacted),
+                }
+
+                match edge {
+                    Some(edge) => {
+                        let expected_color = match state {
+                            DrawState::Default => config.edge_config.color,
+                            DrawState::Highlighted => config.edge_config.highlight_color,
+                            DrawState::Unhighlighted => config.edge_config.unhighlight_color,
+                            DrawState::Hidden => Color::new(0.0, 0.0, 0.0, 0.0),
+                        };
+                        assert_eq!(edge.color, expected_color);
+                        assert_eq!(edge.width, config.edge_config.width);
+                        let outline = if edge_interacted { edge_outline } else { None };
+                        assert_eq!(edge.outline, outline, "{:?} {}", state, interaction);
+                    }
+                    None => assert!(state == DrawState::Hidden && !edge_interacted),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_edges_of_hidden_vertices() {
+        let graph = Graph::path(3);
+        let mut config = DrawConfig::default();
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+        embedding.vertex_properties[0].draw_state = DrawState::Hidden;
+
+        assert_eq!(DrawableGraph::compose(&embedding, &config).edges.len(), 2);
+
+        config.edge_config.hidden_vertex_edges = HiddenVertexEdges::Fade;
+        let drawable = DrawableGraph::compose(&embedding, &config);
+        assert_eq!(drawable.edges[0].color.a, FADED_EDGE_ALPHA);
+        assert_eq!(drawable.edges[1].color.a, 1.0);
+
+        config.edge_config.hidden_vertex_edges = HiddenVertexEdges::Hide;
+        let drawable = DrawableGraph::compose(&embedding, &config);
+        assert_eq!(drawable.edges.len(), 1);
+        assert_eq!(drawable.edges[0].start, embedding.get_position(1));
+    }
+
+    #[test]
+    fn test_off_target_edges_get_warning_color() {
+        let graph = Graph::path(3);
+        let mut config = DrawConfig::default();
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+        let length = embedding.edge_length(0).unwrap();
+        embedding.edge_properties[0].target_length = Some(length + 0.5);
+        embedding.edge_properties[1].target_length = Some(length + 5.0);
+
+        let colors = |embedding: &GraphInterface, config: &DrawConfig| -> Vec<Color> {
+            let drawable = DrawableGraph::compose(embedding, config);
+            drawable.edges.iter().map(|edge| edge.color).collect()
+        };
+        assert_eq!(colors(&embedding, &config), vec![BLACK, BLACK]);
+
+        config.edge_config.warn_length_deviation = true;
+        assert_eq!(colors(&embedding, &config), vec![BLACK, RED]);
+    }
+
+    #[test]
+    fn test_level_of_detail() {
+        let mut config = DrawConfig {
+            lod_edge_threshold: 1000,
+            ..Default::default()
+        };
+
+        assert_eq!(config.level_of_detail(1000, false), LevelOfDetail::Full);
+        assert_eq!(
+            config.level_of_detail(1001, false),
+            LevelOfDetail::Reduced { edge_step: 1 }
+        );
+
+        config.lod_subsample = true;
+        assert_eq!(
+            config.level_of_detail(2500, false),
+            LevelOfDetail::Reduced { edge_step: 3 }
+        );
+        // every edge is shown once the layout stands still
+        assert_eq!(
+            config.level_of_detail(2500, true),
+            LevelOfDetail::Reduced { edge_step: 1 }
+        );
+
+        config.quality = DrawQuality::Full;
+        assert_eq!(config.level_of_detail(2500, false), LevelOfDetail::Full);
+
+        // fast quality reduces small graphs too, but never leaves out their edges
+        config.quality = DrawQuality::Fast;
+        assert_eq!(
+            config.level_of_detail(10, false),
+            LevelOfDetail::Reduced { edge_step: 1 }
+        );
+    }
+
+    #[test]
+    fn test_reduced_detail_composition() {
+        let graph = Graph::cycle(6);
+        let mut config = DrawConfig::default();
+        config.vertex_config.draw_index = true;
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+        embedding.edge_properties[0].draw_state = DrawState::Highlighted;
+
+        let drawable = DrawableGraph::compose_with_detail(
+            &embedding,
+            &config,
+            LevelOfDetail::Reduced { edge_step: 2 },
+        );
+
+        assert_eq!(drawable.edges.len(), 3);
+        assert!(drawable
+            .edges
+            .iter()
+            .all(|edge| edge.width == REDUCED_EDGE_WIDTH
+                && edge.color == config.edge_config.color
+                && edge.label.is_none()));
+        assert!(drawable
+            .vertices
+            .iter()
+            .all(|vertex| vertex.label.is_none()));
+
+        // full detail is unaffected by the quality setting
+        config.quality = DrawQuality::Fast;
+        let drawable = DrawableGraph::compose(&embedding, &config);
+        assert_eq!(drawable.edges.len(), 6);
+        assert!(drawable
+            .vertices
+            .iter()
+            .all(|vertex| vertex.label.is_some()));
+    }
+
+    #[test]
+    fn test_label_contrast() {
+        assert_eq!(contrasting_label_color(DARKBLUE), WHITE);
+        assert_eq!(contrasting_label_color(MAROON), WHITE);
+        assert_eq!(contrasting_label_color(SKYBLUE), BLACK);
+        assert_eq!(contrasting_label_color(YELLOW), BLACK);
+
+        let mut config = VertexDrawConfig::default();
+        assert_eq!(config.label_color_on(DARKBLUE, RED), RED);
+        config.auto_label_contrast = true;
+        assert_eq!(config.label_color_on(DARKBLUE, RED), WHITE);
+    }
+
+    #[test]
+    fn test_arrow_head_points_at_target_border() {
+        let [tip, left, right] = arrow_head(vec2(0.0, 0.0), vec2(100.0, 0.0), 10.0, 20.0).unwrap();
+
+        assert_eq!(tip, vec2(90.0, 0.0));
+        assert_eq!(left.x, 70.0);
+        assert_eq!(right.x, 70.0);
+        assert_eq!(left.y, -right.y);
+
+        assert!(arrow_head(vec2(1.0, 1.0), vec2(1.0, 1.0), 10.0, 20.0).is_none());
+    }
+
+    #[test]
+    fn test_curve_points() {
+        let (start, end) = (vec2(0.0, 0.0), vec2(100.0, 0.0));
+        assert_eq!(curve_control(start, end, 0.0), None);
+        assert_eq!(curve_control(start, start, 0.5), None);
+
+        let control = curve_control(start, end, -0.5).unwrap();
+        assert_eq!(control, vec2(50.0, -50.0));
+
+        let points = curve_points(start, control, end);
+        assert_eq!(points.len(), CURVE_SEGMENTS + 1);
+        assert_eq!(points[0], start);
+        assert_eq!(points[CURVE_SEGMENTS / 2], vec2(50.0, -25.0));
+        assert_eq!(points[CURVE_SEGMENTS], end);
+    }
+
+    #[test]
+    fn test_scaled_sizes_stay_in_bounds() {
+        let mut vertex_config = VertexDrawConfig::default();
+        vertex_config.scale_size(2.0);
+        assert_eq!(
+            (vertex_config.main_size, vertex_config.border_size),
+            (24.0, 10.0)
+        );
+        assert_eq!(vertex_config.radius(), 34.0);
+
+        // the border keeps its proportion when the main size hits a bound
+        vertex_config.scale_size(100.0);
+        assert_eq!(vertex_config.main_size, MAX_VERTEX_SIZE);
+        assert_eq!(vertex_config.border_size, 25.0);
+        vertex_config.scale_size(0.0001);
+        assert_eq!(vertex_config.main_size, MIN_VERTEX_SIZE);
+
+        let mut edge_config = EdgeDrawConfig::default();
+        edge_config.scale_width(1.0 / 1000.0);
+        assert_eq!(edge_config.width, MIN_EDGE_WIDTH);
+        edge_config.scale_width(1000.0);
+        assert_eq!(edge_config.width, MAX_EDGE_WIDTH);
+    }
+
+    #[test]
+    fn test_anti_parallel_pair_bends_apart() {
+        use crate::svg_writer::SVGItem;
+
+        let mut graph = Graph::path(2);
+        graph.set_orientation(0, 1, crate::graph::Orientation::Forward);
+        graph.add_reverse_edge(0, 1).unwrap();
+        let positions = [vec2(0.0, 0.0), vec2(100.0, 0.0)];
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+        embedding.set_positions(&positions);
+        let config = DrawConfig::default();
+
+        let exported = DrawableGraph::from_positions(&graph, &positions, &config);
+        let composed = DrawableGraph::compose(&embedding, &config);
+
+        for drawable in [&exported, &composed] {
+            let [forward, backward] = &drawable.edges[..] else {
+                panic!("expected two edges");
+            };
+            assert_eq!((forward.start, forward.end), (positions[0], positions[1]));
+            assert_eq!((backward.start, backward.end), (positions[1], positions[0]));
+            assert!(forward.control.unwrap().abs_diff_eq(vec2(50.0, 15.0), 1e-4));
+            assert!(backward
+                .control
+                .unwrap()
+                .abs_diff_eq(vec2(50.0, -15.0), 1e-4));
+            assert!(forward.arrow.unwrap()[0].x > 50.0);
+            assert!(backward.arrow.unwrap()[0].x < 50.0);
+        }
+
+        let svg = exported.to_svg_string();
+        assert_eq!(svg.matches("<path").count(), 2);
+        assert!(!svg.contains("<line"));
+    }
+
+    #[test]
+    fn test_from_positions_triangle() {
+        let mut graph = Graph::new(3);
+        graph.edges.push((0, 1));
+        graph.edges.push((1, 2));
+        graph.edges.push((0, 2));
+
+        let positions = [vec2(0.0, 0.0), vec2(100.0, 0.0), vec2(50.0, 80.0)];
+        let drawable = DrawableGraph::from_positions(&graph, &positions, &DrawConfig::default());
+
+        assert_eq!(drawable.vertices.len(), 3);
+        assert_eq!(drawable.edges.len(), 3);
+        assert_eq!(drawable.edges[1].start, positions[1]);
+        assert_eq!(drawable.edges[1].end, positions[2]);
+        assert!(drawable.vertices.iter().any(|v| v.position == positions[2]));
+    }
+
+    #[test]
+    fn test_from_positions_follows_config() {
+        let mut graph = Graph::new(2);
+        graph.edges.push((0, 1));
+        graph.set_orientation(0, 1, crate::graph::Orientation::Forward);
+        let mut config = DrawConfig::default();
+        config.vertex_config.scale_size(2.0);
+        config.vertex_config.main_color = RED;
+        config.edge_config.width = 7.0;
+        config.edge_config.color = BLUE;
+
+        let drawable =
+            DrawableGraph::from_positions(&graph, &[vec2(0.0, 0.0), vec2(200.0, 0.0)], &config);
+
+        for vertex in &drawable.vertices {
+            assert_eq!(vertex.main_radius, config.vertex_config.main_size);
+            assert_eq!(vertex.border_radius, config.vertex_config.radius());
+            assert_eq!(vertex.main_color, RED);
+        }
+        let edge = &drawable.edges[0];
+        assert_eq!((edge.width, edge.color), (7.0, BLUE));
+        // the arrow ends at the border of the scaled vertex
+        let tip = edge.arrow.unwrap()[0];
+        assert!((tip.x - (200.0 - config.vertex_config.radius())).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_dash_segments() {
+        let segments = dash_segments(vec2(0.0, 0.0), vec2(25.0, 0.0), 10.0, 5.0);
+
+        assert_eq!(
+            segments,
+            vec![
+                (vec2(0.0, 0.0), vec2(10.0, 0.0)),
+                (vec2(15.0, 0.0), vec2(25.0, 0.0)),
+            ]
+        );
+        assert!(dash_segments(vec2(1.0, 1.0), vec2(1.0, 1.0), 10.0, 5.0).is_empty());
+    }
+
+    #[test]
+    fn test_vertex_style_keeps_border() {
+        let config = VertexDrawConfig::default();
+
+        for state in [DrawState::Highlighted, DrawState::Unhighlighted] {
+            let style = vertex_style(state, &config);
+            assert_ne!(style.main_color, style.border_color);
+        }
+    }
+
+    #[test]
+    fn test_compose_uses_width_override() {
+        let mut graph = Graph::new(3);
+        graph.edges.push((0, 1));
+        graph.edges.push((1, 2));
+
+        let config = DrawConfig::default();
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+        embedding.edge_properties[1].width_override = Some(7.0);
+        embedding.resolve_edge_widths(&config.edge_config);
+
+        let drawable = DrawableGraph::compose(&embedding, &config);
+
+        assert_eq!(drawable.edges[0].width, config.edge_config.width);
+        assert_eq!(drawable.edges[1].width, 7.0);
+    }
+
+    #[test]
+    fn test_accumulated_highlights_get_distinct_colors() {
+        let mut graph = Graph::new(4);
+        graph.edges.extend([(0, 1), (1, 2), (2, 3)]);
+
+        let config = DrawConfig::default();
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+        for edges in [vec![(0, 1)], vec![(1, 2)]] {
+            let mut highlight = Graph::new(4);
+            highlight.edges = edges;
+            embedding.add_edge_highlighting_and_add_to_history(highlight);
+        }
+
+        let drawable = DrawableGraph::compose(&embedding, &config);
+        let colors: Vec<Color> = drawable.edges.iter().map(|edge| edge.color).collect();
+
+        assert_eq!(colors[0], config.edge_config.highlight_color);
+        assert_eq!(colors[1], config.edge_highlight_color(1));
+        assert_ne!(colors[0], colors[1]);
+        assert_eq!(colors[2], config.edge_config.color);
+    }
+
+    #[test]
+    fn test_edge_highlight_colors_are_distinct() {
+        for palette in Palette::ALL {
+            let mut config = DrawConfig::default();
+            palette.apply(&mut config);
+
+            let colors: Vec<Color> = (0..20).map(|i| config.edge_highlight_color(i)).collect();
+            for i in 0..colors.len() {
+                for j in 0..i {
+                    assert_ne!(colors[i], colors[j], "{:?}: {} and {}", palette, i, j);
+                }
+            }
         }
     }
+
+    #[test]
+    fn test_index_labels_in_both_modes() {
+        assert_eq!(index_label(0, Indexing::Zero), "0");
+        assert_eq!(index_label(0, Indexing::One), "1");
+
+        // edge (1, 2) is the third edge of the complete graph ordering
+        assert_eq!(edge_index_label((2, 1), Indexing::Zero), "2");
+        assert_eq!(edge_index_label((1, 2), Indexing::One), "3");
+    }
+
+    #[test]
+    fn test_auto_label_placement() {
+        let config = VertexDrawConfig {
+            label_size: 20.0,
+            ..Default::default()
+        };
+        let fits = |characters: usize, main_radius: f32| {
+            let label = vertex_label(
+                "8".repeat(characters),
+                Vec2::ZERO,
+                (main_radius, main_radius),
+                &config,
+                (BLACK, BLACK),
+            );
+            VertexLabelPlacement::Auto.is_inside(label_size(&label), main_radius)
+        };
+
+        // (characters, main radius, inside)
+        let cases = [
+            (1, 10.0, true),
+            (2, 10.0, false),
+            (2, 12.0, true),
+            (3, 12.0, false),
+            (3, 20.0, true),
+            (1, 5.0, false),
+        ];
+        for (characters, main_radius, inside) in cases {
+            assert_eq!(
+                fits(characters, main_radius),
+                inside,
+                "{} {}",
+                characters,
+                main_radius
+            );
+        }
+
+        assert!(VertexLabelPlacement::Inside.is_inside(vec2(100.0, 100.0), 1.0));
+        assert!(!VertexLabelPlacement::Outside.is_inside(Vec2::ZERO, 100.0));
+    }
+
+    #[test]
+    fn test_outside_labels_contrast_with_the_background() {
+        let config = VertexDrawConfig {
+            label_size: 20.0,
+            label_placement: VertexLabelPlacement::Auto,
+            ..Default::default()
+        };
+        let draw_config = DrawConfig::default();
+        let colors = (WHITE, draw_config.outside_label_color());
+        let label = |content: &str| {
+            vertex_label(
+                content.to_string(),
+                Vec2::ZERO,
+                (10.0, 12.0),
+                &config,
+                colors,
+            )
+        };
+
+        assert_eq!(label("1").color, WHITE);
+        // too long for the vertex, so it sits on the light background
+        assert_eq!(label("1234").color, BLACK);
+    }
+
+    #[test]
+    fn test_edge_labels_clear_the_edge() {
+        let config = EdgeDrawConfig::default();
+        let label_rect = |start: Vec2, end: Vec2| {
+            let label = edge_label("a long label".to_string(), start, end, None, &config);
+            let size = label_size(&label);
+            Rect::new(label.position.x, label.position.y - size.y, size.x, size.y)
+        };
+
+        // above a horizontal edge, centered on its middle
+        let rect = label_rect(vec2(0.0, 100.0), vec2(200.0, 100.0));
+        assert!(rect.y + rect.h < 100.0 - config.width / 2.0);
+        assert!((rect.center().x - 100.0).abs() < 0.001);
+
+        // beside a vertical edge, however long the label
+        let rect = label_rect(vec2(100.0, 0.0), vec2(100.0, 200.0));
+        assert!(
+            rect.x > 100.0 + config.width / 2.0 || rect.x + rect.w < 100.0 - config.width / 2.0
+        );
+        assert!((rect.center().y - 100.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_long_labels_shrink_to_fit() {
+        let config = VertexDrawConfig {
+            main_size: 12.0,
+            border_size: 3.0,
+            label_size: 35.0,
+            ..Default::default()
+        };
+        let position = vec2(100.0, 100.0);
+        let radii = (config.main_size, config.radius());
+
+        // three characters at half a size each fit the diameter of 30 at size 20
+        let label = vertex_label("123".to_string(), position, radii, &config, (BLACK, BLACK));
+        assert!((label.size - 20.0).abs() < 0.001);
+        assert!(label_size(&label).x <= 2.0 * config.radius() + 0.001);
+        let center = label.position + label_size(&label) * vec2(0.5, -0.5);
+        assert!(center.abs_diff_eq(position, 0.001));
+
+        // short labels keep the configured size
+        let label = vertex_label("7".to_string(), position, radii, &config, (BLACK, BLACK));
+        assert_eq!(label.size, 35.0);
+
+        let config = VertexDrawConfig {
+            fit_labels: false,
+            ..config
+        };
+        let label = vertex_label("123".to_string(), position, radii, &config, (BLACK, BLACK));
+        assert_eq!(label.size, 35.0);
+    }
+
+    #[test]
+    fn test_outside_labels_clear_the_vertex() {
+        let graph = Graph::new(1);
+        let position = vec2(200.0, 200.0);
+        let mut config = DrawConfig::default();
+        config.vertex_config.label_size = 20.0;
+
+        let inside = DrawableGraph::from_positions(&graph, &[position], &config);
+        let inside = inside.vertices[0].label.as_ref().unwrap().position;
+        assert!(inside.distance(position) < 10.0);
+
+        config.vertex_config.label_placement = VertexLabelPlacement::Outside;
+        let drawable = DrawableGraph::from_positions(&graph, &[position], &config);
+        let vertex = &drawable.vertices[0];
+        let label = vertex.label.as_ref().unwrap();
+        let rect = candidate_rect(label, vertex.position, vertex.border_radius, Vec2::ZERO);
+
+        assert!(!crate::label_placement::circle_intersects_rect(
+            position,
+            config.vertex_config.main_size + config.vertex_config.border_size,
+            rect
+        ));
+        // up and to the right
+        assert!(rect.x > position.x && rect.y + rect.h < position.y);
+    }
+
+    #[test]
+    fn test_outside_label_offset_moves_label_outward() {
+        let graph = Graph::new(1);
+        let position = vec2(200.0, 200.0);
+        let mut config = DrawConfig::default();
+        config.vertex_config.label_placement = VertexLabelPlacement::Outside;
+        config.vertex_config.outside_label_angle = 90.0;
+        let label_position = |config: &DrawConfig| {
+            let drawable = DrawableGraph::from_positions(&graph, &[position], config);
+            drawable.vertices[0].label.as_ref().unwrap().position
+        };
+
+        let close = label_position(&config);
+        config.vertex_config.outside_label_offset = 15.0;
+        let offset = label_position(&config);
+
+        // straight up, fifteen pixels further
+        assert!((offset.x - close.x).abs() < 0.001);
+        assert!((offset.y - (close.y - 15.0)).abs() < 0.001);
+    }
 }
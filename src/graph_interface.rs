@@ -1,18 +1,53 @@
-use std::time::{Duration, Instant};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 use macroquad::{prelude::*, rand};
 
 use crate::{
-    graph::Graph,
-    graph_drawer::{EdgeDrawConfig, VertexDrawConfig},
+    annotations::distance_to_segment,
+    edge_labels::canonical_edge,
+    force::ForceConfig,
+    graph::{anti_parallel_edges, edge_order, path_edges, Graph, Orientation, Subgraph},
+    graph_drawer::{
+        curve_control, curve_points, EdgeDrawConfig, VertexDrawConfig, ANTI_PARALLEL_CURVATURE,
+    },
     grid::{CircleGrid, SquareGrid},
-    ui_manager::main_screen_width,
+    input::{InputConfig, InputEvents},
+    label_placement::segments_intersect,
+    lanes::LaneConfig,
+    rng::SeededRng,
+    spatial_index::SpatialIndex,
+    viewport::{ResizeMode, ViewportChange},
 };
 
+const HOVER_CELL_SIZE: f32 = 64.0;
+const HOVER_INDEX_MIN_ITEMS: usize = 32;
+/// Graphs with more edges only hover vertices, hit testing the edges of near-complete graphs
+/// every frame is too slow.
+pub const EDGE_HOVER_LIMIT: usize = 2000;
+const AXIS_LOCK_THRESHOLD: f32 = 4.0;
+/// Smallest distance between consecutive points of a lasso.
+const LASSO_POINT_SPACING: f32 = 4.0;
+/// Fraction of the distance to the centroid an isolated vertex moves per layout step.
+const ISOLATED_GRAVITY: f32 = 0.05;
+/// Largest offset in pixels of the symmetry breaking nudge, see [`GraphInterface::start_force`].
+const SYMMETRY_BREAKING_OFFSET: f32 = 1.0;
+
+/// Hovered vertex and hovered edge at a position.
+pub type HitTestResult = (Option<usize>, Option<usize>);
+
+#[derive(Clone, PartialEq)]
 pub struct VertexProperties {
     pub position: Vec2,
+    /// Effective radius, see [`GraphInterface::resolve_vertex_radii`].
     pub radius: f32,
+    pub radius_override: Option<f32>,
     pub draw_state: DrawState,
+    pub color_override: Option<Color>,
+    pub label: Option<String>,
+    pub pinned: bool,
+    pub frozen: bool,
+    /// See [`crate::lanes::LaneConfig`].
+    pub lane: Option<usize>,
 }
 
 impl VertexProperties {
@@ -32,16 +67,37 @@ impl Default for VertexProperties {
 
         Self {
             position: Vec2::ZERO,
-            radius: vertex_config.main_size + vertex_config.border_size,
+            radius: vertex_config.radius(),
+            radius_override: None,
             draw_state: DrawState::Default,
+            color_override: None,
+            label: None,
+            pinned: false,
+            frozen: false,
+            lane: None,
         }
     }
 }
 
+#[derive(Clone, PartialEq)]
 pub struct EdgeProperties {
     pub vertices: (usize, usize),
+    /// Effective width, see [`GraphInterface::resolve_edge_widths`].
     pub width: f32,
+    pub width_override: Option<f32>,
+    pub weight: Option<f32>,
     pub draw_state: DrawState,
+    /// Color of a highlighted edge, see [`crate::graph_drawer::DrawConfig::edge_highlight_color`].
+    pub highlight_color_index: usize,
+    /// Replaces the highlight color, see [`HighlightEntry::color_override`].
+    pub highlight_color_override: Option<Color>,
+    pub path_direction: Option<(usize, usize)>,
+    pub orientation: Orientation,
+    pub style: EdgeStyle,
+    pub color_override: Option<Color>,
+    pub label: Option<String>,
+    /// Length the layout keeps the edge at, see [`GraphInterface::project_edge_lengths`].
+    pub target_length: Option<f32>,
 }
 
 impl EdgeProperties {
@@ -62,12 +118,22 @@ impl Default for EdgeProperties {
         Self {
             vertices: (0, 0),
             width: config.width,
+            width_override: None,
+            weight: None,
             draw_state: DrawState::Default,
+            highlight_color_index: 0,
+            highlight_color_override: None,
+            path_direction: None,
+            orientation: Orientation::Undirected,
+            style: EdgeStyle::Solid,
+            color_override: None,
+            label: None,
+            target_length: None,
         }
     }
 }
 
-#[derive(PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum DrawState {
     Default,
     Highlighted,
@@ -75,35 +141,144 @@ pub enum DrawState {
     Hidden,
 }
 
+impl DrawState {
+    pub const ALL: [DrawState; 4] = [
+        DrawState::Default,
+        DrawState::Highlighted,
+        DrawState::Unhighlighted,
+        DrawState::Hidden,
+    ];
+    pub const NAMES: [&'static str; 4] = ["Default", "Highlighted", "Unhighlighted", "Hidden"];
+
+    pub fn index(&self) -> usize {
+        Self::ALL.iter().position(|state| state == self).unwrap()
+    }
+}
+
+/// How the edges of a hidden vertex are drawn.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum HiddenVertexEdges {
+    #[default]
+    Keep,
+    Fade,
+    Hide,
+}
+
+impl HiddenVertexEdges {
+    pub const ALL: [HiddenVertexEdges; 3] = [
+        HiddenVertexEdges::Keep,
+        HiddenVertexEdges::Fade,
+        HiddenVertexEdges::Hide,
+    ];
+    pub const NAMES: [&'static str; 3] = ["Keep", "Fade", "Hide"];
+
+    pub fn index(&self) -> usize {
+        Self::ALL.iter().position(|rule| rule == self).unwrap()
+    }
+}
+
+/// Appearance an edge takes on from the draw states of its endpoints.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InducedEdgeState {
+    Hidden,
+    Faded,
+    Emphasized,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EdgeStyle {
+    Solid,
+    Dashed,
+}
+
+impl EdgeStyle {
+    pub const ALL: [EdgeStyle; 2] = [EdgeStyle::Solid, EdgeStyle::Dashed];
+    pub const NAMES: [&'static str; 2] = ["Solid", "Dashed"];
+
+    pub fn index(&self) -> usize {
+        Self::ALL.iter().position(|style| style == self).unwrap()
+    }
+}
+
+/// A graph in the highlight history. The id stays with the entry when the history is reordered.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HighlightEntry {
+    pub id: usize,
+    pub name: String,
+    pub graph: Graph,
+    /// Palette color of the entry, see [`crate::graph_drawer::DrawConfig::edge_highlight_color`].
+    /// Assigned when the entry is added, so that it keeps its color when others are deleted or
+    /// moved.
+    pub color_index: usize,
+    pub color_override: Option<Color>,
+}
+
+/// Vertices drawn as a single meta-vertex, see [`GraphInterface::collapse`].
+pub struct CollapsedGroup {
+    /// The lowest member, which is drawn as the meta-vertex.
+    pub representative: usize,
+    /// In increasing order.
+    pub members: Vec<usize>,
+    /// Properties of the members and their edges from before the group was collapsed. They are
+    /// swapped with the current properties to expand the group.
+    vertices: Vec<VertexProperties>,
+    edges: Vec<(usize, EdgeProperties)>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Selection {
+    Vertex(usize),
+    Edge(usize),
+}
+
+/// Mouse interactions, collected until [`GraphInterface::drain_events`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InterfaceEvent {
+    VertexClicked(usize),
+    EdgeClicked(usize),
+    DragStarted(usize),
+    DragEnded(usize),
+}
+
 pub struct GraphInterface {
     pub vertex_properties: Vec<VertexProperties>,
     pub edge_properties: Vec<EdgeProperties>,
     pub dragged_vertex: Option<usize>,
     pub hovered_vertex: Option<usize>,
     pub hovered_edge: Option<usize>,
+    pub selected: Option<Selection>,
+    /// Vertices picked with box select, in increasing order.
+    pub selected_vertices: Vec<usize>,
     drag_state: Option<DragState>,
-    click_handler: ClickHandler,
-    highlight_graph_history: Vec<Graph>,
-    pub current_highlight_graph: Option<usize>,
+    selection_drag: Option<SelectionDrag>,
+    highlight_graph_history: Vec<HighlightEntry>,
+    next_highlight_id: usize,
+    /// Id of the shown history entry.
+    current_highlight: Option<usize>,
+    pub viewport: Vec2,
+    dirty: bool,
+    layout_displacement: f32,
+    hover_cache: HoverCache,
+    incident_edges: Vec<Vec<usize>>,
+    neighbourhood: Option<NeighbourhoodHighlight>,
+    edge_curvature: f32,
+    /// Per edge, whether there is an edge in the opposite direction.
+    anti_parallel: Vec<bool>,
+    collapsed_groups: Vec<CollapsedGroup>,
+    events: Vec<InterfaceEvent>,
+    /// See [`hit_distance`].
+    hit_tolerance: f32,
+    view_scale: Option<f32>,
+    hover_edges: bool,
 }
 
 impl GraphInterface {
-    pub fn new(graph: &Graph) -> Self {
-        let center = Vec2::new(main_screen_width() / 2.0, screen_height() / 2.0);
-        let tau_part = std::f32::consts::TAU / graph.vertices as f32;
-
+    pub fn with_viewport(graph: &Graph, viewport: Vec2) -> Self {
         let mut vertex_properties = Vec::with_capacity(graph.vertices);
 
-        let offset_magnitude = main_screen_width().min(screen_height()) / 2.0 - 50.0;
-
         for i in 0..graph.vertices {
-            let i = i as f32;
-            let x_offset = (i * tau_part).sin() * offset_magnitude;
-            let y_offset = -(i * tau_part).cos() * offset_magnitude;
-            let offset = Vec2::new(x_offset, y_offset);
-
             let properties = VertexProperties {
-                position: center + offset,
+                position: ring_position(i, graph.vertices, viewport),
                 ..Default::default()
             };
 
@@ -115,31 +290,54 @@ impl GraphInterface {
         for edge in &graph.edges {
             let properties = EdgeProperties {
                 vertices: *edge,
+                orientation: graph.orientation(edge.0, edge.1),
                 ..Default::default()
             };
 
             edge_properties.push(properties);
         }
 
-        GraphInterface {
+        let mut embedding = GraphInterface {
             vertex_properties,
             edge_properties,
             dragged_vertex: None,
             hovered_vertex: None,
             drag_state: None,
             hovered_edge: None,
-            click_handler: ClickHandler::new(),
+            selected: None,
+            selected_vertices: Vec::new(),
+            selection_drag: None,
             highlight_graph_history: Vec::new(),
-            current_highlight_graph: None,
-        }
+            next_highlight_id: 0,
+            current_highlight: None,
+            viewport,
+            dirty: true,
+            layout_displacement: f32::INFINITY,
+            hover_cache: HoverCache::new(),
+            incident_edges: Vec::new(),
+            neighbourhood: None,
+            edge_curvature: 0.0,
+            anti_parallel: Vec::new(),
+            collapsed_groups: Vec::new(),
+            events: Vec::new(),
+            hit_tolerance: InputConfig::default().hit_tolerance,
+            view_scale: None,
+            hover_edges: true,
+        };
+
+        embedding.rebuild_incident_edges();
+        embedding.rebuild_anti_parallel();
+        embedding
     }
 
     pub fn update_edges(&mut self, graph: &Graph) {
+        self.expand_all();
         let mut edge_properties = Vec::with_capacity(graph.edges.len());
 
         for edge in &graph.edges {
             let properties = EdgeProperties {
                 vertices: *edge,
+                orientation: graph.orientation(edge.0, edge.1),
                 ..Default::default()
             };
 
@@ -147,6 +345,281 @@ impl GraphInterface {
         }
 
         self.edge_properties = edge_properties;
+        self.mark_geometry_changed();
+
+        if let Some(Selection::Edge(_)) = self.selected {
+            self.selected = None;
+        }
+
+        self.update_vertex_count(graph.vertices);
+        self.rebuild_incident_edges();
+        self.rebuild_anti_parallel();
+        self.neighbourhood = None;
+    }
+
+    /// Mirrors [`Graph::subdivide_edge`]: the new vertex is placed at the midpoint of the edge
+    /// and both halves keep the properties of the original edge.
+    pub fn subdivide_edge(&mut self, a: usize, b: usize, new_vertex: usize) {
+        self.expand_all();
+        let Some(index) = self
+            .edge_properties
+            .iter()
+            .position(|properties| properties.vertices == (a, b) || properties.vertices == (b, a))
+        else {
+            return;
+        };
+
+        let original = self.edge_properties.remove(index);
+        let midpoint = (self.get_position(a) + self.get_position(b)) / 2.0;
+        self.set_position(new_vertex, midpoint);
+
+        // the original orientation as seen from a to b
+        let orientation = if original.vertices == (a, b) {
+            original.orientation
+        } else {
+            original.orientation.reversed()
+        };
+
+        for (vertices, orientation) in [
+            ((a, new_vertex), orientation),
+            ((b, new_vertex), orientation.reversed()),
+        ] {
+            self.edge_properties.push(EdgeProperties {
+                vertices,
+                orientation,
+                width: original.width,
+                width_override: original.width_override,
+                draw_state: original.draw_state,
+                style: original.style,
+                color_override: original.color_override,
+                ..Default::default()
+            });
+        }
+
+        if let Some(Selection::Edge(_)) = self.selected {
+            self.selected = None;
+        }
+
+        self.sort_edges();
+        self.mark_geometry_changed();
+    }
+
+    fn rebuild_incident_edges(&mut self) {
+        let mut incident_edges = vec![Vec::new(); self.vertex_properties.len()];
+
+        for (index, edge_properties) in self.edge_properties.iter().enumerate() {
+            let (a, b) = edge_properties.vertices;
+            for vertex in [a, b] {
+                if let Some(edges) = incident_edges.get_mut(vertex) {
+                    if !edges.contains(&index) {
+                        edges.push(index);
+                    }
+                }
+            }
+        }
+
+        self.incident_edges = incident_edges;
+    }
+
+    fn rebuild_anti_parallel(&mut self) {
+        let directions: Vec<Option<(usize, usize)>> = self
+            .edge_properties
+            .iter()
+            .map(|properties| properties.orientation.direction(properties.vertices))
+            .collect();
+
+        self.anti_parallel = anti_parallel_edges(&directions);
+        self.mark_geometry_changed();
+    }
+
+    /// Control point of the edge with the given global `curvature`. The two edges of an
+    /// anti-parallel pair bend away from each other instead.
+    pub fn edge_control(&self, edge: usize, curvature: f32) -> Option<Vec2> {
+        let properties = self.edge_properties.get(edge)?;
+
+        if self.anti_parallel.get(edge) == Some(&true) {
+            let (tail, head) = properties.orientation.direction(properties.vertices)?;
+            return curve_control(
+                self.get_position(tail),
+                self.get_position(head),
+                ANTI_PARALLEL_CURVATURE,
+            );
+        }
+
+        let (a, b) = properties.vertices;
+        curve_control(self.get_position(a), self.get_position(b), curvature)
+    }
+
+    /// Mirrors [`Graph::reconnect_edge`], the edge keeps its other properties.
+    pub fn reconnect_edge(&mut self, old: (usize, usize), new: (usize, usize)) {
+        self.expand_all();
+        let Some(properties) = self
+            .edge_properties
+            .iter_mut()
+            .find(|properties| properties.vertices == old)
+        else {
+            return;
+        };
+
+        properties.vertices = new;
+        self.sort_edges();
+    }
+
+    /// Adds an edge with its own default properties, see [`Graph::add_reverse_edge`].
+    pub fn add_edge(&mut self, vertices: (usize, usize), orientation: Orientation) {
+        self.expand_all();
+        self.edge_properties.push(EdgeProperties {
+            vertices,
+            orientation,
+            ..Default::default()
+        });
+
+        self.sort_edges();
+    }
+
+    /// Mirrors [`Graph::sort_edges`], so that the edges keep the indices of the graph. The
+    /// selected and hovered edge stay the same edge.
+    fn sort_edges(&mut self) {
+        let mut order: Vec<usize> = (0..self.edge_properties.len()).collect();
+        order.sort_unstable_by_key(|&index| edge_order(self.edge_properties[index].vertices));
+
+        let mut new_index = vec![0; order.len()];
+        for (new, &old) in order.iter().enumerate() {
+            new_index[old] = new;
+        }
+        self.edge_properties = order
+            .iter()
+            .map(|&old| self.edge_properties[old].clone())
+            .collect();
+
+        if let Some(Selection::Edge(edge)) = &mut self.selected {
+            *edge = new_index[*edge];
+        }
+        if let Some(edge) = &mut self.hovered_edge {
+            *edge = new_index[*edge];
+        }
+
+        self.rebuild_incident_edges();
+        self.rebuild_anti_parallel();
+        self.mark_geometry_changed();
+    }
+
+    /// Indices into [`Self::edge_properties`] of the edges incident to a vertex.
+    pub fn incident_edges(&self, vertex: usize) -> &[usize] {
+        self.incident_edges
+            .get(vertex)
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+
+    /// Highlights the edges incident to a vertex and its neighbours and adds the edges to the
+    /// highlight history. Toggling the same vertex again restores the previous vertex states.
+    pub fn toggle_neighbourhood_highlighting(&mut self, vertex: usize) {
+        if let Some(previous) = self.neighbourhood.take() {
+            for (neighbour, draw_state) in previous.vertex_states {
+                if let Some(properties) = self.vertex_properties.get_mut(neighbour) {
+                    properties.draw_state = draw_state;
+                }
+            }
+            self.clear_edge_highlighting();
+
+            if previous.vertex == vertex {
+                return;
+            }
+        }
+
+        if vertex >= self.vertex_properties.len() {
+            return;
+        }
+
+        let mut highlight = Graph::new(self.vertex_properties.len());
+        let mut neighbours = Vec::new();
+        for &edge in self.incident_edges(vertex) {
+            let (a, b) = self.edge_properties[edge].vertices;
+            highlight.edges.push((a, b));
+            neighbours.push(if a == vertex { b } else { a });
+        }
+        neighbours.sort_unstable();
+        neighbours.dedup();
+
+        // the neighbours and the vertices that lose their highlight
+        let vertex_states = self
+            .vertex_properties
+            .iter()
+            .enumerate()
+            .filter(|(vertex, properties)| {
+                neighbours.binary_search(vertex).is_ok()
+                    || properties.draw_state == DrawState::Highlighted
+            })
+            .map(|(vertex, properties)| (vertex, properties.draw_state))
+            .collect();
+
+        self.set_edge_highlighting_and_add_to_history(highlight);
+        self.set_vertex_highlighting(&neighbours);
+        self.neighbourhood = Some(NeighbourhoodHighlight {
+            vertex,
+            vertex_states,
+        });
+    }
+
+    /// Grows or shrinks the vertex properties to match the vertex count of a new graph,
+    /// keeping the positions of the vertices that remain.
+    fn update_vertex_count(&mut self, vertices: usize) {
+        self.vertex_properties.truncate(vertices);
+
+        // vertices that did not exist before are placed on the layout ring instead of the origin
+        for i in self.vertex_properties.len()..vertices {
+            self.vertex_properties.push(VertexProperties {
+                position: ring_position(i, vertices, self.viewport),
+                ..Default::default()
+            });
+        }
+
+        let is_removed = |vertex: Option<usize>| vertex.is_some_and(|v| v >= vertices);
+
+        if is_removed(self.hovered_vertex) {
+            self.hovered_vertex = None;
+        }
+        if is_removed(self.dragged_vertex) {
+            self.dragged_vertex = None;
+            self.drag_state = None;
+        }
+        if let Some(Selection::Vertex(vertex)) = self.selected {
+            if vertex >= vertices {
+                self.selected = None;
+            }
+        }
+        self.selected_vertices.retain(|&vertex| vertex < vertices);
+    }
+
+    /// Whether anything that affects drawing changed since the last [`Self::clear_dirty`].
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    pub fn mark_dirty(&mut self) {
+        self.mark_geometry_changed();
+    }
+
+    /// Positions or sizes changed, so the hover cache has to be rebuilt.
+    fn mark_geometry_changed(&mut self) {
+        self.dirty = true;
+        self.hover_cache.query = None;
+        self.hover_cache.index_valid = false;
+    }
+
+    pub fn clear_dirty(&mut self) {
+        self.dirty = false;
+    }
+
+    pub fn handle_viewport_change(&mut self, change: ViewportChange, mode: ResizeMode) {
+        self.viewport = change.new;
+
+        for properties in self.vertex_properties.iter_mut() {
+            properties.position = mode.apply(properties.position, change);
+        }
+
+        self.mark_geometry_changed();
     }
 
     pub fn get_position(&self, vertex: usize) -> Vec2 {
@@ -156,8 +629,25 @@ impl GraphInterface {
             .unwrap_or(Vec2::ZERO)
     }
 
+    pub fn positions(&self) -> Vec<Vec2> {
+        self.vertex_properties
+            .iter()
+            .map(|vertex| vertex.position)
+            .collect()
+    }
+
+    pub fn set_positions(&mut self, positions: &[Vec2]) {
+        for (vertex, &position) in positions.iter().enumerate() {
+            self.set_position(vertex, position);
+        }
+    }
+
     pub fn set_position(&mut self, vertex: usize, position: Vec2) {
         if vertex < self.vertex_properties.len() {
+            if self.vertex_properties[vertex].position == position {
+                return;
+            }
+
             self.vertex_properties[vertex].position = position;
         } else {
             for _ in self.vertex_properties.len()..vertex {
@@ -168,56 +658,181 @@ impl GraphInterface {
                 ..Default::default()
             });
         }
+
+        self.mark_geometry_changed();
     }
 
     pub fn get_vertex_at_position(&self, position: Vec2) -> Option<usize> {
-        for (index, vertex_properties) in self.vertex_properties.iter().enumerate() {
-            let distance = position.distance(vertex_properties.position);
+        (0..self.vertex_properties.len()).find(|&vertex| self.vertex_contains(vertex, position))
+    }
+
+    pub fn get_edge_at_position(&self, position: Vec2) -> Option<usize> {
+        if !self.edges_hoverable() {
+            return None;
+        }
+        (0..self.edge_properties.len()).find(|&edge| self.edge_contains(edge, position))
+    }
 
-            if distance < vertex_properties.radius {
-                return Some(index);
+    /// Whether edges are hit tested, they aren't when switched off or above
+    /// [`EDGE_HOVER_LIMIT`].
+    pub fn edges_hoverable(&self) -> bool {
+        self.hover_edges && self.edge_properties.len() <= EDGE_HOVER_LIMIT
+    }
+
+    pub fn set_edge_hover(&mut self, enabled: bool) {
+        if self.hover_edges != enabled {
+            self.hover_edges = enabled;
+            self.mark_geometry_changed();
+        }
+    }
+
+    /// Same result as [`Self::get_vertex_at_position`] and [`Self::get_edge_at_position`],
+    /// but cached while nothing moves and backed by a spatial index otherwise.
+    pub fn hit_test(&mut self, position: Vec2) -> HitTestResult {
+        if let Some((query, result)) = self.hover_cache.query {
+            if query == position {
+                return result;
             }
         }
 
-        None
+        // small graphs are scanned directly
+        if self.vertex_properties.len() + self.edge_properties.len() < HOVER_INDEX_MIN_ITEMS {
+            let result = (
+                self.get_vertex_at_position(position),
+                self.get_edge_at_position(position),
+            );
+            self.hover_cache.query = Some((position, result));
+            return result;
+        }
+
+        if !self.hover_cache.index_valid {
+            self.rebuild_hover_index();
+        }
+
+        let cache = &self.hover_cache;
+
+        let vertex = cache
+            .vertices
+            .candidates(position)
+            .iter()
+            .copied()
+            .filter(|&index| self.vertex_contains(index, position))
+            .min();
+
+        let edge = cache
+            .edges
+            .candidates(position)
+            .iter()
+            .copied()
+            .filter(|&index| self.edge_contains(index, position))
+            .min();
+
+        self.hover_cache.query = Some((position, (vertex, edge)));
+        (vertex, edge)
     }
 
-    pub fn get_edge_at_position(&self, position: Vec2) -> Option<usize> {
-        for (i, edge_properties) in self.edge_properties.iter().enumerate() {
-            let vertices = edge_properties.vertices;
-            let start = self.get_position(vertices.0);
-            let end = self.get_position(vertices.1);
-            let width = edge_properties.width;
+    fn rebuild_hover_index(&mut self) {
+        let mut vertices = SpatialIndex::new(HOVER_CELL_SIZE);
+        let mut edges = SpatialIndex::new(HOVER_CELL_SIZE);
+
+        for (index, properties) in self.vertex_properties.iter().enumerate() {
+            let extent = Vec2::splat(self.hit_distance(properties.radius));
+            vertices.insert(
+                index,
+                properties.position - extent,
+                properties.position + extent,
+            );
+        }
+
+        let hoverable_edges = if self.edges_hoverable() {
+            self.edge_properties.as_slice()
+        } else {
+            &[]
+        };
+        for (index, properties) in hoverable_edges.iter().enumerate() {
+            let start = self.get_position(properties.vertices.0);
+            let end = self.get_position(properties.vertices.1);
+            let control = self
+                .edge_control(index, self.edge_curvature)
+                .unwrap_or(start);
+            let extent = Vec2::splat(self.hit_distance(properties.width));
+            edges.insert(
+                index,
+                start.min(end).min(control) - extent,
+                start.max(end).max(control) + extent,
+            );
+        }
+
+        self.hover_cache.vertices = vertices;
+        self.hover_cache.edges = edges;
+        self.hover_cache.index_valid = true;
+    }
 
+    fn vertex_contains(&self, vertex: usize, position: Vec2) -> bool {
+        let properties = &self.vertex_properties[vertex];
+        position.distance(properties.position) < self.hit_distance(properties.radius)
+    }
+
+    fn edge_contains(&self, edge: usize, position: Vec2) -> bool {
+        let properties = &self.edge_properties[edge];
+        let start = self.get_position(properties.vertices.0);
+        let end = self.get_position(properties.vertices.1);
+        let width = self.hit_distance(properties.width);
+
+        let Some(control) = self.edge_control(edge, self.edge_curvature) else {
             let (min_x, max_x) = (start.x.min(end.x) - width, start.x.max(end.x) + width);
             let (min_y, max_y) = (start.y.min(end.y) - width, start.y.max(end.y) + width);
 
             // mouse position is within bounding box of line
-            if (min_x <= position.x && position.x <= max_x)
+            return (min_x <= position.x && position.x <= max_x)
                 && (min_y <= position.y && position.y <= max_y)
-            {
-                let distance = distance_to_line(start, end, position);
+                && distance_to_line(start, end, position) < width;
+        };
 
-                if distance < width {
-                    return Some(i);
-                }
-            }
+        // the curve stays within the triangle of its endpoints and control point
+        let min = start.min(end).min(control) - Vec2::splat(width);
+        let max = start.max(end).max(control) + Vec2::splat(width);
+        if position.cmplt(min).any() || position.cmpgt(max).any() {
+            return false;
         }
 
-        None
+        curve_points(start, control, end)
+            .windows(2)
+            .any(|pair| distance_to_segment(pair[0], pair[1], position) < width)
     }
 
-    pub fn handle_mouse_input(&mut self) {
-        let mouse_position: Vec2 = mouse_position().into();
-        self.click_handler.register_mouse_button_status();
+    pub fn handle_mouse_input(&mut self, input: &InputEvents) {
+        let mouse_position = input.position;
+        let left = input.button(MouseButton::Left);
+
+        let interaction = (
+            self.hovered_vertex,
+            self.hovered_edge,
+            self.dragged_vertex,
+            self.selected,
+        );
 
         // Dragging vertex
         if let Some(previous_drag_state) = self.drag_state {
             let dragged_vertex = previous_drag_state.vertex;
 
             // Still dragging
-            if self.click_handler.mouse_drag() {
-                let delta = mouse_position - previous_drag_state.mouse_position;
+            if left.drag {
+                let (axis_lock, delta) = if input.shift {
+                    let lock = previous_drag_state.axis_lock.unwrap_or(AxisLock {
+                        origin: previous_drag_state.mouse_position,
+                        axis: None,
+                    });
+                    let (lock, delta) = constrained_drag_delta(
+                        lock,
+                        previous_drag_state.mouse_position,
+                        mouse_position,
+                    );
+                    (Some(lock), delta)
+                } else {
+                    (None, mouse_position - previous_drag_state.mouse_position)
+                };
+
                 let old_pos = self.get_position(dragged_vertex);
                 let new_pos = old_pos + delta;
                 self.set_position(dragged_vertex, new_pos);
@@ -225,31 +840,67 @@ impl GraphInterface {
                 self.drag_state = Some(DragState {
                     vertex: dragged_vertex,
                     mouse_position,
+                    axis_lock,
                 });
 
             // Stop dragging
             } else {
+                self.events.push(InterfaceEvent::DragEnded(dragged_vertex));
                 self.drag_state = None;
                 self.dragged_vertex = None;
             }
         }
         // Not dragging vertex
         else {
-            let hovered_vertex = self.get_vertex_at_position(mouse_position);
-            let hovered_edge = self.get_edge_at_position(mouse_position);
+            // the settings panel covers everything outside of the viewport
+            let in_viewport = mouse_position.x <= self.viewport.x;
+            let (hovered_vertex, hovered_edge) = self.hit_test(mouse_position);
+            let hovered_vertex = hovered_vertex.filter(|_| in_viewport);
+            let hovered_edge = hovered_edge.filter(|_| in_viewport);
 
             // Highlight hovered vertex
-            if !self.click_handler.mouse_drag() {
+            if !left.drag {
                 self.hovered_vertex = hovered_vertex;
+
+                match self.selection_drag.take() {
+                    Some(SelectionDrag::Box { start, end }) => self.select_vertices_in(
+                        Rect::new(start.x, start.y, 0.0, 0.0)
+                            .combine_with(Rect::new(end.x, end.y, 0.0, 0.0)),
+                    ),
+                    Some(SelectionDrag::Lasso(polygon)) => {
+                        self.select_vertices_in_polygon(&polygon)
+                    }
+                    None => {}
+                }
+            // Extend the selection box or lasso
+            } else if let Some(selection_drag) = &mut self.selection_drag {
+                selection_drag.extend(mouse_position);
+                self.dirty = true;
             // Possibly start dragging vertex
             } else {
                 self.hovered_vertex = None;
 
+                // a drag on the empty canvas selects the vertices in a box, or in a lasso
+                // with Alt held, instead
+                if hovered_vertex.is_none() && in_viewport {
+                    self.selection_drag = Some(if input.alt {
+                        SelectionDrag::Lasso(vec![mouse_position])
+                    } else {
+                        SelectionDrag::Box {
+                            start: mouse_position,
+                            end: mouse_position,
+                        }
+                    });
+                }
+
                 if let Some(dragged_vertex) = hovered_vertex {
+                    self.events
+                        .push(InterfaceEvent::DragStarted(dragged_vertex));
                     self.dragged_vertex = Some(dragged_vertex);
                     self.drag_state = Some(DragState {
                         vertex: dragged_vertex,
                         mouse_position,
+                        axis_lock: None,
                     })
                 }
             }
@@ -260,33 +911,208 @@ impl GraphInterface {
                 self.hovered_edge = None;
             }
 
-            if self.click_handler.mouse_click() {
+            if left.click {
                 if let Some(hovered_vertex) = self.hovered_vertex {
                     let vertex_properties = self.vertex_properties.get_mut(hovered_vertex).unwrap();
 
                     vertex_properties.cycle_drawstate();
+                    self.selected = Some(Selection::Vertex(hovered_vertex));
+                    self.events
+                        .push(InterfaceEvent::VertexClicked(hovered_vertex));
                 }
 
                 if let Some(hovered_edge) = self.hovered_edge {
                     let edge_properties = self.edge_properties.get_mut(hovered_edge).unwrap();
 
                     edge_properties.cycle_drawstate();
+                    self.selected = Some(Selection::Edge(hovered_edge));
+                    self.events.push(InterfaceEvent::EdgeClicked(hovered_edge));
+                }
+
+                self.dirty = true;
+            }
+
+            // a double click on the empty canvas drops the selection
+            if left.double_click && hovered_vertex.is_none() && hovered_edge.is_none() {
+                self.selected = None;
+            }
+
+            // right click inspects without cycling the draw state
+            if input.button(MouseButton::Right).click {
+                if let Some(hovered_vertex) = hovered_vertex {
+                    self.selected = Some(Selection::Vertex(hovered_vertex));
+                } else if let Some(hovered_edge) = hovered_edge {
+                    self.selected = Some(Selection::Edge(hovered_edge));
                 }
             }
+
+            // middle click is separate from the click cycling of the left button
+            if input.button(MouseButton::Middle).pressed {
+                if let Some(hovered_vertex) = hovered_vertex {
+                    self.toggle_neighbourhood_highlighting(hovered_vertex);
+                }
+            }
+        }
+
+        if interaction
+            != (
+                self.hovered_vertex,
+                self.hovered_edge,
+                self.dragged_vertex,
+                self.selected,
+            )
+        {
+            self.dirty = true;
+        }
+    }
+
+    /// Takes the interactions since the last call, oldest first.
+    pub fn drain_events(&mut self) -> Vec<InterfaceEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    pub fn handle_keyboard_input(&mut self, input: &InputEvents) {
+        if input.escape {
+            let had_selection =
+                self.selected.take().is_some() || !self.selected_vertices.is_empty();
+            self.selected_vertices.clear();
+            if had_selection {
+                self.dirty = true;
+            }
+        }
+    }
+
+    /// The box being dragged out, see [`Self::select_vertices_in`].
+    pub fn selection_box(&self) -> Option<Rect> {
+        let Some(SelectionDrag::Box { start, end }) = self.selection_drag else {
+            return None;
+        };
+        let min = start.min(end);
+        let size = (end - start).abs();
+        Some(Rect::new(min.x, min.y, size.x, size.y))
+    }
+
+    /// The lasso being traced, see [`Self::select_vertices_in_polygon`].
+    pub fn lasso(&self) -> Option<&[Vec2]> {
+        match &self.selection_drag {
+            Some(SelectionDrag::Lasso(points)) => Some(points),
+            _ => None,
+        }
+    }
+
+    /// Replaces the box selection with the vertices whose center lies in `rect`.
+    pub fn select_vertices_in(&mut self, rect: Rect) {
+        self.selected_vertices = (0..self.vertex_properties.len())
+            .filter(|&vertex| rect.contains(self.get_position(vertex)))
+            .collect();
+        self.dirty = true;
+    }
+
+    /// Replaces the box selection with the vertices whose center lies in the closed `polygon`.
+    pub fn select_vertices_in_polygon(&mut self, polygon: &[Vec2]) {
+        self.selected_vertices = (0..self.vertex_properties.len())
+            .filter(|&vertex| polygon_contains(polygon, self.get_position(vertex)))
+            .collect();
+        self.dirty = true;
+    }
+
+    /// Vertices a local relax moves: the box selection, or else the selected vertex.
+    pub fn relax_mask(&self) -> Option<Vec<bool>> {
+        let mut mask = vec![false; self.vertex_properties.len()];
+
+        if !self.selected_vertices.is_empty() {
+            for &vertex in &self.selected_vertices {
+                mask[vertex] = true;
+            }
+        } else if let Some(Selection::Vertex(vertex)) = self.selected {
+            *mask.get_mut(vertex)? = true;
+        } else {
+            return None;
+        }
+
+        Some(mask)
+    }
+
+    /// Runs the force layout on the vertices in `mask` only, with every other vertex as a fixed
+    /// anchor, until it converges or `max_steps` have run. Returns the number of steps.
+    pub fn relax_locally(
+        &mut self,
+        graph: &Graph,
+        config: &ForceConfig,
+        mask: &[bool],
+        max_steps: usize,
+        threshold: f32,
+    ) -> usize {
+        for step in 0..max_steps {
+            self.apply_force(graph, config, Some(mask));
+            if self.layout_converged(threshold) {
+                return step + 1;
+            }
         }
+
+        max_steps
     }
 
-    pub fn apply_force(&mut self, graph: &Graph) {
+    /// One layout step. With a `mask`, only the vertices in it move and they only interact with
+    /// each other and their neighbours, the other vertices are fixed anchors.
+    pub fn apply_force(&mut self, graph: &Graph, config: &ForceConfig, mask: Option<&[bool]>) {
+        let in_mask = |vertex: usize| mask.is_none_or(|mask| mask.get(vertex) == Some(&true));
+
         let mut forces: Vec<Vec2> = Vec::with_capacity(graph.vertices);
 
+        // the hidden members of a collapsed group sit on their meta-vertex, their edges pull
+        // on the meta-vertex instead
+        let meta_vertices: Vec<usize> = (0..graph.vertices)
+            .map(|vertex| self.meta_vertex(vertex))
+            .collect();
+        let is_hidden_member = |vertex: usize| meta_vertices[vertex] != vertex;
+        let edges: HashSet<(usize, usize)> = graph
+            .edges
+            .iter()
+            .filter(|&&(a, b)| a < graph.vertices && b < graph.vertices)
+            .map(|&(a, b)| canonical_edge(meta_vertices[a], meta_vertices[b]))
+            .filter(|(a, b)| a != b)
+            .collect();
+
+        let mut isolated = vec![true; graph.vertices];
+        for &(a, b) in &edges {
+            isolated[a] = false;
+            isolated[b] = false;
+        }
+
+        let connected_positions: Vec<Vec2> = (0..graph.vertices)
+            .filter(|&vertex| !isolated[vertex] && !is_hidden_member(vertex))
+            .map(|vertex| self.get_position(vertex))
+            .collect();
+        let centroid = if connected_positions.is_empty() {
+            self.viewport / 2.0
+        } else {
+            connected_positions.iter().sum::<Vec2>() / connected_positions.len() as f32
+        };
+
         // calculate forces
-        for main_vertex in 0..graph.vertices {
+        for (main_vertex, &is_isolated) in isolated.iter().enumerate() {
             let mut total_force = Vec2::ZERO;
 
+            if !in_mask(main_vertex) || is_hidden_member(main_vertex) {
+                forces.push(total_force);
+                continue;
+            }
+
             let main_position = self.get_position(main_vertex);
 
+            // repulsion alone would push isolated vertices into a corner
+            if is_isolated {
+                total_force += (centroid - main_position) * ISOLATED_GRAVITY;
+            }
+
             for secondary_vertex in 0..graph.vertices {
-                if secondary_vertex == main_vertex {
+                if secondary_vertex == main_vertex || is_hidden_member(secondary_vertex) {
+                    continue;
+                }
+
+                let is_edge = edges.contains(&canonical_edge(main_vertex, secondary_vertex));
+                if !is_edge && !in_mask(secondary_vertex) {
                     continue;
                 }
 
@@ -298,15 +1124,10 @@ impl GraphInterface {
                 } else {
                     let direction = (secondary_position - main_position).normalize();
 
-                    let edge = (
-                        main_vertex.min(secondary_vertex),
-                        main_vertex.max(secondary_vertex),
-                    );
-
-                    let magnitude = if graph.edges.contains(&edge) {
+                    let magnitude = if is_edge {
                         (distance / 70.0).log10()
                     } else {
-                        -50.0 * 0.5f32.powf(distance / 20.0)
+                        -config.repulsion.magnitude(distance)
                     };
 
                     direction * magnitude
@@ -318,33 +1139,201 @@ impl GraphInterface {
             forces.push(total_force);
         }
 
+        // unselected vertices still push and pull, they are only pinned in place
+        let selection = config.selected_only.then(|| self.relax_mask()).flatten();
+        let movable = match (mask, selection) {
+            (Some(mask), Some(selection)) => Some(
+                mask.iter()
+                    .zip(selection)
+                    .map(|(&in_mask, selected)| in_mask && selected)
+                    .collect(),
+            ),
+            (Some(mask), None) => Some(mask.to_vec()),
+            (None, selection) => selection,
+        };
+        let movable = movable.as_deref();
+
+        self.layout_displacement = forces
+            .iter()
+            .enumerate()
+            .filter(|(vertex, _)| self.is_movable_in(*vertex, movable))
+            .map(|(_, force)| force.length())
+            .fold(0.0, f32::max);
+
         // apply forces
-        self.apply_forces(&forces);
+        self.apply_forces_in(&forces, movable);
+        self.project_edge_lengths(movable);
+        self.constrain_to_lanes(&config.lanes, movable);
     }
 
-    pub fn align_to_square_grid(&mut self, grid: &SquareGrid) {
-        fn parabole(x: f32, top_x: f32) -> f32 {
-            let x = x / (2.0 * top_x);
-            4.0 * (x - x * x)
+    /// Moves every movable vertex with a lane towards it. Only the vertical coordinate changes.
+    pub fn constrain_to_lanes(&mut self, lanes: &LaneConfig, mask: Option<&[bool]>) {
+        if lanes.count == 0 {
+            return;
         }
 
-        let mut forces: Vec<Vec2> = Vec::with_capacity(self.vertex_properties.len());
+        for vertex in 0..self.vertex_properties.len() {
+            let Some(lane) = self.vertex_properties[vertex].lane else {
+                continue;
+            };
+            if !self.is_movable_in(vertex, mask) {
+                continue;
+            }
 
-        let delta_avg = grid.delta_avg();
+            let position = self.get_position(vertex);
+            let y = lanes.constrain(position.y, lane, self.viewport.y);
+            self.set_position(vertex, vec2(position.x, y));
+        }
+    }
 
-        for vertex in self.vertex_properties.iter() {
+    pub fn set_lanes(&mut self, lanes: &[Option<usize>]) {
+        for (properties, &lane) in self.vertex_properties.iter_mut().zip(lanes) {
+            properties.lane = lane;
+        }
+        self.dirty = true;
+    }
+
+    /// Moves the endpoints of every edge with a target length along the edge until it has
+    /// that length. Both endpoints move half the way, unless one of them can't be moved.
+    pub fn project_edge_lengths(&mut self, mask: Option<&[bool]>) {
+        for index in 0..self.edge_properties.len() {
+            let edge = &self.edge_properties[index];
+            let Some(target) = edge.target_length else {
+                continue;
+            };
+            let (a, b) = edge.vertices;
+
+            let (position_a, position_b) = (self.get_position(a), self.get_position(b));
+            let distance = position_a.distance(position_b);
+            let direction = if distance == 0.0 {
+                Vec2::X
+            } else {
+                (position_b - position_a) / distance
+            };
+            let correction = direction * (target - distance);
+
+            match (self.is_movable_in(a, mask), self.is_movable_in(b, mask)) {
+                (true, true) => {
+                    self.set_position(a, position_a - correction / 2.0);
+                    self.set_position(b, position_b + correction / 2.0);
+                }
+                (true, false) => self.set_position(a, position_a - correction),
+                (false, true) => self.set_position(b, position_b + correction),
+                (false, false) => {}
+            }
+        }
+    }
+
+    pub fn edge_length(&self, edge: usize) -> Option<f32> {
+        let (a, b) = self.edge_properties.get(edge)?.vertices;
+        Some(self.get_position(a).distance(self.get_position(b)))
+    }
+
+    /// Prepares a force layout run, breaking the symmetry of the layout if `config` asks for it.
+    pub fn start_force(&mut self, config: &ForceConfig) {
+        if !config.break_symmetry {
+            return;
+        }
+
+        let mut rng = SeededRng::new(config.symmetry_seed);
+        for vertex in 0..self.vertex_properties.len() {
+            let offset = vec2(rng.gen_range(-1.0, 1.0), rng.gen_range(-1.0, 1.0));
+            if self.is_movable(vertex) {
+                let position = self.get_position(vertex) + offset * SYMMETRY_BREAKING_OFFSET;
+                self.set_position(vertex, position);
+            }
+        }
+    }
+
+    /// Runs `steps` force layout steps without drawing and returns the total distance the
+    /// vertices moved in the last one.
+    pub fn step_force(&mut self, graph: &Graph, config: &ForceConfig, steps: usize) -> f32 {
+        let mut movement = 0.0;
+
+        for _ in 0..steps {
+            let before = self.positions();
+            self.apply_force(graph, config, None);
+            movement = before
+                .iter()
+                .zip(self.positions())
+                .map(|(before, after)| before.distance(after))
+                .sum();
+        }
+
+        movement
+    }
+
+    /// Whether the last layout step moved no vertex by more than `threshold`.
+    pub fn layout_converged(&self, threshold: f32) -> bool {
+        self.layout_displacement < threshold
+    }
+
+    /// Moves every movable vertex by a random offset of at most `magnitude`. The offset shrinks
+    /// with the degree of the vertex, so well connected vertices keep their rough position.
+    pub fn shake(&mut self, graph: &Graph, magnitude: f32, rng: &mut SeededRng) {
+        for vertex in 0..self.vertex_properties.len() {
+            if !self.is_movable(vertex) {
+                continue;
+            }
+
+            let degree = graph
+                .edges
+                .iter()
+                .filter(|(a, b)| *a == vertex || *b == vertex)
+                .count();
+            let scale = magnitude / ((1 + degree) as f32).sqrt();
+
+            let offset = vec2(rng.gen_range(-1.0, 1.0), rng.gen_range(-1.0, 1.0)) * scale;
+            let position = (self.get_position(vertex) + offset).clamp(Vec2::ZERO, self.viewport);
+
+            self.set_position(vertex, position);
+        }
+
+        self.layout_displacement = f32::INFINITY;
+    }
+
+    /// Number of pairs of edges without a shared endpoint that cross each other.
+    pub fn edge_crossings(&self) -> usize {
+        let edges: Vec<(usize, usize)> = self
+            .edge_properties
+            .iter()
+            .map(|properties| properties.vertices)
+            .collect();
+
+        count_crossings(&self.positions(), &edges, |_| true).unwrap_or_default()
+    }
+
+    fn is_movable(&self, vertex: usize) -> bool {
+        if self.dragged_vertex == Some(vertex) {
+            return false;
+        }
+
+        self.vertex_properties
+            .get(vertex)
+            .is_some_and(|properties| !properties.pinned && !properties.frozen)
+    }
+
+    /// Whether the vertex is movable and, when there is a mask, in it.
+    fn is_movable_in(&self, vertex: usize, mask: Option<&[bool]>) -> bool {
+        self.is_movable(vertex) && mask.is_none_or(|mask| mask.get(vertex) == Some(&true))
+    }
+
+    pub fn align_to_square_grid(&mut self, grid: &SquareGrid) {
+        fn parabole(x: f32, top_x: f32) -> f32 {
+            let x = x / (2.0 * top_x);
+            4.0 * (x - x * x)
+        }
+
+        let mut forces: Vec<Vec2> = Vec::with_capacity(self.vertex_properties.len());
+
+        for vertex in self.vertex_properties.iter() {
             let vertex_pos = vertex.position;
-            let closest_grid_point = Vec2::new(
-                ((vertex_pos.x - grid.x_offset) / grid.x_delta).round() * grid.x_delta
-                    + grid.x_offset,
-                ((vertex_pos.y - grid.y_offset) / grid.y_delta).round() * grid.y_delta
-                    + grid.y_offset,
-            );
+            let closest_grid_point = grid.closest_snap_point(vertex_pos);
             let distance = closest_grid_point.distance(vertex_pos);
 
             let force = if distance > 0.0 {
                 let direction = (closest_grid_point - vertex_pos).normalize();
-                let magnitude = 5.0 * parabole(distance, delta_avg);
+                let magnitude = 5.0 * parabole(distance, grid.snap_delta);
                 direction * magnitude
             } else {
                 Vec2::ZERO
@@ -363,20 +1352,22 @@ impl GraphInterface {
         }
 
         let mut forces: Vec<Vec2> = Vec::with_capacity(self.vertex_properties.len());
+        // weaker on small snap sizes, where the full strength would overshoot the ring
+        let strength = 5.0_f32.min(0.25 * grid.snap_r_delta);
 
         for vertex in self.vertex_properties.iter() {
             let vertex_pos = vertex.position;
 
             let force = if let Some(direction) = (vertex_pos - grid.center).try_normalize() {
                 let center_distance = grid.center.distance(vertex_pos);
-                let distance_mod = center_distance % grid.r_delta;
-                let sign = if distance_mod - 0.5 * grid.r_delta >= 0.0 {
+                let distance_mod = center_distance % grid.snap_r_delta;
+                let sign = if distance_mod - 0.5 * grid.snap_r_delta >= 0.0 {
                     1.0
                 } else {
                     -1.0
                 };
 
-                let magnitude = 5.0 * parabole(distance_mod, 0.5 * grid.r_delta);
+                let magnitude = strength * parabole(distance_mod, 0.5 * grid.snap_r_delta);
 
                 sign * magnitude * direction
             } else {
@@ -389,175 +1380,2878 @@ impl GraphInterface {
         self.apply_forces(&forces);
     }
 
+    /// Rotates every edge towards the nearest multiple of 45°, by `strength` of the remaining
+    /// angle. Edges turn around their midpoint, or around their endpoint that can't move.
+    pub fn align_edges_to_angles(&mut self, strength: f32) {
+        const SNAP_ANGLE: f32 = std::f32::consts::FRAC_PI_4;
+
+        let mut forces = vec![Vec2::ZERO; self.vertex_properties.len()];
+
+        for edge in &self.edge_properties {
+            let (a, b) = edge.vertices;
+            let (position_a, position_b) = (self.get_position(a), self.get_position(b));
+            let direction = position_b - position_a;
+            if direction == Vec2::ZERO {
+                continue;
+            }
+
+            let angle = direction.y.atan2(direction.x);
+            let correction = ((angle / SNAP_ANGLE).round() * SNAP_ANGLE - angle) * strength;
+            let rotation = Vec2::from_angle(correction);
+
+            let pivot = match (self.is_movable(a), self.is_movable(b)) {
+                (true, true) => (position_a + position_b) / 2.0,
+                (true, false) => position_b,
+                (false, true) => position_a,
+                (false, false) => continue,
+            };
+
+            forces[a] += pivot + rotation.rotate(position_a - pivot) - position_a;
+            forces[b] += pivot + rotation.rotate(position_b - pivot) - position_b;
+        }
+
+        self.apply_forces(&forces);
+    }
+
     pub fn apply_forces(&mut self, forces: &[Vec2]) {
+        self.apply_forces_in(forces, None);
+    }
+
+    /// Like [`Self::apply_forces`], but vertices outside `mask` stay where they are.
+    fn apply_forces_in(&mut self, forces: &[Vec2], mask: Option<&[bool]>) {
         if forces.len() != self.vertex_properties.len() {
             debug!("forces and vertex properties arrays not of same length");
             return;
         }
 
         for (vertex, force) in forces.iter().enumerate() {
-            if let Some(dragged) = self.dragged_vertex {
-                if dragged == vertex {
-                    continue;
-                }
+            if !self.is_movable_in(vertex, mask) {
+                continue;
             }
 
             let old_position = self.get_position(vertex);
             let new_position = old_position + *force;
-            let clamped_position =
-                new_position.clamp(vec2(0.0, 0.0), vec2(main_screen_width(), screen_height()));
+            let clamped_position = new_position.clamp(vec2(0.0, 0.0), self.viewport);
 
             self.set_position(vertex, clamped_position);
         }
     }
 
-    pub fn clear_edge_highlighting(&mut self) {
-        for edge in self.edge_properties.iter_mut() {
-            edge.draw_state = DrawState::Default;
+    /// Freezes the selected vertex, or all highlighted vertices when no vertex is selected.
+    /// Frozen vertices are skipped by the layout forces but can still be dragged.
+    pub fn freeze_selection(&mut self) {
+        if let Some(Selection::Vertex(vertex)) = self.selected {
+            if let Some(properties) = self.vertex_properties.get_mut(vertex) {
+                properties.frozen = true;
+            }
+            return;
+        }
+
+        for properties in self.vertex_properties.iter_mut() {
+            if properties.draw_state == DrawState::Highlighted {
+                properties.frozen = true;
+            }
         }
-        self.current_highlight_graph = None;
     }
 
-    pub fn add_edge_highlighting(&mut self, edges: &[(usize, usize)]) {
-        for edge_properties in self.edge_properties.iter_mut() {
-            if edges.contains(&edge_properties.vertices) {
-                edge_properties.draw_state = DrawState::Highlighted;
+    pub fn unfreeze_all(&mut self) {
+        for properties in self.vertex_properties.iter_mut() {
+            properties.frozen = false;
+        }
+    }
+
+    pub fn frozen_count(&self) -> usize {
+        self.vertex_properties
+            .iter()
+            .filter(|properties| properties.frozen)
+            .count()
+    }
+
+    /// Draws `vertices` as one meta-vertex at their centroid, labelled with the size of the
+    /// group. The edges within the group are hidden and the edges to each other vertex are
+    /// drawn once, labelled with their count when there are several. The graph itself is not
+    /// changed. Returns the index of the group, or `None` for fewer than two vertices or when
+    /// a vertex is collapsed already.
+    pub fn collapse(&mut self, vertices: &[usize]) -> Option<usize> {
+        let mut members = vertices.to_vec();
+        members.sort_unstable();
+        members.dedup();
+
+        let is_valid = |vertex: usize| {
+            vertex < self.vertex_properties.len() && self.collapsed_group_of(vertex).is_none()
+        };
+        if members.len() < 2 || !members.iter().all(|&vertex| is_valid(vertex)) {
+            return None;
+        }
+
+        let centroid = members
+            .iter()
+            .fold(Vec2::ZERO, |sum, &vertex| sum + self.get_position(vertex))
+            / members.len() as f32;
+
+        Some(self.collapse_at(members, centroid))
+    }
+
+    /// Collapses the vertex together with its neighbours, see [`Self::collapse`].
+    pub fn collapse_neighbourhood(&mut self, vertex: usize) -> Option<usize> {
+        let mut vertices = vec![vertex];
+        for &edge in self.incident_edges(vertex) {
+            let (a, b) = self.edge_properties[edge].vertices;
+            vertices.push(if a == vertex { b } else { a });
+        }
+
+        self.collapse(&vertices)
+    }
+
+    fn collapse_at(&mut self, members: Vec<usize>, position: Vec2) -> usize {
+        let representative = members[0];
+        let is_member = |vertex: usize| members.binary_search(&vertex).is_ok();
+
+        let mut touched_edges: Vec<usize> = members
+            .iter()
+            .flat_map(|&member| self.incident_edges(member).iter().copied())
+            .collect();
+        touched_edges.sort_unstable();
+        touched_edges.dedup();
+
+        let vertices = members
+            .iter()
+            .map(|&member| self.vertex_properties[member].clone())
+            .collect();
+        let edges = touched_edges
+            .iter()
+            .map(|&edge| (edge, self.edge_properties[edge].clone()))
+            .collect();
+
+        // edges leaving the group, by the vertex they lead to, or its own meta-vertex
+        let mut leaving: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        for &edge in &touched_edges {
+            let (a, b) = self.edge_properties[edge].vertices;
+            match (is_member(a), is_member(b)) {
+                (true, true) => self.edge_properties[edge].draw_state = DrawState::Hidden,
+                (true, false) => leaving.entry(self.meta_vertex(b)).or_default().push(edge),
+                _ => leaving.entry(self.meta_vertex(a)).or_default().push(edge),
+            }
+        }
+        for parallel in leaving.values() {
+            if parallel.len() > 1 {
+                self.edge_properties[parallel[0]].label = Some(format!("×{}", parallel.len()));
+            }
+            for &edge in &parallel[1..] {
+                self.edge_properties[edge].draw_state = DrawState::Hidden;
+            }
+        }
+
+        for &member in &members {
+            let properties = &mut self.vertex_properties[member];
+            properties.position = position;
+            if member != representative {
+                properties.draw_state = DrawState::Hidden;
+                properties.frozen = true;
+            }
+        }
+        self.vertex_properties[representative].label = Some(format!("[{}]", members.len()));
+
+        let is_hidden_member = |vertex: usize| vertex != representative && is_member(vertex);
+        self.selected_vertices
+            .retain(|&vertex| !is_hidden_member(vertex));
+        if let Some(Selection::Vertex(vertex)) = self.selected {
+            if is_hidden_member(vertex) {
+                self.selected = Some(Selection::Vertex(representative));
             }
         }
+
+        self.collapsed_groups.push(CollapsedGroup {
+            representative,
+            members,
+            vertices,
+            edges,
+        });
+        self.mark_geometry_changed();
+
+        self.collapsed_groups.len() - 1
     }
 
-    pub fn set_edge_highlighting(&mut self, edges: &[(usize, usize)]) {
-        self.clear_edge_highlighting();
-        self.add_edge_highlighting(edges);
+    /// Restores the group exactly as it was before it was collapsed.
+    pub fn expand(&mut self, group: usize) -> bool {
+        if group >= self.collapsed_groups.len() {
+            return false;
+        }
+
+        // later groups may have stored properties this group changed, they are collapsed
+        // again on top of the restored properties
+        let later: Vec<(Vec<usize>, Vec2)> = self.collapsed_groups[group + 1..]
+            .iter()
+            .map(|later| {
+                let position = self.get_position(later.representative);
+                (later.members.clone(), position)
+            })
+            .collect();
+
+        while self.collapsed_groups.len() > group {
+            let mut expanded = self.collapsed_groups.pop().unwrap();
+            self.swap_group_properties(&mut expanded);
+        }
+        for (members, position) in later {
+            self.collapse_at(members, position);
+        }
+
+        self.mark_geometry_changed();
+        true
     }
 
-    pub fn clear_highlight_history(&mut self) {
-        self.highlight_graph_history.clear();
-        self.clear_edge_highlighting();
+    pub fn expand_all(&mut self) {
+        if self.collapsed_groups.is_empty() {
+            return;
+        }
+
+        while let Some(mut expanded) = self.collapsed_groups.pop() {
+            self.swap_group_properties(&mut expanded);
+        }
+        self.mark_geometry_changed();
     }
 
-    pub fn add_graph_to_history(&mut self, graph: Graph) {
-        self.highlight_graph_history.push(graph);
+    /// Calls `f` with every group expanded, and collapses them again afterwards.
+    pub fn with_groups_expanded<R>(&mut self, f: impl FnOnce(&Self) -> R) -> R {
+        let mut groups = std::mem::take(&mut self.collapsed_groups);
+
+        for group in groups.iter_mut().rev() {
+            self.swap_group_properties(group);
+        }
+        let result = f(self);
+        for group in groups.iter_mut() {
+            self.swap_group_properties(group);
+        }
+
+        self.collapsed_groups = groups;
+        result
     }
 
-    pub fn get_history_size(&self) -> usize {
-        self.highlight_graph_history.len()
+    fn swap_group_properties(&mut self, group: &mut CollapsedGroup) {
+        for (&member, properties) in group.members.iter().zip(&mut group.vertices) {
+            std::mem::swap(&mut self.vertex_properties[member], properties);
+        }
+        for (edge, properties) in &mut group.edges {
+            std::mem::swap(&mut self.edge_properties[*edge], properties);
+        }
     }
 
-    pub fn set_edge_highlighting_and_add_to_history(&mut self, graph: Graph) {
-        self.set_edge_highlighting(&graph.edges);
-        self.add_graph_to_history(graph);
-        self.current_highlight_graph = Some(self.highlight_graph_history.len() - 1);
+    pub fn collapsed_groups(&self) -> &[CollapsedGroup] {
+        &self.collapsed_groups
     }
 
-    pub fn set_next_highlighting(&mut self) {
-        let highlight_vec = &self.highlight_graph_history;
-        let target_index = match self.current_highlight_graph {
-            Some(index) => index + 1,
-            None => 0,
-        };
+    pub fn collapsed_group_of(&self, vertex: usize) -> Option<usize> {
+        self.collapsed_groups
+            .iter()
+            .position(|group| group.members.binary_search(&vertex).is_ok())
+    }
 
-        if target_index < highlight_vec.len() {
-            let graph = &highlight_vec[target_index];
-            self.current_highlight_graph = Some(target_index);
-            for edge_properties in self.edge_properties.iter_mut() {
-                if graph.edges.contains(&edge_properties.vertices) {
-                    edge_properties.draw_state = DrawState::Highlighted;
-                } else {
-                    edge_properties.draw_state = DrawState::Default;
+    /// The vertex that stands in for `vertex`, itself unless it is in a collapsed group.
+    fn meta_vertex(&self, vertex: usize) -> usize {
+        self.collapsed_group_of(vertex)
+            .map_or(vertex, |group| self.collapsed_groups[group].representative)
+    }
+
+    /// Moves the hidden members of every group to their meta-vertex, which the layout or a
+    /// drag may have moved, so that their edges keep starting at the meta-vertex.
+    pub fn follow_meta_vertices(&mut self) {
+        let mut moved = false;
+
+        for group in &self.collapsed_groups {
+            let position = self.vertex_properties[group.representative].position;
+            for &member in &group.members {
+                let properties = &mut self.vertex_properties[member];
+                if properties.position != position {
+                    properties.position = position;
+                    moved = true;
                 }
             }
         }
+
+        if moved {
+            self.mark_geometry_changed();
+        }
     }
 
-    pub fn set_previous_highlighting(&mut self) {
-        let highlight_vec = &self.highlight_graph_history;
-        let target_index = match self.current_highlight_graph {
-            Some(index) => index - 1,
-            None => highlight_vec.len() - 1,
-        };
+    /// Positions of the vertices with every group expanded.
+    pub fn expanded_positions(&self) -> Vec<Vec2> {
+        let mut positions = self.positions();
+
+        for group in &self.collapsed_groups {
+            for (&member, properties) in group.members.iter().zip(&group.vertices) {
+                positions[member] = properties.position;
+            }
+        }
 
-        if target_index < highlight_vec.len() {
-            let graph = &highlight_vec[target_index];
-            self.current_highlight_graph = Some(target_index);
+        positions
+    }
+
+    pub fn clear_edge_highlighting(&mut self) {
+        for edge in self.edge_properties.iter_mut() {
+            edge.draw_state = DrawState::Default;
+            edge.highlight_color_index = 0;
+            edge.highlight_color_override = None;
+            edge.path_direction = None;
+        }
+        self.current_highlight = None;
+        self.dirty = true;
+    }
+
+    /// Highlights `vertices` instead of the vertices highlighted before.
+    pub fn set_vertex_highlighting(&mut self, vertices: &[usize]) {
+        for properties in self.vertex_properties.iter_mut() {
+            if properties.draw_state == DrawState::Highlighted {
+                properties.draw_state = DrawState::Default;
+            }
+        }
+        for &vertex in vertices {
+            if let Some(properties) = self.vertex_properties.get_mut(vertex) {
+                properties.draw_state = DrawState::Highlighted;
+            }
+        }
+        self.dirty = true;
+    }
+
+    pub fn highlight_edges_where(&mut self, predicate: impl Fn(usize, usize) -> bool) {
+        let edges: Vec<(usize, usize)> = self
+            .edge_properties
+            .iter()
+            .map(|edge_properties| edge_properties.vertices)
+            .filter(|&(a, b)| predicate(a, b))
+            .collect();
+
+        self.set_edge_highlighting(&edges);
+    }
+
+    /// Highlights `edges` instead of the edges highlighted before, without adding them to the
+    /// highlight history.
+    pub fn set_edge_highlighting(&mut self, edges: &[(usize, usize)]) {
+        self.clear_edge_highlighting();
+
+        for edge_properties in self.edge_properties.iter_mut() {
+            if edges.contains(&edge_properties.vertices) {
+                edge_properties.draw_state = DrawState::Highlighted;
+            }
+        }
+        self.dirty = true;
+    }
+
+    pub fn set_path_highlighting(&mut self, path: &[usize]) {
+        self.clear_edge_highlighting();
+
+        for (from, to) in path_edges(path) {
             for edge_properties in self.edge_properties.iter_mut() {
-                if graph.edges.contains(&edge_properties.vertices) {
+                let (a, b) = edge_properties.vertices;
+                if (a, b) == (from, to) || (b, a) == (from, to) {
                     edge_properties.draw_state = DrawState::Highlighted;
-                } else {
-                    edge_properties.draw_state = DrawState::Default;
+                    edge_properties.path_direction = Some((from, to));
                 }
             }
         }
     }
-}
 
-#[derive(Clone, Copy)]
-struct DragState {
-    vertex: usize,
-    mouse_position: Vec2,
-}
+    /// Highlights the edges of history entry `index` in its color, on top of the current
+    /// highlighting.
+    fn add_edge_highlighting(&mut self, index: usize) {
+        let entry = &self.highlight_graph_history[index];
+        let highlighted = undirected_edges(&entry.graph);
+        for edge_properties in self.edge_properties.iter_mut() {
+            let (a, b) = edge_properties.vertices;
+            if highlighted.contains(&canonical_edge(a, b)) {
+                edge_properties.draw_state = DrawState::Highlighted;
+                edge_properties.highlight_color_index = entry.color_index;
+                edge_properties.highlight_color_override = entry.color_override;
+            }
+        }
+        self.dirty = true;
+    }
 
-struct ClickHandler {
-    click_start: Option<(Instant, Vec2)>,
-    drag_min_duration: Duration,
-    registered_click_this_frame: bool,
-}
+    /// Attaches labels to the matching edges and returns the pairs without a matching edge.
+    pub fn set_edge_labels(
+        &mut self,
+        labels: &HashMap<(usize, usize), String>,
+    ) -> Vec<(usize, usize)> {
+        let mut unmatched: Vec<(usize, usize)> = labels.keys().copied().collect();
 
-impl ClickHandler {
-    fn new() -> Self {
-        Self {
-            click_start: None,
-            drag_min_duration: Duration::from_millis(125),
-            registered_click_this_frame: false,
+        for edge_properties in self.edge_properties.iter_mut() {
+            let (a, b) = edge_properties.vertices;
+            let edge = canonical_edge(a, b);
+
+            if let Some(label) = labels.get(&edge) {
+                edge_properties.weight = label.trim().parse().ok();
+                edge_properties.label = Some(label.clone());
+                unmatched.retain(|pair| *pair != edge);
+            }
+        }
+
+        self.dirty = true;
+
+        unmatched.sort_unstable();
+        unmatched
+    }
+
+    pub fn clear_edge_labels(&mut self) {
+        for edge_properties in self.edge_properties.iter_mut() {
+            edge_properties.label = None;
+            edge_properties.weight = None;
+        }
+        self.dirty = true;
+    }
+
+    /// Sets the effective radius of every vertex, its override or the configured radius. Drawing
+    /// and hit-testing both use it.
+    pub fn resolve_vertex_radii(&mut self, config: &VertexDrawConfig) {
+        let mut changed = false;
+
+        for vertex_properties in self.vertex_properties.iter_mut() {
+            let radius = vertex_properties
+                .radius_override
+                .unwrap_or_else(|| config.radius());
+
+            if vertex_properties.radius != radius {
+                vertex_properties.radius = radius;
+                changed = true;
+            }
+        }
+
+        if changed {
+            self.mark_geometry_changed();
         }
     }
 
-    fn register_mouse_button_status(&mut self) {
-        let mouse_down = is_mouse_button_down(MouseButton::Left);
+    /// Sets the effective width of every edge: an override wins, then the weight when
+    /// `width_by_weight` is enabled, then the configured width.
+    pub fn resolve_edge_widths(&mut self, config: &EdgeDrawConfig) {
+        let weight_range = self
+            .edge_properties
+            .iter()
+            .filter_map(|edge_properties| edge_properties.weight)
+            .fold(None, |range: Option<(f32, f32)>, weight| match range {
+                Some((min, max)) => Some((min.min(weight), max.max(weight))),
+                None => Some((weight, weight)),
+            });
+
+        let mut changed = false;
 
-        self.registered_click_this_frame = false;
+        for edge_properties in self.edge_properties.iter_mut() {
+            let width = match (edge_properties.width_override, edge_properties.weight) {
+                (Some(width), _) => width,
+                (None, Some(weight)) if config.width_by_weight => weight_to_width(
+                    weight,
+                    weight_range.unwrap_or((weight, weight)),
+                    (config.min_weight_width, config.max_weight_width),
+                ),
+                _ => config.width,
+            };
 
-        if mouse_down {
-            if self.click_start.is_none() {
-                self.click_start = Some((Instant::now(), mouse_position().into()))
+            if edge_properties.width != width {
+                edge_properties.width = width;
+                changed = true;
             }
-        } else if let Some(start) = self.click_start {
-            let time_since_start = Instant::now() - start.0;
+        }
 
-            self.registered_click_this_frame = time_since_start < self.drag_min_duration;
+        if changed {
+            self.mark_geometry_changed();
+        }
+    }
 
-            self.click_start = None;
+    /// Sets how close to a vertex or an edge on screen the mouse has to be to hit it.
+    /// `view_scale` is the number of screen pixels per unit of the layout, when the view is
+    /// transformed.
+    pub fn set_hit_tolerance(&mut self, screen_tolerance: f32, view_scale: Option<f32>) {
+        if (self.hit_tolerance, self.view_scale) != (screen_tolerance, view_scale) {
+            self.hit_tolerance = screen_tolerance;
+            self.view_scale = view_scale;
+            self.mark_geometry_changed();
         }
     }
 
-    fn mouse_drag(&self) -> bool {
-        if let Some(start) = self.click_start {
-            let time_since_start = Instant::now() - start.0;
-            let drag_distance = start.1.distance(mouse_position().into());
+    fn hit_distance(&self, visual_size: f32) -> f32 {
+        hit_distance(visual_size, self.hit_tolerance, self.view_scale)
+    }
 
-            return time_since_start > self.drag_min_duration || drag_distance > 5.0;
+    /// Bends every edge like [`EdgeDrawConfig::curvature`], for hit-testing.
+    pub fn set_edge_curvature(&mut self, curvature: f32) {
+        if self.edge_curvature != curvature {
+            self.edge_curvature = curvature;
+            self.mark_geometry_changed();
         }
+    }
 
-        false
+    /// Advances the orientation of an edge and returns its vertices with the previous orientation.
+    pub fn cycle_edge_orientation(&mut self, edge: usize) -> Option<((usize, usize), Orientation)> {
+        let edge_properties = self.edge_properties.get_mut(edge)?;
+        let previous = edge_properties.orientation;
+        edge_properties.orientation = previous.next();
+        let vertices = edge_properties.vertices;
+        self.rebuild_anti_parallel();
+
+        Some((vertices, previous))
     }
 
-    fn mouse_click(&self) -> bool {
-        self.registered_click_this_frame
+    /// Sets the orientation as seen from `a` to `b`, returns whether such an edge exists.
+    pub fn set_edge_orientation(&mut self, a: usize, b: usize, orientation: Orientation) -> bool {
+        // the exact match first, so each edge of an anti-parallel pair can be set
+        let Some(edge_properties) = self
+            .edge_properties
+            .iter()
+            .position(|properties| properties.vertices == (a, b))
+            .or_else(|| {
+                self.edge_properties
+                    .iter()
+                    .position(|properties| properties.vertices == (b, a))
+            })
+            .map(|index| &mut self.edge_properties[index])
+        else {
+            return false;
+        };
+
+        edge_properties.orientation = if edge_properties.vertices == (a, b) {
+            orientation
+        } else {
+            orientation.reversed()
+        };
+        self.rebuild_anti_parallel();
+
+        true
     }
-}
 
-fn distance_to_line(line_start: Vec2, line_end: Vec2, point: Vec2) -> f32 {
-    let a = line_end.x - line_start.x;
-    let b = line_end.y - line_start.y;
+    /// State induced on an edge by its endpoints. It is derived on every call instead of being
+    /// written to the edge, so the edge keeps its own state and shows it again once no endpoint
+    /// induces anything. Emphasis only applies to edges in the default state.
+    pub fn induced_edge_state(
+        &self,
+        edge: usize,
+        hidden_vertex_edges: HiddenVertexEdges,
+        emphasize_highlighted: bool,
+    ) -> Option<InducedEdgeState> {
+        let edge_properties = self.edge_properties.get(edge)?;
+        let (a, b) = edge_properties.vertices;
+        // the hidden members of a collapsed group show through their meta-vertex
+        let endpoint_states = [a, b].map(|vertex| {
+            self.vertex_properties
+                .get(self.meta_vertex(vertex))
+                .map(|p| p.draw_state)
+        });
 
-    let c = a * (line_start.y - point.y) - (line_start.x - point.x) * b;
-    let abs_c = c.abs();
+        if endpoint_states.contains(&Some(DrawState::Hidden)) {
+            match hidden_vertex_edges {
+                HiddenVertexEdges::Keep => {}
+                HiddenVertexEdges::Fade => return Some(InducedEdgeState::Faded),
+                HiddenVertexEdges::Hide => return Some(InducedEdgeState::Hidden),
+            }
+        }
 
-    let root = (a * a + b * b).sqrt();
+        let emphasized = emphasize_highlighted
+            && edge_properties.draw_state == DrawState::Default
+            && endpoint_states.contains(&Some(DrawState::Highlighted));
 
-    if root == 0.0 {
-        return f32::MAX;
+        emphasized.then_some(InducedEdgeState::Emphasized)
     }
 
-    abs_c / root
+    /// Adds the edge to or removes it from the current highlighting, as a new history entry.
+    pub fn toggle_edge_highlighting(&mut self, edge: usize) {
+        let Some(&EdgeProperties { vertices, .. }) = self.edge_properties.get(edge) else {
+            return;
+        };
+
+        let mut highlight = Graph::new(self.vertex_properties.len());
+        highlight.edges = self.highlighted_edges();
+        match highlight.edges.iter().position(|&edge| edge == vertices) {
+            Some(index) => {
+                highlight.edges.remove(index);
+            }
+            None => highlight.edges.push(vertices),
+        }
+
+        self.set_edge_highlighting_and_add_to_history(highlight);
+    }
+
+    pub fn highlighted_edges(&self) -> Vec<(usize, usize)> {
+        self.edge_properties
+            .iter()
+            .filter(|edge_properties| edge_properties.draw_state == DrawState::Highlighted)
+            .map(|edge_properties| edge_properties.vertices)
+            .collect()
+    }
+
+    /// Positions of the vertices of a subgraph, those of the original vertices.
+    pub fn subgraph_positions(&self, subgraph: &Subgraph) -> Vec<Vec2> {
+        subgraph
+            .original_vertices
+            .iter()
+            .map(|&original| self.get_position(original))
+            .collect()
+    }
+
+    pub fn clear_highlight_history(&mut self) {
+        self.highlight_graph_history.clear();
+        self.clear_edge_highlighting();
+    }
+
+    /// Adds an entry named after its id.
+    pub fn add_graph_to_history(&mut self, graph: Graph) {
+        let id = self.next_highlight_id;
+        self.next_highlight_id += 1;
+
+        let color_index = self.unused_color_index();
+        self.highlight_graph_history.push(HighlightEntry {
+            id,
+            name: format!("Highlight {}", id + 1),
+            graph,
+            color_index,
+            color_override: None,
+        });
+    }
+
+    /// The first palette color no entry has, which is the position of a new entry in a
+    /// history that was only added to.
+    fn unused_color_index(&self) -> usize {
+        (0..)
+            .find(|&color_index| {
+                self.highlight_graph_history
+                    .iter()
+                    .all(|entry| entry.color_index != color_index)
+            })
+            .unwrap()
+    }
+
+    pub fn get_history_size(&self) -> usize {
+        self.highlight_graph_history.len()
+    }
+
+    pub fn set_edge_highlighting_and_add_to_history(&mut self, graph: Graph) {
+        self.clear_edge_highlighting();
+        self.add_edge_highlighting_and_add_to_history(graph);
+    }
+
+    /// Highlights the edges on top of the current highlighting, in the color of the new entry.
+    pub fn add_edge_highlighting_and_add_to_history(&mut self, graph: Graph) {
+        let index = self.highlight_graph_history.len();
+        self.add_graph_to_history(graph);
+        self.add_edge_highlighting(index);
+        self.current_highlight = Some(self.highlight_graph_history[index].id);
+    }
+
+    pub fn highlight_history(&self) -> &[HighlightEntry] {
+        &self.highlight_graph_history
+    }
+
+    /// Position of the shown entry in the history.
+    pub fn current_highlight_graph(&self) -> Option<usize> {
+        let id = self.current_highlight?;
+        self.highlight_graph_history
+            .iter()
+            .position(|entry| entry.id == id)
+    }
+
+    /// Shows the highlighting of a history entry, returns false when there is no such entry.
+    pub fn jump_to_highlighting(&mut self, index: usize) -> bool {
+        let Some(entry) = self.highlight_graph_history.get(index) else {
+            return false;
+        };
+
+        self.current_highlight = Some(entry.id);
+        let highlighted = undirected_edges(&entry.graph);
+        for edge_properties in self.edge_properties.iter_mut() {
+            let (a, b) = edge_properties.vertices;
+            if highlighted.contains(&canonical_edge(a, b)) {
+                edge_properties.draw_state = DrawState::Highlighted;
+                edge_properties.highlight_color_index = entry.color_index;
+                edge_properties.highlight_color_override = entry.color_override;
+            } else {
+                edge_properties.draw_state = DrawState::Default;
+            }
+        }
+        self.dirty = true;
+
+        true
+    }
+
+    pub fn set_next_highlighting(&mut self) {
+        let target_index = match self.current_highlight_graph() {
+            Some(index) => index + 1,
+            None => 0,
+        };
+
+        self.jump_to_highlighting(target_index);
+    }
+
+    pub fn set_previous_highlighting(&mut self) {
+        let target_index = match self.current_highlight_graph() {
+            Some(index) => index.checked_sub(1),
+            None => self.highlight_graph_history.len().checked_sub(1),
+        };
+
+        if let Some(target_index) = target_index {
+            self.jump_to_highlighting(target_index);
+        }
+    }
+
+    pub fn rename_highlight(&mut self, index: usize, name: &str) -> bool {
+        let Some(entry) = self.highlight_graph_history.get_mut(index) else {
+            return false;
+        };
+
+        entry.name = name.to_string();
+        true
+    }
+
+    /// Overrides the palette color of an entry, also on its edges that are highlighted.
+    pub fn set_highlight_color(&mut self, index: usize, color_override: Option<Color>) -> bool {
+        let Some(entry) = self.highlight_graph_history.get_mut(index) else {
+            return false;
+        };
+        entry.color_override = color_override;
+
+        for edge_properties in self.edge_properties.iter_mut() {
+            if edge_properties.draw_state == DrawState::Highlighted
+                && edge_properties.highlight_color_index == entry.color_index
+            {
+                edge_properties.highlight_color_override = color_override;
+            }
+        }
+        self.dirty = true;
+
+        true
+    }
+
+    /// Removes an entry. Deleting the shown entry shows the one taking its place, or the new last
+    /// entry, or nothing when the history is empty.
+    pub fn delete_highlight(&mut self, index: usize) -> bool {
+        if index >= self.highlight_graph_history.len() {
+            return false;
+        }
+
+        let was_current = self.current_highlight_graph() == Some(index);
+        self.highlight_graph_history.remove(index);
+
+        if was_current {
+            let replacement = index.min(self.highlight_graph_history.len().saturating_sub(1));
+            if !self.jump_to_highlighting(replacement) {
+                self.clear_edge_highlighting();
+            }
+        }
+
+        true
+    }
+
+    /// Inserts a copy with a new id right after the entry and returns its position.
+    pub fn duplicate_highlight(&mut self, index: usize) -> Option<usize> {
+        let entry = self.highlight_graph_history.get(index)?;
+        let copy = HighlightEntry {
+            id: self.next_highlight_id,
+            name: format!("{} (copy)", entry.name),
+            graph: entry.graph.clone(),
+            color_index: self.unused_color_index(),
+            color_override: entry.color_override,
+        };
+        self.next_highlight_id += 1;
+
+        self.highlight_graph_history.insert(index + 1, copy);
+        Some(index + 1)
+    }
+
+    /// Moves an entry to `target`, shifting the entries in between. The shown entry stays shown.
+    pub fn move_highlight(&mut self, index: usize, target: usize) -> bool {
+        let len = self.highlight_graph_history.len();
+        if index >= len || target >= len {
+            return false;
+        }
+
+        let entry = self.highlight_graph_history.remove(index);
+        self.highlight_graph_history.insert(target, entry);
+        true
+    }
+}
+
+/// Like [`GraphInterface::edge_crossings`], for a copy of the layout. `progress` gets the
+/// fraction of the edges done after every edge and stops the count by returning false.
+pub fn count_crossings(
+    positions: &[Vec2],
+    edges: &[(usize, usize)],
+    mut progress: impl FnMut(f32) -> bool,
+) -> Option<usize> {
+    let position = |vertex: usize| positions.get(vertex).copied().unwrap_or_default();
+    let mut crossings = 0;
+
+    for (i, &(a, b)) in edges.iter().enumerate() {
+        for &(c, d) in &edges[i + 1..] {
+            if a == c || a == d || b == c || b == d {
+                continue;
+            }
+
+            if segments_intersect(position(a), position(b), position(c), position(d)) {
+                crossings += 1;
+            }
+        }
+
+        if !progress((i + 1) as f32 / edges.len() as f32) {
+            return None;
+        }
+    }
+
+    Some(crossings)
+}
+
+/// Last hit-test result and the spatial index used to answer new queries.
+struct HoverCache {
+    query: Option<(Vec2, HitTestResult)>,
+    vertices: SpatialIndex,
+    edges: SpatialIndex,
+    index_valid: bool,
+}
+
+impl HoverCache {
+    fn new() -> Self {
+        Self {
+            query: None,
+            vertices: SpatialIndex::new(HOVER_CELL_SIZE),
+            edges: SpatialIndex::new(HOVER_CELL_SIZE),
+            index_valid: false,
+        }
+    }
+}
+
+/// Vertex whose neighbourhood is highlighted and the vertex states to restore.
+struct NeighbourhoodHighlight {
+    vertex: usize,
+    vertex_states: Vec<(usize, DrawState)>,
+}
+
+/// A box or lasso selection being dragged out.
+#[derive(Clone, Debug, PartialEq)]
+enum SelectionDrag {
+    Box { start: Vec2, end: Vec2 },
+    Lasso(Vec<Vec2>),
+}
+
+impl SelectionDrag {
+    fn extend(&mut self, mouse_position: Vec2) {
+        match self {
+            SelectionDrag::Box { end, .. } => *end = mouse_position,
+            SelectionDrag::Lasso(points) => {
+                let last = *points.last().unwrap();
+                if last.distance(mouse_position) >= LASSO_POINT_SPACING {
+                    points.push(mouse_position);
+                }
+            }
+        }
+    }
+}
+
+/// Even-odd rule, so self-intersecting lassos select the regions enclosed an odd number of times.
+pub fn polygon_contains(polygon: &[Vec2], point: Vec2) -> bool {
+    if polygon.len() < 3 {
+        return false;
+    }
+
+    let mut inside = false;
+    let mut previous = polygon[polygon.len() - 1];
+
+    for &current in polygon {
+        // the edge crosses the horizontal ray to the right of the point
+        if (current.y > point.y) != (previous.y > point.y) {
+            let x = current.x
+                + (point.y - current.y) * (previous.x - current.x) / (previous.y - current.y);
+            if point.x < x {
+                inside = !inside;
+            }
+        }
+        previous = current;
+    }
+
+    inside
+}
+
+#[derive(Clone, Copy)]
+struct DragState {
+    vertex: usize,
+    mouse_position: Vec2,
+    axis_lock: Option<AxisLock>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum DragAxis {
+    Horizontal,
+    Vertical,
+}
+
+impl DragAxis {
+    /// Dominant direction of a movement, once it is longer than [`AXIS_LOCK_THRESHOLD`].
+    fn of_movement(movement: Vec2) -> Option<DragAxis> {
+        if movement.length() <= AXIS_LOCK_THRESHOLD {
+            None
+        } else if movement.x.abs() >= movement.y.abs() {
+            Some(DragAxis::Horizontal)
+        } else {
+            Some(DragAxis::Vertical)
+        }
+    }
+
+    fn project(&self, delta: Vec2) -> Vec2 {
+        match self {
+            DragAxis::Horizontal => vec2(delta.x, 0.0),
+            DragAxis::Vertical => vec2(0.0, delta.y),
+        }
+    }
+}
+
+/// Drag constrained to one axis, decided from the movement since `origin`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct AxisLock {
+    origin: Vec2,
+    axis: Option<DragAxis>,
+}
+
+/// Projects the mouse movement of one frame onto the locked axis. Until the axis is decided the
+/// vertex stays put; the frame that decides it catches up on the movement since the origin.
+fn constrained_drag_delta(lock: AxisLock, previous_mouse: Vec2, mouse: Vec2) -> (AxisLock, Vec2) {
+    match lock.axis {
+        Some(axis) => (lock, axis.project(mouse - previous_mouse)),
+        None => match DragAxis::of_movement(mouse - lock.origin) {
+            Some(axis) => (
+                AxisLock {
+                    axis: Some(axis),
+                    ..lock
+                },
+                axis.project(mouse - lock.origin),
+            ),
+            None => (lock, Vec2::ZERO),
+        },
+    }
+}
+
+/// Maps a weight linearly from the weight range to the width range.
+/// When all weights are equal, the midpoint of the width range is used.
+pub fn weight_to_width(weight: f32, weight_range: (f32, f32), width_range: (f32, f32)) -> f32 {
+    let (min_weight, max_weight) = weight_range;
+    let (min_width, max_width) = width_range;
+
+    if max_weight <= min_weight {
+        return (min_width + max_width) / 2.0;
+    }
+
+    let t = ((weight - min_weight) / (max_weight - min_weight)).clamp(0.0, 1.0);
+    min_width + t * (max_width - min_width)
+}
+
+/// Distance in layout units within which an item of `visual_size` is hit: its size, but never
+/// less than `screen_tolerance` pixels on screen, whatever the zoom.
+fn hit_distance(visual_size: f32, screen_tolerance: f32, view_scale: Option<f32>) -> f32 {
+    let scale = view_scale.filter(|&scale| scale > 0.0).unwrap_or(1.0);
+    visual_size.max(screen_tolerance / scale)
+}
+
+/// Position of vertex `index` when `count` vertices are spread evenly over a ring in the viewport.
+fn ring_position(index: usize, count: usize, viewport: Vec2) -> Vec2 {
+    let center = viewport / 2.0;
+    if count <= 1 {
+        return center;
+    }
+
+    let tau_part = std::f32::consts::TAU / count as f32;
+    let offset_magnitude = viewport.x.min(viewport.y) / 2.0 - 50.0;
+
+    let i = index as f32;
+    let x_offset = (i * tau_part).sin() * offset_magnitude;
+    let y_offset = -(i * tau_part).cos() * offset_magnitude;
+
+    center + Vec2::new(x_offset, y_offset)
+}
+
+fn distance_to_line(line_start: Vec2, line_end: Vec2, point: Vec2) -> f32 {
+    let a = line_end.x - line_start.x;
+    let b = line_end.y - line_start.y;
+
+    let c = a * (line_start.y - point.y) - (line_start.x - point.x) * b;
+    let abs_c = c.abs();
+
+    let root = (a * a + b * b).sqrt();
+
+    if root == 0.0 {
+        return f32::MAX;
+    }
+
+    abs_c / root
+}
+
+/// The edges of a highlight graph without their direction, like [`Graph::subgraph_mismatch`]
+/// compares them.
+fn undirected_edges(graph: &Graph) -> HashSet<(usize, usize)> {
+    graph
+        .edges
+        .iter()
+        .map(|&(a, b)| canonical_edge(a, b))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        force::RepulsionModel,
+        graph_drawer::{DrawConfig, DrawableGraph},
+        input::{InputConfig, InputScript, InputTracker, RawInput, ScriptStep},
+    };
+
+    /// A path on three vertices at (100, 200), (200, 200) and (300, 200).
+    fn scripted_path() -> GraphInterface {
+        let graph = Graph::path(3);
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+        embedding.set_positions(&[vec2(100.0, 200.0), vec2(200.0, 200.0), vec2(300.0, 200.0)]);
+        embedding
+    }
+
+    fn play(embedding: &mut GraphInterface, script: &mut InputScript, steps: &[ScriptStep]) {
+        script.play(steps, |input| {
+            embedding.handle_mouse_input(input);
+            embedding.handle_keyboard_input(input);
+        });
+    }
+
+    #[test]
+    fn test_set_edge_labels_reports_missing_edges() {
+        let mut graph = Graph::new(3);
+        graph.edges.push((0, 1));
+        graph.edges.push((1, 2));
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+
+        let mut labels = HashMap::new();
+        labels.insert((1, 2), "b".to_string());
+        labels.insert((0, 2), "missing".to_string());
+
+        let unmatched = embedding.set_edge_labels(&labels);
+
+        assert_eq!(unmatched, vec![(0, 2)]);
+        assert_eq!(embedding.edge_properties[0].label, None);
+        assert_eq!(embedding.edge_properties[1].label, Some("b".to_string()));
+
+        embedding.clear_edge_labels();
+        assert_eq!(embedding.edge_properties[1].label, None);
+    }
+
+    #[test]
+    fn test_update_edges_places_new_vertices_on_ring() {
+        let small_graph = Graph::new(2);
+        let mut embedding = GraphInterface::with_viewport(&small_graph, vec2(400.0, 400.0));
+
+        let mut large_graph = Graph::new(5);
+        large_graph.edges.push((0, 4));
+        large_graph.edges.push((3, 4));
+        embedding.update_edges(&large_graph);
+
+        assert_eq!(embedding.vertex_properties.len(), 5);
+        for vertex in &embedding.vertex_properties {
+            assert_ne!(vertex.position, Vec2::ZERO);
+        }
+        assert_ne!(
+            embedding.get_position(3),
+            embedding.get_position(4),
+            "new vertices should not coincide"
+        );
+    }
+
+    #[test]
+    fn test_update_edges_with_fewer_vertices() {
+        let mut large_graph = Graph::new(5);
+        large_graph.edges.push((0, 4));
+        let mut embedding = GraphInterface::with_viewport(&large_graph, vec2(400.0, 400.0));
+        let kept_position = embedding.get_position(1);
+        embedding.selected = Some(Selection::Vertex(4));
+
+        let mut small_graph = Graph::new(3);
+        small_graph.edges.push((0, 2));
+        embedding.update_edges(&small_graph);
+
+        assert_eq!(embedding.vertex_properties.len(), 3);
+        assert_eq!(embedding.edge_properties.len(), 1);
+        assert_eq!(embedding.get_position(1), kept_position);
+        assert_eq!(embedding.selected, None);
+    }
+
+    #[test]
+    fn test_highlight_edges_where() {
+        let mut graph = Graph::new(6);
+        graph.edges.extend([(0, 1), (1, 5), (2, 4), (0, 4), (3, 5)]);
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+
+        embedding.highlight_edges_where(|a, b| a == 5 || b == 5);
+        let highlighted: Vec<(usize, usize)> = embedding
+            .edge_properties
+            .iter()
+            .filter(|edge| edge.draw_state == DrawState::Highlighted)
+            .map(|edge| edge.vertices)
+            .collect();
+        assert_eq!(highlighted, vec![(1, 5), (3, 5)]);
+
+        embedding.highlight_edges_where(|a, b| a % 2 == 0 && b % 2 == 0);
+        let highlighted: Vec<(usize, usize)> = embedding
+            .edge_properties
+            .iter()
+            .filter(|edge| edge.draw_state == DrawState::Highlighted)
+            .map(|edge| edge.vertices)
+            .collect();
+        assert_eq!(highlighted, vec![(2, 4), (0, 4)]);
+    }
+
+    #[test]
+    fn test_frozen_vertices_do_not_move() {
+        let mut graph = Graph::new(5);
+        graph
+            .edges
+            .extend([(0, 1), (1, 2), (2, 3), (3, 4), (0, 4), (0, 2)]);
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+
+        embedding.vertex_properties[1].draw_state = DrawState::Highlighted;
+        embedding.vertex_properties[3].draw_state = DrawState::Highlighted;
+        embedding.freeze_selection();
+        assert_eq!(embedding.frozen_count(), 2);
+
+        let frozen_before = [embedding.get_position(1), embedding.get_position(3)];
+        let free_before = embedding.get_position(0);
+
+        for _ in 0..100 {
+            embedding.apply_force(&graph, &ForceConfig::default(), None);
+        }
+
+        assert_eq!(
+            embedding.get_position(1).to_array(),
+            frozen_before[0].to_array()
+        );
+        assert_eq!(
+            embedding.get_position(3).to_array(),
+            frozen_before[1].to_array()
+        );
+        assert_ne!(embedding.get_position(0), free_before);
+
+        embedding.unfreeze_all();
+        assert_eq!(embedding.frozen_count(), 0);
+    }
+
+    #[test]
+    fn test_square_alignment_uses_snap_delta() {
+        let graph = Graph::new(1);
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+        embedding.set_position(0, vec2(33.0, 40.0));
+
+        let mut grid = SquareGrid::new(10.0, 10.0);
+        grid.set_snap_delta(40.0);
+        grid.set_offsets_from_window(vec2(400.0, 400.0));
+
+        embedding.align_to_square_grid(&grid);
+
+        // the nearest visual grid line is at x = 30, the nearest snap line at x = 40
+        assert!(embedding.get_position(0).x > 33.0);
+        assert_eq!(grid.x_delta(), 10.0);
+    }
+
+    #[test]
+    fn test_circular_alignment_uses_snap_delta() {
+        let graph = Graph::new(1);
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+        embedding.set_position(0, vec2(200.0 + 33.0, 200.0));
+
+        let mut grid = CircleGrid::new(10.0, vec2(400.0, 400.0));
+        grid.set_snap_r_delta(40.0);
+
+        embedding.align_to_circular_grid(&grid);
+
+        assert!(embedding.get_position(0).x > 233.0);
+        assert_eq!(grid.r_delta(), 10.0);
+    }
+
+    #[test]
+    fn test_edges_rotate_towards_horizontal() {
+        let mut graph = Graph::new(3);
+        graph.edges.extend([(0, 1)]);
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+        embedding.set_position(0, vec2(100.0, 200.0));
+        embedding.set_position(1, vec2(200.0, 210.0));
+        embedding.set_position(2, vec2(300.0, 300.0));
+
+        embedding.align_edges_to_angles(0.5);
+
+        let direction = embedding.get_position(1) - embedding.get_position(0);
+        let angle = direction.y.atan2(direction.x);
+        assert!(angle.abs() < 0.1f32.atan2(1.0));
+        assert!(angle > 0.0);
+        assert!((direction.length() - vec2(100.0, 10.0).length()).abs() < 1e-3);
+        assert_eq!(embedding.get_position(2), vec2(300.0, 300.0));
+
+        let pinned_position = embedding.get_position(0);
+        embedding.vertex_properties[0].pinned = true;
+        embedding.align_edges_to_angles(1.0);
+
+        assert_eq!(embedding.get_position(0), pinned_position);
+        assert!((embedding.get_position(1).y - pinned_position.y).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_draw_state_index_round_trip() {
+        for (index, state) in DrawState::ALL.iter().enumerate() {
+            assert_eq!(state.index(), index);
+        }
+
+        for (index, style) in EdgeStyle::ALL.iter().enumerate() {
+            assert_eq!(style.index(), index);
+        }
+    }
+
+    #[test]
+    fn test_weight_to_width() {
+        assert_eq!(weight_to_width(1.0, (1.0, 3.0), (2.0, 10.0)), 2.0);
+        assert_eq!(weight_to_width(2.0, (1.0, 3.0), (2.0, 10.0)), 6.0);
+        assert_eq!(weight_to_width(3.0, (1.0, 3.0), (2.0, 10.0)), 10.0);
+        assert_eq!(weight_to_width(5.0, (5.0, 5.0), (2.0, 10.0)), 6.0);
+    }
+
+    #[test]
+    fn test_hover_follows_vertex_size() {
+        let graph = Graph::new(2);
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+        embedding.set_position(0, vec2(100.0, 100.0));
+        embedding.set_position(1, vec2(300.0, 100.0));
+        let mut config = VertexDrawConfig::default();
+        let near = vec2(130.0, 100.0);
+
+        embedding.resolve_vertex_radii(&config);
+        assert_eq!(embedding.hit_test(near), (None, None));
+
+        config.scale_size(2.0);
+        embedding.resolve_vertex_radii(&config);
+        assert_eq!(embedding.vertex_properties[0].radius, 34.0);
+        assert_eq!(embedding.hit_test(near), (Some(0), None));
+
+        // an override keeps its radius whatever the configured size
+        embedding.vertex_properties[1].radius_override = Some(10.0);
+        embedding.resolve_vertex_radii(&config);
+        assert_eq!(embedding.vertex_properties[1].radius, 10.0);
+        assert_eq!(embedding.hit_test(vec2(315.0, 100.0)), (None, None));
+        assert_eq!(embedding.hit_test(vec2(105.0, 100.0)), (Some(0), None));
+    }
+
+    #[test]
+    fn test_resolve_edge_widths_by_weight() {
+        let mut graph = Graph::new(4);
+        graph.edges.push((0, 1));
+        graph.edges.push((1, 2));
+        graph.edges.push((2, 3));
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+
+        let mut labels = HashMap::new();
+        labels.insert((0, 1), "2".to_string());
+        labels.insert((1, 2), "4".to_string());
+        labels.insert((2, 3), "heavy".to_string());
+        embedding.set_edge_labels(&labels);
+
+        let config = EdgeDrawConfig {
+            width_by_weight: true,
+            ..Default::default()
+        };
+        embedding.resolve_edge_widths(&config);
+
+        assert_eq!(embedding.edge_properties[0].width, config.min_weight_width);
+        assert_eq!(embedding.edge_properties[1].width, config.max_weight_width);
+        // a label that isn't a number has no weight
+        assert_eq!(embedding.edge_properties[2].width, config.width);
+    }
+
+    #[test]
+    fn test_dirty_tracking() {
+        let mut graph = Graph::new(2);
+        graph.edges.push((0, 1));
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+        assert!(embedding.is_dirty());
+
+        embedding.resolve_edge_widths(&EdgeDrawConfig::default());
+        embedding.clear_dirty();
+
+        // a frame without changes
+        let position = embedding.get_position(0);
+        embedding.set_position(0, position);
+        embedding.apply_forces(&[Vec2::ZERO, Vec2::ZERO]);
+        embedding.resolve_edge_widths(&EdgeDrawConfig::default());
+        assert!(!embedding.is_dirty());
+
+        embedding.set_position(0, position + vec2(1.0, 0.0));
+        assert!(embedding.is_dirty());
+    }
+
+    #[test]
+    fn test_viewport_change_rescales_positions() {
+        let graph = Graph::new(2);
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+        embedding.set_position(0, vec2(100.0, 200.0));
+        embedding.set_position(1, vec2(390.0, 10.0));
+        embedding.clear_dirty();
+
+        let change = ViewportChange {
+            old: vec2(400.0, 400.0),
+            new: vec2(200.0, 800.0),
+        };
+        embedding.handle_viewport_change(change, ResizeMode::Rescale);
+
+        assert_eq!(embedding.viewport, vec2(200.0, 800.0));
+        assert_eq!(embedding.get_position(0), vec2(50.0, 400.0));
+        assert_eq!(embedding.get_position(1), vec2(195.0, 20.0));
+        assert!(embedding.is_dirty());
+
+        let change = ViewportChange {
+            old: vec2(200.0, 800.0),
+            new: vec2(100.0, 800.0),
+        };
+        embedding.handle_viewport_change(change, ResizeMode::Clamp);
+        assert_eq!(embedding.get_position(1), vec2(100.0, 20.0));
+    }
+
+    #[test]
+    fn test_shake_is_reproducible() {
+        let mut graph = Graph::new(4);
+        graph.edges.push((0, 1));
+        graph.edges.push((1, 2));
+        graph.edges.push((1, 3));
+        let mut first = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+        let mut second = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+        first.vertex_properties[0].pinned = true;
+        second.vertex_properties[0].pinned = true;
+        let pinned_position = first.get_position(0);
+
+        first.shake(&graph, 30.0, &mut SeededRng::new(5));
+        second.shake(&graph, 30.0, &mut SeededRng::new(5));
+
+        assert_eq!(first.positions(), second.positions());
+        assert_eq!(first.get_position(0), pinned_position);
+        assert_ne!(
+            first.positions(),
+            GraphInterface::with_viewport(&graph, vec2(400.0, 400.0)).positions()
+        );
+        for position in first.positions() {
+            assert!(position.cmpge(Vec2::ZERO).all() && position.cmple(vec2(400.0, 400.0)).all());
+        }
+    }
+
+    #[test]
+    fn test_edge_crossings() {
+        let mut graph = Graph::new(4);
+        graph.edges.push((0, 2));
+        graph.edges.push((1, 3));
+        graph.edges.push((0, 1));
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+
+        // the diagonals of a square cross, the side shares endpoints with both
+        embedding.set_position(0, vec2(0.0, 0.0));
+        embedding.set_position(1, vec2(100.0, 0.0));
+        embedding.set_position(2, vec2(100.0, 100.0));
+        embedding.set_position(3, vec2(0.0, 100.0));
+        assert_eq!(embedding.edge_crossings(), 1);
+
+        embedding.set_position(2, vec2(0.0, 50.0));
+        assert_eq!(embedding.edge_crossings(), 0);
+    }
+
+    #[test]
+    fn test_count_crossings_reports_progress() {
+        let positions = [
+            vec2(0.0, 0.0),
+            vec2(100.0, 0.0),
+            vec2(100.0, 100.0),
+            vec2(0.0, 100.0),
+        ];
+        let edges = [(0, 2), (1, 3), (0, 1), (2, 3)];
+        let mut reported = Vec::new();
+
+        let crossings = count_crossings(&positions, &edges, |fraction| {
+            reported.push(fraction);
+            true
+        });
+        assert_eq!(crossings, Some(1));
+        assert_eq!(reported, vec![0.25, 0.5, 0.75, 1.0]);
+
+        // stopped after the first edge
+        assert_eq!(count_crossings(&positions, &edges, |_| false), None);
+        assert_eq!(count_crossings(&positions, &[], |_| false), Some(0));
+    }
+
+    #[test]
+    fn test_hit_test_matches_brute_force() {
+        let mut rng = SeededRng::new(11);
+        let mut graph = Graph::new(40);
+        for _ in 0..80 {
+            let a = (rng.next_u64() % 40) as usize;
+            let b = (rng.next_u64() % 40) as usize;
+            if a != b {
+                graph.edges.push((a.min(b), a.max(b)));
+            }
+        }
+
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+        for vertex in 0..graph.vertices {
+            let position = vec2(rng.gen_range(0.0, 400.0), rng.gen_range(0.0, 400.0));
+            embedding.set_position(vertex, position);
+        }
+
+        let mut mouse = vec2(200.0, 200.0);
+        for step in 0..5000 {
+            // mostly small steps, sometimes standing still or jumping
+            match step % 50 {
+                0 => mouse = vec2(rng.gen_range(0.0, 400.0), rng.gen_range(0.0, 400.0)),
+                1..=9 => {}
+                _ => mouse += vec2(rng.gen_range(-4.0, 4.0), rng.gen_range(-4.0, 4.0)),
+            }
+
+            if step % 97 == 0 {
+                let vertex = (rng.next_u64() % 40) as usize;
+                let offset = vec2(rng.gen_range(-20.0, 20.0), rng.gen_range(-20.0, 20.0));
+                embedding.set_position(vertex, embedding.get_position(vertex) + offset);
+            }
+
+            let expected = (
+                embedding.get_vertex_at_position(mouse),
+                embedding.get_edge_at_position(mouse),
+            );
+            assert_eq!(embedding.hit_test(mouse), expected, "step {}", step);
+        }
+    }
+
+    #[test]
+    fn test_subdivide_edge_places_vertex_at_midpoint() {
+        let mut graph = Graph::new(2);
+        graph.edges.push((0, 1));
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+        embedding.set_position(0, vec2(100.0, 100.0));
+        embedding.set_position(1, vec2(300.0, 200.0));
+        embedding.edge_properties[0].style = EdgeStyle::Dashed;
+
+        let c = graph.subdivide_edge(0, 1).unwrap();
+        embedding.subdivide_edge(0, 1, c);
+
+        assert_eq!(embedding.vertex_properties.len(), 3);
+        assert_eq!(embedding.get_position(c), vec2(200.0, 150.0));
+        let vertices: Vec<(usize, usize)> = embedding
+            .edge_properties
+            .iter()
+            .map(|properties| properties.vertices)
+            .collect();
+        assert_eq!(vertices, graph.edges);
+        assert!(embedding
+            .edge_properties
+            .iter()
+            .all(|properties| properties.style == EdgeStyle::Dashed));
+    }
+
+    #[test]
+    fn test_reconnected_edge_keeps_its_properties() {
+        let mut graph = Graph::path(4);
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+        embedding.edge_properties[0].style = EdgeStyle::Dashed;
+        embedding.selected = Some(Selection::Edge(0));
+
+        assert!(graph.reconnect_edge((0, 1), (3, 0)));
+        embedding.reconnect_edge((0, 1), (3, 0));
+
+        let vertices: Vec<(usize, usize)> = embedding
+            .edge_properties
+            .iter()
+            .map(|properties| properties.vertices)
+            .collect();
+        assert_eq!(vertices, graph.edges);
+        assert_eq!(embedding.edge_properties[0].vertices, (3, 0));
+        assert_eq!(embedding.edge_properties[0].style, EdgeStyle::Dashed);
+        assert_eq!(embedding.selected, Some(Selection::Edge(0)));
+        assert_eq!(embedding.incident_edges(1), &[1]);
+    }
+
+    #[test]
+    fn test_incident_edges_by_degree() {
+        let mut graph = Graph::new(8);
+        graph
+            .edges
+            .extend([(0, 1), (0, 2), (3, 0), (0, 4), (0, 5), (6, 0)]);
+        let embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+
+        assert!(embedding.incident_edges(7).is_empty());
+        assert_eq!(embedding.incident_edges(1), &[0]);
+        assert_eq!(embedding.incident_edges(0), &[0, 1, 2, 3, 4, 5]);
+        assert!(embedding.incident_edges(8).is_empty());
+    }
+
+    #[test]
+    fn test_toggle_neighbourhood_highlighting() {
+        let mut graph = Graph::new(4);
+        graph.edges.extend([(0, 1), (1, 2), (2, 3)]);
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+
+        embedding.toggle_neighbourhood_highlighting(1);
+        let highlighted: Vec<(usize, usize)> = embedding
+            .edge_properties
+            .iter()
+            .filter(|edge| edge.draw_state == DrawState::Highlighted)
+            .map(|edge| edge.vertices)
+            .collect();
+        assert_eq!(highlighted, vec![(0, 1), (1, 2)]);
+        assert_eq!(
+            embedding.vertex_properties[0].draw_state,
+            DrawState::Highlighted
+        );
+        assert_eq!(
+            embedding.vertex_properties[2].draw_state,
+            DrawState::Highlighted
+        );
+        assert_eq!(
+            embedding.vertex_properties[3].draw_state,
+            DrawState::Default
+        );
+        assert_eq!(embedding.get_history_size(), 1);
+
+        embedding.toggle_neighbourhood_highlighting(1);
+        assert!(embedding
+            .edge_properties
+            .iter()
+            .all(|edge| edge.draw_state == DrawState::Default));
+        assert_eq!(
+            embedding.vertex_properties[0].draw_state,
+            DrawState::Default
+        );
+        assert_eq!(embedding.get_history_size(), 1);
+    }
+
+    #[test]
+    fn test_vertex_highlighting_replaces_the_previous_one() {
+        let graph = Graph::new(4);
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+        embedding.vertex_properties[3].draw_state = DrawState::Hidden;
+
+        embedding.set_vertex_highlighting(&[0, 1]);
+        embedding.set_vertex_highlighting(&[2]);
+
+        let states: Vec<DrawState> = embedding
+            .vertex_properties
+            .iter()
+            .map(|properties| properties.draw_state)
+            .collect();
+        assert_eq!(
+            states,
+            vec![
+                DrawState::Default,
+                DrawState::Default,
+                DrawState::Highlighted,
+                DrawState::Hidden
+            ]
+        );
+    }
+
+    #[test]
+    fn test_neighbourhood_restores_the_previous_vertex_highlighting() {
+        let mut graph = Graph::new(4);
+        graph.edges.extend([(0, 1), (1, 2)]);
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+        embedding.set_vertex_highlighting(&[3]);
+
+        embedding.toggle_neighbourhood_highlighting(1);
+        assert_eq!(
+            embedding.vertex_properties[3].draw_state,
+            DrawState::Default
+        );
+
+        embedding.toggle_neighbourhood_highlighting(1);
+        assert_eq!(
+            embedding.vertex_properties[3].draw_state,
+            DrawState::Highlighted
+        );
+    }
+
+    #[test]
+    fn test_drag_axis_of_movement() {
+        assert_eq!(DragAxis::of_movement(vec2(2.0, 1.0)), None);
+        assert_eq!(
+            DragAxis::of_movement(vec2(10.0, -4.0)),
+            Some(DragAxis::Horizontal)
+        );
+        assert_eq!(
+            DragAxis::of_movement(vec2(-3.0, 8.0)),
+            Some(DragAxis::Vertical)
+        );
+    }
+
+    #[test]
+    fn test_constrained_drag_delta() {
+        let lock = AxisLock {
+            origin: vec2(100.0, 100.0),
+            axis: None,
+        };
+
+        let (lock, delta) = constrained_drag_delta(lock, vec2(100.0, 100.0), vec2(102.0, 101.0));
+        assert_eq!(lock.axis, None);
+        assert_eq!(delta, Vec2::ZERO);
+
+        let (lock, delta) = constrained_drag_delta(lock, vec2(102.0, 101.0), vec2(100.0, 110.0));
+        assert_eq!(lock.axis, Some(DragAxis::Vertical));
+        assert_eq!(delta, vec2(0.0, 10.0));
+
+        let (lock, delta) = constrained_drag_delta(lock, vec2(100.0, 110.0), vec2(130.0, 115.0));
+        assert_eq!(lock.axis, Some(DragAxis::Vertical));
+        assert_eq!(delta, vec2(0.0, 5.0));
+    }
+
+    #[test]
+    fn test_extract_highlighted_subgraph() {
+        let mut graph = Graph::new(6);
+        graph
+            .edges
+            .extend([(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (1, 4)]);
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+        embedding.set_edge_highlighting(&[(1, 2), (1, 4), (4, 5)]);
+
+        let highlighted = embedding.highlighted_edges();
+        let subgraph = graph.edge_subgraph(&highlighted);
+        let positions = embedding.subgraph_positions(&subgraph);
+
+        let mapped_back: Vec<(usize, usize)> = subgraph
+            .graph
+            .edges
+            .iter()
+            .map(|&(a, b)| (subgraph.original_vertices[a], subgraph.original_vertices[b]))
+            .collect();
+        assert_eq!(mapped_back, vec![(1, 2), (1, 4), (4, 5)]);
+        assert_eq!(subgraph.original_vertices, vec![1, 2, 4, 5]);
+
+        for (vertex, &original) in subgraph.original_vertices.iter().enumerate() {
+            assert_eq!(positions[vertex], embedding.get_position(original));
+        }
+    }
+
+    #[test]
+    fn test_isolated_vertex_stays_near_centroid() {
+        let mut graph = Graph::new(5);
+        graph.edges.extend([(0, 1), (1, 2), (2, 3), (0, 3)]);
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(800.0, 800.0));
+        embedding.set_position(4, vec2(5.0, 5.0));
+
+        for _ in 0..500 {
+            embedding.apply_force(&graph, &ForceConfig::default(), None);
+        }
+
+        let centroid = (0..4)
+            .map(|vertex| embedding.get_position(vertex))
+            .fold(Vec2::ZERO, |sum, position| sum + position)
+            / 4.0;
+        let isolated = embedding.get_position(4);
+        assert!(
+            isolated.distance(centroid) < 5.0,
+            "isolated vertex at {} with centroid {}",
+            isolated,
+            centroid
+        );
+    }
+
+    #[test]
+    fn test_isolated_vertices_stay_apart() {
+        let graph = Graph::new(4);
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(800.0, 800.0));
+
+        for _ in 0..500 {
+            embedding.apply_force(&graph, &ForceConfig::default(), None);
+        }
+
+        let positions = embedding.positions();
+        for a in 0..4 {
+            for b in a + 1..4 {
+                let distance = positions[a].distance(positions[b]);
+                assert!(distance > 20.0, "vertices {} and {} at {}", a, b, distance);
+            }
+        }
+    }
+
+    #[test]
+    fn test_induced_edge_states_keep_explicit_states() {
+        let graph = Graph::path(3);
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+        let induced = |embedding: &GraphInterface, edge: usize| {
+            embedding.induced_edge_state(edge, HiddenVertexEdges::Hide, true)
+        };
+
+        embedding.edge_properties[0].draw_state = DrawState::Highlighted;
+        embedding.vertex_properties[1].draw_state = DrawState::Hidden;
+        assert_eq!(induced(&embedding, 0), Some(InducedEdgeState::Hidden));
+        assert_eq!(induced(&embedding, 1), Some(InducedEdgeState::Hidden));
+        assert_eq!(
+            embedding.induced_edge_state(0, HiddenVertexEdges::Keep, false),
+            None
+        );
+        assert_eq!(
+            embedding.induced_edge_state(0, HiddenVertexEdges::Fade, false),
+            Some(InducedEdgeState::Faded)
+        );
+
+        // explicit changes while the hiding is induced survive it
+        embedding.edge_properties[1].draw_state = DrawState::Unhighlighted;
+        embedding.vertex_properties[0].draw_state = DrawState::Hidden;
+
+        embedding.vertex_properties[1].draw_state = DrawState::Default;
+        assert_eq!(induced(&embedding, 0), Some(InducedEdgeState::Hidden));
+        assert_eq!(induced(&embedding, 1), None);
+        assert_eq!(
+            embedding.edge_properties[1].draw_state,
+            DrawState::Unhighlighted
+        );
+
+        embedding.vertex_properties[0].draw_state = DrawState::Default;
+        assert_eq!(induced(&embedding, 0), None);
+        assert_eq!(
+            embedding.edge_properties[0].draw_state,
+            DrawState::Highlighted
+        );
+    }
+
+    #[test]
+    fn test_highlighted_vertex_emphasizes_default_edges() {
+        let graph = Graph::path(3);
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+        embedding.vertex_properties[2].draw_state = DrawState::Highlighted;
+        embedding.edge_properties[1].draw_state = DrawState::Unhighlighted;
+
+        assert_eq!(
+            embedding.induced_edge_state(1, HiddenVertexEdges::Hide, true),
+            None
+        );
+
+        embedding.edge_properties[1].draw_state = DrawState::Default;
+        assert_eq!(
+            embedding.induced_edge_state(1, HiddenVertexEdges::Hide, true),
+            Some(InducedEdgeState::Emphasized)
+        );
+        assert_eq!(
+            embedding.induced_edge_state(1, HiddenVertexEdges::Hide, false),
+            None
+        );
+        assert_eq!(
+            embedding.induced_edge_state(0, HiddenVertexEdges::Hide, true),
+            None
+        );
+
+        // hiding wins over emphasis
+        embedding.vertex_properties[1].draw_state = DrawState::Hidden;
+        assert_eq!(
+            embedding.induced_edge_state(1, HiddenVertexEdges::Hide, true),
+            Some(InducedEdgeState::Hidden)
+        );
+    }
+
+    #[test]
+    fn test_step_force_reduces_energy() {
+        // the potential whose gradient the layout forces follow
+        fn energy(graph: &Graph, embedding: &GraphInterface) -> f32 {
+            let positions = embedding.positions();
+            let mut energy = 0.0;
+            for a in 0..graph.vertices {
+                for b in a + 1..graph.vertices {
+                    let distance = positions[a].distance(positions[b]);
+                    energy += if graph.edges.contains(&(a, b)) {
+                        distance * ((distance / 70.0).log10() - std::f32::consts::LOG10_E)
+                    } else {
+                        50.0 * 20.0 / std::f32::consts::LN_2 * 0.5f32.powf(distance / 20.0)
+                    };
+                }
+            }
+            energy
+        }
+
+        let graph = Graph::cycle(5);
+        let config = ForceConfig::default();
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(800.0, 800.0));
+        let initial_energy = energy(&graph, &embedding);
+
+        let first_movement = embedding.step_force(&graph, &config, 1);
+        let last_movement = embedding.step_force(&graph, &config, 200);
+
+        assert!(energy(&graph, &embedding) < initial_energy);
+        assert!(last_movement < first_movement);
+        assert_eq!(embedding.step_force(&graph, &config, 0), 0.0);
+    }
+
+    #[test]
+    fn test_layout_with_collapsed_group_converges() {
+        let graph = Graph::path(6);
+        let config = ForceConfig::default();
+        let viewport = vec2(800.0, 800.0);
+        let mut embedding = GraphInterface::with_viewport(&graph, viewport);
+        let group = embedding.collapse(&[2, 3]).unwrap();
+
+        for _ in 0..2000 {
+            embedding.apply_force(&graph, &config, None);
+            embedding.follow_meta_vertices();
+        }
+
+        assert!(embedding.layout_converged(0.1));
+        let meta_vertex =
+            embedding.get_position(embedding.collapsed_groups()[group].representative);
+        assert!(
+            meta_vertex.cmpgt(vec2(10.0, 10.0)).all() && meta_vertex.cmplt(viewport - 10.0).all(),
+            "meta-vertex pushed to the border at {}",
+            meta_vertex
+        );
+    }
+
+    #[test]
+    fn test_hit_distance_keeps_screen_tolerance() {
+        assert_eq!(hit_distance(2.0, 8.0, None), 8.0);
+        assert_eq!(hit_distance(12.0, 8.0, None), 12.0);
+
+        // zoomed out, eight pixels on screen cover more of the layout
+        assert_eq!(hit_distance(2.0, 8.0, Some(0.5)), 16.0);
+        assert_eq!(hit_distance(12.0, 8.0, Some(0.5)), 16.0);
+        // zoomed in, the visual size takes over sooner
+        assert_eq!(hit_distance(2.0, 8.0, Some(4.0)), 2.0);
+        assert_eq!(hit_distance(2.0, 8.0, Some(0.0)), 8.0);
+    }
+
+    #[test]
+    fn test_thin_edges_are_hit_within_tolerance() {
+        let mut embedding = scripted_path();
+        embedding.edge_properties[0].width = 1.0;
+        let near_edge = vec2(150.0, 206.0);
+
+        assert_eq!(embedding.hit_test(near_edge), (None, Some(0)));
+
+        embedding.set_hit_tolerance(4.0, None);
+        assert_eq!(embedding.hit_test(near_edge), (None, None));
+
+        embedding.set_hit_tolerance(4.0, Some(0.5));
+        assert_eq!(embedding.hit_test(near_edge), (None, Some(0)));
+    }
+
+    #[test]
+    fn test_dense_graphs_only_hover_vertices() {
+        let graph = Graph::complete(64);
+        assert!(graph.edges.len() > EDGE_HOVER_LIMIT);
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(800.0, 800.0));
+        let (a, b) = graph.edges[0];
+        let on_edge = (embedding.get_position(a) + embedding.get_position(b)) / 2.0;
+        let on_vertex = embedding.get_position(5);
+
+        assert!(!embedding.edges_hoverable());
+        assert_eq!(embedding.get_edge_at_position(on_edge), None);
+        assert_eq!(embedding.hit_test(on_edge).1, None);
+        assert_eq!(embedding.hit_test(on_vertex), (Some(5), None));
+
+        // switched off by hand below the limit
+        let mut embedding = scripted_path();
+        let on_edge = vec2(150.0, 200.0);
+        assert_eq!(embedding.get_edge_at_position(on_edge), Some(0));
+        embedding.set_edge_hover(false);
+        assert_eq!(embedding.get_edge_at_position(on_edge), None);
+        assert_eq!(embedding.hit_test(on_edge), (None, None));
+    }
+
+    #[test]
+    fn test_right_click_selects_and_double_click_deselects() {
+        let graph = Graph::path(2);
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+        let mut tracker = InputTracker::new();
+        let config = InputConfig::default();
+        let mut frame = |embedding: &mut GraphInterface, time: f64, position: Vec2, down| {
+            let input = RawInput {
+                time,
+                position,
+                down,
+                ..Default::default()
+            };
+            embedding.handle_mouse_input(&tracker.update(input, &config));
+        };
+
+        let vertex = embedding.get_position(1);
+        frame(&mut embedding, 0.0, vertex, [false, true, false]);
+        frame(&mut embedding, 0.05, vertex, [false, false, false]);
+
+        assert_eq!(embedding.selected, Some(Selection::Vertex(1)));
+        assert_eq!(
+            embedding.vertex_properties[1].draw_state,
+            DrawState::Default
+        );
+
+        let empty = vec2(5.0, 5.0);
+        for time in [1.0, 1.2] {
+            frame(&mut embedding, time, empty, [true, false, false]);
+            frame(&mut embedding, time + 0.05, empty, [false, false, false]);
+        }
+        assert_eq!(embedding.selected, None);
+    }
+
+    #[test]
+    fn test_scripted_drag_moves_vertex() {
+        use ScriptStep::*;
+        let mut embedding = scripted_path();
+        let start = vec2(100.0, 200.0);
+        let mut script = InputScript::new(start);
+
+        play(
+            &mut embedding,
+            &mut script,
+            &[
+                Press(MouseButton::Left),
+                MoveTo(start + vec2(30.0, 0.0), 3.0),
+            ],
+        );
+        assert_eq!(embedding.dragged_vertex, Some(0));
+        assert_eq!(embedding.hovered_vertex, None);
+
+        // hovering resumes the frame after the release
+        play(
+            &mut embedding,
+            &mut script,
+            &[Release(MouseButton::Left), Wait(0.02)],
+        );
+        // the vertex follows the mouse from the frame it moved past the drag distance
+        assert_eq!(embedding.get_position(0), vec2(124.0, 200.0));
+        assert_eq!(embedding.dragged_vertex, None);
+        assert_eq!(embedding.hovered_vertex, Some(0));
+        // a drag is no click, so the draw state stays
+        assert_eq!(
+            embedding.vertex_properties[0].draw_state,
+            DrawState::Default
+        );
+    }
+
+    #[test]
+    fn test_clicks_and_drags_are_reported() {
+        use ScriptStep::*;
+        let mut embedding = scripted_path();
+        let mut script = InputScript::new(vec2(200.0, 200.0));
+
+        play(
+            &mut embedding,
+            &mut script,
+            &[
+                Press(MouseButton::Left),
+                Release(MouseButton::Left),
+                MoveTo(vec2(150.0, 200.0), 10.0),
+                Press(MouseButton::Left),
+                Release(MouseButton::Left),
+                MoveTo(vec2(100.0, 200.0), 10.0),
+                Wait(0.3),
+                Press(MouseButton::Left),
+                MoveTo(vec2(130.0, 200.0), 3.0),
+                Release(MouseButton::Left),
+                Wait(0.02),
+            ],
+        );
+
+        assert_eq!(
+            embedding.drain_events(),
+            vec![
+                InterfaceEvent::VertexClicked(1),
+                InterfaceEvent::EdgeClicked(0),
+                InterfaceEvent::DragStarted(0),
+                InterfaceEvent::DragEnded(0),
+            ]
+        );
+        assert!(embedding.drain_events().is_empty());
+    }
+
+    #[test]
+    fn test_scripted_shift_drag_locks_axis() {
+        use ScriptStep::*;
+        let mut embedding = scripted_path();
+        let start = vec2(100.0, 200.0);
+        let mut script = InputScript::new(start);
+
+        play(
+            &mut embedding,
+            &mut script,
+            &[
+                Shift(true),
+                Press(MouseButton::Left),
+                MoveTo(start + vec2(40.0, 8.0), 2.0),
+                Release(MouseButton::Left),
+            ],
+        );
+
+        let position = embedding.get_position(0);
+        assert!(position.x > 120.0, "{}", position);
+        assert_eq!(position.y, 200.0);
+    }
+
+    #[test]
+    fn test_scripted_hover_and_click() {
+        use ScriptStep::*;
+        let mut embedding = scripted_path();
+        let mut script = InputScript::new(vec2(200.0, 100.0));
+
+        play(
+            &mut embedding,
+            &mut script,
+            &[MoveTo(vec2(200.0, 200.0), 10.0)],
+        );
+        assert_eq!(embedding.hovered_vertex, Some(1));
+
+        play(
+            &mut embedding,
+            &mut script,
+            &[
+                Press(MouseButton::Left),
+                Release(MouseButton::Left),
+                MoveTo(vec2(150.0, 200.0), 10.0),
+            ],
+        );
+        assert_eq!(
+            embedding.vertex_properties[1].draw_state,
+            DrawState::Highlighted
+        );
+        assert_eq!(embedding.selected, Some(Selection::Vertex(1)));
+        assert_eq!(embedding.hovered_vertex, None);
+        assert_eq!(embedding.hovered_edge, Some(0));
+
+        // held too long for a click
+        play(
+            &mut embedding,
+            &mut script,
+            &[
+                Press(MouseButton::Left),
+                Wait(0.3),
+                Release(MouseButton::Left),
+            ],
+        );
+        assert_eq!(
+            embedding.vertex_properties[1].draw_state,
+            DrawState::Highlighted
+        );
+    }
+
+    #[test]
+    fn test_scripted_box_and_lasso_selection() {
+        use ScriptStep::*;
+        let mut embedding = scripted_path();
+        let mut script = InputScript::new(vec2(50.0, 150.0));
+
+        play(
+            &mut embedding,
+            &mut script,
+            &[Press(MouseButton::Left), MoveTo(vec2(250.0, 250.0), 5.0)],
+        );
+        assert!(embedding.selection_box().is_some());
+        play(&mut embedding, &mut script, &[Release(MouseButton::Left)]);
+        assert_eq!(embedding.selection_box(), None);
+        assert_eq!(embedding.selected_vertices, vec![0, 1]);
+
+        // a triangle around the last vertex only
+        play(
+            &mut embedding,
+            &mut script,
+            &[
+                Alt(true),
+                Press(MouseButton::Left),
+                MoveTo(vec2(300.0, 120.0), 5.0),
+                MoveTo(vec2(350.0, 250.0), 5.0),
+                Release(MouseButton::Left),
+            ],
+        );
+        assert_eq!(embedding.selected_vertices, vec![2]);
+
+        play(&mut embedding, &mut script, &[Escape]);
+        assert!(embedding.selected_vertices.is_empty());
+    }
+
+    #[test]
+    fn test_project_edge_lengths() {
+        let mut graph = Graph::new(3);
+        graph.edges.extend([(0, 1), (1, 2)]);
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+        embedding.set_position(0, vec2(100.0, 100.0));
+        embedding.set_position(1, vec2(130.0, 140.0));
+        embedding.set_position(2, vec2(300.0, 100.0));
+        embedding.edge_properties[0].target_length = Some(100.0);
+
+        embedding.project_edge_lengths(None);
+
+        // both endpoints move by half the difference, along the edge
+        assert!((embedding.edge_length(0).unwrap() - 100.0).abs() < 1e-3);
+        assert!(embedding
+            .get_position(0)
+            .abs_diff_eq(vec2(100.0 - 15.0, 100.0 - 20.0), 1e-3));
+        assert_eq!(embedding.get_position(2), vec2(300.0, 100.0));
+
+        // a pinned endpoint stays put and the free one moves the whole way
+        embedding.edge_properties[0].target_length = None;
+        embedding.edge_properties[1].target_length = Some(50.0);
+        embedding.vertex_properties[2].pinned = true;
+        let pinned = embedding.get_position(2);
+        embedding.project_edge_lengths(None);
+
+        assert_eq!(embedding.get_position(2), pinned);
+        assert!((embedding.edge_length(1).unwrap() - 50.0).abs() < 1e-3);
+
+        // coincident endpoints are pulled apart horizontally
+        embedding.vertex_properties[2].pinned = false;
+        embedding.set_position(2, embedding.get_position(1));
+        embedding.project_edge_lengths(None);
+        assert!((embedding.edge_length(1).unwrap() - 50.0).abs() < 1e-3);
+
+        // nothing moves when neither endpoint can
+        embedding.vertex_properties[1].pinned = true;
+        embedding.vertex_properties[2].frozen = true;
+        embedding.edge_properties[1].target_length = Some(10.0);
+        let before = embedding.positions();
+        embedding.project_edge_lengths(None);
+        assert_eq!(embedding.positions(), before);
+    }
+
+    #[test]
+    fn test_edge_length_constraint_beats_springs() {
+        let graph = Graph::cycle(4);
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(800.0, 800.0));
+        embedding.edge_properties[0].target_length = Some(150.0);
+
+        embedding.step_force(&graph, &ForceConfig::default(), 50);
+
+        assert!((embedding.edge_length(0).unwrap() - 150.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_symmetry_breaking_escapes_collinear_cycle() {
+        // the signed area of the quadrilateral 0-1-2-3
+        fn area(positions: &[Vec2]) -> f32 {
+            (0..4)
+                .map(|i| positions[i].perp_dot(positions[(i + 1) % 4]))
+                .sum::<f32>()
+                / 2.0
+        }
+
+        let graph = Graph::cycle(4);
+        let collinear = |config: &ForceConfig| {
+            let mut embedding = GraphInterface::with_viewport(&graph, vec2(800.0, 800.0));
+            for (vertex, x) in [300.0, 350.0, 450.0, 500.0].into_iter().enumerate() {
+                embedding.set_position(vertex, vec2(x, 400.0));
+            }
+            embedding.start_force(config);
+            embedding.step_force(&graph, config, 500);
+            embedding.positions()
+        };
+
+        let stuck = collinear(&ForceConfig {
+            break_symmetry: false,
+            ..Default::default()
+        });
+        assert!(stuck.iter().all(|position| position.y == 400.0));
+
+        let config = ForceConfig::default();
+        let positions = collinear(&config);
+        assert!(area(&positions).abs() > 1000.0, "{:?}", positions);
+        assert_eq!(positions, collinear(&config));
+    }
+
+    #[test]
+    fn test_inverse_square_repulsion_spreads_ring() {
+        fn mean_radius(graph: &Graph, repulsion: RepulsionModel) -> f32 {
+            let config = ForceConfig {
+                repulsion,
+                ..Default::default()
+            };
+            let mut embedding = GraphInterface::with_viewport(graph, vec2(1000.0, 1000.0));
+            embedding.step_force(graph, &config, 300);
+
+            let positions = embedding.positions();
+            let centroid = positions.iter().sum::<Vec2>() / positions.len() as f32;
+            positions
+                .iter()
+                .map(|position| position.distance(centroid))
+                .sum::<f32>()
+                / positions.len() as f32
+        }
+
+        let graph = Graph::cycle(12);
+        let exponential = mean_radius(&graph, RepulsionModel::Exponential);
+        let inverse_square = mean_radius(&graph, RepulsionModel::InverseSquare);
+
+        assert!(
+            inverse_square > 1.1 * exponential,
+            "{} vs {}",
+            inverse_square,
+            exponential
+        );
+    }
+
+    #[test]
+    fn test_force_keeps_vertices_in_lanes() {
+        let graph = Graph::path(4);
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+        embedding.set_lanes(&graph.depths(0));
+        embedding.vertex_properties[2].pinned = true;
+        let pinned = embedding.get_position(2);
+        let config = ForceConfig {
+            lanes: LaneConfig {
+                count: 2,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        embedding.step_force(&graph, &config, 50);
+
+        // depths beyond the last lane end up in it
+        for (vertex, range) in [(0, 0.0..200.0), (1, 200.0..400.0), (3, 200.0..400.0)] {
+            assert!(
+                range.contains(&embedding.get_position(vertex).y),
+                "{}",
+                vertex
+            );
+        }
+        assert_eq!(embedding.get_position(2), pinned);
+    }
+
+    #[test]
+    fn test_hit_test_follows_curved_edges() {
+        let graph = Graph::path(2);
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+        embedding.set_position(0, vec2(100.0, 200.0));
+        embedding.set_position(1, vec2(300.0, 200.0));
+        let on_chord = vec2(200.0, 200.0);
+        // the control point is 50 below the chord, the curve midpoint half of that
+        let on_curve = vec2(200.0, 225.0);
+
+        assert_eq!(embedding.hit_test(on_chord), (None, Some(0)));
+        assert_eq!(embedding.hit_test(on_curve), (None, None));
+
+        embedding.set_edge_curvature(0.25);
+        assert_eq!(embedding.hit_test(on_chord), (None, None));
+        assert_eq!(embedding.hit_test(on_curve), (None, Some(0)));
+    }
+
+    #[test]
+    fn test_hit_test_separates_anti_parallel_edges() {
+        let mut graph = Graph::path(2);
+        graph.set_orientation(0, 1, Orientation::Forward);
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+        embedding.set_position(0, vec2(100.0, 200.0));
+        embedding.set_position(1, vec2(300.0, 200.0));
+
+        let reverse = graph.add_reverse_edge(0, 1).unwrap();
+        embedding.add_edge(reverse, graph.orientation(reverse.0, reverse.1));
+
+        // the control points are 30 to either side, the curve midpoints half of that
+        assert_eq!(embedding.hit_test(vec2(200.0, 215.0)), (None, Some(0)));
+        assert_eq!(embedding.hit_test(vec2(200.0, 185.0)), (None, Some(1)));
+
+        // undirecting one edge makes the other straight again
+        assert!(embedding.set_edge_orientation(1, 0, Orientation::Undirected));
+        assert_eq!(embedding.edge_control(0, 0.0), None);
+        assert_eq!(embedding.hit_test(vec2(200.0, 200.0)), (None, Some(0)));
+    }
+
+    #[test]
+    fn test_edges_follow_the_order_of_the_graph() {
+        let mut graph = Graph::path(4);
+        graph.set_orientation(0, 1, Orientation::Forward);
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+        embedding.selected = Some(Selection::Edge(2));
+        let edges = |embedding: &GraphInterface| -> Vec<(usize, usize)> {
+            embedding
+                .edge_properties
+                .iter()
+                .map(|properties| properties.vertices)
+                .collect()
+        };
+
+        let reverse = graph.add_reverse_edge(0, 1).unwrap();
+        embedding.add_edge(reverse, graph.orientation(reverse.0, reverse.1));
+        assert_eq!(edges(&embedding), graph.edges);
+        // still edge 2 - 3
+        assert_eq!(embedding.selected, Some(Selection::Edge(3)));
+
+        let c = graph.subdivide_edge(1, 2).unwrap();
+        embedding.subdivide_edge(1, 2, c);
+        assert_eq!(edges(&embedding), graph.edges);
+        assert_eq!(embedding.incident_edges(c), &[2, 4]);
+    }
+
+    #[test]
+    fn test_local_relax_moves_only_masked_vertices() {
+        let graph = Graph::path(5);
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+        for vertex in 0..5 {
+            embedding.set_position(vertex, vec2(50.0 * vertex as f32 + 100.0, 200.0));
+        }
+        embedding.set_position(2, vec2(200.0, 350.0));
+        let before = embedding.positions();
+
+        let mut mask = vec![false; 5];
+        mask[2] = true;
+        let steps = embedding.relax_locally(&graph, &ForceConfig::default(), &mask, 1000, 0.05);
+
+        assert!(steps < 1000);
+        let after = embedding.positions();
+        for vertex in [0, 1, 3, 4] {
+            assert_eq!(after[vertex], before[vertex], "vertex {}", vertex);
+        }
+        // the fixed neighbours pulled the vertex back to about the rest length of the springs
+        for neighbour in [1, 3] {
+            let distance = after[2].distance(after[neighbour]);
+            assert!((distance - 70.0).abs() < 10.0, "{}", distance);
+        }
+    }
+
+    #[test]
+    fn test_force_on_selection_only() {
+        let graph = Graph::cycle(6);
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+        embedding.set_position(1, vec2(200.0, 200.0));
+        embedding.set_position(2, vec2(205.0, 200.0));
+        embedding.selected_vertices = vec![1, 2];
+        let before = embedding.positions();
+        let config = ForceConfig {
+            selected_only: true,
+            ..Default::default()
+        };
+
+        for _ in 0..500 {
+            embedding.apply_force(&graph, &config, None);
+        }
+
+        let after = embedding.positions();
+        for vertex in [0, 3, 4, 5] {
+            assert_eq!(after[vertex], before[vertex], "vertex {}", vertex);
+        }
+        assert!(after[1] != before[1] && after[2] != before[2]);
+        assert!(embedding.layout_converged(0.05));
+        // settled between their fixed neighbours instead of on top of each other
+        assert!(after[1].distance(after[2]) > 40.0);
+    }
+
+    #[test]
+    fn test_box_selection_becomes_relax_mask() {
+        let graph = Graph::path(4);
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+        for vertex in 0..4 {
+            embedding.set_position(vertex, vec2(100.0 * vertex as f32 + 50.0, 200.0));
+        }
+        assert_eq!(embedding.relax_mask(), None);
+
+        embedding.selected = Some(Selection::Vertex(3));
+        assert_eq!(
+            embedding.relax_mask(),
+            Some(vec![false, false, false, true])
+        );
+
+        // the box selection takes precedence
+        embedding.select_vertices_in(Rect::new(100.0, 150.0, 200.0, 100.0));
+        assert_eq!(embedding.selected_vertices, vec![1, 2]);
+        assert_eq!(embedding.relax_mask(), Some(vec![false, true, true, false]));
+    }
+
+    #[test]
+    fn test_polygon_contains_concave() {
+        // a U shape, open at the top between x = 10 and x = 20
+        let polygon = [
+            vec2(0.0, 0.0),
+            vec2(10.0, 0.0),
+            vec2(10.0, 20.0),
+            vec2(20.0, 20.0),
+            vec2(20.0, 0.0),
+            vec2(30.0, 0.0),
+            vec2(30.0, 30.0),
+            vec2(0.0, 30.0),
+        ];
+
+        // (point, inside)
+        let cases = [
+            (vec2(5.0, 10.0), true),
+            (vec2(25.0, 10.0), true),
+            (vec2(15.0, 25.0), true),
+            // in the notch of the U
+            (vec2(15.0, 10.0), false),
+            (vec2(15.0, -5.0), false),
+            (vec2(35.0, 10.0), false),
+            (vec2(-5.0, 25.0), false),
+        ];
+        for (point, inside) in cases {
+            assert_eq!(polygon_contains(&polygon, point), inside, "{}", point);
+        }
+
+        assert!(!polygon_contains(&polygon[..2], vec2(5.0, 0.0)));
+    }
+
+    #[test]
+    fn test_lasso_selects_enclosed_vertices() {
+        let graph = Graph::new(3);
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+        embedding.set_positions(&[vec2(50.0, 50.0), vec2(150.0, 50.0), vec2(100.0, 150.0)]);
+
+        let mut lasso = SelectionDrag::Lasso(vec![vec2(0.0, 0.0)]);
+        for point in [
+            vec2(1.0, 1.0),
+            vec2(100.0, 0.0),
+            vec2(100.0, 200.0),
+            vec2(0.0, 200.0),
+        ] {
+            lasso.extend(point);
+        }
+        let SelectionDrag::Lasso(polygon) = lasso else {
+            unreachable!()
+        };
+        // points closer than the spacing are skipped
+        assert_eq!(polygon.len(), 4);
+
+        embedding.select_vertices_in_polygon(&polygon);
+        assert_eq!(embedding.selected_vertices, vec![0]);
+    }
+
+    #[test]
+    fn test_toggle_edge_highlighting() {
+        let graph = Graph::cycle(3);
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+
+        embedding.toggle_edge_highlighting(1);
+        embedding.toggle_edge_highlighting(2);
+        assert_eq!(
+            embedding.highlighted_edges(),
+            vec![graph.edges[1], graph.edges[2]]
+        );
+
+        embedding.toggle_edge_highlighting(1);
+        assert_eq!(embedding.highlighted_edges(), vec![graph.edges[2]]);
+        assert_eq!(embedding.get_history_size(), 3);
+        assert_eq!(embedding.current_highlight_graph(), Some(2));
+
+        embedding.toggle_edge_highlighting(3);
+        assert_eq!(embedding.get_history_size(), 3);
+    }
+
+    #[test]
+    fn test_jump_to_highlighting() {
+        let mut graph = Graph::new(3);
+        graph.edges.extend([(0, 1), (1, 2)]);
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+        let state = |embedding: &GraphInterface| -> Vec<DrawState> {
+            embedding
+                .edge_properties
+                .iter()
+                .map(|edge| edge.draw_state)
+                .collect()
+        };
+
+        embedding.set_previous_highlighting();
+        assert_eq!(embedding.current_highlight_graph(), None);
+
+        for edge in [(0, 1), (1, 2)] {
+            let mut highlight = Graph::new(3);
+            highlight.edges.push(edge);
+            embedding.add_graph_to_history(highlight);
+        }
+
+        assert!(embedding.jump_to_highlighting(1));
+        assert_eq!(embedding.current_highlight_graph(), Some(1));
+        assert_eq!(
+            state(&embedding),
+            vec![DrawState::Default, DrawState::Highlighted]
+        );
+        assert!(!embedding.jump_to_highlighting(2));
+
+        embedding.set_previous_highlighting();
+        assert_eq!(embedding.current_highlight_graph(), Some(0));
+        assert_eq!(
+            state(&embedding),
+            vec![DrawState::Highlighted, DrawState::Default]
+        );
+        embedding.set_previous_highlighting();
+        assert_eq!(embedding.current_highlight_graph(), Some(0));
+        embedding.set_next_highlighting();
+        assert_eq!(embedding.current_highlight_graph(), Some(1));
+    }
+
+    /// Embedding of a path with one history entry per edge, named after the edge.
+    fn path_with_history(edges: usize) -> GraphInterface {
+        let graph = Graph::path(edges + 1);
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+        for &edge in &graph.edges {
+            let mut highlight = Graph::new(graph.vertices);
+            highlight.edges.push(edge);
+            embedding.add_graph_to_history(highlight);
+            let index = embedding.get_history_size() - 1;
+            embedding.rename_highlight(index, &format!("{}-{}", edge.0, edge.1));
+        }
+        embedding
+    }
+
+    fn history_names(embedding: &GraphInterface) -> Vec<&str> {
+        embedding
+            .highlight_history()
+            .iter()
+            .map(|entry| entry.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_delete_highlight_entries() {
+        let mut embedding = path_with_history(4);
+        embedding.jump_to_highlighting(2);
+
+        // before the shown entry, which stays shown at its new position
+        assert!(embedding.delete_highlight(0));
+        assert_eq!(history_names(&embedding), vec!["1-2", "2-3", "3-4"]);
+        assert_eq!(embedding.current_highlight_graph(), Some(1));
+        assert_eq!(embedding.highlighted_edges(), vec![(2, 3)]);
+
+        // after it
+        assert!(embedding.delete_highlight(2));
+        assert_eq!(embedding.current_highlight_graph(), Some(1));
+        assert!(!embedding.delete_highlight(2));
+
+        // the shown entry, the next one takes its place
+        embedding.jump_to_highlighting(0);
+        assert!(embedding.delete_highlight(0));
+        assert_eq!(history_names(&embedding), vec!["2-3"]);
+        assert_eq!(embedding.current_highlight_graph(), Some(0));
+        assert_eq!(embedding.highlighted_edges(), vec![(2, 3)]);
+
+        assert!(embedding.delete_highlight(0));
+        assert_eq!(embedding.current_highlight_graph(), None);
+        assert!(embedding.highlighted_edges().is_empty());
+    }
+
+    #[test]
+    fn test_delete_last_shown_highlight() {
+        let mut embedding = path_with_history(3);
+        embedding.jump_to_highlighting(2);
+
+        assert!(embedding.delete_highlight(2));
+        assert_eq!(embedding.current_highlight_graph(), Some(1));
+        assert_eq!(embedding.highlighted_edges(), vec![(1, 2)]);
+    }
+
+    #[test]
+    fn test_reversed_highlight_edges_are_highlighted() {
+        let graph = Graph::path(3);
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+        let mut highlight = Graph::new(3);
+        highlight.edges.push((2, 1));
+        assert!(highlight.subgraph_mismatch(&graph).missing_edges.is_empty());
+
+        embedding.add_edge_highlighting_and_add_to_history(highlight);
+        let states = |embedding: &GraphInterface| {
+            embedding
+                .edge_properties
+                .iter()
+                .map(|edge| edge.draw_state)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            states(&embedding),
+            vec![DrawState::Default, DrawState::Highlighted]
+        );
+
+        embedding.clear_edge_highlighting();
+        assert!(embedding.jump_to_highlighting(0));
+        assert_eq!(
+            states(&embedding),
+            vec![DrawState::Default, DrawState::Highlighted]
+        );
+    }
+
+    #[test]
+    fn test_move_highlight_entries() {
+        let mut embedding = path_with_history(4);
+        embedding.jump_to_highlighting(1);
+
+        // the shown entry itself
+        assert!(embedding.move_highlight(1, 3));
+        assert_eq!(history_names(&embedding), vec!["0-1", "2-3", "3-4", "1-2"]);
+        assert_eq!(embedding.current_highlight_graph(), Some(3));
+        assert_eq!(embedding.highlighted_edges(), vec![(1, 2)]);
+
+        // another entry past it
+        assert!(embedding.move_highlight(0, 3));
+        assert_eq!(embedding.current_highlight_graph(), Some(2));
+        assert!(!embedding.move_highlight(0, 4));
+
+        // stepping follows the new order
+        embedding.set_next_highlighting();
+        assert_eq!(embedding.highlighted_edges(), vec![(0, 1)]);
+    }
+
+    #[test]
+    fn test_duplicate_highlight_entry() {
+        let mut embedding = path_with_history(2);
+        embedding.jump_to_highlighting(1);
+
+        assert_eq!(embedding.duplicate_highlight(0), Some(1));
+        assert_eq!(history_names(&embedding), vec!["0-1", "0-1 (copy)", "1-2"]);
+        assert_eq!(embedding.current_highlight_graph(), Some(2));
+
+        let history = embedding.highlight_history();
+        assert_eq!(history[0].graph, history[1].graph);
+        assert_ne!(history[0].id, history[1].id);
+        assert_eq!(embedding.duplicate_highlight(3), None);
+
+        // new entries are named after their id
+        embedding.add_graph_to_history(Graph::new(3));
+        assert_eq!(history_names(&embedding)[3], "Highlight 4");
+    }
+
+    fn history_colors(embedding: &GraphInterface) -> Vec<usize> {
+        embedding
+            .highlight_history()
+            .iter()
+            .map(|entry| entry.color_index)
+            .collect()
+    }
+
+    #[test]
+    fn test_history_colors_stay_with_their_entries() {
+        let mut embedding = path_with_history(4);
+        assert_eq!(history_colors(&embedding), vec![0, 1, 2, 3]);
+
+        assert!(embedding.move_highlight(0, 2));
+        assert_eq!(history_colors(&embedding), vec![1, 2, 0, 3]);
+        assert!(embedding.delete_highlight(1));
+        assert_eq!(history_colors(&embedding), vec![1, 0, 3]);
+
+        // new entries take the first free color, not the one of their position
+        embedding.add_graph_to_history(Graph::new(5));
+        assert_eq!(embedding.duplicate_highlight(0), Some(1));
+        assert_eq!(history_colors(&embedding), vec![1, 4, 0, 3, 2]);
+
+        // the edges are drawn in the color of their entry
+        embedding.jump_to_highlighting(0);
+        assert_eq!(embedding.edge_properties[1].highlight_color_index, 1);
+        embedding.add_edge_highlighting(2);
+        assert_eq!(embedding.edge_properties[0].highlight_color_index, 0);
+        assert_eq!(embedding.edge_properties[1].highlight_color_index, 1);
+    }
+
+    #[test]
+    fn test_history_color_override_takes_precedence() {
+        let config = DrawConfig::default();
+        let mut embedding = path_with_history(3);
+        embedding.jump_to_highlighting(1);
+        let entry_color = |embedding: &GraphInterface, index: usize| {
+            config.history_entry_color(&embedding.highlight_history()[index])
+        };
+        assert_eq!(entry_color(&embedding, 1), config.edge_highlight_color(1));
+
+        assert!(embedding.set_highlight_color(1, Some(PINK)));
+        assert_eq!(entry_color(&embedding, 1), PINK);
+        assert_eq!(entry_color(&embedding, 2), config.edge_highlight_color(2));
+        let drawable = DrawableGraph::compose(&embedding, &config);
+        assert_eq!(drawable.edges[1].color, PINK);
+
+        // the override moves along with the entry and comes back when it is shown again
+        assert!(embedding.move_highlight(1, 0));
+        embedding.jump_to_highlighting(2);
+        assert_eq!(embedding.edge_properties[2].highlight_color_override, None);
+        embedding.jump_to_highlighting(0);
+        assert_eq!(
+            embedding.edge_properties[1].highlight_color_override,
+            Some(PINK)
+        );
+
+        assert!(embedding.set_highlight_color(0, None));
+        assert_eq!(entry_color(&embedding, 0), config.edge_highlight_color(1));
+        assert!(!embedding.set_highlight_color(3, Some(PINK)));
+    }
+
+    /// Vertices 1, 2 and 3 are the group. Vertex 4 has three edges into it, 5 and 0 one each.
+    fn clustered_embedding() -> (Graph, GraphInterface) {
+        let mut graph = Graph::new(6);
+        graph.edges = vec![(0, 1), (1, 2), (2, 3), (1, 4), (2, 4), (3, 4), (3, 5)];
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+        embedding.set_positions(&[
+            vec2(20.0, 20.0),
+            vec2(100.0, 100.0),
+            vec2(140.0, 100.0),
+            vec2(120.0, 160.0),
+            vec2(300.0, 120.0),
+            vec2(200.0, 300.0),
+        ]);
+
+        (graph, embedding)
+    }
+
+    type VertexState = (Vec2, DrawState, Option<String>, bool);
+    type EdgeState = ((usize, usize), DrawState, Option<String>);
+
+    fn vertex_states(embedding: &GraphInterface) -> Vec<VertexState> {
+        embedding
+            .vertex_properties
+            .iter()
+            .map(|p| (p.position, p.draw_state, p.label.clone(), p.frozen))
+            .collect()
+    }
+
+    fn edge_states(embedding: &GraphInterface) -> Vec<EdgeState> {
+        embedding
+            .edge_properties
+            .iter()
+            .map(|p| (p.vertices, p.draw_state, p.label.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_collapse_reroutes_edges_to_the_meta_vertex() {
+        let (_, mut embedding) = clustered_embedding();
+
+        assert_eq!(embedding.collapse(&[3, 1, 2, 2]), Some(0));
+
+        let group = &embedding.collapsed_groups()[0];
+        assert_eq!(
+            (group.representative, &group.members[..]),
+            (1, &[1, 2, 3][..])
+        );
+        let centroid = vec2(120.0, 120.0);
+        for vertex in 1..=3 {
+            assert_eq!(embedding.get_position(vertex), centroid);
+            assert_eq!(embedding.collapsed_group_of(vertex), Some(0));
+        }
+        assert_eq!(embedding.collapsed_group_of(4), None);
+
+        let vertices = vertex_states(&embedding);
+        assert_eq!(vertices[1].1, DrawState::Default);
+        assert_eq!(vertices[1].2.as_deref(), Some("[3]"));
+        assert!(!vertices[1].3);
+        for vertex in [2, 3] {
+            assert_eq!(vertices[vertex].1, DrawState::Hidden);
+            assert!(vertices[vertex].3);
+        }
+
+        // the edges within the group are hidden, the three edges to 4 are drawn once
+        let edges = edge_states(&embedding);
+        assert_eq!(edges[0], ((0, 1), DrawState::Default, None));
+        assert_eq!(edges[1].1, DrawState::Hidden);
+        assert_eq!(edges[2].1, DrawState::Hidden);
+        assert_eq!(
+            edges[3],
+            ((1, 4), DrawState::Default, Some("×3".to_string()))
+        );
+        assert_eq!(edges[4].1, DrawState::Hidden);
+        assert_eq!(edges[5].1, DrawState::Hidden);
+        assert_eq!(edges[6], ((3, 5), DrawState::Default, None));
+
+        // the kept edges start at the meta-vertex, even when they belong to a hidden member
+        let drawable = DrawableGraph::compose(&embedding, &Default::default());
+        assert_eq!(drawable.vertices.len(), 4);
+        assert_eq!(drawable.edges.len(), 3);
+        for edge in &drawable.edges {
+            assert!(edge.start == centroid || edge.end == centroid);
+        }
+
+        // hidden members do not hide or fade the edge drawn for them
+        assert_eq!(
+            embedding.induced_edge_state(6, HiddenVertexEdges::Hide, false),
+            None
+        );
+    }
+
+    #[test]
+    fn test_collapse_rejects_invalid_groups() {
+        let (_, mut embedding) = clustered_embedding();
+
+        assert_eq!(embedding.collapse(&[]), None);
+        assert_eq!(embedding.collapse(&[2, 2]), None);
+        assert_eq!(embedding.collapse(&[2, 6]), None);
+
+        assert_eq!(embedding.collapse(&[1, 2]), Some(0));
+        assert_eq!(embedding.collapse(&[2, 3]), None);
+        assert_eq!(embedding.collapse_neighbourhood(4), None);
+        assert_eq!(embedding.collapse(&[3, 4]), Some(1));
+        assert_eq!(embedding.collapsed_groups().len(), 2);
+
+        assert!(!embedding.expand(2));
+    }
+
+    #[test]
+    fn test_expand_restores_everything() {
+        let (_, mut embedding) = clustered_embedding();
+        embedding.vertex_properties[2].label = Some("b".to_string());
+        embedding.vertex_properties[3].draw_state = DrawState::Highlighted;
+        embedding.edge_properties[4].label = Some("heavy".to_string());
+        embedding.edge_properties[1].draw_state = DrawState::Highlighted;
+        embedding.selected_vertices = vec![0, 2, 3];
+        let vertices = vertex_states(&embedding);
+        let edges = edge_states(&embedding);
+
+        assert_eq!(embedding.collapse_neighbourhood(2), Some(0));
+        assert_eq!(embedding.collapsed_groups()[0].members, vec![1, 2, 3, 4]);
+        assert_eq!(embedding.selected_vertices, vec![0]);
+
+        // the meta-vertex is dragged, its members follow
+        embedding.set_position(1, vec2(50.0, 250.0));
+        embedding.follow_meta_vertices();
+        assert_eq!(embedding.get_position(4), vec2(50.0, 250.0));
+        let positions: Vec<Vec2> = vertices.iter().map(|state| state.0).collect();
+        assert_eq!(embedding.expanded_positions(), positions);
+
+        assert!(embedding.expand(0));
+        assert!(embedding.collapsed_groups().is_empty());
+        assert_eq!(vertex_states(&embedding), vertices);
+        assert_eq!(edge_states(&embedding), edges);
+    }
+
+    #[test]
+    fn test_groups_expand_in_any_order() {
+        let (_, mut embedding) = clustered_embedding();
+        let vertices = vertex_states(&embedding);
+        let edges = edge_states(&embedding);
+
+        // the edges 1-4, 2-4 and 3-4 run between the groups
+        assert_eq!(embedding.collapse(&[4, 5]), Some(0));
+        let one_group = (vertex_states(&embedding), edge_states(&embedding));
+        assert_eq!(embedding.collapse(&[1, 2, 3]), Some(1));
+        assert_eq!(
+            edge_states(&embedding)[3],
+            ((1, 4), DrawState::Default, Some("×4".to_string()))
+        );
+
+        // expanding the first group leaves the second collapsed
+        assert!(embedding.expand(0));
+        assert_eq!(embedding.collapsed_groups().len(), 1);
+        assert_eq!(embedding.collapsed_groups()[0].members, vec![1, 2, 3]);
+        assert_eq!(embedding.vertex_properties[2].draw_state, DrawState::Hidden);
+        assert_eq!(
+            embedding.vertex_properties[5].draw_state,
+            DrawState::Default
+        );
+        assert_eq!(
+            edge_states(&embedding)[3],
+            ((1, 4), DrawState::Default, Some("×3".to_string()))
+        );
+
+        assert!(embedding.expand(0));
+        assert_eq!(vertex_states(&embedding), vertices);
+        assert_eq!(edge_states(&embedding), edges);
+
+        // in reverse order
+        embedding.collapse(&[4, 5]);
+        embedding.collapse(&[1, 2, 3]);
+        assert!(embedding.expand(1));
+        assert_eq!(
+            (vertex_states(&embedding), edge_states(&embedding)),
+            one_group
+        );
+        assert!(embedding.expand(0));
+        assert_eq!(vertex_states(&embedding), vertices);
+        assert_eq!(edge_states(&embedding), edges);
+    }
+
+    #[test]
+    fn test_export_sees_the_expanded_graph() {
+        let (graph, mut embedding) = clustered_embedding();
+        let vertices = vertex_states(&embedding);
+        embedding.collapse(&[1, 2, 3]);
+        let collapsed = (vertex_states(&embedding), edge_states(&embedding));
+
+        let seen = embedding.with_groups_expanded(|expanded| {
+            assert!(expanded.collapsed_groups().is_empty());
+            vertex_states(expanded)
+        });
+        assert_eq!(seen, vertices);
+        assert_eq!(
+            (vertex_states(&embedding), edge_states(&embedding)),
+            collapsed
+        );
+        assert_eq!(embedding.collapsed_groups().len(), 1);
+
+        // changing the edges expands every group first
+        embedding.update_edges(&graph);
+        assert!(embedding.collapsed_groups().is_empty());
+        assert_eq!(vertex_states(&embedding), vertices);
+    }
 }
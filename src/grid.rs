@@ -1,16 +1,23 @@
 use macroquad::{
-    prelude::{Vec2, WHITE},
-    shapes::{draw_circle_lines, draw_line},
-    window::*,
+    prelude::{vec2, Rect, Vec2, WHITE},
+    shapes::draw_line,
 };
 
-use crate::{graph_drawer::Drawable, ui_manager::main_screen_width};
+use crate::graph_drawer::Drawable;
+
+const CIRCLE_SEGMENTS: usize = 96;
+/// Space kept free between the outermost ring of a radial layout and the window border.
+const RADIAL_MARGIN: f32 = 30.0;
 
 pub struct SquareGrid {
-    pub x_delta: f32,
-    pub y_delta: f32,
-    pub x_offset: f32,
-    pub y_offset: f32,
+    x_delta: f32,
+    y_delta: f32,
+    x_offset: f32,
+    y_offset: f32,
+    pub snap_delta: f32,
+    pub snap_offset: Vec2,
+    window: Vec2,
+    segments: Vec<(Vec2, Vec2)>,
 }
 
 impl SquareGrid {
@@ -20,87 +27,389 @@ impl SquareGrid {
             y_delta,
             x_offset: 0.0,
             y_offset: 0.0,
+            snap_delta: (x_delta + y_delta) * 0.5,
+            snap_offset: Vec2::ZERO,
+            window: Vec2::ZERO,
+            segments: Vec::new(),
         }
     }
 
     pub fn make_square(&mut self) {
         let min = self.x_delta.min(self.y_delta);
-        self.x_delta = min;
-        self.y_delta = min;
+        self.set_deltas(min, min);
     }
 
     pub fn set_deltas(&mut self, x_delta: f32, y_delta: f32) {
+        if self.x_delta == x_delta && self.y_delta == y_delta {
+            return;
+        }
+
         self.x_delta = x_delta;
         self.y_delta = y_delta;
+        self.update_cache();
     }
 
     pub fn set_deltas_square(&mut self, delta: f32) {
         self.set_deltas(delta, delta);
     }
 
+    pub fn set_snap_delta(&mut self, snap_delta: f32) {
+        if self.snap_delta == snap_delta {
+            return;
+        }
+
+        self.snap_delta = snap_delta;
+        self.update_cache();
+    }
+
     pub fn set_offsets_from_window(&mut self, window_dimensions: Vec2) {
-        let mid = window_dimensions / 2.0;
-        self.x_offset = mid.x % self.x_delta;
-        self.y_offset = mid.y % self.y_delta;
+        if self.window == window_dimensions {
+            return;
+        }
+
+        self.window = window_dimensions;
+        self.update_cache();
     }
 
-    pub fn delta_avg(&self) -> f32 {
-        (self.x_delta + self.y_delta) * 0.5
+    pub fn segments(&self) -> &[(Vec2, Vec2)] {
+        &self.segments
     }
-}
 
-impl Drawable for SquareGrid {
-    fn draw(&self) {
-        let mut x = self.x_offset;
-        let mut y = self.y_offset;
+    pub fn closest_snap_point(&self, position: Vec2) -> Vec2 {
+        ((position - self.snap_offset) / self.snap_delta).round() * self.snap_delta
+            + self.snap_offset
+    }
+
+    fn update_cache(&mut self) {
+        let mid = self.window / 2.0;
+        self.x_offset = mid.x % self.x_delta;
+        self.y_offset = mid.y % self.y_delta;
+        self.snap_offset = vec2(mid.x % self.snap_delta, mid.y % self.snap_delta);
+
+        self.segments.clear();
+
+        if self.x_delta <= 0.0 || self.y_delta <= 0.0 {
+            return;
+        }
 
-        while x < main_screen_width() {
-            draw_line(x, 0.0, x, screen_height(), 2.0, WHITE);
+        let mut x = self.x_offset;
+        while x < self.window.x {
+            self.segments.push((vec2(x, 0.0), vec2(x, self.window.y)));
             x += self.x_delta;
         }
 
-        while y < screen_height() {
-            draw_line(0.0, y, screen_width(), y, 2.0, WHITE);
+        let mut y = self.y_offset;
+        while y < self.window.y {
+            self.segments.push((vec2(0.0, y), vec2(self.window.x, y)));
             y += self.y_delta;
         }
     }
 }
 
+/// Spacing of the drawn lines, which only changes through [`SquareGrid::set_deltas`].
+#[cfg(test)]
+impl SquareGrid {
+    pub fn x_delta(&self) -> f32 {
+        self.x_delta
+    }
+}
+
+impl Drawable for SquareGrid {
+    fn draw(&self) {
+        draw_segments(self.segments());
+    }
+}
+
 pub struct CircleGrid {
-    pub r_delta: f32,
+    r_delta: f32,
+    pub snap_r_delta: f32,
     pub center: Vec2,
-    pub max: f32,
+    window: Vec2,
+    segments: Vec<(Vec2, Vec2)>,
 }
 
 impl CircleGrid {
     pub fn new(r_delta: f32, window_dimensions: Vec2) -> Self {
-        let max = window_dimensions.x.max(window_dimensions.y);
-        let center = window_dimensions / 2.0;
-
-        Self {
+        let mut grid = Self {
             r_delta,
-            center,
-            max,
-        }
+            snap_r_delta: r_delta,
+            center: window_dimensions / 2.0,
+            window: window_dimensions,
+            segments: Vec::new(),
+        };
+
+        grid.update_cache();
+        grid
     }
 
     pub fn set_r_delta(&mut self, r_delta: f32) {
+        if self.r_delta == r_delta {
+            return;
+        }
+
         self.r_delta = r_delta;
+        self.update_cache();
+    }
+
+    pub fn set_snap_r_delta(&mut self, snap_r_delta: f32) {
+        self.snap_r_delta = snap_r_delta;
     }
 
     pub fn set_from_window(&mut self, window_dimensions: Vec2) {
-        self.max = window_dimensions.x.max(window_dimensions.y);
+        if self.window == window_dimensions {
+            return;
+        }
+
+        self.window = window_dimensions;
         self.center = window_dimensions / 2.0;
+        self.update_cache();
+    }
+
+    pub fn segments(&self) -> &[(Vec2, Vec2)] {
+        &self.segments
+    }
+
+    /// Places every vertex on the ring of its depth, see [`crate::graph::Graph::depths`], with
+    /// depth 0 at the center. The vertices of a ring are spread evenly by angle in vertex order,
+    /// and vertices without a depth go on the ring past the deepest one. The rings are
+    /// [`Self::ring_spacing`] apart.
+    pub fn radial_positions(&self, depths: &[Option<usize>]) -> Vec<Vec2> {
+        let rings = depth_rings(depths);
+        let outer_ring = rings.iter().copied().max().unwrap_or(0);
+
+        let mut ring_sizes = vec![0; outer_ring + 1];
+        for &ring in &rings {
+            ring_sizes[ring] += 1;
+        }
+
+        let spacing = self.ring_spacing(depths);
+
+        let mut placed = vec![0; outer_ring + 1];
+        rings
+            .iter()
+            .map(|&ring| {
+                let angle = std::f32::consts::TAU * placed[ring] as f32 / ring_sizes[ring] as f32;
+                placed[ring] += 1;
+                self.center + Vec2::from_angle(angle) * ring as f32 * spacing
+            })
+            .collect()
+    }
+
+    /// Distance between the rings of a radial layout of `depths`. It is less than the grid
+    /// when the outermost ring would not fit in the window otherwise.
+    pub fn ring_spacing(&self, depths: &[Option<usize>]) -> f32 {
+        let outermost = depth_rings(depths).into_iter().max().unwrap_or(0);
+        if outermost == 0 {
+            return self.r_delta;
+        }
+
+        let fit_radius = (self.window.min_element() / 2.0 - RADIAL_MARGIN).max(0.0);
+        self.r_delta.min(fit_radius / outermost as f32)
+    }
+
+    fn update_cache(&mut self) {
+        self.segments.clear();
+
+        if self.r_delta <= 0.0 {
+            return;
+        }
+
+        let clip_rect = Rect::new(0.0, 0.0, self.window.x, self.window.y);
+        let max = self.center.length();
+        let angle_step = std::f32::consts::TAU / CIRCLE_SEGMENTS as f32;
+
+        let mut r = self.r_delta;
+        while r < max {
+            for i in 0..CIRCLE_SEGMENTS {
+                let start = self.center + Vec2::from_angle(i as f32 * angle_step) * r;
+                let end = self.center + Vec2::from_angle((i + 1) as f32 * angle_step) * r;
+
+                if let Some(segment) = clip_segment(start, end, clip_rect) {
+                    self.segments.push(segment);
+                }
+            }
+
+            r += self.r_delta;
+        }
+    }
+}
+
+/// Spacing of the drawn rings, which only changes through [`CircleGrid::set_r_delta`].
+#[cfg(test)]
+impl CircleGrid {
+    pub fn r_delta(&self) -> f32 {
+        self.r_delta
     }
 }
 
 impl Drawable for CircleGrid {
     fn draw(&self) {
-        let mut r = self.r_delta;
+        draw_segments(self.segments());
+    }
+}
 
-        while r < self.max {
-            draw_circle_lines(self.center.x, self.center.y, r, 2.0, WHITE);
-            r += self.r_delta;
+fn draw_segments(segments: &[(Vec2, Vec2)]) {
+    for (start, end) in segments {
+        draw_line(start.x, start.y, end.x, end.y, 2.0, WHITE);
+    }
+}
+
+/// Clips a line segment to a rectangle (Liang-Barsky).
+pub fn clip_segment(start: Vec2, end: Vec2, rect: Rect) -> Option<(Vec2, Vec2)> {
+    let delta = end - start;
+    let mut t_min: f32 = 0.0;
+    let mut t_max: f32 = 1.0;
+
+    let checks = [
+        (-delta.x, start.x - rect.x),
+        (delta.x, rect.x + rect.w - start.x),
+        (-delta.y, start.y - rect.y),
+        (delta.y, rect.y + rect.h - start.y),
+    ];
+
+    for (p, q) in checks {
+        if p == 0.0 {
+            if q < 0.0 {
+                return None;
+            }
+        } else {
+            let t = q / p;
+            if p < 0.0 {
+                t_min = t_min.max(t);
+            } else {
+                t_max = t_max.min(t);
+            }
         }
     }
+
+    if t_min > t_max {
+        return None;
+    }
+
+    Some((start + delta * t_min, start + delta * t_max))
+}
+
+/// Ring of every vertex of a radial layout, the vertices without a depth go on the ring past the
+/// deepest one.
+fn depth_rings(depths: &[Option<usize>]) -> Vec<usize> {
+    let outer_ring = depths.iter().flatten().max().map_or(0, |&depth| depth + 1);
+    depths
+        .iter()
+        .map(|depth| depth.unwrap_or(outer_ring))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snap_point_independent_of_visual_delta() {
+        let mut grid = SquareGrid::new(10.0, 10.0);
+        grid.set_snap_delta(40.0);
+        grid.set_offsets_from_window(vec2(400.0, 400.0));
+
+        assert_eq!(grid.closest_snap_point(vec2(33.0, 58.0)), vec2(40.0, 40.0));
+        assert_eq!(grid.x_offset, 0.0);
+        assert_eq!(grid.x_delta, 10.0);
+    }
+
+    #[test]
+    fn test_square_segments_follow_parameters() {
+        let mut grid = SquareGrid::new(30.0, 30.0);
+        grid.set_offsets_from_window(vec2(120.0, 60.0));
+
+        // vertical lines at 0, 30, 60, 90 and horizontal lines at 0, 30
+        assert_eq!(grid.segments().len(), 6);
+
+        grid.set_deltas_square(60.0);
+        assert_eq!(grid.segments().len(), 3);
+
+        grid.set_offsets_from_window(vec2(240.0, 60.0));
+        assert_eq!(grid.segments().len(), 5);
+    }
+
+    #[test]
+    fn test_square_segments_stay_in_main_area() {
+        let mut grid = SquareGrid::new(25.0, 25.0);
+        grid.set_offsets_from_window(vec2(500.0, 300.0));
+
+        for (start, end) in grid.segments() {
+            assert!(start.x <= 500.0 && end.x <= 500.0);
+            assert!(start.y <= 300.0 && end.y <= 300.0);
+        }
+    }
+
+    #[test]
+    fn test_circle_segments_are_clipped_and_updated() {
+        let mut grid = CircleGrid::new(50.0, vec2(200.0, 100.0));
+        let segment_count = grid.segments().len();
+
+        for (start, end) in grid.segments() {
+            for point in [start, end] {
+                assert!(point.x >= -0.001 && point.x <= 200.001);
+                assert!(point.y >= -0.001 && point.y <= 100.001);
+            }
+        }
+
+        grid.set_r_delta(25.0);
+        assert!(grid.segments().len() > segment_count);
+
+        grid.set_from_window(vec2(400.0, 400.0));
+        assert_eq!(grid.center, vec2(200.0, 200.0));
+    }
+
+    #[test]
+    fn test_radial_positions_follow_depths() {
+        let grid = CircleGrid::new(50.0, vec2(400.0, 400.0));
+        let center = vec2(200.0, 200.0);
+        let depths = [Some(1), Some(0), Some(1), None, Some(2), Some(1)];
+
+        let positions = grid.radial_positions(&depths);
+
+        let radii: Vec<f32> = positions
+            .iter()
+            .map(|position| position.distance(center).round())
+            .collect();
+        // the unreachable vertex goes on the ring past depth 2
+        assert_eq!(radii, vec![50.0, 0.0, 50.0, 150.0, 100.0, 50.0]);
+
+        // ring 1 holds three vertices a third of a turn apart, in vertex order
+        let angle = |vertex: usize| {
+            let offset = positions[vertex] - center;
+            offset.y.atan2(offset.x).rem_euclid(std::f32::consts::TAU)
+        };
+        let third = std::f32::consts::TAU / 3.0;
+        assert!(angle(0).abs() < 0.001);
+        assert!((angle(2) - third).abs() < 0.001);
+        assert!((angle(5) - 2.0 * third).abs() < 0.001);
+
+        assert_eq!(grid.radial_positions(&depths), positions);
+        assert_eq!(grid.radial_positions(&[None, None]), vec![center, center]);
+    }
+
+    #[test]
+    fn test_deep_radial_layouts_fit_the_window() {
+        let grid = CircleGrid::new(50.0, vec2(400.0, 300.0));
+        let depths: Vec<Option<usize>> = (0..20).map(Some).collect();
+
+        let positions = grid.radial_positions(&depths);
+
+        let deepest = positions[19].distance(grid.center);
+        assert!((deepest - (150.0 - RADIAL_MARGIN)).abs() < 0.001);
+        // the rings stay evenly spaced
+        let first = positions[1].distance(grid.center);
+        assert!((deepest - 19.0 * first).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_clip_segment() {
+        let rect = Rect::new(0.0, 0.0, 10.0, 10.0);
+
+        assert_eq!(
+            clip_segment(vec2(-5.0, 5.0), vec2(15.0, 5.0), rect),
+            Some((vec2(0.0, 5.0), vec2(10.0, 5.0)))
+        );
+        assert_eq!(clip_segment(vec2(-5.0, -5.0), vec2(-1.0, 20.0), rect), None);
+    }
 }
@@ -0,0 +1,67 @@
+/// How vertex and edge indices are shown to and read from the user.
+/// Internally all indices are zero-based; conversions only happen at input and output.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Indexing {
+    Zero,
+    #[default]
+    One,
+}
+
+impl Indexing {
+    pub const ALL: [Indexing; 2] = [Indexing::Zero, Indexing::One];
+    pub const NAMES: [&'static str; 2] = ["zero-indexed", "one-indexed"];
+
+    pub fn index(&self) -> usize {
+        Self::ALL
+            .iter()
+            .position(|indexing| indexing == self)
+            .unwrap()
+    }
+
+    pub fn offset(&self) -> usize {
+        match self {
+            Indexing::Zero => 0,
+            Indexing::One => 1,
+        }
+    }
+
+    /// Index as shown to the user.
+    pub fn display(&self, index: usize) -> usize {
+        index + self.offset()
+    }
+
+    /// Internal index of a number typed by the user, `None` when it is out of range.
+    pub fn internal(&self, number: usize) -> Option<usize> {
+        number.checked_sub(self.offset())
+    }
+
+    pub fn parse(&self, token: &str) -> Option<usize> {
+        token
+            .parse::<usize>()
+            .ok()
+            .and_then(|number| self.internal(number))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_in_both_modes() {
+        for indexing in Indexing::ALL {
+            for index in [0, 1, 41] {
+                let shown = indexing.display(index).to_string();
+                assert_eq!(indexing.parse(&shown), Some(index));
+            }
+        }
+    }
+
+    #[test]
+    fn test_one_indexed_rejects_zero() {
+        assert_eq!(Indexing::One.parse("0"), None);
+        assert_eq!(Indexing::Zero.parse("0"), Some(0));
+        assert_eq!(Indexing::One.parse("x"), None);
+        assert_eq!(Indexing::default(), Indexing::One);
+    }
+}
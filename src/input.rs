@@ -0,0 +1,458 @@
+use macroquad::prelude::*;
+
+const BUTTONS: [MouseButton; 3] = [MouseButton::Left, MouseButton::Right, MouseButton::Middle];
+
+/// Thresholds that tell clicks from drags and double clicks from two clicks.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InputConfig {
+    /// Seconds a button has to be held before it counts as a drag.
+    pub drag_delay: f32,
+    /// Pixels the mouse has to move while held before it counts as a drag.
+    pub drag_distance: f32,
+    /// Most seconds between two clicks of a double click.
+    pub double_click_delay: f32,
+    /// Most pixels between two clicks of a double click.
+    pub double_click_distance: f32,
+    /// Pixels on screen within which thin edges and small vertices are still hovered.
+    pub hit_tolerance: f32,
+    /// Off to only hover and click vertices.
+    pub hover_edges: bool,
+}
+
+impl Default for InputConfig {
+    fn default() -> Self {
+        Self {
+            drag_delay: 0.125,
+            drag_distance: 5.0,
+            double_click_delay: 0.4,
+            double_click_distance: 5.0,
+            hit_tolerance: 8.0,
+            hover_edges: true,
+        }
+    }
+}
+
+/// Mouse and modifier key state sampled at the start of a frame. [`Self::poll`] reads it from
+/// macroquad, tests build it by hand or replay a script of it.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RawInput {
+    /// Seconds since an arbitrary, fixed point in time.
+    pub time: f64,
+    pub position: Vec2,
+    /// Left, right and middle button.
+    pub down: [bool; 3],
+    pub shift: bool,
+    pub alt: bool,
+    pub ctrl: bool,
+    /// Escape was pressed this frame.
+    pub escape: bool,
+    /// Vertical scroll this frame, positive away from the user.
+    pub wheel: f32,
+}
+
+impl RawInput {
+    pub fn poll() -> Self {
+        Self {
+            time: get_time(),
+            position: mouse_position().into(),
+            down: BUTTONS.map(is_mouse_button_down),
+            shift: is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift),
+            alt: is_key_down(KeyCode::LeftAlt) || is_key_down(KeyCode::RightAlt),
+            ctrl: is_key_down(KeyCode::LeftControl) || is_key_down(KeyCode::RightControl),
+            escape: is_key_pressed(KeyCode::Escape),
+            wheel: mouse_wheel().1,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ButtonEvents {
+    pub down: bool,
+    pub pressed: bool,
+    pub released: bool,
+    /// Held long enough or moved far enough to be a drag, until it is released.
+    pub drag: bool,
+    /// Released without having become a drag.
+    pub click: bool,
+    /// A click soon after and close to the previous one. The first click was reported too.
+    pub double_click: bool,
+}
+
+/// What the mouse did this frame, see [`InputTracker::update`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct InputEvents {
+    pub position: Vec2,
+    buttons: [ButtonEvents; 3],
+    pub shift: bool,
+    pub alt: bool,
+    pub ctrl: bool,
+    pub escape: bool,
+    pub wheel: f32,
+}
+
+impl InputEvents {
+    pub fn button(&self, button: MouseButton) -> ButtonEvents {
+        BUTTONS
+            .iter()
+            .position(|&candidate| candidate == button)
+            .map(|index| self.buttons[index])
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct ButtonTracker {
+    press: Option<(f64, Vec2)>,
+    dragging: bool,
+    last_click: Option<(f64, Vec2)>,
+}
+
+impl ButtonTracker {
+    fn update(
+        &mut self,
+        down: bool,
+        time: f64,
+        position: Vec2,
+        config: &InputConfig,
+    ) -> ButtonEvents {
+        let mut events = ButtonEvents {
+            down,
+            ..Default::default()
+        };
+
+        match (self.press, down) {
+            (None, true) => {
+                self.press = Some((time, position));
+                events.pressed = true;
+            }
+            (Some(_), false) => {
+                events.released = true;
+                events.click = !self.dragging;
+                self.press = None;
+                self.dragging = false;
+            }
+            _ => {}
+        }
+
+        if let Some((press_time, press_position)) = self.press {
+            self.dragging |= time - press_time > config.drag_delay as f64
+                || press_position.distance(position) > config.drag_distance;
+            events.drag = self.dragging;
+        }
+
+        if events.click {
+            events.double_click = self.last_click.is_some_and(|(click_time, click_position)| {
+                time - click_time <= config.double_click_delay as f64
+                    && click_position.distance(position) <= config.double_click_distance
+            });
+            // a third click starts a new double click
+            self.last_click = (!events.double_click).then_some((time, position));
+        }
+
+        events
+    }
+}
+
+/// Turns the sampled mouse state of consecutive frames into click, drag and double click
+/// events.
+#[derive(Default)]
+pub struct InputTracker {
+    buttons: [ButtonTracker; 3],
+}
+
+impl InputTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn update(&mut self, input: RawInput, config: &InputConfig) -> InputEvents {
+        let mut events = InputEvents {
+            position: input.position,
+            shift: input.shift,
+            alt: input.alt,
+            ctrl: input.ctrl,
+            escape: input.escape,
+            wheel: input.wheel,
+            ..Default::default()
+        };
+
+        for (index, tracker) in self.buttons.iter_mut().enumerate() {
+            events.buttons[index] =
+                tracker.update(input.down[index], input.time, input.position, config);
+        }
+
+        events
+    }
+}
+
+/// One step of an [`InputScript`].
+#[cfg(test)]
+#[derive(Clone, Copy, Debug)]
+pub enum ScriptStep {
+    /// Moves the mouse in a straight line, one frame per `step` pixels.
+    MoveTo(Vec2, f32),
+    Press(MouseButton),
+    Release(MouseButton),
+    /// Holds still for this many seconds.
+    Wait(f64),
+    Shift(bool),
+    Alt(bool),
+    Escape,
+}
+
+/// Replays mouse and keyboard input through an [`InputTracker`], one frame per 1/60 second, for
+/// testing the interaction logic without a window.
+#[cfg(test)]
+pub struct InputScript {
+    tracker: InputTracker,
+    config: InputConfig,
+    input: RawInput,
+}
+
+#[cfg(test)]
+impl InputScript {
+    const FRAME_TIME: f64 = 1.0 / 60.0;
+
+    pub fn new(position: Vec2) -> Self {
+        Self {
+            tracker: InputTracker::new(),
+            config: InputConfig::default(),
+            input: RawInput {
+                position,
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Runs the steps and hands the events of every frame to `handle`.
+    pub fn play(&mut self, steps: &[ScriptStep], mut handle: impl FnMut(&InputEvents)) {
+        for &step in steps {
+            match step {
+                ScriptStep::MoveTo(target, step) => {
+                    let start = self.input.position;
+                    let frames = (start.distance(target) / step).ceil().max(1.0) as usize;
+                    for frame in 1..=frames {
+                        self.input.position = start.lerp(target, frame as f32 / frames as f32);
+                        handle(&self.frame());
+                    }
+                }
+                ScriptStep::Press(button) | ScriptStep::Release(button) => {
+                    let index = BUTTONS.iter().position(|&other| other == button).unwrap();
+                    self.input.down[index] = matches!(step, ScriptStep::Press(_));
+                    handle(&self.frame());
+                }
+                ScriptStep::Wait(seconds) => {
+                    let frames = (seconds / Self::FRAME_TIME).ceil() as usize;
+                    for _ in 0..frames {
+                        handle(&self.frame());
+                    }
+                }
+                ScriptStep::Shift(down) => self.input.shift = down,
+                ScriptStep::Alt(down) => self.input.alt = down,
+                ScriptStep::Escape => {
+                    self.input.escape = true;
+                    handle(&self.frame());
+                    self.input.escape = false;
+                }
+            }
+        }
+    }
+
+    fn frame(&mut self) -> InputEvents {
+        self.input.time += Self::FRAME_TIME;
+        self.tracker.update(self.input, &self.config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Time, horizontal mouse position and whether the left button is down.
+    type Frame = (f64, f32, bool);
+
+    /// Feeds `frames` and returns the left button events of each.
+    fn left_events(frames: &[Frame]) -> Vec<ButtonEvents> {
+        let mut tracker = InputTracker::new();
+        let config = InputConfig::default();
+
+        frames
+            .iter()
+            .map(|&(time, x, down)| {
+                let input = RawInput {
+                    time,
+                    position: vec2(x, 0.0),
+                    down: [down, false, false],
+                    ..Default::default()
+                };
+                tracker.update(input, &config).button(MouseButton::Left)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_click_or_drag() {
+        // (frames, last frame is a click, drag seen before the release)
+        let cases: [(&[Frame], bool, bool); 6] = [
+            // short press without movement
+            (&[(0.0, 0.0, true), (0.1, 0.0, false)], true, false),
+            // held just within the delay
+            (
+                &[(0.0, 0.0, true), (0.125, 0.0, true), (0.13, 0.0, false)],
+                true,
+                false,
+            ),
+            // held past the delay
+            (
+                &[(0.0, 0.0, true), (0.126, 0.0, true), (0.13, 0.0, false)],
+                false,
+                true,
+            ),
+            // moved exactly the drag distance
+            (
+                &[(0.0, 0.0, true), (0.05, 5.0, true), (0.1, 5.0, false)],
+                true,
+                false,
+            ),
+            // moved past the drag distance, quickly
+            (
+                &[(0.0, 0.0, true), (0.05, 5.5, true), (0.1, 5.5, false)],
+                false,
+                true,
+            ),
+            // moved away and back still counts as a drag
+            (
+                &[
+                    (0.0, 0.0, true),
+                    (0.02, 9.0, true),
+                    (0.04, 0.0, true),
+                    (0.06, 0.0, false),
+                ],
+                false,
+                true,
+            ),
+        ];
+
+        for (index, (frames, click, drag)) in cases.into_iter().enumerate() {
+            let events = left_events(frames);
+            let last = events.last().unwrap();
+
+            assert!(events[0].pressed && events[0].down, "case {}", index);
+            assert!(last.released && !last.down, "case {}", index);
+            assert_eq!(last.click, click, "case {}", index);
+            assert_eq!(
+                events.iter().any(|events| events.drag),
+                drag,
+                "case {}",
+                index
+            );
+            assert!(!last.drag, "case {}", index);
+        }
+    }
+
+    #[test]
+    fn test_events_between_presses() {
+        let events = left_events(&[(0.0, 0.0, false), (0.1, 0.0, true), (0.2, 0.0, true)]);
+
+        assert_eq!(events[0], ButtonEvents::default());
+        assert!(events[1].pressed);
+        assert!(!events[2].pressed && events[2].down && !events[2].click);
+    }
+
+    #[test]
+    fn test_double_click_window() {
+        let click_at = |time: f64, x: f32| [(time, x, true), (time + 0.05, x, false)];
+        let double_clicks = |frames: Vec<Frame>| -> Vec<bool> {
+            left_events(&frames)
+                .iter()
+                .filter(|events| events.click)
+                .map(|events| events.double_click)
+                .collect()
+        };
+
+        // (second click time, second click x, double click)
+        let cases = [
+            (0.3, 0.0, true),
+            (0.39, 0.0, true),
+            (0.41, 0.0, false),
+            (0.3, 5.0, true),
+            (0.3, 6.0, false),
+        ];
+        for (time, x, double_click) in cases {
+            let frames = [click_at(0.0, 0.0), click_at(time, x)].concat();
+            assert_eq!(
+                double_clicks(frames),
+                vec![false, double_click],
+                "{} {}",
+                time,
+                x
+            );
+        }
+
+        // the third click of a quick series starts over
+        let frames = [click_at(0.0, 0.0), click_at(0.2, 0.0), click_at(0.4, 0.0)].concat();
+        assert_eq!(double_clicks(frames), vec![false, true, false]);
+
+        // a drag in between is no click
+        let frames = vec![
+            (0.0, 0.0, true),
+            (0.05, 0.0, false),
+            (0.1, 0.0, true),
+            (0.3, 0.0, true),
+            (0.35, 0.0, false),
+        ];
+        assert_eq!(double_clicks(frames), vec![false]);
+    }
+
+    #[test]
+    fn test_buttons_are_independent() {
+        let mut tracker = InputTracker::new();
+        let config = InputConfig::default();
+        let frame = |time: f64, down: [bool; 3]| RawInput {
+            time,
+            position: Vec2::ZERO,
+            down,
+            ..Default::default()
+        };
+
+        tracker.update(frame(0.0, [true, false, false]), &config);
+        let events = tracker.update(frame(0.05, [true, true, false]), &config);
+        assert!(events.button(MouseButton::Right).pressed);
+        assert!(!events.button(MouseButton::Left).pressed);
+
+        let events = tracker.update(frame(0.1, [false, true, true]), &config);
+        assert!(events.button(MouseButton::Left).click);
+        assert!(events.button(MouseButton::Middle).pressed);
+        assert_eq!(events.button(MouseButton::Unknown), ButtonEvents::default());
+    }
+
+    #[test]
+    fn test_configured_thresholds() {
+        let config = InputConfig {
+            drag_delay: 1.0,
+            drag_distance: 20.0,
+            ..Default::default()
+        };
+        let mut tracker = InputTracker::new();
+        let frame = |time: f64, x: f32, down: bool| RawInput {
+            time,
+            position: vec2(x, 0.0),
+            down: [down, false, false],
+            ..Default::default()
+        };
+
+        tracker.update(frame(0.0, 0.0, true), &config);
+        assert!(
+            !tracker
+                .update(frame(0.5, 15.0, true), &config)
+                .button(MouseButton::Left)
+                .drag
+        );
+        assert!(
+            tracker
+                .update(frame(0.6, 15.0, false), &config)
+                .button(MouseButton::Left)
+                .click
+        );
+    }
+}
@@ -0,0 +1,255 @@
+use std::collections::{HashMap, HashSet};
+
+use macroquad::prelude::*;
+
+use crate::graph_drawer::{DrawableEdge, DrawableGraph, DrawableLabel, DrawableVertex};
+
+/// Copy of `graph` moved by `offset` after scaling by `scale`, with every position, radius
+/// and width rounded to an integer. Vertices that would round to the same point are moved apart,
+/// and edges end in the moved vertices.
+pub fn snap_to_integers(graph: &DrawableGraph, offset: Vec2, scale: f32) -> DrawableGraph {
+    let transform = |point: Vec2| point * scale + offset;
+
+    let mut order: Vec<usize> = (0..graph.vertices.len()).collect();
+    order.sort_by_key(|&position| graph.vertices[position].index);
+
+    let positions: Vec<Vec2> = order
+        .iter()
+        .map(|&position| transform(graph.vertices[position].position))
+        .collect();
+    let snapped = snap_positions(&positions);
+
+    let mut snapped_by_index = HashMap::new();
+    let mut snapped_by_position = HashMap::new();
+    let mut vertices: Vec<_> = graph.vertices.iter().map(|_| None).collect();
+    for ((&position, &snapped_position), &transformed) in order.iter().zip(&snapped).zip(&positions)
+    {
+        let vertex = &graph.vertices[position];
+        let moved = snapped_position - transformed;
+        snapped_by_index.insert(vertex.index, snapped_position);
+        snapped_by_position
+            .entry(key(vertex.position))
+            .or_insert(snapped_position);
+
+        let main_radius = (vertex.main_radius * scale).round();
+        vertices[position] = Some(DrawableVertex {
+            position: snapped_position,
+            main_radius,
+            border_radius: (vertex.border_radius * scale).round().max(main_radius),
+            label: vertex
+                .label
+                .as_ref()
+                .map(|label| moved_label(label, transform, scale, moved)),
+            title: vertex.title.clone(),
+            ..*vertex
+        });
+    }
+
+    // edges keep ending in their vertices, even when those were moved apart
+    let endpoint = |vertex: Option<usize>, point: Vec2| {
+        vertex
+            .and_then(|vertex| snapped_by_index.get(&vertex))
+            .or_else(|| snapped_by_position.get(&key(point)))
+            .copied()
+            .unwrap_or(transform(point).round())
+    };
+
+    let edges = graph
+        .edges
+        .iter()
+        .map(|edge| {
+            let end = endpoint(edge.endpoints.map(|(_, end)| end), edge.end);
+            let end_offset = end - transform(edge.end);
+
+            DrawableEdge {
+                start: endpoint(edge.endpoints.map(|(start, _)| start), edge.start),
+                end,
+                width: (edge.width * scale).round().max(1.0),
+                label: edge
+                    .label
+                    .as_ref()
+                    .map(|label| moved_label(label, transform, scale, Vec2::ZERO)),
+                arrow: edge
+                    .arrow
+                    .map(|arrow| arrow.map(|point| (transform(point) + end_offset).round())),
+                control: edge.control.map(|control| transform(control).round()),
+                title: edge.title.clone(),
+                ..*edge
+            }
+        })
+        .collect();
+
+    DrawableGraph {
+        vertices: vertices.into_iter().flatten().collect(),
+        edges,
+    }
+}
+
+/// Rounds every position, moving a position whose point is taken to the nearest free point
+/// in the smallest ring of points around it that has one.
+fn snap_positions(positions: &[Vec2]) -> Vec<Vec2> {
+    let mut taken = HashSet::new();
+
+    positions
+        .iter()
+        .map(|&position| {
+            let rounded = position.round();
+            let snapped = (0..)
+                .find_map(|ring| {
+                    ring_points(rounded, ring)
+                        .filter(|point| !taken.contains(&key(*point)))
+                        .min_by(|a, b| {
+                            a.distance_squared(position)
+                                .total_cmp(&b.distance_squared(position))
+                        })
+                })
+                .unwrap();
+
+            taken.insert(key(snapped));
+            snapped
+        })
+        .collect()
+}
+
+/// Integer points at a Chebyshev distance of `ring` from `center`, row by row.
+fn ring_points(center: Vec2, ring: i32) -> impl Iterator<Item = Vec2> {
+    (-ring..=ring).flat_map(move |dy| {
+        (-ring..=ring)
+            .filter(move |dx| dx.abs() == ring || dy.abs() == ring)
+            .map(move |dx| center + vec2(dx as f32, dy as f32))
+    })
+}
+
+fn moved_label(
+    label: &DrawableLabel,
+    transform: impl Fn(Vec2) -> Vec2,
+    scale: f32,
+    offset: Vec2,
+) -> DrawableLabel {
+    DrawableLabel {
+        content: label.content.clone(),
+        position: (transform(label.position) + offset).round(),
+        size: label.size * scale,
+        ..*label
+    }
+}
+
+fn key(point: Vec2) -> (u32, u32) {
+    (point.x.to_bits(), point.y.to_bits())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_integer(point: Vec2) -> bool {
+        point == point.round()
+    }
+
+    #[test]
+    fn test_dense_cluster_snaps_to_distinct_points() {
+        // 25 vertices in a two by two square
+        let positions: Vec<Vec2> = (0..25)
+            .map(|index| vec2(0.37 * (index % 5) as f32, 0.41 * (index / 5) as f32 + 0.05))
+            .collect();
+        let vertices = positions
+            .iter()
+            .enumerate()
+            .map(|(index, &position)| DrawableVertex {
+                index,
+                position,
+                main_radius: 3.4,
+                border_radius: 4.6,
+                ..Default::default()
+            })
+            .rev()
+            .collect();
+        let edges = (0..24)
+            .map(|index| DrawableEdge {
+                start: positions[index],
+                end: positions[(index * 7 + 3) % 25],
+                width: 0.4,
+                ..Default::default()
+            })
+            .collect();
+        let graph = DrawableGraph { vertices, edges };
+
+        let snapped = snap_to_integers(&graph, Vec2::ZERO, 1.0);
+
+        let mut points = HashSet::new();
+        for vertex in &snapped.vertices {
+            assert!(is_integer(vertex.position));
+            assert!(points.insert(key(vertex.position)));
+            assert_eq!((vertex.main_radius, vertex.border_radius), (3.0, 5.0));
+        }
+        assert_eq!(points.len(), 25);
+
+        let snapped_position = |index: usize| {
+            snapped
+                .vertices
+                .iter()
+                .find(|vertex| vertex.index == index)
+                .unwrap()
+                .position
+        };
+        for (index, edge) in snapped.edges.iter().enumerate() {
+            assert_eq!(edge.start, snapped_position(index));
+            assert_eq!(edge.end, snapped_position((index * 7 + 3) % 25));
+            assert_eq!(edge.width, 1.0);
+        }
+
+        // the first vertex keeps its rounded point, and snapping is repeatable
+        assert_eq!(snapped_position(0), vec2(0.0, 0.0));
+        let again = snap_to_integers(&graph, Vec2::ZERO, 1.0);
+        for (a, b) in snapped.vertices.iter().zip(&again.vertices) {
+            assert_eq!(a.position, b.position);
+        }
+    }
+
+    #[test]
+    fn test_edges_follow_vertices_on_the_same_spot() {
+        let position = vec2(20.0, 20.0);
+        let vertices = (0..2)
+            .map(|index| DrawableVertex {
+                index,
+                position,
+                ..Default::default()
+            })
+            .collect();
+        let edges = [(0, 1), (1, 0)]
+            .into_iter()
+            .map(|endpoints| DrawableEdge {
+                start: position,
+                end: position,
+                endpoints: Some(endpoints),
+                ..Default::default()
+            })
+            .collect();
+        let graph = DrawableGraph { vertices, edges };
+
+        let snapped = snap_to_integers(&graph, vec2(5.0, 0.0), 2.0);
+
+        let (first, second) = (snapped.vertices[0].position, snapped.vertices[1].position);
+        assert_eq!(first, vec2(45.0, 40.0));
+        assert_ne!(first, second);
+        assert_eq!(
+            (snapped.edges[0].start, snapped.edges[0].end),
+            (first, second)
+        );
+        assert_eq!(
+            (snapped.edges[1].start, snapped.edges[1].end),
+            (second, first)
+        );
+    }
+
+    #[test]
+    fn test_collisions_move_to_the_nearest_free_point() {
+        let positions = [vec2(10.2, 10.1), vec2(9.9, 10.4), vec2(10.4, 9.8)];
+
+        let snapped = snap_positions(&positions);
+
+        assert_eq!(snapped[0], vec2(10.0, 10.0));
+        assert_eq!(snapped[1], vec2(10.0, 11.0));
+        assert_eq!(snapped[2], vec2(11.0, 10.0));
+    }
+}
@@ -0,0 +1,385 @@
+use macroquad::prelude::*;
+
+use crate::graph_drawer::{DrawableGraph, DrawableLabel, VertexDrawConfig};
+
+const LABEL_WIDTH_FACTOR: f32 = 0.5;
+const LABEL_HEIGHT_FACTOR: f32 = 0.6;
+const LABEL_MARGIN: f32 = 2.0;
+
+const LABEL_OVERLAP_WEIGHT: u32 = 3;
+const VERTEX_OVERLAP_WEIGHT: u32 = 2;
+const EDGE_OVERLAP_WEIGHT: u32 = 1;
+
+/// Candidate placements around a vertex. The zero direction is the default placement.
+pub const CANDIDATE_DIRECTIONS: [Vec2; 9] = [
+    Vec2::ZERO,
+    Vec2::new(0.0, -1.0),
+    Vec2::new(1.0, -1.0),
+    Vec2::new(1.0, 0.0),
+    Vec2::new(1.0, 1.0),
+    Vec2::new(0.0, 1.0),
+    Vec2::new(-1.0, 1.0),
+    Vec2::new(-1.0, 0.0),
+    Vec2::new(-1.0, -1.0),
+];
+
+/// Keeps the chosen candidate per vertex so placement is stable between frames.
+#[derive(Default)]
+pub struct LabelPlacement {
+    previous: Vec<Option<usize>>,
+}
+
+impl LabelPlacement {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Moves the labels of `graph` to the candidates that overlap the least. Labels away from
+    /// their vertex keep [`VertexDrawConfig::outside_label_offset`] from its border and are
+    /// drawn in `outside_color`.
+    pub fn apply(
+        &mut self,
+        graph: &mut DrawableGraph,
+        config: &VertexDrawConfig,
+        outside_color: Color,
+    ) {
+        let vertex_count = graph
+            .vertices
+            .iter()
+            .map(|vertex| vertex.index + 1)
+            .max()
+            .unwrap_or(0);
+        self.previous.resize(vertex_count, None);
+
+        let obstacles = Obstacles {
+            circles: graph
+                .vertices
+                .iter()
+                .map(|vertex| (vertex.index, vertex.position, vertex.border_radius))
+                .collect(),
+            segments: graph
+                .edges
+                .iter()
+                .map(|edge| (edge.start, edge.end))
+                .collect(),
+        };
+
+        let mut placed: Vec<Rect> = Vec::new();
+
+        for vertex in graph.vertices.iter_mut() {
+            let Some(label) = &mut vertex.label else {
+                continue;
+            };
+
+            let candidates: Vec<Rect> = CANDIDATE_DIRECTIONS
+                .iter()
+                .map(|&direction| {
+                    let radius = vertex.border_radius + config.outside_label_offset;
+                    candidate_rect(label, vertex.position, radius, direction)
+                })
+                .collect();
+
+            let scores: Vec<u32> = candidates
+                .iter()
+                .map(|rect| obstacles.score(*rect, vertex.index, &placed))
+                .collect();
+
+            let choice = choose_candidate(&scores, self.previous[vertex.index]);
+            self.previous[vertex.index] = Some(choice);
+
+            let rect = candidates[choice];
+            label.position = vec2(rect.x, rect.y + rect.h);
+            if choice != 0 {
+                label.color = outside_color;
+            }
+            placed.push(rect);
+        }
+    }
+}
+
+struct Obstacles {
+    circles: Vec<(usize, Vec2, f32)>,
+    segments: Vec<(Vec2, Vec2)>,
+}
+
+impl Obstacles {
+    fn score(&self, rect: Rect, own_vertex: usize, placed_labels: &[Rect]) -> u32 {
+        let labels = placed_labels
+            .iter()
+            .filter(|other| other.overlaps(&rect))
+            .count() as u32;
+
+        let vertices = self
+            .circles
+            .iter()
+            .filter(|(index, center, radius)| {
+                *index != own_vertex && circle_intersects_rect(*center, *radius, rect)
+            })
+            .count() as u32;
+
+        let edges = self
+            .segments
+            .iter()
+            .filter(|(start, end)| segment_intersects_rect(*start, *end, rect))
+            .count() as u32;
+
+        LABEL_OVERLAP_WEIGHT * labels
+            + VERTEX_OVERLAP_WEIGHT * vertices
+            + EDGE_OVERLAP_WEIGHT * edges
+    }
+}
+
+/// Picks the candidate to use given the collision score of every candidate.
+/// The previous choice is kept while it is collision free, otherwise the first
+/// collision free candidate is used, falling back to the default placement.
+pub fn choose_candidate(scores: &[u32], previous: Option<usize>) -> usize {
+    if let Some(previous) = previous {
+        if scores.get(previous) == Some(&0) {
+            return previous;
+        }
+    }
+
+    scores.iter().position(|&score| score == 0).unwrap_or(0)
+}
+
+pub fn label_size(label: &DrawableLabel) -> Vec2 {
+    vec2(
+        text_width(&label.content, label.size),
+        label.size * LABEL_HEIGHT_FACTOR,
+    )
+}
+
+/// Width of `content` drawn at `size` in the default font.
+pub fn text_width(content: &str, size: f32) -> f32 {
+    // tests have no window and so no font to measure with, they assume a fixed width font
+    if cfg!(test) {
+        content.chars().count().max(1) as f32 * size * LABEL_WIDTH_FACTOR
+    } else {
+        measure_text(content, None, size as u16, 1.0).width
+    }
+}
+
+/// Largest size up to `max_size` at which `content` is at most `width` wide.
+pub fn fitted_label_size(content: &str, max_size: f32, width: f32) -> f32 {
+    // text widths grow in proportion to the size
+    let full_width = text_width(content, max_size);
+    if full_width <= width {
+        return max_size;
+    }
+
+    max_size * width / full_width
+}
+
+/// Bounding box of a label placed in `direction` around a vertex.
+/// A zero direction keeps the label where compose put it.
+pub fn candidate_rect(label: &DrawableLabel, center: Vec2, radius: f32, direction: Vec2) -> Rect {
+    let size = label_size(label);
+
+    if direction == Vec2::ZERO {
+        return Rect::new(label.position.x, label.position.y - size.y, size.x, size.y);
+    }
+
+    let direction = direction.normalize();
+    let distance = radius + size.max_element() / 2.0 + LABEL_MARGIN;
+    let label_center = center + direction * distance;
+
+    Rect::new(
+        label_center.x - size.x / 2.0,
+        label_center.y - size.y / 2.0,
+        size.x,
+        size.y,
+    )
+}
+
+pub fn circle_intersects_rect(center: Vec2, radius: f32, rect: Rect) -> bool {
+    let closest = vec2(
+        center.x.clamp(rect.x, rect.x + rect.w),
+        center.y.clamp(rect.y, rect.y + rect.h),
+    );
+
+    closest.distance(center) < radius
+}
+
+pub fn segment_intersects_rect(start: Vec2, end: Vec2, rect: Rect) -> bool {
+    if rect.contains(start) || rect.contains(end) {
+        return true;
+    }
+
+    let corners = [
+        rect.point(),
+        vec2(rect.x + rect.w, rect.y),
+        vec2(rect.x + rect.w, rect.y + rect.h),
+        vec2(rect.x, rect.y + rect.h),
+    ];
+
+    (0..4).any(|i| segments_intersect(start, end, corners[i], corners[(i + 1) % 4]))
+}
+
+/// Whether two segments cross at a single interior point.
+pub fn segments_intersect(a: Vec2, b: Vec2, c: Vec2, d: Vec2) -> bool {
+    let cross = |o: Vec2, p: Vec2, q: Vec2| (p - o).perp_dot(q - o);
+
+    let d1 = cross(c, d, a);
+    let d2 = cross(c, d, b);
+    let d3 = cross(a, b, c);
+    let d4 = cross(a, b, d);
+
+    ((d1 > 0.0 && d2 < 0.0) || (d1 < 0.0 && d2 > 0.0))
+        && ((d3 > 0.0 && d4 < 0.0) || (d3 < 0.0 && d4 > 0.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn label_at(position: Vec2) -> DrawableLabel {
+        DrawableLabel {
+            content: "1".to_string(),
+            position,
+            size: 20.0,
+            color: BLACK,
+            halo: None,
+        }
+    }
+
+    #[test]
+    fn test_choose_candidate_prefers_previous() {
+        assert_eq!(choose_candidate(&[0, 0, 0], Some(2)), 2);
+        assert_eq!(choose_candidate(&[0, 0, 1], Some(2)), 0);
+        assert_eq!(choose_candidate(&[3, 1, 0], None), 2);
+    }
+
+    #[test]
+    fn test_choose_candidate_falls_back_to_default() {
+        assert_eq!(choose_candidate(&[4, 1, 2], None), 0);
+        assert_eq!(choose_candidate(&[4, 1, 2], Some(1)), 0);
+    }
+
+    #[test]
+    fn test_candidate_rect_outside_vertex() {
+        let label = label_at(Vec2::ZERO);
+        let rect = candidate_rect(&label, Vec2::ZERO, 10.0, vec2(1.0, 0.0));
+
+        assert!(rect.x > 10.0);
+        assert!(!circle_intersects_rect(Vec2::ZERO, 10.0, rect));
+        assert_eq!(rect.center().y, 0.0);
+    }
+
+    #[test]
+    fn test_circle_intersects_rect() {
+        let rect = Rect::new(0.0, 0.0, 10.0, 10.0);
+
+        assert!(circle_intersects_rect(vec2(5.0, 5.0), 1.0, rect));
+        assert!(circle_intersects_rect(vec2(12.0, 5.0), 3.0, rect));
+        assert!(!circle_intersects_rect(vec2(15.0, 15.0), 3.0, rect));
+    }
+
+    #[test]
+    fn test_segment_intersects_rect() {
+        let rect = Rect::new(0.0, 0.0, 10.0, 10.0);
+
+        assert!(segment_intersects_rect(
+            vec2(-5.0, 5.0),
+            vec2(15.0, 5.0),
+            rect
+        ));
+        assert!(segment_intersects_rect(
+            vec2(5.0, 5.0),
+            vec2(50.0, 50.0),
+            rect
+        ));
+        assert!(!segment_intersects_rect(
+            vec2(-5.0, -5.0),
+            vec2(-5.0, 15.0),
+            rect
+        ));
+        assert!(!segment_intersects_rect(
+            vec2(20.0, 0.0),
+            vec2(30.0, 10.0),
+            rect
+        ));
+    }
+
+    #[test]
+    fn test_overlapping_labels_are_separated() {
+        let mut graph = DrawableGraph {
+            vertices: Vec::new(),
+            edges: Vec::new(),
+        };
+
+        for (index, position) in [vec2(100.0, 100.0), vec2(104.0, 100.0)].iter().enumerate() {
+            graph.vertices.push(crate::graph_drawer::DrawableVertex {
+                index,
+                position: *position,
+                label: Some(label_at(*position)),
+                ..Default::default()
+            });
+        }
+
+        let config = VertexDrawConfig::default();
+        let mut placement = LabelPlacement::new();
+        placement.apply(&mut graph, &config, BLACK);
+
+        let rects: Vec<Rect> = graph
+            .vertices
+            .iter()
+            .map(|vertex| {
+                let label = vertex.label.as_ref().unwrap();
+                let size = label_size(label);
+                Rect::new(label.position.x, label.position.y - size.y, size.x, size.y)
+            })
+            .collect();
+
+        assert!(!rects[0].overlaps(&rects[1]));
+
+        let first_positions: Vec<Vec2> = graph
+            .vertices
+            .iter()
+            .map(|vertex| vertex.label.as_ref().unwrap().position)
+            .collect();
+
+        for vertex in graph.vertices.iter_mut() {
+            vertex.label = Some(label_at(vertex.position));
+        }
+        placement.apply(&mut graph, &config, BLACK);
+
+        for (vertex, position) in graph.vertices.iter().zip(first_positions) {
+            assert_eq!(vertex.label.as_ref().unwrap().position, position);
+        }
+    }
+
+    #[test]
+    fn test_moved_labels_keep_the_outside_offset() {
+        let place = |outside_label_offset: f32| {
+            let mut graph = DrawableGraph {
+                vertices: Vec::new(),
+                edges: Vec::new(),
+            };
+            for (index, position) in [vec2(100.0, 100.0), vec2(104.0, 100.0)].iter().enumerate() {
+                graph.vertices.push(crate::graph_drawer::DrawableVertex {
+                    index,
+                    position: *position,
+                    label: Some(label_at(*position)),
+                    ..Default::default()
+                });
+            }
+            let config = VertexDrawConfig {
+                outside_label_offset,
+                ..Default::default()
+            };
+            LabelPlacement::new().apply(&mut graph, &config, BLACK);
+
+            graph
+                .vertices
+                .iter()
+                .map(|vertex| {
+                    let label = vertex.label.as_ref().unwrap();
+                    let center = label.position + label_size(label) * vec2(0.5, -0.5);
+                    center.distance(vertex.position)
+                })
+                .fold(0.0, f32::max)
+        };
+
+        assert!((place(15.0) - place(0.0) - 15.0).abs() < 0.001);
+    }
+}
@@ -0,0 +1,266 @@
+use macroquad::prelude::*;
+use thiserror::Error;
+
+use crate::{
+    graph_drawer::Drawable,
+    svg_writer::{SVGItem, SVGNumber},
+};
+
+/// Distance kept between a vertex in a lane and the lane boundaries, at most a quarter of the
+/// lane height.
+const LANE_MARGIN: f32 = 20.0;
+const SEPARATOR_WIDTH: f32 = 1.0;
+const SEPARATOR_COLOR: Color = Color::new(0.5, 0.5, 0.5, 0.4);
+
+/// How vertices are kept inside their lane.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LaneMode {
+    /// Vertices can't leave their lane.
+    #[default]
+    Clamp,
+    /// Vertices outside their lane are pulled back a fraction of the way every step.
+    Attract,
+}
+
+impl LaneMode {
+    pub const ALL: [LaneMode; 2] = [LaneMode::Clamp, LaneMode::Attract];
+    pub const NAMES: [&'static str; 2] = ["Clamp", "Attract"];
+
+    pub fn index(&self) -> usize {
+        Self::ALL.iter().position(|mode| mode == self).unwrap()
+    }
+}
+
+/// Horizontal bands of equal height spanning the drawing area. Lanes are numbered from the top,
+/// starting at zero. Vertices assigned to a lane past the last one belong to the last lane.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LaneConfig {
+    /// No lanes when zero.
+    pub count: u32,
+    pub mode: LaneMode,
+    /// Fraction of the distance to its lane a vertex moves per step in [`LaneMode::Attract`].
+    pub attraction: f32,
+    pub draw_separators: bool,
+    pub export_separators: bool,
+}
+
+impl Default for LaneConfig {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            mode: LaneMode::default(),
+            attraction: 0.2,
+            draw_separators: true,
+            export_separators: false,
+        }
+    }
+}
+
+impl LaneConfig {
+    /// Top and bottom of a lane in a drawing area of the given height.
+    pub fn lane_range(&self, lane: usize, height: f32) -> Option<(f32, f32)> {
+        if self.count == 0 {
+            return None;
+        }
+
+        let count = self.count as usize;
+        let lane_height = height / count as f32;
+        let lane = lane.min(count - 1) as f32;
+
+        Some((lane * lane_height, (lane + 1.0) * lane_height))
+    }
+
+    /// Vertical coordinate that moves `y` into its lane, according to the mode.
+    pub fn constrain(&self, y: f32, lane: usize, height: f32) -> f32 {
+        let Some((top, bottom)) = self.lane_range(lane, height) else {
+            return y;
+        };
+
+        let margin = LANE_MARGIN.min((bottom - top) / 4.0);
+        let clamped = y.clamp(top + margin, bottom - margin);
+
+        match self.mode {
+            LaneMode::Clamp => clamped,
+            LaneMode::Attract => y + (clamped - y) * self.attraction,
+        }
+    }
+}
+
+/// Lane of every vertex from its breadth first search depth. Deeper vertices than there are
+/// lanes go to the last lane.
+pub fn lanes_from_depths(depths: &[Option<usize>], count: u32) -> Vec<Option<usize>> {
+    let last = (count as usize).checked_sub(1);
+
+    depths
+        .iter()
+        .map(|&depth| Some(depth?.min(last?)))
+        .collect()
+}
+
+/// Parses one lane per line, in vertex order. A `-` leaves the vertex without a lane. Empty
+/// lines and lines starting with `#` are skipped.
+pub fn parse_lanes(text: &str) -> Result<Vec<Option<usize>>, LaneParseError> {
+    text.lines()
+        .enumerate()
+        .map(|(line_index, line)| (line_index, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(line_index, line)| match line {
+            "-" => Ok(None),
+            _ => line
+                .parse::<usize>()
+                .map(Some)
+                .map_err(|_| LaneParseError::InvalidLane {
+                    line: line_index + 1,
+                }),
+        })
+        .collect()
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum LaneParseError {
+    #[error("Invalid lane on line {line}")]
+    InvalidLane { line: usize },
+}
+
+/// The lines between neighbouring lanes.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LaneSeparators {
+    segments: Vec<(Vec2, Vec2)>,
+}
+
+impl LaneSeparators {
+    pub fn new(config: &LaneConfig, window: Vec2) -> Self {
+        let segments = (1..config.count as usize)
+            .filter_map(|lane| config.lane_range(lane, window.y))
+            .map(|(top, _)| (vec2(0.0, top), vec2(window.x, top)))
+            .collect();
+
+        Self { segments }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+}
+
+impl Drawable for LaneSeparators {
+    fn draw(&self) {
+        for (start, end) in &self.segments {
+            draw_line(
+                start.x,
+                start.y,
+                end.x,
+                end.y,
+                SEPARATOR_WIDTH,
+                SEPARATOR_COLOR,
+            );
+        }
+    }
+}
+
+impl SVGItem for LaneSeparators {
+    fn to_svg_string(&self) -> String {
+        self.segments
+            .iter()
+            .map(|(start, end)| {
+                format!(
+                    r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}" stroke-opacity="{}" stroke-width="{}"/>"#,
+                    SVGNumber(start.x),
+                    SVGNumber(start.y),
+                    SVGNumber(end.x),
+                    SVGNumber(end.y),
+                    SEPARATOR_COLOR.to_svg_string(),
+                    SVGNumber(SEPARATOR_COLOR.a),
+                    SVGNumber(SEPARATOR_WIDTH)
+                ) + "\n"
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lanes(count: u32, mode: LaneMode) -> LaneConfig {
+        LaneConfig {
+            count,
+            mode,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_lane_ranges_follow_height() {
+        let config = lanes(4, LaneMode::Clamp);
+
+        assert_eq!(config.lane_range(0, 400.0), Some((0.0, 100.0)));
+        assert_eq!(config.lane_range(2, 400.0), Some((200.0, 300.0)));
+        assert_eq!(config.lane_range(2, 800.0), Some((400.0, 600.0)));
+        // past the last lane
+        assert_eq!(config.lane_range(9, 400.0), Some((300.0, 400.0)));
+        assert_eq!(lanes(0, LaneMode::Clamp).lane_range(0, 400.0), None);
+    }
+
+    #[test]
+    fn test_constrain() {
+        let clamp = lanes(4, LaneMode::Clamp);
+
+        // lane 1 spans 100 to 200, minus the margin
+        assert_eq!(clamp.constrain(150.0, 1, 400.0), 150.0);
+        assert_eq!(clamp.constrain(10.0, 1, 400.0), 120.0);
+        assert_eq!(clamp.constrain(390.0, 1, 400.0), 180.0);
+        // the margin shrinks for narrow lanes
+        assert_eq!(lanes(40, LaneMode::Clamp).constrain(0.0, 1, 400.0), 12.5);
+
+        let attract = LaneConfig {
+            attraction: 0.5,
+            ..lanes(4, LaneMode::Attract)
+        };
+        assert_eq!(attract.constrain(150.0, 1, 400.0), 150.0);
+        assert_eq!(attract.constrain(20.0, 1, 400.0), 70.0);
+        assert_eq!(attract.constrain(380.0, 1, 400.0), 280.0);
+
+        assert_eq!(lanes(0, LaneMode::Clamp).constrain(390.0, 1, 400.0), 390.0);
+    }
+
+    #[test]
+    fn test_lanes_from_depths() {
+        let depths = [Some(0), Some(1), None, Some(5)];
+
+        assert_eq!(
+            lanes_from_depths(&depths, 3),
+            vec![Some(0), Some(1), None, Some(2)]
+        );
+        assert_eq!(lanes_from_depths(&depths, 0), vec![None; 4]);
+    }
+
+    #[test]
+    fn test_parse_lanes() {
+        assert_eq!(
+            parse_lanes("# lanes\n0\n-\n\n2\n"),
+            Ok(vec![Some(0), None, Some(2)])
+        );
+        assert_eq!(
+            parse_lanes("0\nx\n"),
+            Err(LaneParseError::InvalidLane { line: 2 })
+        );
+    }
+
+    #[test]
+    fn test_separators() {
+        let separators = LaneSeparators::new(&lanes(3, LaneMode::Clamp), vec2(200.0, 300.0));
+
+        assert_eq!(
+            separators.segments,
+            vec![
+                (vec2(0.0, 100.0), vec2(200.0, 100.0)),
+                (vec2(0.0, 200.0), vec2(200.0, 200.0))
+            ]
+        );
+        assert!(separators
+            .to_svg_string()
+            .starts_with(r#"<line x1="0" y1="100" x2="200" y2="100""#));
+        assert!(LaneSeparators::new(&lanes(1, LaneMode::Clamp), vec2(200.0, 300.0)).is_empty());
+    }
+}
@@ -1,101 +1,1080 @@
 use std::time::{Duration, Instant};
 
-use graph::Graph;
-use graph_drawer::{Drawable, DrawableGraph};
-use graph_interface::GraphInterface;
+use annotations::AnnotationLayer;
+use autosave::{AutosavePaths, Autosaver, Snapshot};
+use cli::{read_piped_stdin, resolve_startup_graph, StartupGraph, USAGE};
+use clipboard_image::copy_canvas_to_clipboard;
+use file_names::{file_name_or_default, timestamped_file_name};
+use frame_times::FrameTimes;
+use graph::{Graph, Orientation};
+use graph_drawer::{
+    contrasting_label_color, draw_checkerboard, BackgroundMode, DrawConfig, Drawable,
+    DrawableGraph, LevelOfDetail,
+};
+use graph_interface::{count_crossings, DrawState, GraphInterface, InterfaceEvent};
 use grid::{CircleGrid, SquareGrid};
+use input::{InputEvents, InputTracker, RawInput};
+use label_placement::LabelPlacement;
+use lanes::LaneSeparators;
 use macroquad::prelude::*;
-use ui_manager::{handle_ui, main_screen_width, UIData, UI_WIDTH};
+use png_export::export_png;
+use recorder::Recorder;
+use report::{current_timestamp, format_timestamp, GraphStats};
+use rng::SeededRng;
+use scripted_run::{ScriptCommand, ScriptPlayer};
+use svg_writer::{graph_to_svg_string, SVGExportOptions, SVGWriter};
+use tasks::BackgroundTask;
+use thumbnails::HistoryThumbnails;
+use ui_manager::{
+    export_drawable, export_svg, handle_ui, load_graph, main_screen_width, report_export,
+    subgraph_warnings, UIData,
+};
+use viewport::ViewportTracker;
 
+mod annotations;
+mod autosave;
+mod cli;
+mod clipboard_image;
+mod edge_labels;
+mod file_names;
+mod force;
+mod frame_times;
+mod generators;
 mod graph;
 mod graph_drawer;
 mod graph_interface;
 mod grid;
+mod indexing;
+mod input;
+mod integer_coordinates;
+mod label_placement;
+mod lanes;
+mod palette;
+mod png_export;
+mod positions;
+mod recorder;
+mod report;
+mod rng;
+mod scripted_run;
+mod spatial_index;
 mod svg_writer;
+mod tasks;
+mod thumbnails;
 mod ui_manager;
+mod viewport;
+
+/// Largest per-step displacement at which the force layout counts as converged.
+const CONVERGENCE_THRESHOLD: f32 = 0.05;
+/// How long the layout has to stand still before it is drawn with every edge again.
+const STILL_DURATION: Duration = Duration::from_millis(300);
+const SELECTION_BOX_COLOR: Color = Color::new(0.3, 0.6, 1.0, 0.8);
+/// Change of the vertex size or edge width per scroll step.
+const SIZE_SCROLL_FACTOR: f32 = 1.1;
+/// How long the new size stays shown next to the cursor.
+const SIZE_FEEDBACK_DURATION: Duration = Duration::from_secs(1);
+
+/// Layout steps of a local relax per frame, so that long relaxes keep the window responsive.
+const LOCAL_RELAX_STEPS_PER_FRAME: usize = 20;
+
+/// Vertex positions and edges, what the number of edge crossings depends on.
+type Layout = (Vec<Vec2>, Vec<(usize, usize)>);
+
+/// A local relax that runs a few steps each frame until it converges or runs out of steps.
+struct LocalRelax {
+    mask: Vec<bool>,
+    steps_done: usize,
+    max_steps: usize,
+}
+
+impl LocalRelax {
+    fn progress(&self) -> f32 {
+        self.steps_done as f32 / self.max_steps.max(1) as f32
+    }
+}
 
 pub struct Content {
     graph: Graph,
     embedding: GraphInterface,
     drawable_graph: DrawableGraph,
     ui_data: UIData,
+    recorder: Option<Recorder>,
+    /// Writes every recorded frame.
+    frame_writer: SVGWriter,
+    label_placement: LabelPlacement,
+    composed_config: DrawConfig,
+    composed_detail: LevelOfDetail,
+    /// When the embedding last changed, to tell whether the layout stands still.
+    last_change: Instant,
+    rng: SeededRng,
+    script_player: Option<ScriptPlayer>,
+    orientation_undo: Vec<((usize, usize), Orientation)>,
+    /// Descriptions of the operations performed in this session, oldest first.
+    action_log: Vec<String>,
+    /// Position of the dragged vertex when the drag started.
+    drag_origin: Option<Vec2>,
+    thumbnails: HistoryThumbnails,
+    stats: GraphStats,
+    annotations: AnnotationLayer,
+    autosave_paths: AutosavePaths,
+    /// The graph and the positions as of the start of the session or the last explicit save.
+    saved_state: (Graph, Vec<Vec2>),
+    /// An autosave with unsaved work from an earlier session, until it is restored or dismissed.
+    restorable_autosave: Option<Snapshot>,
+    /// The size last set by scrolling and when.
+    size_feedback: Option<(Instant, String)>,
+    crossing_task: Option<BackgroundTask<usize>>,
+    /// Result of the last crossing count in the background.
+    counted_crossings: Option<usize>,
+    /// Positions and edges the running or last crossing count is for.
+    crossing_layout: Option<Layout>,
+    local_relax: Option<LocalRelax>,
 }
 
 impl Content {
-    fn new() -> Self {
-        let mut graph = Graph::new(4);
-        graph.edges.push((0, 1));
-        graph.edges.push((1, 2));
-        graph.edges.push((2, 3));
-        graph.edges.push((0, 3));
-        graph.edges.push((1, 3));
-
-        let embedding = GraphInterface::new(&graph);
-        let ui_data = UIData::new();
+    fn new(startup_graph: Option<StartupGraph>) -> Self {
+        // the settings panel starts shown
+        Self::with_viewport(
+            startup_graph,
+            vec2(main_screen_width(false), screen_height()),
+        )
+    }
+
+    fn with_viewport(startup_graph: Option<StartupGraph>, viewport: Vec2) -> Self {
+        let mut ui_data = UIData::new();
+
+        let startup_graph = match startup_graph {
+            Some(startup_graph) => {
+                ui_data.graph_name = startup_graph.g6_string;
+                startup_graph.graph
+            }
+            None => sample_graph(),
+        };
+        let mut graph = Graph::new(0);
+        let mut embedding = GraphInterface::with_viewport(&graph, viewport);
+        load_graph(
+            &mut embedding,
+            &mut graph,
+            false,
+            startup_graph,
+            &mut ui_data.messages,
+        );
+
+        let saved_state = (graph.clone(), embedding.expanded_positions());
         let drawable_graph = DrawableGraph::compose(&embedding, &ui_data.draw_config);
+        let composed_config = ui_data.draw_config.clone();
+        let stats = GraphStats::new(&graph);
 
         Self {
             graph,
             embedding,
             drawable_graph,
             ui_data,
+            recorder: None,
+            frame_writer: SVGWriter::new(),
+            label_placement: LabelPlacement::new(),
+            composed_config,
+            composed_detail: LevelOfDetail::Full,
+            last_change: Instant::now(),
+            rng: SeededRng::new(0),
+            script_player: None,
+            orientation_undo: Vec::new(),
+            action_log: Vec::new(),
+            drag_origin: None,
+            thumbnails: HistoryThumbnails::new(),
+            stats,
+            annotations: AnnotationLayer::new(),
+            autosave_paths: AutosavePaths::default_location(),
+            saved_state,
+            restorable_autosave: None,
+            size_feedback: None,
+            crossing_task: None,
+            counted_crossings: None,
+            crossing_layout: None,
+            local_relax: None,
+        }
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            timestamp: current_timestamp(),
+            graph: self.graph.clone(),
+            positions: self.embedding.expanded_positions(),
+        }
+    }
+
+    /// Whether the graph or the layout changed since the start or the last explicit save.
+    fn has_unsaved_work(&self) -> bool {
+        let (graph, positions) = &self.saved_state;
+        *graph != self.graph || *positions != self.embedding.expanded_positions()
+    }
+
+    fn restore_snapshot(&mut self, snapshot: Snapshot) {
+        load_graph(
+            &mut self.embedding,
+            &mut self.graph,
+            false,
+            snapshot.graph,
+            &mut self.ui_data.messages,
+        );
+        self.embedding.set_positions(&snapshot.positions);
+        self.action_log.push(format!(
+            "Restored autosave from {}",
+            format_timestamp(snapshot.timestamp)
+        ));
+    }
+
+    fn execute_script_command(&mut self, command: ScriptCommand) {
+        match command {
+            ScriptCommand::Graph { graph, g6_string } => {
+                load_graph(
+                    &mut self.embedding,
+                    &mut self.graph,
+                    false,
+                    graph,
+                    &mut self.ui_data.messages,
+                );
+                self.action_log
+                    .push(format!("Loaded graph {} from script", g6_string));
+                self.ui_data.graph_name = g6_string;
+            }
+            ScriptCommand::Highlight(graph) => {
+                self.action_log.push(format!(
+                    "Highlighted {} edges from script",
+                    graph.edges.len()
+                ));
+                self.ui_data.messages.extend(subgraph_warnings(
+                    &graph.subgraph_mismatch(&self.graph),
+                    self.ui_data.draw_config.indexing,
+                ));
+                self.embedding
+                    .set_edge_highlighting_and_add_to_history(graph);
+            }
+            ScriptCommand::ColorVertices(vertices) => {
+                self.action_log
+                    .push(format!("Colored {} vertices from script", vertices.len()));
+                self.embedding.set_vertex_highlighting(&vertices);
+            }
+            ScriptCommand::Layout(steps) => {
+                let config = &self.ui_data.force_config;
+                self.embedding.start_force(config);
+                let movement = self.embedding.step_force(&self.graph, config, steps);
+                self.action_log.push(format!(
+                    "Ran {} layout steps from script, last moved {:.1}",
+                    steps, movement
+                ));
+            }
+            ScriptCommand::Pause(_) => {}
+        }
+    }
+
+    /// Advances the orientation of an edge in both the graph and the embedding.
+    fn cycle_edge_orientation(&mut self, edge: usize) {
+        if let Some(((a, b), previous)) = self.embedding.cycle_edge_orientation(edge) {
+            self.graph.set_orientation(a, b, previous.next());
+            self.orientation_undo.push(((a, b), previous));
+
+            let entry = format!("Set edge {} to {:?}", self.edge_name(a, b), previous.next());
+            self.action_log.push(entry);
+        }
+    }
+
+    /// Reverts the last orientation change of an edge that still exists.
+    fn undo_orientation_change(&mut self) {
+        while let Some(((a, b), previous)) = self.orientation_undo.pop() {
+            if self.embedding.set_edge_orientation(a, b, previous) {
+                self.graph.set_orientation(a, b, previous);
+
+                let entry = format!("Reverted edge {} to {:?}", self.edge_name(a, b), previous);
+                self.action_log.push(entry);
+                return;
+            }
+        }
+    }
+
+    /// Starts relaxing the box selection, or the selected vertex, while every other vertex
+    /// stays put. The steps run over the next frames in `advance_local_relax`.
+    fn relax_selection(&mut self) {
+        let Some(mask) = self.embedding.relax_mask() else {
+            self.ui_data
+                .messages
+                .push("Select vertices to relax first".to_string());
+            return;
+        };
+
+        self.local_relax = Some(LocalRelax {
+            mask,
+            steps_done: 0,
+            max_steps: self.ui_data.local_relax_steps as usize,
+        });
+    }
+
+    /// Runs the next steps of the local relax, if one is running.
+    fn advance_local_relax(&mut self) {
+        let Some(relax) = &mut self.local_relax else {
+            return;
+        };
+        if relax.mask.len() != self.graph.vertices {
+            // the graph changed under the relax
+            self.local_relax = None;
+            return;
+        }
+
+        let batch = LOCAL_RELAX_STEPS_PER_FRAME.min(relax.max_steps - relax.steps_done);
+        let steps = self.embedding.relax_locally(
+            &self.graph,
+            &self.ui_data.force_config,
+            &relax.mask,
+            batch,
+            CONVERGENCE_THRESHOLD,
+        );
+        relax.steps_done += steps;
+
+        let converged = steps < batch;
+        if converged || relax.steps_done >= relax.max_steps {
+            let count = relax.mask.iter().filter(|&&in_mask| in_mask).count();
+            let entry = format!("Relaxed {} vertices in {} steps", count, relax.steps_done);
+            self.action_log.push(entry);
+            self.local_relax = None;
+        }
+    }
+
+    /// Exports to a new file named after the current time, in the configured directory.
+    fn export_timestamped_svg(&mut self) {
+        let path =
+            timestamped_file_name(&self.ui_data.export_directory, current_timestamp(), "svg");
+
+        let result = export_svg(&mut self.embedding, &self.annotations, &self.ui_data, &path);
+        report_export(
+            &mut self.ui_data.messages,
+            &mut self.action_log,
+            "SVG",
+            result,
+        );
+    }
+
+    /// Places the vertices on the rings of the grid by their distance from `center`. When the
+    /// rings have to be closer together to fit the window, the grid and the snapping follow them.
+    fn radial_layout(&mut self, center: usize, grid: &mut CircleGrid) {
+        let depths = self.graph.depths(center);
+        let spacing = grid.ring_spacing(&depths);
+        if spacing < self.ui_data.grid_size {
+            self.ui_data.grid_size = spacing;
+            self.ui_data.snap_size = spacing;
+            grid.set_r_delta(spacing);
+            grid.set_snap_r_delta(spacing);
+        }
+        self.embedding
+            .set_positions(&grid.radial_positions(&depths));
+        self.action_log.push(format!(
+            "Radial layout from {}",
+            self.ui_data.draw_config.indexing.display(center)
+        ));
+    }
+
+    /// Counts the crossings of the current layout on another thread, replacing a count that is
+    /// still running.
+    fn count_crossings_in_background(&mut self) {
+        let positions = self.embedding.positions();
+        let edges = self.graph.edges.clone();
+        self.counted_crossings = None;
+        self.crossing_layout = Some((positions.clone(), edges.clone()));
+
+        self.crossing_task = Some(BackgroundTask::spawn("Crossing count", move |context| {
+            count_crossings(&positions, &edges, |fraction| {
+                context.report_progress(fraction);
+                !context.is_cancelled()
+            })
+        }));
+    }
+
+    /// Drops the crossing count, and cancels a running one, once the layout or the graph
+    /// changed.
+    fn discard_stale_crossing_count(&mut self) {
+        let Some((positions, edges)) = &self.crossing_layout else {
+            return;
+        };
+        if *edges == self.graph.edges && *positions == self.embedding.positions() {
+            return;
         }
+
+        self.crossing_layout = None;
+        self.counted_crossings = None;
+        self.crossing_task = None;
+    }
+
+    fn poll_background_tasks(&mut self) {
+        let Some(result) = self.crossing_task.as_mut().and_then(|task| task.poll()) else {
+            return;
+        };
+        self.crossing_task = None;
+
+        match result {
+            Ok(crossings) => {
+                self.counted_crossings = Some(crossings);
+                self.action_log
+                    .push(format!("Counted {} edge crossings", crossings));
+            }
+            Err(error) => self.ui_data.messages.push(error.to_string()),
+        }
+    }
+
+    /// Tab toggles the settings panel, D cycles the orientation of the hovered edge, Ctrl+Z
+    /// undoes that and Ctrl+E exports a timestamped SVG.
+    fn handle_hotkeys(&mut self) {
+        if is_key_pressed(KeyCode::Tab) {
+            self.ui_data.toggle_panel();
+        }
+        if is_key_pressed(KeyCode::D) {
+            if let Some(edge) = self.embedding.hovered_edge {
+                self.cycle_edge_orientation(edge);
+            }
+        }
+        let control = is_key_down(KeyCode::LeftControl) || is_key_down(KeyCode::RightControl);
+        if control && is_key_pressed(KeyCode::Z) {
+            self.undo_orientation_change();
+        }
+        if control && is_key_pressed(KeyCode::E) {
+            self.export_timestamped_svg();
+        }
+    }
+
+    /// Ctrl+scroll scales the vertices, Alt+scroll the edges.
+    fn handle_size_scroll(&mut self, input: &InputEvents) {
+        if input.wheel == 0.0 || input.position.x > main_screen_width(self.ui_data.panel_hidden) {
+            return;
+        }
+
+        let factor = SIZE_SCROLL_FACTOR.powf(input.wheel.signum());
+        let config = &mut self.ui_data.draw_config;
+        let feedback = if input.ctrl {
+            config.vertex_config.scale_size(factor);
+            format!(
+                "vertex size {:.1}, border {:.1}",
+                config.vertex_config.main_size, config.vertex_config.border_size
+            )
+        } else if input.alt {
+            config.edge_config.scale_width(factor);
+            format!("edge width {:.1}", config.edge_config.width)
+        } else {
+            return;
+        };
+
+        self.size_feedback = Some((Instant::now(), feedback));
+    }
+
+    /// Exports the canvas in full detail at the configured scale.
+    fn export_scaled_png(&mut self) {
+        let data = &self.ui_data;
+        let path = file_name_or_default(&data.png_file_name, &data.graph_name, "png");
+        let drawable = export_drawable(&mut self.embedding, data);
+
+        let result = export_png(
+            &drawable,
+            &self.annotations,
+            data.canvas_size(),
+            data.png_scale,
+            data.draw_config.export_background(),
+            &path,
+            data.export_create_directories,
+        );
+        report_export(
+            &mut self.ui_data.messages,
+            &mut self.action_log,
+            "PNG",
+            result,
+        );
+    }
+
+    /// Copies the canvas to the clipboard as it would be exported, without the overlays.
+    fn copy_image(&mut self) {
+        let data = &self.ui_data;
+        let drawable = export_drawable(&mut self.embedding, data);
+
+        let result = copy_canvas_to_clipboard(
+            &drawable,
+            &self.annotations,
+            data.canvas_size(),
+            data.draw_config.export_background(),
+        );
+        match result {
+            Ok(()) => self
+                .action_log
+                .push("Copied image to clipboard".to_string()),
+            Err(error) => self.ui_data.messages.push(error.to_string()),
+        }
+    }
+
+    /// Logs the events that change the graph drawing: the draw state a click cycles to and
+    /// drags that moved their vertex.
+    fn log_interface_event(&mut self, event: InterfaceEvent) {
+        let indexing = self.ui_data.draw_config.indexing;
+        let entry = match event {
+            InterfaceEvent::VertexClicked(vertex) => {
+                let state = self.embedding.vertex_properties[vertex].draw_state;
+                format!(
+                    "Set vertex {} to {}",
+                    indexing.display(vertex),
+                    DrawState::NAMES[state.index()]
+                )
+            }
+            InterfaceEvent::EdgeClicked(edge) => {
+                let properties = &self.embedding.edge_properties[edge];
+                let (a, b) = properties.vertices;
+                let state = properties.draw_state;
+                format!(
+                    "Set edge {} to {}",
+                    self.edge_name(a, b),
+                    DrawState::NAMES[state.index()]
+                )
+            }
+            InterfaceEvent::DragStarted(vertex) => {
+                self.drag_origin = Some(self.embedding.get_position(vertex));
+                return;
+            }
+            InterfaceEvent::DragEnded(vertex) => {
+                let origin = self.drag_origin.take();
+                if origin == Some(self.embedding.get_position(vertex)) {
+                    return;
+                }
+                format!("Moved vertex {}", indexing.display(vertex))
+            }
+        };
+        self.action_log.push(entry);
+    }
+
+    fn edge_name(&self, a: usize, b: usize) -> String {
+        let indexing = self.ui_data.draw_config.indexing;
+        format!("{} - {}", indexing.display(a), indexing.display(b))
+    }
+
+    /// Recomposes the drawable graph only when the embedding, the draw config or the level of
+    /// detail changed.
+    fn recompose_if_needed(&mut self) {
+        self.embedding.follow_meta_vertices();
+        if self.embedding.is_dirty() {
+            self.last_change = Instant::now();
+            self.discard_stale_crossing_count();
+        }
+
+        let config = &self.ui_data.draw_config;
+        let still = self.last_change.elapsed() >= STILL_DURATION;
+        let detail = config.level_of_detail(self.graph.edges.len(), still);
+
+        if !self.embedding.is_dirty()
+            && self.composed_config == *config
+            && self.composed_detail == detail
+        {
+            return;
+        }
+
+        self.drawable_graph = DrawableGraph::compose_with_detail(&self.embedding, config, detail);
+        self.composed_detail = detail;
+
+        if self.ui_data.draw_config.vertex_config.avoid_label_overlap {
+            self.label_placement.apply(
+                &mut self.drawable_graph,
+                &config.vertex_config,
+                config.outside_label_color(),
+            );
+        }
+
+        self.embedding.clear_dirty();
+        self.composed_config = self.ui_data.draw_config.clone();
+    }
+}
+
+fn sample_graph() -> Graph {
+    let mut graph = Graph::new(4);
+    graph.edges.push((0, 1));
+    graph.edges.push((1, 2));
+    graph.edges.push((2, 3));
+    graph.edges.push((0, 3));
+    graph.edges.push((1, 3));
+    graph
+}
+
+/// Title of the window, named after the graph given on startup.
+fn window_title(startup_graph: Option<&StartupGraph>) -> String {
+    match startup_graph {
+        Some(startup_graph) => format!("graphdrs - {}", startup_graph.g6_string),
+        None => "graphdrs".to_string(),
     }
 }
 
-#[macroquad::main("graphdrs")]
-async fn main() {
+fn main() {
+    // resolved before the window opens, so that the window can be titled after the graph
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let startup_graph = resolve_startup_graph(&args, read_piped_stdin()).unwrap_or_else(|error| {
+        eprintln!("{}\n{}", error, USAGE);
+        None
+    });
+
+    let conf = Conf {
+        window_title: window_title(startup_graph.as_ref()),
+        high_dpi: true,
+        ..Default::default()
+    };
+    macroquad::Window::from_config(conf, run(startup_graph));
+}
+
+async fn run(startup_graph: Option<StartupGraph>) {
     macroquad::telemetry::disable();
 
-    let mut content = Content::new();
+    let mut content = Content::new(startup_graph);
+    content.restorable_autosave = content.autosave_paths.restorable();
+    if let Some(snapshot) = &content.restorable_autosave {
+        content.ui_data.messages.push(format!(
+            "Found unsaved work from {}, see the top of the settings",
+            format_timestamp(snapshot.timestamp)
+        ));
+    }
+    let mut autosaver = Autosaver::start(content.autosave_paths.snapshot.clone());
+    prevent_quit();
+    let viewport = content.ui_data.canvas_size();
+    let mut viewport_tracker = ViewportTracker::new(viewport);
     let mut square_grid = SquareGrid::new(30.0, 30.0);
     square_grid.make_square();
-    let mut circular_grid = CircleGrid::new(30.0, vec2(400.0, 400.0));
+    square_grid.set_offsets_from_window(viewport);
+    let mut circular_grid = CircleGrid::new(30.0, viewport);
+    let mut frame_times = FrameTimes::new(60);
+    let mut input_tracker = InputTracker::new();
+    let start_time = Instant::now();
 
     loop {
+        if is_quit_requested() {
+            // quitting without changes leaves nothing to restore on the next start
+            if content.has_unsaved_work() {
+                autosaver.save(content.snapshot());
+            }
+            autosaver.stop();
+            for error in autosaver.drain_errors() {
+                eprintln!("{}", error);
+            }
+            break;
+        }
+
         let frame_start = Instant::now();
         clear_background(content.ui_data.draw_config.background_color);
+        if content.ui_data.draw_config.background_mode == BackgroundMode::Checkerboard {
+            draw_checkerboard(content.ui_data.canvas_size());
+        }
 
-        draw_text(
-            &get_fps().to_string(),
-            main_screen_width() - 40.0,
-            20.0,
-            30.0,
-            WHITE,
-        );
+        if let Some(change) = viewport_tracker.update(content.ui_data.canvas_size()) {
+            content
+                .embedding
+                .handle_viewport_change(change, content.ui_data.resize_mode);
+            square_grid.set_offsets_from_window(change.new);
+            circular_grid.set_from_window(change.new);
+            content.ui_data.handle_viewport_change();
+        }
 
-        content.embedding.handle_mouse_input();
+        content.poll_background_tasks();
+        content.advance_local_relax();
+        let input = input_tracker.update(RawInput::poll(), &content.ui_data.input_config);
+        content.handle_size_scroll(&input);
+        content
+            .embedding
+            .resolve_vertex_radii(&content.ui_data.draw_config.vertex_config);
+        content
+            .embedding
+            .resolve_edge_widths(&content.ui_data.draw_config.edge_config);
+        content
+            .embedding
+            .set_edge_curvature(content.ui_data.draw_config.edge_config.curvature);
+        // the canvas is drawn unscaled
+        content
+            .embedding
+            .set_hit_tolerance(content.ui_data.input_config.hit_tolerance, None);
+        content
+            .embedding
+            .set_edge_hover(content.ui_data.input_config.hover_edges);
+        // annotations take over the mouse while they are edited
+        if content.annotations.editing {
+            content
+                .annotations
+                .handle_mouse_input(&input, content.ui_data.canvas_size().x);
+        } else {
+            content.embedding.handle_mouse_input(&input);
+        }
+        for event in content.embedding.drain_events() {
+            content.log_interface_event(event);
+        }
+        content.embedding.handle_keyboard_input(&input);
+
+        content.ui_data.track_keyboard_focus();
+        if content.ui_data.hotkeys_enabled() {
+            content.handle_hotkeys();
+        }
 
         if content.ui_data.apply_force {
-            content.embedding.apply_force(&content.graph);
+            content
+                .embedding
+                .apply_force(&content.graph, &content.ui_data.force_config, None);
+
+            let data = &mut content.ui_data;
+            if data.auto_shake
+                && data.auto_shake_restarts < data.auto_shake_max_restarts
+                && content.embedding.layout_converged(CONVERGENCE_THRESHOLD)
+                && content.embedding.edge_crossings() > data.auto_shake_crossing_target as usize
+            {
+                content
+                    .embedding
+                    .shake(&content.graph, data.shake_magnitude, &mut content.rng);
+                data.auto_shake_restarts += 1;
+                content.action_log.push(format!(
+                    "Shook stuck layout (restart {})",
+                    data.auto_shake_restarts
+                ));
+            }
+
+            if let Some(recorder) = &mut content.recorder {
+                recorder.record_step(|| {
+                    let positions = content.embedding.positions();
+                    let frame = DrawableGraph::from_positions(
+                        &content.graph,
+                        &positions,
+                        &content.ui_data.draw_config,
+                    );
+                    let lanes = &content.ui_data.force_config.lanes;
+                    let options = SVGExportOptions {
+                        title: content.ui_data.graph_name.clone(),
+                        lanes: lanes.export_separators.then(|| lanes.clone()),
+                        background: content.ui_data.draw_config.export_background(),
+                        decimals: content.ui_data.export_decimals as usize,
+                        ..Default::default()
+                    };
+                    graph_to_svg_string(
+                        &mut content.frame_writer,
+                        &frame,
+                        &content.annotations,
+                        content.ui_data.canvas_size(),
+                        &options,
+                    )
+                    .ok()
+                });
+            }
+        }
+
+        if let Some(recorder) = &content.recorder {
+            content.ui_data.messages.extend(recorder.drain_errors());
         }
 
+        let autosave_interval = Duration::from_secs(content.ui_data.autosave_minutes as u64 * 60);
+        autosaver.poll(start_time.elapsed(), autosave_interval, || {
+            content.snapshot()
+        });
+        content.ui_data.messages.extend(autosaver.drain_errors());
+
         if content.ui_data.align_to_square_grid {
             square_grid.set_deltas_square(content.ui_data.grid_size);
-            square_grid.set_offsets_from_window(vec2(screen_width() - UI_WIDTH, screen_height()));
+            square_grid.set_snap_delta(content.ui_data.snap_size);
             square_grid.draw();
             content.embedding.align_to_square_grid(&square_grid);
         }
 
         if content.ui_data.align_to_circular_grid {
             circular_grid.set_r_delta(content.ui_data.grid_size);
-            circular_grid.set_from_window(vec2(screen_width() - UI_WIDTH, screen_height()));
+            circular_grid.set_snap_r_delta(content.ui_data.snap_size);
+            if let Some(center) = content.ui_data.radial_layout_center.take() {
+                content.radial_layout(center, &mut circular_grid);
+            }
             circular_grid.draw();
             content.embedding.align_to_circular_grid(&circular_grid);
         }
 
+        if content.ui_data.align_edge_angles {
+            content
+                .embedding
+                .align_edges_to_angles(content.ui_data.edge_angle_strength);
+        }
+
+        content.stats.update(&content.graph);
+        content.thumbnails.update(
+            &content.graph,
+            &content.embedding,
+            &content.ui_data.draw_config,
+        );
         handle_ui(&mut content);
 
-        content.drawable_graph =
-            DrawableGraph::compose(&content.embedding, &content.ui_data.draw_config);
+        let due_commands = match &mut content.script_player {
+            Some(player) => player.poll(start_time.elapsed()),
+            None => Vec::new(),
+        };
+        for command in due_commands {
+            content.execute_script_command(command);
+        }
+
+        let lanes = &content.ui_data.force_config.lanes;
+        if lanes.draw_separators {
+            LaneSeparators::new(lanes, content.ui_data.canvas_size()).draw();
+        }
 
+        content.recompose_if_needed();
         content.drawable_graph.draw();
+        content.annotations.draw();
+        if let Some(rect) = content.embedding.selection_box() {
+            draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 1.0, SELECTION_BOX_COLOR);
+        }
+        if let Some(lasso) = content.embedding.lasso() {
+            for (start, end) in lasso.iter().zip(lasso.iter().cycle().skip(1)) {
+                draw_line(start.x, start.y, end.x, end.y, 1.0, SELECTION_BOX_COLOR);
+            }
+        }
+        let name_color = contrasting_label_color(content.ui_data.draw_config.background_color);
+        draw_text(&content.ui_data.graph_name, 10.0, 20.0, 20.0, name_color);
+        if let Some((time, feedback)) = &content.size_feedback {
+            if time.elapsed() < SIZE_FEEDBACK_DURATION {
+                let (x, y) = mouse_position();
+                draw_text(feedback, x + 16.0, y - 8.0, 20.0, BLACK);
+            }
+        }
+
+        if content.ui_data.show_fps {
+            frame_times.draw(content.ui_data.canvas_size().x);
+        }
 
         let frame_end = Instant::now();
+        frame_times.push(frame_end - frame_start);
 
-        #[cfg(any(unix))]
-        std::thread::sleep(Duration::from_micros(16666) - (frame_end - frame_start));
+        // the offscreen renders can take several frames, so they are left out of the frame times
+        if std::mem::take(&mut content.ui_data.png_export_requested) {
+            content.export_scaled_png();
+        }
+        if std::mem::take(&mut content.ui_data.copy_image_requested) {
+            content.copy_image();
+        }
+
+        #[cfg(unix)]
+        std::thread::sleep(Duration::from_micros(16666).saturating_sub(frame_start.elapsed()));
 
         next_frame().await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui_manager::EMPTY_GRAPH_MESSAGE;
+
+    #[test]
+    fn test_window_is_titled_after_the_startup_graph() {
+        let startup_graph = StartupGraph {
+            graph: Graph::new(4),
+            g6_string: "C?".to_string(),
+        };
+
+        assert_eq!(window_title(Some(&startup_graph)), "graphdrs - C?");
+        assert_eq!(window_title(None), "graphdrs");
+    }
+
+    #[test]
+    fn test_snapping_keeps_the_rings_of_a_fitted_radial_layout() {
+        let viewport = vec2(400.0, 300.0);
+        let mut content = Content::with_viewport(None, viewport);
+        load_graph(
+            &mut content.embedding,
+            &mut content.graph,
+            false,
+            Graph::path(20),
+            &mut content.ui_data.messages,
+        );
+        let mut grid = CircleGrid::new(content.ui_data.grid_size, viewport);
+
+        content.radial_layout(0, &mut grid);
+        for _ in 0..100 {
+            content.embedding.align_to_circular_grid(&grid);
+        }
+
+        let spacing = content.ui_data.grid_size;
+        assert!(spacing < 30.0);
+        assert_eq!(content.ui_data.snap_size, spacing);
+        for vertex in 0..20 {
+            let distance = content.embedding.get_position(vertex).distance(grid.center);
+            assert!(
+                (distance - vertex as f32 * spacing).abs() < 0.5,
+                "vertex {}",
+                vertex
+            );
+        }
+    }
+
+    #[test]
+    fn test_local_relax_runs_over_several_frames() {
+        let mut content = Content::with_viewport(None, vec2(400.0, 400.0));
+        content.ui_data.local_relax_steps = 2000;
+        content.embedding.selected_vertices = vec![0];
+        let position = content.embedding.get_position(0);
+        content
+            .embedding
+            .set_position(0, position + vec2(150.0, 0.0));
+
+        content.relax_selection();
+        assert!(content.local_relax.is_some());
+        assert!(content.action_log.is_empty());
+
+        let mut frames = 0;
+        while content.local_relax.is_some() {
+            content.advance_local_relax();
+            frames += 1;
+        }
+
+        assert!(frames > 1);
+        assert!(content.action_log[0].starts_with("Relaxed 1 vertices in "));
+    }
+
+    #[test]
+    fn test_only_changes_from_the_canvas_are_logged() {
+        let mut content = Content::with_viewport(None, vec2(400.0, 400.0));
+        let name = content.ui_data.draw_config.indexing.display(0);
+
+        // a drag that ends where it started
+        content.log_interface_event(InterfaceEvent::DragStarted(0));
+        content.log_interface_event(InterfaceEvent::DragEnded(0));
+        assert!(content.action_log.is_empty());
+
+        content.log_interface_event(InterfaceEvent::DragStarted(0));
+        let position = content.embedding.get_position(0);
+        content
+            .embedding
+            .set_position(0, position + vec2(10.0, 0.0));
+        content.log_interface_event(InterfaceEvent::DragEnded(0));
+
+        content.embedding.vertex_properties[0].cycle_drawstate();
+        content.log_interface_event(InterfaceEvent::VertexClicked(0));
+
+        assert_eq!(
+            content.action_log,
+            vec![
+                format!("Moved vertex {}", name),
+                format!("Set vertex {} to Highlighted", name),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_restoring_an_autosave_counts_as_unsaved_work() {
+        let mut content = Content::with_viewport(None, vec2(400.0, 400.0));
+        assert!(!content.has_unsaved_work());
+
+        let mut graph = Graph::path(3);
+        graph.edges.push((1, 0));
+        content.restore_snapshot(Snapshot {
+            timestamp: 0,
+            graph,
+            positions: vec![vec2(10.0, 10.0), vec2(20.0, 10.0), vec2(30.0, 10.0)],
+        });
+
+        // restored like any other loaded graph
+        assert_eq!(content.graph.edges, vec![(0, 1), (1, 2)]);
+        assert!(content
+            .ui_data
+            .messages
+            .contains(&"Removed 1 duplicate edges".to_string()));
+        assert_eq!(content.embedding.get_position(2), vec2(30.0, 10.0));
+        assert!(content.has_unsaved_work());
+    }
+
+    #[test]
+    fn test_crossing_count_is_dropped_when_the_layout_changes() {
+        let mut content = Content::with_viewport(None, vec2(400.0, 400.0));
+        content.count_crossings_in_background();
+        let start = Instant::now();
+        while content.crossing_task.is_some() {
+            assert!(start.elapsed() < Duration::from_secs(10));
+            content.poll_background_tasks();
+        }
+        assert!(content.counted_crossings.is_some());
+
+        // redrawing the same layout keeps the count
+        content.embedding.mark_dirty();
+        content.recompose_if_needed();
+        assert!(content.counted_crossings.is_some());
+
+        content.embedding.set_position(0, vec2(1.0, 1.0));
+        content.recompose_if_needed();
+        assert_eq!(content.counted_crossings, None);
+    }
+
+    #[test]
+    fn test_operations_are_logged() {
+        let mut content = Content::with_viewport(None, vec2(400.0, 400.0));
+        let mut graph = Graph::new(3);
+        graph.edges.push((0, 2));
+
+        content.execute_script_command(ScriptCommand::Graph {
+            graph,
+            g6_string: "Bg".to_string(),
+        });
+        content.cycle_edge_orientation(0);
+        content.undo_orientation_change();
+
+        assert_eq!(
+            content.action_log,
+            vec![
+                "Loaded graph Bg from script",
+                "Set edge 1 - 3 to Forward",
+                "Reverted edge 1 - 3 to Undirected",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_empty_and_single_vertex_graphs() {
+        for g6_string in ["?", "@"] {
+            let graph = graph::parse_graph6_string(g6_string).unwrap();
+            let vertices = graph.vertices;
+            let mut content = Content::with_viewport(
+                Some(StartupGraph {
+                    graph,
+                    g6_string: g6_string.to_string(),
+                }),
+                vec2(400.0, 400.0),
+            );
+            assert_eq!(content.embedding.positions().len(), vertices);
+            assert_eq!(
+                content.ui_data.messages,
+                if vertices == 0 {
+                    vec![EMPTY_GRAPH_MESSAGE.to_string()]
+                } else {
+                    Vec::new()
+                }
+            );
+            // a single vertex starts in the middle of the viewport
+            if vertices == 1 {
+                assert_eq!(content.embedding.get_position(0), vec2(200.0, 200.0));
+            }
+
+            content.execute_script_command(ScriptCommand::Layout(20));
+            let fitted = positions::fit_to_rect(
+                &content.embedding.positions(),
+                Rect::new(0.0, 0.0, 400.0, 400.0),
+                10.0,
+            );
+            content.embedding.set_positions(&fitted);
+            content
+                .embedding
+                .shake(&content.graph, 5.0, &mut content.rng);
+            for position in content.embedding.positions() {
+                assert!(position.is_finite(), "{} at {}", g6_string, position);
+            }
+            assert_eq!(content.embedding.edge_crossings(), 0);
+
+            content.recompose_if_needed();
+            assert_eq!(content.drawable_graph.vertices.len(), vertices);
+            let mut writer = SVGWriter::new();
+            writer.write_header(400.0, 400.0).unwrap();
+            writer.add_item(&content.drawable_graph).unwrap();
+            writer.finalise().unwrap();
+            assert!(!writer.take_string().unwrap().contains("NaN"));
+
+            let report = report::graph_report(
+                &content.graph,
+                &content.stats,
+                g6_string,
+                content.ui_data.draw_config.indexing,
+                0,
+            );
+            assert!(!report.contains("NaN"), "{}", report);
+        }
+    }
+}
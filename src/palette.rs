@@ -0,0 +1,143 @@
+use macroquad::prelude::*;
+
+use crate::graph_drawer::DrawConfig;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Palette {
+    #[default]
+    Default,
+    /// Okabe–Ito, distinguishable with the common forms of color blindness.
+    ColorBlindSafe,
+    Grayscale,
+}
+
+const DEFAULT_COLORS: [Color; 8] = [LIME, MAROON, SKYBLUE, ORANGE, PURPLE, GOLD, PINK, DARKGREEN];
+
+/// Okabe–Ito without black, which would not vary in brightness.
+const OKABE_ITO_COLORS: [Color; 7] = [
+    Color::new(0.902, 0.624, 0.000, 1.0),
+    Color::new(0.337, 0.706, 0.914, 1.0),
+    Color::new(0.000, 0.620, 0.451, 1.0),
+    Color::new(0.941, 0.894, 0.259, 1.0),
+    Color::new(0.000, 0.447, 0.698, 1.0),
+    Color::new(0.835, 0.369, 0.000, 1.0),
+    Color::new(0.800, 0.475, 0.655, 1.0),
+];
+
+const GRAYSCALE_COLORS: [Color; 4] = [
+    Color::new(0.15, 0.15, 0.15, 1.0),
+    Color::new(0.45, 0.45, 0.45, 1.0),
+    Color::new(0.70, 0.70, 0.70, 1.0),
+    Color::new(0.95, 0.95, 0.95, 1.0),
+];
+
+impl Palette {
+    pub const ALL: [Palette; 3] = [
+        Palette::Default,
+        Palette::ColorBlindSafe,
+        Palette::Grayscale,
+    ];
+    pub const NAMES: [&'static str; 3] = ["Default", "Color-blind safe", "Grayscale"];
+
+    pub fn index(&self) -> usize {
+        Self::ALL
+            .iter()
+            .position(|palette| palette == self)
+            .unwrap()
+    }
+
+    fn base_colors(&self) -> &'static [Color] {
+        match self {
+            Palette::Default => &DEFAULT_COLORS,
+            Palette::ColorBlindSafe => &OKABE_ITO_COLORS,
+            Palette::Grayscale => &GRAYSCALE_COLORS,
+        }
+    }
+
+    /// Color of class `i`. Past the base colors the palette repeats, alternately darker and
+    /// lighter with every round.
+    pub fn color(&self, i: usize) -> Color {
+        let base_colors = self.base_colors();
+        let base = base_colors[i % base_colors.len()];
+        let round = i / base_colors.len();
+
+        if round == 0 {
+            return base;
+        }
+
+        let amount = (0.3 * round.div_ceil(2) as f32).min(0.9);
+        let target = if round % 2 == 1 { BLACK } else { WHITE };
+        Color::from_vec(base.to_vec().lerp(target.to_vec(), amount))
+    }
+
+    /// Sets the highlight and unhighlight colors of the draw config.
+    pub fn apply(&self, config: &mut DrawConfig) {
+        let (highlight, highlight_border, unhighlight, unhighlight_border, edge_highlight) =
+            match self {
+                Palette::Default => (LIME, DARKGREEN, MAROON, DARKBROWN, MAROON),
+                Palette::ColorBlindSafe => (
+                    OKABE_ITO_COLORS[0],
+                    OKABE_ITO_COLORS[5],
+                    OKABE_ITO_COLORS[4],
+                    BLACK,
+                    OKABE_ITO_COLORS[5],
+                ),
+                Palette::Grayscale => (
+                    GRAYSCALE_COLORS[3],
+                    GRAYSCALE_COLORS[0],
+                    GRAYSCALE_COLORS[1],
+                    GRAYSCALE_COLORS[0],
+                    GRAYSCALE_COLORS[1],
+                ),
+            };
+
+        let vertex_config = &mut config.vertex_config;
+        vertex_config.highlight_color = highlight;
+        vertex_config.highlight_border_color = highlight_border;
+        vertex_config.unhighlight_color = unhighlight;
+        vertex_config.unhighlight_border_color = unhighlight_border;
+        config.edge_config.highlight_color = edge_highlight;
+        config.palette = *self;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_palette_matches_default_config() {
+        let mut config = DrawConfig::default();
+        Palette::ColorBlindSafe.apply(&mut config);
+        assert!(config != DrawConfig::default());
+
+        Palette::Default.apply(&mut config);
+        assert!(config == DrawConfig::default());
+    }
+
+    #[test]
+    fn test_apply_sets_palette_colors() {
+        let mut config = DrawConfig::default();
+        Palette::ColorBlindSafe.apply(&mut config);
+
+        assert_eq!(config.palette, Palette::ColorBlindSafe);
+        assert_eq!(config.vertex_config.highlight_color, OKABE_ITO_COLORS[0]);
+        assert_eq!(config.vertex_config.unhighlight_color, OKABE_ITO_COLORS[4]);
+        assert_eq!(config.edge_config.highlight_color, OKABE_ITO_COLORS[5]);
+    }
+
+    #[test]
+    fn test_color_cycles_with_brightness_variation() {
+        for palette in Palette::ALL {
+            let count = palette.base_colors().len();
+            let colors: Vec<Color> = (0..3 * count).map(|i| palette.color(i)).collect();
+
+            assert_eq!(colors[0], palette.base_colors()[0]);
+            for i in 0..colors.len() {
+                for j in 0..i {
+                    assert_ne!(colors[i], colors[j], "{:?}: {} and {}", palette, i, j);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,198 @@
+use std::path::{Path, PathBuf};
+
+use macroquad::prelude::*;
+use thiserror::Error;
+
+use crate::{
+    annotations::AnnotationLayer,
+    file_names::{resolve_export_path, ExportPathError},
+    graph_drawer::{Drawable, DrawableGraph},
+};
+
+/// Every pixel of the export averages this many rendered pixels along each axis.
+const SUPERSAMPLING: u32 = 2;
+
+#[derive(Error, Debug)]
+pub enum PNGExportError {
+    #[error("Nothing to export, the image would be empty")]
+    EmptyImage,
+    #[error("The image is too large to render")]
+    TooLarge,
+    #[error("Could not write the image: {0}")]
+    Image(#[from] image::ImageError),
+    #[error("{0}")]
+    InvalidPath(#[from] ExportPathError),
+}
+
+/// Pixel size of an export of a canvas of `canvas_size` at `scale`.
+pub fn png_size(canvas_size: Vec2, scale: f32) -> (u32, u32) {
+    let size = (canvas_size * scale).round().max(Vec2::ZERO);
+    (size.x as u32, size.y as u32)
+}
+
+/// Renders the graph and the annotations offscreen at `scale` times the canvas size, whatever
+/// the size of the window, and writes them as a PNG file. Without a `background` the image is
+/// transparent behind the graph. Returns the absolute path of the written file.
+pub fn export_png<P: AsRef<Path>>(
+    graph: &DrawableGraph,
+    annotations: &AnnotationLayer,
+    canvas_size: Vec2,
+    scale: f32,
+    background: Option<Color>,
+    path: P,
+    create_directories: bool,
+) -> Result<PathBuf, PNGExportError> {
+    let image = render_image(graph, annotations, canvas_size, scale, background)?;
+    // only once the image is rendered, so that a failed export creates no directories
+    let path = resolve_export_path(path.as_ref(), "png", create_directories)?;
+
+    image::save_buffer(
+        &path,
+        &image.bytes,
+        image.width as u32,
+        image.height as u32,
+        image::ColorType::Rgba8,
+    )?;
+
+    Ok(path)
+}
+
+/// Renders the graph and the annotations offscreen at `scale` times the canvas size, without
+/// any of the overlays of the window. The rows of the image run top to bottom.
+pub fn render_image(
+    graph: &DrawableGraph,
+    annotations: &AnnotationLayer,
+    canvas_size: Vec2,
+    scale: f32,
+    background: Option<Color>,
+) -> Result<Image, PNGExportError> {
+    let (width, height) = png_size(canvas_size, scale);
+    if width == 0 || height == 0 {
+        return Err(PNGExportError::EmptyImage);
+    }
+    let (render_width, render_height) = (width * SUPERSAMPLING, height * SUPERSAMPLING);
+    if render_width > u16::MAX as u32 || render_height > u16::MAX as u32 {
+        return Err(PNGExportError::TooLarge);
+    }
+
+    let target = render_target(render_width, render_height);
+    let mut camera = Camera2D::from_display_rect(Rect::new(0.0, 0.0, canvas_size.x, canvas_size.y));
+    // so that the rows of the read-back run top to bottom
+    camera.zoom.y = -camera.zoom.y;
+    camera.render_target = Some(target);
+
+    set_camera(&camera);
+    clear_background(background.unwrap_or(BLANK));
+    graph.draw();
+    annotations.draw();
+    set_default_camera();
+
+    let rendered = target.texture.get_texture_data();
+    target.delete();
+
+    Ok(downsample(&rendered, SUPERSAMPLING))
+}
+
+/// Averages every `factor` by `factor` block of pixels into one, weighting colors by their
+/// alpha so that transparent pixels don't darken the edges of shapes. Rows and columns that
+/// don't fill a block are dropped.
+fn downsample(image: &Image, factor: u32) -> Image {
+    let factor = factor.max(1) as usize;
+    let (width, height) = (image.width as usize, image.height as usize);
+    let (small_width, small_height) = (width / factor, height / factor);
+
+    let mut bytes = Vec::with_capacity(small_width * small_height * 4);
+    for y in 0..small_height {
+        for x in 0..small_width {
+            let mut color_sums = [0u32; 3];
+            let mut alpha_sum = 0;
+            for source_y in y * factor..(y + 1) * factor {
+                for source_x in x * factor..(x + 1) * factor {
+                    let offset = (source_y * width + source_x) * 4;
+                    let pixel = &image.bytes[offset..offset + 4];
+                    let alpha = pixel[3] as u32;
+                    for (sum, &byte) in color_sums.iter_mut().zip(&pixel[..3]) {
+                        *sum += byte as u32 * alpha;
+                    }
+                    alpha_sum += alpha;
+                }
+            }
+
+            let count = (factor * factor) as u32;
+            // fully transparent blocks come out transparent black
+            bytes.extend(
+                color_sums
+                    .map(|sum| (sum + alpha_sum / 2).checked_div(alpha_sum).unwrap_or(0) as u8),
+            );
+            bytes.push(((alpha_sum + count / 2) / count) as u8);
+        }
+    }
+
+    Image {
+        width: small_width as u16,
+        height: small_height as u16,
+        bytes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_size_follows_scale() {
+        let canvas_size = vec2(500.0, 300.0);
+
+        for scale in [1.0, 2.0, 3.0] {
+            let (width, height) = png_size(canvas_size, scale);
+            assert_eq!(
+                (width as f32, height as f32),
+                (canvas_size.x * scale, canvas_size.y * scale)
+            );
+
+            // what is rendered shrinks back to the export size
+            let rendered = Image::gen_image_color(
+                (width * SUPERSAMPLING) as u16,
+                (height * SUPERSAMPLING) as u16,
+                WHITE,
+            );
+            let image = downsample(&rendered, SUPERSAMPLING);
+            assert_eq!((image.width as u32, image.height as u32), (width, height));
+            assert_eq!(image.bytes.len(), (width * height * 4) as usize);
+        }
+
+        assert_eq!(png_size(vec2(333.0, 100.0), 1.5), (500, 150));
+        assert_eq!(png_size(canvas_size, 0.0), (0, 0));
+    }
+
+    #[test]
+    fn test_downsample_averages_blocks() {
+        let mut image = Image::gen_image_color(5, 2, BLACK);
+        image.set_pixel(0, 0, WHITE);
+        image.set_pixel(1, 1, WHITE);
+        image.set_pixel(2, 0, WHITE);
+        image.set_pixel(3, 0, WHITE);
+        image.set_pixel(2, 1, WHITE);
+        image.set_pixel(3, 1, WHITE);
+
+        let small = downsample(&image, 2);
+
+        assert_eq!((small.width, small.height), (2, 1));
+        assert_eq!(&small.bytes[..4], &[128, 128, 128, 255]);
+        assert_eq!(&small.bytes[4..], &[255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn test_transparent_background_stays_transparent() {
+        // what a transparent export renders: a blank canvas with a red shape in one corner
+        let mut image = Image::gen_image_color(4, 2, BLANK);
+        image.set_pixel(0, 0, RED);
+
+        let small = downsample(&image, 2);
+
+        // the partly covered block keeps the color of the shape, not darkened by the background
+        assert_eq!(&small.bytes[..3], &image.bytes[..3]);
+        assert_eq!(small.bytes[3], 64);
+        assert_eq!(&small.bytes[4..], &[0, 0, 0, 0]);
+    }
+}
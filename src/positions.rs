@@ -0,0 +1,196 @@
+use macroquad::prelude::*;
+use thiserror::Error;
+
+use crate::{
+    graph::{parse_graph6_string, Graph, Graph6ParseError},
+    svg_writer::fit_transform,
+};
+
+/// Writes one `x y` line per vertex, in vertex order.
+pub fn format_positions(positions: &[Vec2]) -> String {
+    positions
+        .iter()
+        .map(|position| format!("{} {}\n", position.x, position.y))
+        .collect()
+}
+
+/// Parses the output of [`format_positions`]. Empty lines and lines starting with `#` are
+/// skipped.
+pub fn parse_positions(text: &str) -> Result<Vec<Vec2>, PositionsParseError> {
+    let mut positions = Vec::new();
+
+    for (line_index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let coordinates: Vec<f32> = line
+            .split_whitespace()
+            .map(|part| part.parse::<f32>())
+            .collect::<Result<_, _>>()
+            .map_err(|_| PositionsParseError::InvalidCoordinates {
+                line: line_index + 1,
+            })?;
+
+        match coordinates[..] {
+            [x, y] if x.is_finite() && y.is_finite() => positions.push(vec2(x, y)),
+            _ => {
+                return Err(PositionsParseError::InvalidCoordinates {
+                    line: line_index + 1,
+                })
+            }
+        }
+    }
+
+    Ok(positions)
+}
+
+/// Scales and translates `points` uniformly so that their bounding box fits `target`, shrunk by
+/// `margin` on every side, and is centered in it.
+pub fn fit_to_rect(points: &[Vec2], target: Rect, margin: f32) -> Vec<Vec2> {
+    let Some(min) = points.iter().copied().reduce(Vec2::min) else {
+        return Vec::new();
+    };
+    let max = points.iter().copied().fold(min, Vec2::max);
+
+    let inner_size = (target.size() - 2.0 * margin).max(Vec2::ZERO);
+    let (offset, scale) = fit_transform(max - min, inner_size);
+
+    let origin = target.center() - inner_size / 2.0 + offset;
+    points
+        .iter()
+        .map(|&point| origin + (point - min) * scale)
+        .collect()
+}
+
+/// Parses a graph and, when given, the positions of its vertices, which must be one for every
+/// vertex.
+pub fn import_graph_with_positions(
+    g6_string: &str,
+    positions: Option<&str>,
+) -> Result<(Graph, Option<Vec<Vec2>>), GraphImportError> {
+    let graph = parse_graph6_string(g6_string)?;
+
+    let positions = match positions {
+        Some(text) => {
+            let positions = parse_positions(text)?;
+            if positions.len() != graph.vertices {
+                return Err(GraphImportError::VertexCountMismatch {
+                    graph: graph.vertices,
+                    positions: positions.len(),
+                });
+            }
+            Some(positions)
+        }
+        None => None,
+    };
+
+    Ok((graph, positions))
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum PositionsParseError {
+    #[error("Invalid coordinates on line {line}")]
+    InvalidCoordinates { line: usize },
+}
+
+#[derive(Error, Debug)]
+pub enum GraphImportError {
+    #[error("Invalid graph: {0}")]
+    Graph(#[from] Graph6ParseError),
+    #[error("Invalid positions: {0}")]
+    Positions(#[from] PositionsParseError),
+    #[error("The graph has {graph} vertices but there are {positions} positions")]
+    VertexCountMismatch { graph: usize, positions: usize },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph_interface::GraphInterface;
+
+    #[test]
+    fn test_positions_round_trip() {
+        let positions = vec![vec2(1.5, -2.0), vec2(300.0, 0.25)];
+
+        let text = format_positions(&positions);
+
+        assert_eq!(text, "1.5 -2\n300 0.25\n");
+        assert_eq!(parse_positions(&text), Ok(positions));
+        assert_eq!(
+            parse_positions("# layout\n\n1 2\n"),
+            Ok(vec![vec2(1.0, 2.0)])
+        );
+        assert_eq!(
+            parse_positions("1 2\n3\n"),
+            Err(PositionsParseError::InvalidCoordinates { line: 2 })
+        );
+        assert_eq!(
+            parse_positions("1 NaN\n"),
+            Err(PositionsParseError::InvalidCoordinates { line: 1 })
+        );
+    }
+
+    #[test]
+    fn test_fit_to_rect_keeps_aspect_ratio() {
+        let points = [vec2(-1.0, -1.0), vec2(1.0, 0.0), vec2(0.0, 1.0)];
+
+        let fitted = fit_to_rect(&points, Rect::new(100.0, 0.0, 400.0, 200.0), 10.0);
+
+        // the 2x2 box becomes 180x180, centered horizontally in the 380x180 inner rect
+        assert_eq!(
+            fitted,
+            vec![vec2(210.0, 10.0), vec2(390.0, 100.0), vec2(300.0, 190.0)]
+        );
+        assert!(fit_to_rect(&[], Rect::new(0.0, 0.0, 10.0, 10.0), 1.0).is_empty());
+    }
+
+    #[test]
+    fn test_fit_to_rect_degenerate_boxes() {
+        let target = Rect::new(0.0, 0.0, 200.0, 100.0);
+
+        let fitted = fit_to_rect(&[vec2(5.0, 5.0)], target, 10.0);
+        assert_eq!(fitted, vec![vec2(100.0, 50.0)]);
+
+        let fitted = fit_to_rect(&[vec2(0.0, 3.0), vec2(1.0, 3.0)], target, 10.0);
+        assert!(fitted[0].abs_diff_eq(vec2(10.0, 50.0), 1e-3));
+        assert!(fitted[1].abs_diff_eq(vec2(190.0, 50.0), 1e-3));
+    }
+
+    #[test]
+    fn test_import_applies_positions() {
+        let (graph, positions) =
+            import_graph_with_positions("Bw", Some("10 20\n30 40\n50 60\n")).unwrap();
+        let positions = positions.unwrap();
+
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+        embedding.set_positions(&positions);
+
+        assert_eq!(graph.edges.len(), 3);
+        assert_eq!(embedding.positions(), positions);
+    }
+
+    #[test]
+    fn test_import_checks_vertex_count() {
+        let (graph, positions) = import_graph_with_positions("Bw", None).unwrap();
+        assert_eq!(graph.vertices, 3);
+        assert_eq!(positions, None);
+
+        assert!(matches!(
+            import_graph_with_positions("Bw", Some("0 0\n1 1\n")),
+            Err(GraphImportError::VertexCountMismatch {
+                graph: 3,
+                positions: 2
+            })
+        ));
+        assert!(matches!(
+            import_graph_with_positions("Bw", Some("0 0\nx 1\n0 0\n")),
+            Err(GraphImportError::Positions(_))
+        ));
+        assert!(matches!(
+            import_graph_with_positions("", Some("0 0\n")),
+            Err(GraphImportError::Graph(_))
+        ));
+    }
+}
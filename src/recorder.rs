@@ -0,0 +1,293 @@
+use crate::file_names::resolve_export_path;
+use std::{
+    io,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, SyncSender, TrySendError},
+    thread::JoinHandle,
+};
+
+const CHANNEL_CAPACITY: usize = 8;
+
+pub trait FrameWriter: Send {
+    fn write_frame(&mut self, path: &Path, contents: &str) -> io::Result<()>;
+}
+
+/// Writes the frames as files, see [`resolve_export_path`] for where they end up.
+pub struct FileFrameWriter {
+    pub create_directories: bool,
+}
+
+impl FrameWriter for FileFrameWriter {
+    fn write_frame(&mut self, path: &Path, contents: &str) -> io::Result<()> {
+        let path =
+            resolve_export_path(path, "svg", self.create_directories).map_err(io::Error::other)?;
+        std::fs::write(path, contents)
+    }
+}
+
+struct Frame {
+    path: PathBuf,
+    contents: String,
+}
+
+pub struct Recorder {
+    base_name: String,
+    every_nth_step: usize,
+    max_frames: usize,
+    /// Cap on the size of all frames together.
+    max_bytes: usize,
+    step: usize,
+    frames_recorded: usize,
+    bytes_recorded: usize,
+    /// Frames skipped because the writer fell behind.
+    frames_dropped: usize,
+    sender: Option<SyncSender<Frame>>,
+    error_receiver: Receiver<String>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl Recorder {
+    pub fn start<W: FrameWriter + 'static>(
+        base_name: &str,
+        every_nth_step: usize,
+        max_frames: usize,
+        max_bytes: usize,
+        mut writer: W,
+    ) -> Self {
+        let (sender, receiver) = mpsc::sync_channel::<Frame>(CHANNEL_CAPACITY);
+        let (error_sender, error_receiver) = mpsc::channel();
+
+        let worker = std::thread::spawn(move || {
+            for frame in receiver {
+                if let Err(error) = writer.write_frame(&frame.path, &frame.contents) {
+                    let message = format!("Could not write {}: {}", frame.path.display(), error);
+                    if error_sender.send(message).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+
+        Self {
+            base_name: base_name.to_string(),
+            every_nth_step: every_nth_step.max(1),
+            max_frames,
+            max_bytes,
+            step: 0,
+            frames_recorded: 0,
+            bytes_recorded: 0,
+            frames_dropped: 0,
+            sender: Some(sender),
+            error_receiver,
+            worker: Some(worker),
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.sender.is_some()
+    }
+
+    pub fn frames_recorded(&self) -> usize {
+        self.frames_recorded
+    }
+
+    pub fn frames_dropped(&self) -> usize {
+        self.frames_dropped
+    }
+
+    /// Registers a simulation step, capturing a frame on every nth step.
+    /// The frame contents are only generated when a frame is actually captured.
+    pub fn record_step<F: FnOnce() -> Option<String>>(&mut self, frame_contents: F) {
+        let Some(sender) = &self.sender else {
+            return;
+        };
+
+        let capture = self.step == 0;
+        self.step = (self.step + 1) % self.every_nth_step;
+        if !capture {
+            return;
+        }
+
+        let Some(contents) = frame_contents() else {
+            return;
+        };
+
+        let bytes = contents.len();
+        if self.bytes_recorded + bytes > self.max_bytes {
+            self.sender = None;
+            return;
+        }
+
+        let frame = Frame {
+            path: PathBuf::from(frame_file_name(&self.base_name, self.frames_recorded + 1)),
+            contents,
+        };
+
+        match sender.try_send(frame) {
+            Ok(()) => {
+                self.frames_recorded += 1;
+                self.bytes_recorded += bytes;
+            }
+            Err(TrySendError::Full(_)) => self.frames_dropped += 1,
+            Err(TrySendError::Disconnected(_)) => self.sender = None,
+        }
+
+        if self.frames_recorded >= self.max_frames {
+            self.sender = None;
+        }
+    }
+
+    /// Stops accepting frames and waits for the pending ones to be written.
+    pub fn stop(&mut self) {
+        self.sender = None;
+
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+
+    pub fn drain_errors(&self) -> Vec<String> {
+        self.error_receiver.try_iter().collect()
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+pub fn frame_file_name(base_name: &str, frame: usize) -> String {
+    format!("{}_{:04}.svg", base_name, frame)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct MemoryWriter {
+        frames: Arc<Mutex<Vec<(PathBuf, String)>>>,
+    }
+
+    impl FrameWriter for MemoryWriter {
+        fn write_frame(&mut self, path: &Path, contents: &str) -> io::Result<()> {
+            self.frames
+                .lock()
+                .unwrap()
+                .push((path.to_path_buf(), contents.to_string()));
+            Ok(())
+        }
+    }
+
+    struct FailingWriter;
+
+    /// Blocks on every frame until the sender of `wait` is dropped.
+    struct BlockingWriter {
+        wait: Receiver<()>,
+    }
+
+    impl FrameWriter for BlockingWriter {
+        fn write_frame(&mut self, _path: &Path, _contents: &str) -> io::Result<()> {
+            let _ = self.wait.recv();
+            Ok(())
+        }
+    }
+
+    impl FrameWriter for FailingWriter {
+        fn write_frame(&mut self, _path: &Path, _contents: &str) -> io::Result<()> {
+            Err(io::Error::other("disk full"))
+        }
+    }
+
+    #[test]
+    fn test_frame_file_name() {
+        assert_eq!(frame_file_name("layout", 1), "layout_0001.svg");
+        assert_eq!(frame_file_name("layout", 12345), "layout_12345.svg");
+    }
+
+    #[test]
+    fn test_records_every_nth_step() {
+        let writer = MemoryWriter::default();
+        let mut recorder = Recorder::start("frame", 3, 100, usize::MAX, writer.clone());
+
+        for step in 0..7 {
+            recorder.record_step(|| Some(step.to_string()));
+        }
+        recorder.stop();
+
+        let frames = writer.frames.lock().unwrap();
+        let contents: Vec<&str> = frames.iter().map(|(_, c)| c.as_str()).collect();
+        assert_eq!(contents, vec!["0", "3", "6"]);
+        assert_eq!(frames[0].0, PathBuf::from("frame_0001.svg"));
+        assert_eq!(frames[2].0, PathBuf::from("frame_0003.svg"));
+    }
+
+    #[test]
+    fn test_stops_at_frame_cap() {
+        let writer = MemoryWriter::default();
+        let mut recorder = Recorder::start("frame", 1, 2, usize::MAX, writer.clone());
+
+        for _ in 0..5 {
+            recorder.record_step(|| Some(String::new()));
+        }
+
+        assert!(!recorder.is_recording());
+        assert_eq!(recorder.frames_recorded(), 2);
+
+        recorder.stop();
+        assert_eq!(writer.frames.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_stops_at_byte_cap() {
+        let writer = MemoryWriter::default();
+        let mut recorder = Recorder::start("frame", 1, 100, 10, writer.clone());
+
+        for _ in 0..5 {
+            recorder.record_step(|| Some("four".to_string()));
+        }
+
+        assert!(!recorder.is_recording());
+        assert_eq!(recorder.frames_recorded(), 2);
+
+        recorder.stop();
+        assert_eq!(writer.frames.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_frames_are_dropped_while_the_writer_is_behind() {
+        let (release, wait) = mpsc::channel::<()>();
+        let writer = BlockingWriter { wait };
+        let mut recorder = Recorder::start("frame", 1, 100, usize::MAX, writer);
+
+        // one frame blocks the writer, the channel holds the next ones
+        for _ in 0..CHANNEL_CAPACITY + 5 {
+            recorder.record_step(|| Some(String::new()));
+        }
+
+        assert!(recorder.frames_dropped() > 0);
+        assert_eq!(
+            recorder.frames_recorded() + recorder.frames_dropped(),
+            CHANNEL_CAPACITY + 5
+        );
+
+        drop(release);
+        recorder.stop();
+    }
+
+    #[test]
+    fn test_write_errors_are_reported() {
+        let mut recorder = Recorder::start("frame", 1, 10, usize::MAX, FailingWriter);
+
+        recorder.record_step(|| Some(String::new()));
+        recorder.stop();
+
+        let errors = recorder.drain_errors();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("frame_0001.svg"));
+        assert!(errors[0].contains("disk full"));
+    }
+}
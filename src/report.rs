@@ -0,0 +1,206 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{graph::Graph, indexing::Indexing};
+
+/// Invariants shown in the stats panel, recomputed only when the graph changes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GraphStats {
+    computed_for: (usize, Vec<(usize, usize)>),
+    pub density: f32,
+    pub degree_sequence: Vec<usize>,
+    pub component_count: usize,
+    pub bipartite: bool,
+    pub girth: Option<usize>,
+    pub diameter: Option<usize>,
+    pub radius: Option<usize>,
+    pub center: Vec<usize>,
+    pub clustering: f32,
+}
+
+impl GraphStats {
+    pub fn new(graph: &Graph) -> Self {
+        Self {
+            computed_for: (graph.vertices, graph.edges.clone()),
+            density: graph.density(),
+            degree_sequence: graph.degree_sequence(),
+            component_count: graph
+                .components()
+                .into_iter()
+                .max()
+                .map_or(0, |last| last + 1),
+            bipartite: graph.is_bipartite(),
+            girth: graph.girth(),
+            diameter: graph.diameter(),
+            radius: graph.radius(),
+            center: graph.center(),
+            clustering: graph.clustering_coefficient(),
+        }
+    }
+
+    pub fn update(&mut self, graph: &Graph) {
+        let (vertices, edges) = &self.computed_for;
+        if *vertices != graph.vertices || *edges != graph.edges {
+            *self = Self::new(graph);
+        }
+    }
+}
+
+/// Plain-text snapshot of the stats panel. `timestamp` is in seconds since the Unix epoch.
+pub fn graph_report(
+    graph: &Graph,
+    stats: &GraphStats,
+    name: &str,
+    indexing: Indexing,
+    timestamp: u64,
+) -> String {
+    let optional = |value: Option<usize>, missing: &str| {
+        value.map_or(missing.to_string(), |value| value.to_string())
+    };
+    let degree_sequence: Vec<String> = stats
+        .degree_sequence
+        .iter()
+        .map(|degree| degree.to_string())
+        .collect();
+    let center: Vec<String> = stats
+        .center
+        .iter()
+        .map(|&vertex| indexing.display(vertex).to_string())
+        .collect();
+
+    let lines = [
+        format!("Graph: {}", name),
+        format!("Created: {}", format_timestamp(timestamp)),
+        format!("Vertices: {}", graph.vertices),
+        format!("Edges: {}", graph.edges.len()),
+        format!("Density: {:.4}", stats.density),
+        format!("Degree sequence: {}", degree_sequence.join(", ")),
+        format!("Components: {}", stats.component_count),
+        format!("Bipartite: {}", if stats.bipartite { "yes" } else { "no" }),
+        format!("Girth: {}", optional(stats.girth, "none (acyclic)")),
+        format!("Diameter: {}", optional(stats.diameter, "disconnected")),
+        format!("Radius: {}", optional(stats.radius, "disconnected")),
+        format!("Center: {}", center.join(", ")),
+        format!("Clustering: {:.4}", stats.clustering),
+        format!(
+            "g6: {}",
+            graph
+                .to_graph6_string()
+                .unwrap_or_else(|| "too many vertices".to_string())
+        ),
+    ];
+
+    lines.join("\n") + "\n"
+}
+
+pub fn current_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+/// Formats seconds since the Unix epoch as `YYYY-MM-DD HH:MM:SS UTC`.
+pub fn format_timestamp(timestamp: u64) -> String {
+    let time = UtcTime::from_timestamp(timestamp);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        time.year, time.month, time.day, time.hour, time.minute, time.second
+    )
+}
+
+/// Calendar date and time of day in UTC.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct UtcTime {
+    pub year: i64,
+    pub month: i64,
+    pub day: i64,
+    pub hour: u64,
+    pub minute: u64,
+    pub second: u64,
+}
+
+impl UtcTime {
+    /// Date and time of seconds since the Unix epoch.
+    pub fn from_timestamp(timestamp: u64) -> Self {
+        let (days, seconds) = (timestamp / 86400, timestamp % 86400);
+
+        // civil date from days since 1970-01-01, counting in 400 year eras starting in March
+        let days = days as i64 + 719468;
+        let era = days / 146097;
+        let day_of_era = days - era * 146097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        };
+
+        Self {
+            year: year_of_era + era * 400 + i64::from(month <= 2),
+            month,
+            day,
+            hour: seconds / 3600,
+            minute: seconds / 60 % 60,
+            second: seconds % 60,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00:00 UTC");
+        assert_eq!(format_timestamp(951782400), "2000-02-29 00:00:00 UTC");
+        assert_eq!(format_timestamp(1700000000), "2023-11-14 22:13:20 UTC");
+    }
+
+    #[test]
+    fn test_stats_update_only_on_change() {
+        let mut graph = Graph::cycle(5);
+        let mut stats = GraphStats::new(&graph);
+        assert_eq!(stats.girth, Some(5));
+        assert!(!stats.bipartite);
+
+        stats.girth = None;
+        stats.update(&graph);
+        assert_eq!(stats.girth, None);
+
+        graph.edges.push((0, 2));
+        stats.update(&graph);
+        assert_eq!(stats.girth, Some(3));
+    }
+
+    #[test]
+    fn test_graph_report() {
+        let mut graph = Graph::path(3);
+        graph.vertices = 4;
+        let stats = GraphStats::new(&graph);
+
+        let report = graph_report(&graph, &stats, "P3+K1", Indexing::One, 0);
+
+        assert_eq!(
+            report,
+            "Graph: P3+K1\n\
+             Created: 1970-01-01 00:00:00 UTC\n\
+             Vertices: 4\n\
+             Edges: 2\n\
+             Density: 0.3333\n\
+             Degree sequence: 2, 1, 1, 0\n\
+             Components: 2\n\
+             Bipartite: yes\n\
+             Girth: none (acyclic)\n\
+             Diameter: disconnected\n\
+             Radius: disconnected\n\
+             Center: \n\
+             Clustering: 0.0000\n\
+             g6: Cg\n"
+        );
+    }
+}
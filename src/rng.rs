@@ -0,0 +1,56 @@
+/// Small seeded random generator (SplitMix64), so layout perturbations are reproducible
+/// independently of the global macroquad generator.
+#[derive(Clone, Debug)]
+pub struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform float in `[low, high)`.
+    pub fn gen_range(&mut self, low: f32, high: f32) -> f32 {
+        let unit = (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32;
+        low + unit * (high - low)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_sequence() {
+        let mut a = SeededRng::new(42);
+        let mut b = SeededRng::new(42);
+        let mut c = SeededRng::new(43);
+
+        let sequence_a: Vec<u64> = (0..5).map(|_| a.next_u64()).collect();
+        let sequence_b: Vec<u64> = (0..5).map(|_| b.next_u64()).collect();
+        let sequence_c: Vec<u64> = (0..5).map(|_| c.next_u64()).collect();
+
+        assert_eq!(sequence_a, sequence_b);
+        assert_ne!(sequence_a, sequence_c);
+    }
+
+    #[test]
+    fn test_gen_range_bounds() {
+        let mut rng = SeededRng::new(7);
+
+        for _ in 0..1000 {
+            let value = rng.gen_range(-2.0, 3.0);
+            assert!((-2.0..3.0).contains(&value));
+        }
+    }
+}
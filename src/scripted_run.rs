@@ -0,0 +1,325 @@
+use std::time::Duration;
+
+use thiserror::Error;
+
+use crate::{
+    graph::{parse_graph6_string, parse_vertex_list, Graph},
+    indexing::Indexing,
+};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ScriptCommand {
+    Graph {
+        graph: Graph,
+        g6_string: String,
+    },
+    Highlight(Graph),
+    ColorVertices(Vec<usize>),
+    /// Runs this many force layout steps at once.
+    Layout(usize),
+    Pause(Duration),
+}
+
+/// Parses a script with one directive per line:
+/// `graph <g6>`, `highlight <g6>`, `color-vertices <list>`, `layout <steps>` and `pause <ms>`.
+/// Empty lines and lines starting with `#` are skipped.
+pub fn parse_script(
+    text: &str,
+    indexing: Indexing,
+) -> Result<Vec<ScriptCommand>, ScriptParseError> {
+    let mut commands = Vec::new();
+
+    for (line_index, line) in text.lines().enumerate() {
+        let line_number = line_index + 1;
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (directive, argument) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let argument = argument.trim();
+
+        let invalid = |reason: String| ScriptParseError::InvalidArgument {
+            line: line_number,
+            reason,
+        };
+
+        let command = match directive {
+            "graph" => ScriptCommand::Graph {
+                graph: parse_graph6_string(argument).map_err(|error| invalid(error.to_string()))?,
+                g6_string: argument.to_string(),
+            },
+            "highlight" => ScriptCommand::Highlight(
+                parse_graph6_string(argument).map_err(|error| invalid(error.to_string()))?,
+            ),
+            "color-vertices" => ScriptCommand::ColorVertices(
+                parse_vertex_list(argument, indexing)
+                    .map_err(|error| invalid(error.to_string()))?,
+            ),
+            "layout" => ScriptCommand::Layout(
+                argument
+                    .parse()
+                    .map_err(|_| invalid(format!("Invalid step count: {}", argument)))?,
+            ),
+            "pause" => ScriptCommand::Pause(Duration::from_millis(
+                argument
+                    .parse()
+                    .map_err(|_| invalid(format!("Invalid duration: {}", argument)))?,
+            )),
+            _ => {
+                return Err(ScriptParseError::UnknownDirective {
+                    line: line_number,
+                    directive: directive.to_string(),
+                })
+            }
+        };
+
+        commands.push(command);
+    }
+
+    Ok(commands)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PlayerState {
+    Paused,
+    Playing,
+    /// Playing, but waiting for a `pause` directive to elapse.
+    Waiting {
+        until: Duration,
+    },
+    Finished,
+}
+
+/// Steps through script commands. Time is passed in explicitly, so the player does not
+/// depend on the window clock.
+pub struct ScriptPlayer {
+    commands: Vec<ScriptCommand>,
+    next: usize,
+    state: PlayerState,
+}
+
+impl ScriptPlayer {
+    pub fn new(commands: Vec<ScriptCommand>) -> Self {
+        let state = if commands.is_empty() {
+            PlayerState::Finished
+        } else {
+            PlayerState::Paused
+        };
+
+        Self {
+            commands,
+            next: 0,
+            state,
+        }
+    }
+
+    pub fn state(&self) -> PlayerState {
+        self.state
+    }
+
+    pub fn position(&self) -> (usize, usize) {
+        (self.next, self.commands.len())
+    }
+
+    pub fn play(&mut self) {
+        if self.state == PlayerState::Paused {
+            self.state = PlayerState::Playing;
+        }
+    }
+
+    pub fn pause(&mut self) {
+        if matches!(
+            self.state,
+            PlayerState::Playing | PlayerState::Waiting { .. }
+        ) {
+            self.state = PlayerState::Paused;
+        }
+    }
+
+    /// Returns the next command regardless of the play state, skipping any running pause.
+    /// `pause` directives are returned too, but have no effect when stepping.
+    pub fn step(&mut self) -> Option<ScriptCommand> {
+        if self.state == PlayerState::Finished {
+            return None;
+        }
+
+        let command = self.advance();
+
+        if self.state != PlayerState::Finished {
+            self.state = PlayerState::Paused;
+        }
+
+        command
+    }
+
+    /// Returns the commands due at time `now` while playing. A `pause` directive stops the
+    /// batch and delays the following commands.
+    pub fn poll(&mut self, now: Duration) -> Vec<ScriptCommand> {
+        let mut due = Vec::new();
+
+        loop {
+            match self.state {
+                PlayerState::Waiting { until } if now >= until => {
+                    self.state = PlayerState::Playing;
+                }
+                PlayerState::Playing => match self.advance() {
+                    Some(ScriptCommand::Pause(duration)) => {
+                        if self.state == PlayerState::Playing {
+                            self.state = PlayerState::Waiting {
+                                until: now + duration,
+                            };
+                        }
+                    }
+                    Some(command) => due.push(command),
+                    None => break,
+                },
+                _ => break,
+            }
+        }
+
+        due
+    }
+
+    fn advance(&mut self) -> Option<ScriptCommand> {
+        let command = self.commands.get(self.next).cloned();
+
+        if command.is_some() {
+            self.next += 1;
+        }
+
+        if self.next >= self.commands.len() {
+            self.state = PlayerState::Finished;
+        }
+
+        command
+    }
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ScriptParseError {
+    #[error("Unknown directive '{directive}' on line {line}")]
+    UnknownDirective { line: usize, directive: String },
+    #[error("Invalid argument on line {line}: {reason}")]
+    InvalidArgument { line: usize, reason: String },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCRIPT: &str = "# demo\ngraph DQc\n\nhighlight DQc\npause 100\ncolor-vertices 1 3\n";
+
+    #[test]
+    fn test_parse_script() {
+        let commands = parse_script(SCRIPT, Indexing::One).unwrap();
+
+        assert_eq!(commands.len(), 4);
+        assert!(
+            matches!(&commands[0], ScriptCommand::Graph { g6_string, .. } if g6_string == "DQc")
+        );
+        assert!(matches!(&commands[1], ScriptCommand::Highlight(graph) if graph.vertices == 5));
+        assert_eq!(
+            commands[2],
+            ScriptCommand::Pause(Duration::from_millis(100))
+        );
+        assert_eq!(commands[3], ScriptCommand::ColorVertices(vec![0, 2]));
+    }
+
+    #[test]
+    fn test_color_vertices_zero_indexed() {
+        assert_eq!(
+            parse_script("color-vertices 0 2", Indexing::Zero),
+            Ok(vec![ScriptCommand::ColorVertices(vec![0, 2])])
+        );
+        assert!(matches!(
+            parse_script("color-vertices 0 2", Indexing::One),
+            Err(ScriptParseError::InvalidArgument { line: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_layout_steps() {
+        assert_eq!(
+            parse_script("layout 250", Indexing::One),
+            Ok(vec![ScriptCommand::Layout(250)])
+        );
+        assert!(matches!(
+            parse_script("layout -3", Indexing::One),
+            Err(ScriptParseError::InvalidArgument { line: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse_script("graph DQc\njump 3", Indexing::Zero),
+            Err(ScriptParseError::UnknownDirective {
+                line: 2,
+                directive: "jump".to_string()
+            })
+        );
+        assert!(matches!(
+            parse_script("pause soon", Indexing::Zero),
+            Err(ScriptParseError::InvalidArgument { line: 1, .. })
+        ));
+        assert!(matches!(
+            parse_script("graph", Indexing::Zero),
+            Err(ScriptParseError::InvalidArgument { line: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_player_waits_for_pause() {
+        let mut player = ScriptPlayer::new(parse_script(SCRIPT, Indexing::One).unwrap());
+        assert!(player.poll(Duration::ZERO).is_empty());
+
+        player.play();
+        let due = player.poll(Duration::from_millis(1000));
+        assert_eq!(due.len(), 2);
+        assert_eq!(
+            player.state(),
+            PlayerState::Waiting {
+                until: Duration::from_millis(1100)
+            }
+        );
+
+        assert!(player.poll(Duration::from_millis(1050)).is_empty());
+
+        let due = player.poll(Duration::from_millis(1100));
+        assert_eq!(due, vec![ScriptCommand::ColorVertices(vec![0, 2])]);
+        assert_eq!(player.state(), PlayerState::Finished);
+    }
+
+    #[test]
+    fn test_player_step_and_pause() {
+        let mut player = ScriptPlayer::new(parse_script(SCRIPT, Indexing::One).unwrap());
+
+        assert!(matches!(player.step(), Some(ScriptCommand::Graph { .. })));
+        assert_eq!(player.state(), PlayerState::Paused);
+        assert_eq!(player.position(), (1, 4));
+
+        player.play();
+        player.pause();
+        assert!(player.poll(Duration::ZERO).is_empty());
+
+        player.step();
+        assert_eq!(
+            player.step(),
+            Some(ScriptCommand::Pause(Duration::from_millis(100)))
+        );
+        player.step();
+        assert_eq!(player.state(), PlayerState::Finished);
+        assert_eq!(player.step(), None);
+    }
+
+    #[test]
+    fn test_empty_script_is_finished() {
+        let mut player = ScriptPlayer::new(Vec::new());
+        player.play();
+
+        assert_eq!(player.state(), PlayerState::Finished);
+        assert!(player.poll(Duration::ZERO).is_empty());
+    }
+}
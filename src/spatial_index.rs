@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+
+use macroquad::prelude::Vec2;
+
+/// Uniform grid over the plane, mapping cells to the indices of the items overlapping them.
+pub struct SpatialIndex {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<usize>>,
+}
+
+impl SpatialIndex {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+        }
+    }
+
+    /// Registers `index` in every cell overlapping the box from `min` to `max`.
+    pub fn insert(&mut self, index: usize, min: Vec2, max: Vec2) {
+        let (min_x, min_y) = self.cell(min);
+        let (max_x, max_y) = self.cell(max);
+
+        for x in min_x..=max_x {
+            for y in min_y..=max_y {
+                self.cells.entry((x, y)).or_default().push(index);
+            }
+        }
+    }
+
+    /// Indices of the items whose boxes may contain `point`, in insertion order.
+    pub fn candidates(&self, point: Vec2) -> &[usize] {
+        self.cells
+            .get(&self.cell(point))
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+
+    fn cell(&self, point: Vec2) -> (i32, i32) {
+        (
+            (point.x / self.cell_size).floor() as i32,
+            (point.y / self.cell_size).floor() as i32,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use macroquad::prelude::vec2;
+
+    use super::*;
+
+    #[test]
+    fn test_candidates_cover_inserted_boxes() {
+        let mut index = SpatialIndex::new(10.0);
+        index.insert(0, vec2(0.0, 0.0), vec2(25.0, 5.0));
+        index.insert(1, vec2(-5.0, -5.0), vec2(5.0, 5.0));
+
+        assert_eq!(index.candidates(vec2(1.0, 1.0)), &[0, 1]);
+        assert_eq!(index.candidates(vec2(22.0, 3.0)), &[0]);
+        assert_eq!(index.candidates(vec2(-3.0, -3.0)), &[1]);
+        assert!(index.candidates(vec2(50.0, 50.0)).is_empty());
+    }
+}
@@ -1,17 +1,39 @@
-use macroquad::{
-    prelude::{Color, Vec2},
-    window::screen_height,
+use macroquad::prelude::{vec2, Color, Vec2};
+use std::{
+    cell::Cell,
+    f32::consts::PI,
+    fmt,
+    io::Write,
+    path::{Path, PathBuf},
 };
-use std::{io::Write, path::Path};
 use thiserror::Error;
 
 use crate::{
-    graph_drawer::{DrawableEdge, DrawableGraph, DrawableLabel, DrawableVertex},
-    ui_manager::main_screen_width,
+    annotations::AnnotationLayer,
+    file_names::{resolve_export_path, ExportPathError},
+    graph_drawer::{
+        DrawableEdge, DrawableGraph, DrawableLabel, DrawableVertex, BORDER_DASHES, DASH_LENGTH,
+    },
+    graph_interface::EdgeStyle,
+    integer_coordinates::snap_to_integers,
+    lanes::{LaneConfig, LaneSeparators},
 };
 
 const XML_HEADER: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="no"?>"#;
 const DOCSTRING: &str = r#"<!-- Created with GraphDrs -->"#;
+const LABEL_HALO_WIDTH: f32 = 4.0;
+/// Default decimals of coordinates and lengths, see [`SVGNumber`].
+pub const SVG_DECIMALS: usize = 2;
+/// Scale factors need more precision, their rounding error grows with the drawing.
+const SCALE_DECIMALS: usize = 4;
+
+/// Spaces per level of nesting, both of the writer and within composite items.
+const INDENTATION_SIZE: usize = 4;
+
+thread_local! {
+    /// Decimals [`SVGNumber`] writes, those of the export in progress.
+    static DECIMALS: Cell<usize> = const { Cell::new(SVG_DECIMALS) };
+}
 
 pub type SVGOperationResult = Result<(), SVGWriterError>;
 
@@ -20,6 +42,7 @@ pub struct SVGWriter {
     indentation_level: usize,
     indentation_size: usize,
     has_header: bool,
+    has_content: bool,
     finalised: bool,
 }
 
@@ -30,13 +53,24 @@ impl SVGWriter {
         Self {
             svg_string,
             indentation_level: 0,
-            indentation_size: 4,
+            indentation_size: INDENTATION_SIZE,
             has_header: false,
+            has_content: false,
             finalised: false,
         }
     }
 
     pub fn write_header(&mut self, width: f32, height: f32) -> SVGOperationResult {
+        self.write_header_with_size(width, height, None)
+    }
+
+    /// Writes the header, setting explicit `width` and `height` attributes when a size is given.
+    pub fn write_header_with_size(
+        &mut self,
+        width: f32,
+        height: f32,
+        size: Option<Vec2>,
+    ) -> SVGOperationResult {
         if self.has_header {
             return Err(SVGWriterError::AlreadyHasHeader);
         }
@@ -52,13 +86,72 @@ impl SVGWriter {
 
         self.has_header = true;
 
+        if let Some(size) = size {
+            self.add_item(&format!(
+                r#"width="{}" height="{}""#,
+                SVGNumber(size.x),
+                SVGNumber(size.y)
+            ))?;
+        }
         self.add_item(&SVGViewBox { width, height })?;
         self.add_item(&r#"version="1.1""#.to_string())?;
         self.add_item(&r#"xmlns="http://www.w3.org/2000/svg">"#.to_string())?;
+        self.has_content = false;
 
         Ok(())
     }
 
+    /// Adds a `<title>` element and a metadata comment. Must precede all other content.
+    pub fn write_title(&mut self, title: &str) -> SVGOperationResult {
+        if self.has_content {
+            return Err(SVGWriterError::TitleAfterContent);
+        }
+
+        self.add_item(&svg_title(title))?;
+        self.add_item(&format!("<!-- Graph: {} -->", escape_comment(title)))?;
+
+        Ok(())
+    }
+
+    pub fn open_group(&mut self, attributes: &str) -> SVGOperationResult {
+        self.add_item(&format!("<g {}>", attributes))?;
+        self.indentation_level += 1;
+
+        Ok(())
+    }
+
+    pub fn close_group(&mut self) -> SVGOperationResult {
+        if self.indentation_level <= 1 {
+            return Err(SVGWriterError::UnexpectedIndentationLevel {
+                expected: 2,
+                found: self.indentation_level,
+            });
+        }
+
+        self.indentation_level -= 1;
+        self.add_item(&"</g>".to_string())
+    }
+
+    /// Opens a group whose content is shifted by `(dx, dy)`. Close it with [`Self::end_group`].
+    pub fn begin_group_translated(&mut self, dx: f32, dy: f32) -> SVGOperationResult {
+        self.open_group(&format!(
+            r#"transform="translate({} {})""#,
+            SVGNumber(dx),
+            SVGNumber(dy)
+        ))
+    }
+
+    pub fn end_group(&mut self) -> SVGOperationResult {
+        self.close_group()
+    }
+
+    /// Adds `item` in its own group, shifted by `offset`.
+    pub fn add_item_at<T: SVGItem>(&mut self, item: &T, offset: Vec2) -> SVGOperationResult {
+        self.begin_group_translated(offset.x, offset.y)?;
+        self.add_item(item)?;
+        self.end_group()
+    }
+
     pub fn finalise(&mut self) -> SVGOperationResult {
         if !self.has_header {
             return Err(SVGWriterError::MissingHeader);
@@ -102,34 +195,269 @@ impl SVGWriter {
             ));
         }
 
+        self.has_content = true;
+
         Ok(())
     }
 
-    pub fn write_to_file<P: AsRef<Path>>(&self, path: P) -> SVGOperationResult {
+    /// Clears the writer, so that it can write the next document.
+    pub fn reset(&mut self) {
+        self.svg_string.clear();
+        self.indentation_level = 0;
+        self.has_header = false;
+        self.has_content = false;
+        self.finalised = false;
+    }
+
+    /// Takes the finalised document and resets the writer.
+    pub fn take_string(&mut self) -> Result<String, SVGWriterError> {
+        if !self.finalised {
+            return Err(SVGWriterError::NotFinalised);
+        }
+
+        let svg_string = std::mem::take(&mut self.svg_string);
+        self.reset();
+
+        Ok(svg_string)
+    }
+
+    /// Writes the finalised document, see [`resolve_export_path`] for where it ends up.
+    /// Returns the absolute path of the written file.
+    pub fn write_to_file<P: AsRef<Path>>(
+        &self,
+        path: P,
+        create_directories: bool,
+    ) -> Result<PathBuf, SVGWriterError> {
         if !self.finalised {
             return Err(SVGWriterError::NotFinalised);
         }
 
-        let mut file = std::fs::File::create(path)
+        let path = resolve_export_path(path.as_ref(), "svg", create_directories)?;
+        let mut file = std::fs::File::create(&path)
             .map_err(|error| SVGWriterError::FileIOError { source: error })?;
 
         file.write_all(self.svg_string.as_bytes())
             .map_err(|error| SVGWriterError::FileIOError { source: error })?;
 
+        Ok(path)
+    }
+}
+
+pub struct SVGExportOptions {
+    pub title: String,
+    /// Output size in pixels. The drawing is scaled to fit and centered.
+    pub target_size: Option<Vec2>,
+    /// Draws the separators of these lanes behind the graph.
+    pub lanes: Option<LaneConfig>,
+    /// Rounds the positions, radii and widths of the graph, see [`snap_to_integers`].
+    pub integer_coordinates: bool,
+    /// Fills the canvas behind the graph, which is transparent otherwise.
+    pub background: Option<Color>,
+    /// Creates the missing directories of the output path.
+    pub create_directories: bool,
+    /// Decimals of the coordinates and lengths.
+    pub decimals: usize,
+}
+
+impl Default for SVGExportOptions {
+    fn default() -> Self {
+        Self {
+            title: String::new(),
+            target_size: None,
+            lanes: None,
+            integer_coordinates: false,
+            background: None,
+            create_directories: false,
+            decimals: SVG_DECIMALS,
+        }
+    }
+}
+
+pub fn draw_graph_to_file<P: AsRef<Path>>(
+    graph: &DrawableGraph,
+    annotations: &AnnotationLayer,
+    canvas_size: Vec2,
+    options: &SVGExportOptions,
+    path: P,
+) -> Result<PathBuf, SVGWriterError> {
+    let mut writer = SVGWriter::new();
+
+    write_graph(&mut writer, graph, annotations, canvas_size, options)?;
+
+    writer.write_to_file(path, options.create_directories)
+}
+
+/// Writes the graph with `writer`, which is reset first so that it can be reused between calls.
+pub fn graph_to_svg_string(
+    writer: &mut SVGWriter,
+    graph: &DrawableGraph,
+    annotations: &AnnotationLayer,
+    canvas_size: Vec2,
+    options: &SVGExportOptions,
+) -> Result<String, SVGWriterError> {
+    writer.reset();
+
+    write_graph(writer, graph, annotations, canvas_size, options)?;
+
+    writer.take_string()
+}
+
+fn write_graph(
+    writer: &mut SVGWriter,
+    graph: &DrawableGraph,
+    annotations: &AnnotationLayer,
+    window: Vec2,
+    options: &SVGExportOptions,
+) -> SVGOperationResult {
+    let previous_decimals = DECIMALS.replace(options.decimals);
+    let result = write_graph_content(writer, graph, annotations, window, options);
+    DECIMALS.set(previous_decimals);
+
+    result
+}
+
+fn write_graph_content(
+    writer: &mut SVGWriter,
+    graph: &DrawableGraph,
+    annotations: &AnnotationLayer,
+    window: Vec2,
+    options: &SVGExportOptions,
+) -> SVGOperationResult {
+    let transform = options
+        .target_size
+        .map(|target_size| fit_transform(window, target_size));
+
+    match options.target_size {
+        Some(target_size) => {
+            writer.write_header_with_size(target_size.x, target_size.y, Some(target_size))?
+        }
+        None => writer.write_header(window.x, window.y)?,
+    }
+    writer.write_title(&options.title)?;
+
+    let separators = options
+        .lanes
+        .as_ref()
+        .map(|lanes| LaneSeparators::new(lanes, window))
+        .filter(|separators| !separators.is_empty());
+    let write_backdrop = |writer: &mut SVGWriter| -> SVGOperationResult {
+        if let Some(color) = options.background {
+            writer.add_item(&format!(
+                r#"<rect width="{}" height="{}" fill="{}"/>"#,
+                SVGNumber(window.x),
+                SVGNumber(window.y),
+                color.to_svg_string()
+            ))?;
+        }
+        if let Some(separators) = &separators {
+            writer.add_item(separators)?;
+        }
         Ok(())
+    };
+    let write_annotations = |writer: &mut SVGWriter| -> SVGOperationResult {
+        if !annotations.is_empty() {
+            writer.add_item(annotations)?;
+        }
+        Ok(())
+    };
+
+    if options.integer_coordinates {
+        // rounded after the fit, so that the written coordinates are the integers
+        let (offset, scale) = transform.unwrap_or((Vec2::ZERO, 1.0));
+        let snapped = snap_to_integers(graph, offset, scale);
+
+        if options.background.is_some() || separators.is_some() {
+            write_transformed(writer, transform, write_backdrop)?;
+        }
+        writer.add_item(&snapped)?;
+        if !annotations.is_empty() {
+            write_transformed(writer, transform, write_annotations)?;
+        }
+    } else {
+        write_transformed(writer, transform, |writer| {
+            write_backdrop(writer)?;
+            writer.add_item(graph)?;
+            write_annotations(writer)
+        })?;
     }
+
+    writer.finalise()
+}
+
+/// Writes the content in a group that moves it by the offset after scaling it by the scale of
+/// `transform`, or as it is without a transform.
+fn write_transformed(
+    writer: &mut SVGWriter,
+    transform: Option<(Vec2, f32)>,
+    write_content: impl FnOnce(&mut SVGWriter) -> SVGOperationResult,
+) -> SVGOperationResult {
+    let Some((offset, scale)) = transform else {
+        return write_content(writer);
+    };
+
+    writer.open_group(&format!(
+        r#"transform="translate({} {}) scale({})""#,
+        SVGNumber(offset.x),
+        SVGNumber(offset.y),
+        format_number(scale, SCALE_DECIMALS)
+    ))?;
+    write_content(writer)?;
+    writer.close_group()
 }
 
-pub fn draw_graph_to_file<P: AsRef<Path>>(graph: &DrawableGraph, path: P) -> SVGOperationResult {
+/// Draws the graphs side by side, row by row in `columns` columns, each in a cell the size of
+/// the window.
+pub fn draw_graphs_to_file<P: AsRef<Path>>(
+    graphs: &[&DrawableGraph],
+    canvas_size: Vec2,
+    columns: usize,
+    spacing: f32,
+    path: P,
+    create_directories: bool,
+) -> Result<PathBuf, SVGWriterError> {
     let mut writer = SVGWriter::new();
 
-    writer.write_header(main_screen_width(), screen_height())?;
-    writer.add_item(graph)?;
-    writer.finalise()?;
+    write_graphs(&mut writer, graphs, canvas_size, columns, spacing)?;
 
-    writer.write_to_file(path)?;
+    writer.write_to_file(path, create_directories)
+}
+
+fn write_graphs(
+    writer: &mut SVGWriter,
+    graphs: &[&DrawableGraph],
+    cell: Vec2,
+    columns: usize,
+    spacing: f32,
+) -> SVGOperationResult {
+    let columns = columns.clamp(1, graphs.len().max(1));
+    let rows = graphs.len().div_ceil(columns).max(1);
+    let size = |count: usize, cell_size: f32| {
+        count as f32 * cell_size + count.saturating_sub(1) as f32 * spacing
+    };
+
+    writer.write_header(size(columns, cell.x), size(rows, cell.y))?;
+
+    for (index, graph) in graphs.iter().enumerate() {
+        let (row, column) = (index / columns, index % columns);
+        let offset = vec2(column as f32, row as f32) * (cell + spacing);
+        writer.add_item_at(*graph, offset)?;
+    }
+
+    writer.finalise()
+}
 
-    Ok(())
+/// Returns the offset and uniform scale that fit `content` inside `target`, centered.
+pub fn fit_transform(content: Vec2, target: Vec2) -> (Vec2, f32) {
+    // a degenerate box is only stretched along the dimension it has
+    let scale = [(content.x, target.x), (content.y, target.y)]
+        .into_iter()
+        .filter(|&(content, _)| content > 0.0)
+        .map(|(content, target)| target / content)
+        .reduce(f32::min)
+        .unwrap_or(1.0);
+    let offset = (target - content * scale) / 2.0;
+
+    (offset, scale)
 }
 
 #[derive(Error, Debug)]
@@ -142,6 +470,8 @@ pub enum SVGWriterError {
     AlreadyFinalised,
     #[error("SVG data not yet finalised")]
     NotFinalised,
+    #[error("Title must be written before any content")]
+    TitleAfterContent,
     #[error("Unexpected indentation level. Expected: {expected}, found: {found}")]
     UnexpectedIndentationLevel { expected: usize, found: usize },
     #[error("Error in file IO: {source}")]
@@ -149,6 +479,8 @@ pub enum SVGWriterError {
         #[from]
         source: std::io::Error,
     },
+    #[error("{0}")]
+    InvalidPath(#[from] ExportPathError),
 }
 
 pub trait SVGItem {
@@ -177,7 +509,8 @@ impl SVGItem for DrawableLabel {
 
         string.push_str(&format!(
             r#" x="{}" y="{}""#,
-            self.position.x, self.position.y
+            SVGNumber(self.position.x),
+            SVGNumber(self.position.y)
         ));
 
         string.push_str(&format!(
@@ -185,13 +518,78 @@ impl SVGItem for DrawableLabel {
             self.color.to_svg_string()
         ));
 
+        // the stroke is painted first, so it only shows around the glyphs
+        if let Some(halo) = self.halo {
+            string.push_str(&format!(
+                r#" stroke="{}" stroke-width="{}" stroke-linejoin="round" paint-order="stroke""#,
+                halo.to_svg_string(),
+                SVGNumber(LABEL_HALO_WIDTH)
+            ));
+        }
+
         string.push('>');
-        string.push_str(&self.content);
+        string.push_str(&escape_xml(&self.content));
         string.push_str("</text>\n");
         string
     }
 }
 
+/// Rounds `value` to `decimals` decimals without trailing zeros, so that the same layout always
+/// gives the same output. Negative zero is written as zero.
+pub fn format_number(value: f32, decimals: usize) -> String {
+    let mut string = format!("{:.*}", decimals, value);
+
+    if string.contains('.') {
+        let length = string.trim_end_matches('0').trim_end_matches('.').len();
+        string.truncate(length);
+    }
+
+    if string == "-0" {
+        string.remove(0);
+    }
+
+    string
+}
+
+/// Displays a coordinate or length with the decimals of the export in progress, see
+/// [`SVGExportOptions::decimals`] and [`format_number`].
+#[derive(Clone, Copy, Debug)]
+pub struct SVGNumber(pub f32);
+
+impl fmt::Display for SVGNumber {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(&format_number(self.0, DECIMALS.get()))
+    }
+}
+
+pub fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for character in text.chars() {
+        match character {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(character),
+        }
+    }
+
+    escaped
+}
+
+/// Comments may not contain `--`, so consecutive dashes are separated.
+fn escape_comment(text: &str) -> String {
+    let mut escaped = text.to_string();
+
+    while escaped.contains("--") {
+        escaped = escaped.replace("--", "- -");
+    }
+
+    escaped
+}
+
 fn svg_circle(position: Vec2, radius: f32, color: Color) -> String {
     let mut string = String::new();
 
@@ -199,7 +597,9 @@ fn svg_circle(position: Vec2, radius: f32, color: Color) -> String {
 
     string.push_str(&format!(
         r#" cx="{}" cy="{}" r="{}""#,
-        position.x, position.y, radius
+        SVGNumber(position.x),
+        SVGNumber(position.y),
+        SVGNumber(radius)
     ));
 
     string.push_str(&format!(r#" fill="{}""#, color.to_svg_string()));
@@ -208,27 +608,90 @@ fn svg_circle(position: Vec2, radius: f32, color: Color) -> String {
     string
 }
 
+fn svg_stroked_circle(
+    position: Vec2,
+    radius: f32,
+    color: Color,
+    stroke_width: f32,
+    stroke_color: Color,
+    dash: Option<f32>,
+) -> String {
+    let mut string = String::new();
+
+    string.push_str("<circle");
+
+    string.push_str(&format!(
+        r#" cx="{}" cy="{}" r="{}""#,
+        SVGNumber(position.x),
+        SVGNumber(position.y),
+        SVGNumber(radius)
+    ));
+
+    string.push_str(&format!(r#" fill="{}""#, color.to_svg_string()));
+
+    string.push_str(&format!(
+        r#" stroke="{}" stroke-width="{}""#,
+        stroke_color.to_svg_string(),
+        SVGNumber(stroke_width)
+    ));
+
+    if let Some(dash) = dash {
+        string.push_str(&format!(r#" stroke-dasharray="{}""#, SVGNumber(dash)));
+    }
+
+    string.push_str("/>\n");
+    string
+}
+
 impl SVGItem for DrawableVertex {
     fn to_svg_string(&self) -> String {
         let mut string = String::new();
 
-        string.push_str(&svg_circle(
-            self.position,
-            self.border_radius,
-            self.border_color,
-        ));
+        // the circles and label are grouped so the tooltip covers all of them
+        if let Some(title) = &self.title {
+            string.push_str(&svg_title(title));
+            string.push('\n');
+        }
 
-        string.push_str(&svg_circle(
-            self.position,
-            self.main_radius,
-            self.main_color,
-        ));
+        if self.svg_stroke || self.dashed_border {
+            // The stroke is centred on the circle, so it covers the band between both radii
+            let stroke_width = self.border_radius - self.main_radius;
+            let radius = self.main_radius + stroke_width * 0.5;
+            let dash = self
+                .dashed_border
+                .then(|| PI * radius / BORDER_DASHES as f32);
+
+            string.push_str(&svg_stroked_circle(
+                self.position,
+                radius,
+                self.main_color,
+                stroke_width,
+                self.border_color,
+                dash,
+            ));
+        } else {
+            string.push_str(&svg_circle(
+                self.position,
+                self.border_radius,
+                self.border_color,
+            ));
+
+            string.push_str(&svg_circle(
+                self.position,
+                self.main_radius,
+                self.main_color,
+            ));
+        }
 
         if let Some(label) = &self.label {
             string.push_str(&label.to_svg_string());
         }
 
-        string
+        if self.title.is_some() {
+            svg_group("<g>", &string)
+        } else {
+            string
+        }
     }
 }
 
@@ -236,19 +699,73 @@ impl SVGItem for DrawableEdge {
     fn to_svg_string(&self) -> String {
         let mut string = String::new();
 
-        string.push_str("<line");
-
-        string.push_str(&format!(r#" x1="{}" y1="{}""#, self.start.x, self.start.y));
-        string.push_str(&format!(r#" x2="{}" y2="{}""#, self.end.x, self.end.y));
+        match self.control {
+            Some(control) => {
+                string.push_str(&format!(
+                    r#"<path d="M {} {} Q {} {} {} {}" fill="none""#,
+                    SVGNumber(self.start.x),
+                    SVGNumber(self.start.y),
+                    SVGNumber(control.x),
+                    SVGNumber(control.y),
+                    SVGNumber(self.end.x),
+                    SVGNumber(self.end.y)
+                ));
+            }
+            None => {
+                string.push_str("<line");
+                string.push_str(&format!(
+                    r#" x1="{}" y1="{}""#,
+                    SVGNumber(self.start.x),
+                    SVGNumber(self.start.y)
+                ));
+                string.push_str(&format!(
+                    r#" x2="{}" y2="{}""#,
+                    SVGNumber(self.end.x),
+                    SVGNumber(self.end.y)
+                ));
+            }
+        }
         string.push_str(&format!(
             r#" stroke="{}" stroke-width="{}""#,
             self.color.to_svg_string(),
-            self.width
+            SVGNumber(self.width)
         ));
 
-        string.push_str("/>");
+        if self.style == EdgeStyle::Dashed {
+            string.push_str(&format!(
+                r#" stroke-dasharray="{}""#,
+                SVGNumber(DASH_LENGTH)
+            ));
+        }
+
+        match &self.title {
+            Some(title) => {
+                string.push('>');
+                string.push_str(&svg_title(title));
+                string.push_str(if self.control.is_some() {
+                    "</path>"
+                } else {
+                    "</line>"
+                });
+            }
+            None => string.push_str("/>"),
+        }
         string.push('\n');
 
+        if let Some([tip, left, right]) = self.arrow {
+            string.push_str(&format!(
+                r#"<polygon points="{},{} {},{} {},{}" fill="{}"/>"#,
+                SVGNumber(tip.x),
+                SVGNumber(tip.y),
+                SVGNumber(left.x),
+                SVGNumber(left.y),
+                SVGNumber(right.x),
+                SVGNumber(right.y),
+                self.color.to_svg_string()
+            ));
+            string.push('\n');
+        }
+
         if let Some(label) = &self.label {
             string.push_str(&label.to_svg_string());
         }
@@ -257,6 +774,22 @@ impl SVGItem for DrawableEdge {
     }
 }
 
+/// `content` indented one level within a group opened by `opening_tag`.
+fn svg_group(opening_tag: &str, content: &str) -> String {
+    let mut string = format!("{}\n", opening_tag);
+
+    for line in content.lines() {
+        string.push_str(&format!("{:2$}{}\n", "", line, INDENTATION_SIZE));
+    }
+
+    string.push_str("</g>\n");
+    string
+}
+
+fn svg_title(title: &str) -> String {
+    format!("<title>{}</title>", escape_xml(title))
+}
+
 struct SVGViewBox {
     width: f32,
     height: f32,
@@ -266,7 +799,11 @@ impl SVGItem for SVGViewBox {
     fn to_svg_string(&self) -> String {
         let mut string = String::new();
 
-        string.push_str(&format!(r#"viewBox="0 0 {} {}""#, self.width, self.height,));
+        string.push_str(&format!(
+            r#"viewBox="0 0 {} {}""#,
+            SVGNumber(self.width),
+            SVGNumber(self.height)
+        ));
 
         string
     }
@@ -293,6 +830,64 @@ mod tests {
     use macroquad::{color::*, prelude::Vec2};
 
     use super::*;
+    use crate::{
+        annotations::Annotation,
+        graph::{Graph, Orientation},
+        graph_drawer::{DrawConfig, Outline},
+        graph_interface::GraphInterface,
+    };
+
+    #[test]
+    fn test_format_number() {
+        let cases = [
+            (383.33334, 2, "383.33"),
+            (1e-7, 2, "0"),
+            (-1e-7, 2, "0"),
+            (-0.0, 2, "0"),
+            (-2.5, 2, "-2.5"),
+            (12.0, 2, "12"),
+            (0.125, 3, "0.125"),
+            (0.666, 1, "0.7"),
+            (100.0, 0, "100"),
+        ];
+
+        for (value, decimals, expected) in cases {
+            assert_eq!(format_number(value, decimals), expected, "{}", value);
+        }
+        assert_eq!(SVGNumber(2.0 / 3.0).to_string(), "0.67");
+    }
+
+    #[test]
+    fn test_golden_document() {
+        let graph = Graph::path(3);
+        let positions = [
+            vec2(100.0 / 3.0, 50.0),
+            vec2(150.0, 200.0 / 3.0 + 1e-6),
+            vec2(-0.0, 1.0 / 7.0),
+        ];
+        let mut config = DrawConfig::default();
+        config.edge_config.draw_index = true;
+        let drawable = DrawableGraph::from_positions(&graph, &positions, &config);
+        let options = SVGExportOptions {
+            title: "P3".to_string(),
+            ..Default::default()
+        };
+
+        let mut writer = SVGWriter::new();
+        write_graph(
+            &mut writer,
+            &drawable,
+            &AnnotationLayer::new(),
+            vec2(300.0, 200.0),
+            &options,
+        )
+        .unwrap();
+
+        assert_eq!(
+            writer.take_string().unwrap(),
+            include_str!("../testdata/path3.svg")
+        );
+    }
 
     #[test]
     fn test_black() {
@@ -331,11 +926,629 @@ mod tests {
             position: Vec2::new(0.0, 0.0),
             size: 10.0,
             color: WHITE,
+            halo: None,
         };
 
-        let string = r##"<text x="0" y="0" fill="#FFFFFF">test label</text>"##.to_string();
+        let string = r##"<text x="0" y="0" fill="#FFFFFF" font-size="24">test label</text>"##
+            .to_string()
+            + "\n";
         print!("printed: {}", &string);
 
         assert_eq!(label.to_svg_string(), string);
     }
+
+    #[test]
+    fn test_label_halo() {
+        let mut label = DrawableLabel {
+            content: "1".to_string(),
+            position: Vec2::new(5.0, 6.0),
+            size: 10.0,
+            color: BLACK,
+            halo: Some(WHITE),
+        };
+
+        assert_eq!(
+            label.to_svg_string(),
+            "<text x=\"5\" y=\"6\" fill=\"#000000\" font-size=\"24\" stroke=\"#FFFFFF\" \
+             stroke-width=\"4\" stroke-linejoin=\"round\" paint-order=\"stroke\">1</text>\n"
+        );
+
+        label.halo = None;
+        assert!(!label.to_svg_string().contains("stroke"));
+    }
+
+    #[test]
+    fn test_vertex_labels_get_halo_when_enabled() {
+        let graph = Graph::path(2);
+        let positions = [vec2(0.0, 0.0), vec2(100.0, 0.0)];
+        let mut config = DrawConfig::default();
+        config.edge_config.draw_index = true;
+
+        let drawable = DrawableGraph::from_positions(&graph, &positions, &config);
+        assert!(!drawable.to_svg_string().contains("paint-order"));
+
+        config.vertex_config.label_halo = true;
+        let svg = DrawableGraph::from_positions(&graph, &positions, &config).to_svg_string();
+        // both vertex labels, but not the edge label
+        assert_eq!(svg.matches(r#"paint-order="stroke""#).count(), 2);
+    }
+
+    #[test]
+    fn test_label_is_escaped() {
+        let label = DrawableLabel {
+            content: "a<b & \"c\"".to_string(),
+            position: Vec2::new(0.0, 0.0),
+            size: 10.0,
+            color: WHITE,
+            halo: None,
+        };
+
+        assert!(label
+            .to_svg_string()
+            .contains(">a&lt;b &amp; &quot;c&quot;</text>"));
+    }
+
+    fn test_vertex(svg_stroke: bool) -> DrawableVertex {
+        DrawableVertex {
+            position: Vec2::new(10.0, 20.0),
+            main_radius: 12.0,
+            border_radius: 16.0,
+            main_color: WHITE,
+            border_color: BLACK,
+            svg_stroke,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_vertex_as_two_circles() {
+        let string = test_vertex(false).to_svg_string();
+
+        assert_eq!(
+            string,
+            concat!(
+                r##"<circle cx="10" cy="20" r="16" fill="#000000"/>"##,
+                "\n",
+                r##"<circle cx="10" cy="20" r="12" fill="#FFFFFF"/>"##,
+                "\n"
+            )
+        );
+    }
+
+    #[test]
+    fn test_vertex_as_stroked_circle() {
+        let string = test_vertex(true).to_svg_string();
+
+        assert_eq!(
+            string,
+            r##"<circle cx="10" cy="20" r="14" fill="#FFFFFF" stroke="#000000" stroke-width="4"/>"##
+                .to_string() + "\n"
+        );
+    }
+
+    #[test]
+    fn test_outline_is_not_exported() {
+        let outline = Some(Outline {
+            color: ORANGE,
+            width: 4.0,
+        });
+        let vertex = DrawableVertex {
+            outline,
+            ..test_vertex(false)
+        };
+        let edge = DrawableEdge {
+            outline,
+            ..Default::default()
+        };
+
+        assert_eq!(vertex.to_svg_string(), test_vertex(false).to_svg_string());
+        assert_eq!(
+            edge.to_svg_string(),
+            DrawableEdge::default().to_svg_string()
+        );
+    }
+
+    #[test]
+    fn test_reset_writer_writes_another_document() {
+        let mut writer = SVGWriter::new();
+        writer.write_header(100.0, 100.0).unwrap();
+        writer.open_group("").unwrap();
+        writer.add_item(&DrawableVertex::default()).unwrap();
+
+        // abandoned halfway, inside a group
+        writer.reset();
+        assert!(matches!(
+            writer.finalise(),
+            Err(SVGWriterError::MissingHeader)
+        ));
+
+        let mut documents = Vec::new();
+        for title in ["first", "second"] {
+            writer.write_header(100.0, 100.0).unwrap();
+            writer.write_title(title).unwrap();
+            writer.add_item(&DrawableVertex::default()).unwrap();
+            writer.finalise().unwrap();
+            documents.push(writer.take_string().unwrap());
+        }
+
+        assert!(matches!(
+            writer.take_string(),
+            Err(SVGWriterError::NotFinalised)
+        ));
+        for (document, title) in documents.iter().zip(["first", "second"]) {
+            assert!(document.starts_with(XML_HEADER));
+            assert!(document.ends_with("</svg>\n"));
+            assert_eq!(document.matches("</svg>").count(), 1);
+            assert!(document.contains(&format!("<title>{}</title>", title)));
+        }
+        assert_eq!(documents[0].replace("first", "second"), documents[1]);
+
+        let mut fresh_writer = SVGWriter::new();
+        fresh_writer.write_header(100.0, 100.0).unwrap();
+        fresh_writer.write_title("first").unwrap();
+        fresh_writer.add_item(&DrawableVertex::default()).unwrap();
+        fresh_writer.finalise().unwrap();
+        assert_eq!(fresh_writer.take_string().unwrap(), documents[0]);
+    }
+
+    #[test]
+    fn test_title_is_escaped() {
+        let mut writer = SVGWriter::new();
+        writer.write_header(100.0, 100.0).unwrap();
+        writer.write_title("a<b & c--->").unwrap();
+        writer.finalise().unwrap();
+
+        let string = writer.take_string().unwrap();
+        assert!(string.contains("<title>a&lt;b &amp; c---&gt;</title>"));
+        assert!(string.contains("<!-- Graph: a<b & c- - -> -->"));
+    }
+
+    #[test]
+    fn test_title_precedes_graphical_elements() {
+        let mut writer = SVGWriter::new();
+        writer.write_header(100.0, 100.0).unwrap();
+        writer.write_title("K2").unwrap();
+        writer.add_item(&DrawableVertex::default()).unwrap();
+        writer.finalise().unwrap();
+
+        let string = writer.take_string().unwrap();
+        let title = string.find("<title>").unwrap();
+        let comment = string.find("<!-- Graph:").unwrap();
+        let circle = string.find("<circle").unwrap();
+
+        assert!(string.find("xmlns").unwrap() < title);
+        assert!(title < circle && comment < circle);
+    }
+
+    #[test]
+    fn test_title_after_content_is_rejected() {
+        let mut writer = SVGWriter::new();
+        writer.write_header(100.0, 100.0).unwrap();
+        writer.add_item(&DrawableVertex::default()).unwrap();
+
+        assert!(matches!(
+            writer.write_title("late"),
+            Err(SVGWriterError::TitleAfterContent)
+        ));
+    }
+
+    #[test]
+    fn test_fit_transform_letterboxes() {
+        let (offset, scale) = fit_transform(vec2(400.0, 400.0), vec2(800.0, 600.0));
+
+        assert_eq!(scale, 1.5);
+        assert_eq!(offset, vec2(100.0, 0.0));
+
+        // a vertical line is only stretched vertically, and centered horizontally
+        assert_eq!(
+            fit_transform(vec2(0.0, 100.0), vec2(800.0, 600.0)),
+            (vec2(400.0, 0.0), 6.0)
+        );
+    }
+
+    #[test]
+    fn test_configured_decimals() {
+        let graph = DrawableGraph {
+            vertices: vec![DrawableVertex {
+                position: vec2(10.123456, 20.5),
+                ..Default::default()
+            }],
+            edges: Vec::new(),
+        };
+        let export = |decimals: usize| {
+            let options = SVGExportOptions {
+                decimals,
+                ..Default::default()
+            };
+            let mut writer = SVGWriter::new();
+            graph_to_svg_string(
+                &mut writer,
+                &graph,
+                &AnnotationLayer::new(),
+                vec2(100.0, 100.0),
+                &options,
+            )
+            .unwrap()
+        };
+
+        assert!(export(4).contains(r#"cx="10.1235" cy="20.5""#));
+        assert!(export(0).contains(r#"cx="10" cy="20""#));
+        // only for the duration of the export
+        assert_eq!(SVGNumber(10.123456).to_string(), "10.12");
+    }
+
+    #[test]
+    fn test_target_size_sets_dimensions_and_centers_content() {
+        let graph = DrawableGraph {
+            vertices: vec![DrawableVertex::default()],
+            edges: Vec::new(),
+        };
+        let options = SVGExportOptions {
+            target_size: Some(vec2(800.0, 600.0)),
+            ..Default::default()
+        };
+
+        let mut writer = SVGWriter::new();
+        write_graph(
+            &mut writer,
+            &graph,
+            &AnnotationLayer::new(),
+            vec2(200.0, 100.0),
+            &options,
+        )
+        .unwrap();
+        let string = writer.take_string().unwrap();
+
+        assert!(string.contains(r#"width="800" height="600""#));
+        assert!(string.contains(r#"viewBox="0 0 800 600""#));
+        // 200x100 scaled by 4 is 800x400, leaving 100 above and below
+        assert!(string.contains(r#"<g transform="translate(0 100) scale(4)">"#));
+        assert!(string.find("</g>").unwrap() > string.find("<circle").unwrap());
+    }
+
+    #[test]
+    fn test_integer_coordinates_are_rounded_after_the_fit() {
+        let positions = [vec2(10.3, 20.7), vec2(55.1, 33.3)];
+        let graph = DrawableGraph {
+            vertices: positions
+                .iter()
+                .enumerate()
+                .map(|(index, &position)| DrawableVertex {
+                    index,
+                    position,
+                    ..Default::default()
+                })
+                .collect(),
+            edges: vec![DrawableEdge {
+                start: positions[0],
+                end: positions[1],
+                endpoints: Some((0, 1)),
+                ..Default::default()
+            }],
+        };
+        let options = SVGExportOptions {
+            target_size: Some(vec2(333.0, 200.0)),
+            integer_coordinates: true,
+            ..Default::default()
+        };
+
+        let mut writer = SVGWriter::new();
+        write_graph(
+            &mut writer,
+            &graph,
+            &AnnotationLayer::new(),
+            vec2(200.0, 100.0),
+            &options,
+        )
+        .unwrap();
+        let string = writer.take_string().unwrap();
+
+        // the graph is written in output coordinates, without a scaling group
+        assert!(!string.contains("<g transform"));
+        for attribute in [" cx=", " cy=", " r=", " x1=", " y1=", " x2=", " y2="] {
+            for (start, _) in string.match_indices(attribute) {
+                let value = string[start + attribute.len() + 1..]
+                    .split('"')
+                    .next()
+                    .unwrap();
+                let value: f32 = value.parse().unwrap();
+                assert_eq!(value, value.round(), "{}{}", attribute, value);
+            }
+        }
+        // scaled by 1.665 and moved down by 16.75, then rounded
+        assert!(string.contains(r#" cx="17""#));
+        assert!(string.contains(r#" cy="51""#));
+    }
+
+    #[test]
+    fn test_background_rect_only_when_filled() {
+        let graph = DrawableGraph {
+            vertices: vec![DrawableVertex::default()],
+            edges: Vec::new(),
+        };
+        let write = |background: Option<Color>| {
+            let options = SVGExportOptions {
+                background,
+                ..Default::default()
+            };
+            let mut writer = SVGWriter::new();
+            write_graph(
+                &mut writer,
+                &graph,
+                &AnnotationLayer::new(),
+                vec2(200.0, 100.0),
+                &options,
+            )
+            .unwrap();
+            writer.take_string().unwrap()
+        };
+
+        let filled = write(Some(WHITE));
+        assert!(filled.contains(r##"<rect width="200" height="100" fill="#FFFFFF"/>"##));
+        // the background is drawn first, behind the graph
+        assert!(filled.find("<rect").unwrap() < filled.find("<circle").unwrap());
+
+        assert!(!write(None).contains("<rect"));
+        // exports are transparent unless a fill is chosen
+        assert!(!write(DrawConfig::default().export_background()).contains("<rect"));
+    }
+
+    #[test]
+    fn test_annotations_follow_graph() {
+        let graph = DrawableGraph {
+            vertices: vec![DrawableVertex::default()],
+            edges: Vec::new(),
+        };
+        let mut annotations = AnnotationLayer::new();
+        annotations.annotations.push(Annotation::Arrow {
+            start: vec2(0.0, 0.0),
+            end: vec2(50.0, 50.0),
+        });
+        let options = SVGExportOptions {
+            target_size: Some(vec2(400.0, 200.0)),
+            ..Default::default()
+        };
+
+        let mut writer = SVGWriter::new();
+        write_graph(
+            &mut writer,
+            &graph,
+            &annotations,
+            vec2(200.0, 100.0),
+            &options,
+        )
+        .unwrap();
+        let string = writer.take_string().unwrap();
+
+        // drawn on top of the graph and scaled along with it
+        let line = string.find("<line").unwrap();
+        assert!(string.find("<circle").unwrap() < line);
+        assert!(line < string.find("</g>").unwrap());
+        assert!(string.contains(r#"marker-end="url(#annotation-arrow)""#));
+    }
+
+    #[test]
+    fn test_curved_edges_are_paths() {
+        let graph = Graph::path(2);
+        let positions = [vec2(0.0, 0.0), vec2(100.0, 0.0)];
+        let mut config = DrawConfig::default();
+
+        let straight = DrawableGraph::from_positions(&graph, &positions, &config).to_svg_string();
+        assert!(straight.contains("<line") && !straight.contains("<path"));
+
+        config.edge_config.curvature = 0.25;
+        let curved = DrawableGraph::from_positions(&graph, &positions, &config).to_svg_string();
+        assert!(!curved.contains("<line"));
+        assert!(curved.contains(
+            r##"<path d="M 0 0 Q 50 25 100 0" fill="none" stroke="#000000" stroke-width="5"/>"##
+        ));
+    }
+
+    #[test]
+    fn test_tooltips() {
+        let graph = Graph::path(3);
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+        embedding.edge_properties[1].weight = Some(2.5);
+        let mut config = DrawConfig::default();
+
+        let plain = DrawableGraph::compose(&embedding, &config).to_svg_string();
+        assert!(!plain.contains("<title>"));
+
+        config.svg_tooltips = true;
+        config.edge_config.draw_index = false;
+        config.vertex_config.draw_index = false;
+        let svg = DrawableGraph::compose(&embedding, &config).to_svg_string();
+
+        assert!(svg.contains(r#" stroke-width="5"><title>Edge 1: 1 - 2</title></line>"#));
+        assert!(svg.contains("<title>Edge 3: 2 - 3, weight 2.5</title></line>"));
+        assert!(svg.contains("<g>\n    <title>Vertex 2, degree 2</title>\n    <circle"));
+        assert_eq!(svg.matches("<g>").count(), 3);
+        assert_eq!(svg.matches("</g>").count(), 3);
+
+        config.edge_config.curvature = 0.2;
+        let curved =
+            DrawableGraph::from_positions(&graph, &embedding.positions(), &config).to_svg_string();
+        assert!(curved.contains("<title>Edge 1: 1 - 2</title></path>"));
+        assert!(curved.contains("<title>Vertex 1, degree 1</title>"));
+    }
+
+    #[test]
+    fn test_nested_elements_are_indented() {
+        let graph = DrawableGraph {
+            vertices: vec![DrawableVertex {
+                title: Some("Vertex 1".to_string()),
+                label: Some(DrawableLabel {
+                    content: "1".to_string(),
+                    position: vec2(5.0, 25.0),
+                    size: 20.0,
+                    color: BLACK,
+                    halo: None,
+                }),
+                ..test_vertex(false)
+            }],
+            edges: vec![DrawableEdge::default()],
+        };
+
+        let mut writer = SVGWriter::new();
+        writer.write_header(100.0, 100.0).unwrap();
+        writer.add_item_at(&graph, vec2(10.0, 20.0)).unwrap();
+        writer.finalise().unwrap();
+        let string = writer.take_string().unwrap();
+
+        let indentation = |prefix: &str| {
+            let line = string
+                .lines()
+                .find(|line| line.trim_start().starts_with(prefix))
+                .unwrap();
+            line.len() - line.trim_start().len()
+        };
+
+        assert_eq!(indentation("<g transform"), 4);
+        assert_eq!(indentation("<line"), 8);
+        assert_eq!(indentation("<g>"), 8);
+        assert_eq!(indentation("<title>"), 12);
+        assert_eq!(indentation("<circle"), 12);
+        assert_eq!(indentation("<text"), 12);
+
+        let closing: Vec<usize> = string
+            .lines()
+            .filter(|line| line.trim_start() == "</g>")
+            .map(|line| line.len() - line.trim_start().len())
+            .collect();
+        assert_eq!(closing, vec![8, 4]);
+    }
+
+    #[test]
+    fn test_lane_separators_behind_graph() {
+        let graph = DrawableGraph {
+            vertices: vec![DrawableVertex::default()],
+            edges: Vec::new(),
+        };
+        let export = |lanes: Option<LaneConfig>| {
+            let options = SVGExportOptions {
+                lanes,
+                ..Default::default()
+            };
+            let mut writer = SVGWriter::new();
+            write_graph(
+                &mut writer,
+                &graph,
+                &AnnotationLayer::new(),
+                vec2(200.0, 100.0),
+                &options,
+            )
+            .unwrap();
+            writer.take_string().unwrap()
+        };
+
+        assert!(!export(None).contains("<line"));
+
+        let string = export(Some(LaneConfig {
+            count: 2,
+            ..Default::default()
+        }));
+        assert_eq!(string.matches("<line").count(), 1);
+        assert!(string.contains(r#"<line x1="0" y1="50" x2="200" y2="50""#));
+        assert!(string.find("<line").unwrap() < string.find("<circle").unwrap());
+    }
+
+    #[test]
+    fn test_graphs_in_grid() {
+        let graph = DrawableGraph {
+            vertices: vec![DrawableVertex::default()],
+            edges: Vec::new(),
+        };
+        let graphs = [&graph; 4];
+
+        let mut writer = SVGWriter::new();
+        write_graphs(&mut writer, &graphs, vec2(200.0, 100.0), 2, 10.0).unwrap();
+        let string = writer.take_string().unwrap();
+
+        assert!(string.contains(r#"viewBox="0 0 410 210""#));
+        for offset in ["0 0", "210 0", "0 110", "210 110"] {
+            assert!(string.contains(&format!(r#"<g transform="translate({})">"#, offset)));
+        }
+        assert_eq!(string.matches("</g>").count(), 4);
+        assert!(string.contains(r#"        <circle"#));
+    }
+
+    #[test]
+    fn test_two_panels_at_offsets() {
+        let panel = |x: f32| DrawableGraph {
+            vertices: vec![DrawableVertex {
+                position: vec2(x, 50.0),
+                ..Default::default()
+            }],
+            edges: Vec::new(),
+        };
+
+        let mut writer = SVGWriter::new();
+        writer.write_header(400.0, 100.0).unwrap();
+        writer.add_item_at(&panel(11.0), vec2(0.0, 0.0)).unwrap();
+        writer.add_item_at(&panel(22.0), vec2(200.0, 0.0)).unwrap();
+        writer.finalise().unwrap();
+        let string = writer.take_string().unwrap();
+
+        let first = string.find(r#"<g transform="translate(0 0)">"#).unwrap();
+        let second = string.find(r#"<g transform="translate(200 0)">"#).unwrap();
+        let first_vertex = string.find(r#"cx="11""#).unwrap();
+        let second_vertex = string.find(r#"cx="22""#).unwrap();
+        assert!(first < first_vertex && first_vertex < second && second < second_vertex);
+        assert_eq!(string.matches("</g>").count(), 2);
+    }
+
+    #[test]
+    fn test_nested_groups_must_be_closed() {
+        let mut writer = SVGWriter::new();
+        writer.write_header(100.0, 100.0).unwrap();
+        writer.begin_group_translated(10.0, 0.0).unwrap();
+        writer.begin_group_translated(0.0, 10.0).unwrap();
+        writer.end_group().unwrap();
+
+        assert!(matches!(
+            writer.finalise(),
+            Err(SVGWriterError::UnexpectedIndentationLevel {
+                expected: 1,
+                found: 2
+            })
+        ));
+
+        writer.end_group().unwrap();
+        assert!(writer.end_group().is_err());
+        writer.finalise().unwrap();
+
+        let string = writer.take_string().unwrap();
+        assert!(string.contains("\n        <g transform=\"translate(0 10)\">\n        </g>\n"));
+    }
+
+    #[test]
+    fn test_edge_orientation_arrows() {
+        let mut graph = Graph::new(4);
+        graph.edges.extend([(0, 1), (2, 3), (0, 2)]);
+        graph.set_orientation(0, 1, Orientation::Forward);
+        graph.set_orientation(2, 3, Orientation::Backward);
+        let positions = [
+            vec2(0.0, 0.0),
+            vec2(100.0, 0.0),
+            vec2(0.0, 100.0),
+            vec2(100.0, 100.0),
+        ];
+        let drawable = DrawableGraph::from_positions(&graph, &positions, &DrawConfig::default());
+
+        let mut writer = SVGWriter::new();
+        write_graph(
+            &mut writer,
+            &drawable,
+            &AnnotationLayer::new(),
+            vec2(100.0, 100.0),
+            &SVGExportOptions::default(),
+        )
+        .unwrap();
+        let string = writer.take_string().unwrap();
+
+        assert_eq!(string.matches("<polygon").count(), 2);
+        // the tips stop at the border of the head vertex, which has radius 17
+        assert!(string.contains(r#"<polygon points="83,0"#));
+        assert!(string.contains(r#"<polygon points="17,100"#));
+    }
 }
@@ -0,0 +1,234 @@
+use std::{
+    any::Any,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc,
+    },
+    thread::JoinHandle,
+};
+
+use thiserror::Error;
+
+/// Handed to a running task, to report progress and to notice cancellation.
+pub struct TaskContext {
+    cancelled: Arc<AtomicBool>,
+    progress: Sender<f32>,
+}
+
+impl TaskContext {
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Fraction of the work done, between 0 and 1.
+    pub fn report_progress(&self, fraction: f32) {
+        let _ = self.progress.send(fraction.clamp(0.0, 1.0));
+    }
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum TaskError {
+    #[error("{0} was cancelled")]
+    Cancelled(String),
+    #[error("{0} failed: {1}")]
+    Panicked(String, String),
+}
+
+/// A computation on its own thread, so that the frame keeps being drawn while it runs. The task
+/// returns `None` when it stops because it was cancelled.
+pub struct BackgroundTask<T> {
+    name: String,
+    cancelled: Arc<AtomicBool>,
+    progress: f32,
+    progress_receiver: Receiver<f32>,
+    result_receiver: Receiver<Result<T, TaskError>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl<T: Send + 'static> BackgroundTask<T> {
+    pub fn spawn<F>(name: &str, task: F) -> Self
+    where
+        F: FnOnce(&TaskContext) -> Option<T> + Send + 'static,
+    {
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let (progress_sender, progress_receiver) = mpsc::channel();
+        let (result_sender, result_receiver) = mpsc::channel();
+
+        let context = TaskContext {
+            cancelled: cancelled.clone(),
+            progress: progress_sender,
+        };
+        let task_name = name.to_string();
+        let worker = std::thread::spawn(move || {
+            let result = match panic::catch_unwind(AssertUnwindSafe(|| task(&context))) {
+                Ok(Some(value)) if !context.is_cancelled() => Ok(value),
+                Ok(_) => Err(TaskError::Cancelled(task_name)),
+                Err(payload) => Err(TaskError::Panicked(task_name, panic_message(payload))),
+            };
+            let _ = result_sender.send(result);
+        });
+
+        Self {
+            name: name.to_string(),
+            cancelled,
+            progress: 0.0,
+            progress_receiver,
+            result_receiver,
+            worker: Some(worker),
+        }
+    }
+}
+
+impl<T> BackgroundTask<T> {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Latest reported progress, as of the last [`Self::poll`].
+    pub fn progress(&self) -> f32 {
+        self.progress
+    }
+
+    /// Asks the task to stop. It is up to the task to check [`TaskContext::is_cancelled`].
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Takes the result once the task has finished, without blocking.
+    pub fn poll(&mut self) -> Option<Result<T, TaskError>> {
+        self.drain_progress();
+
+        let result = self.result_receiver.try_recv().ok()?;
+        // progress reported right before the result may have arrived after the first drain
+        self.drain_progress();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+
+        Some(result)
+    }
+
+    fn drain_progress(&mut self) {
+        if let Some(progress) = self.progress_receiver.try_iter().last() {
+            self.progress = progress;
+        }
+    }
+}
+
+impl<T> Drop for BackgroundTask<T> {
+    /// Leaves a task that is still running to notice the cancellation on its own, instead of
+    /// waiting for it.
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown error".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::*;
+
+    /// Polls until the task finishes, failing the test when it takes too long.
+    fn wait_for<T>(task: &mut BackgroundTask<T>) -> Result<T, TaskError> {
+        let start = Instant::now();
+        loop {
+            if let Some(result) = task.poll() {
+                return result;
+            }
+            assert!(
+                start.elapsed() < Duration::from_secs(10),
+                "task never finished"
+            );
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn test_task_completes_with_progress() {
+        let mut task = BackgroundTask::spawn("Sum", |context| {
+            let mut sum = 0;
+            for step in 1..=4 {
+                sum += step;
+                context.report_progress(step as f32 / 4.0);
+            }
+            Some(sum)
+        });
+
+        assert_eq!(task.name(), "Sum");
+        assert_eq!(wait_for(&mut task), Ok(10));
+        assert_eq!(task.progress(), 1.0);
+    }
+
+    #[test]
+    fn test_cancelled_task() {
+        let mut task = BackgroundTask::spawn("Count", |context| {
+            let mut count = 0u64;
+            while !context.is_cancelled() {
+                count += 1;
+                std::thread::yield_now();
+            }
+            // the partial count is dropped
+            Some(count)
+        });
+
+        task.cancel();
+        assert_eq!(
+            wait_for(&mut task),
+            Err(TaskError::Cancelled("Count".to_string()))
+        );
+
+        let mut task = BackgroundTask::<u64>::spawn("Stop", |_| None);
+        assert_eq!(
+            wait_for(&mut task),
+            Err(TaskError::Cancelled("Stop".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_panics_become_errors() {
+        let mut task = BackgroundTask::<u32>::spawn("Layout", |_| panic!("no vertices"));
+        let error = wait_for(&mut task).unwrap_err();
+        assert_eq!(
+            error,
+            TaskError::Panicked("Layout".to_string(), "no vertices".to_string())
+        );
+        assert_eq!(error.to_string(), "Layout failed: no vertices");
+
+        let mut task = BackgroundTask::<u32>::spawn("Index", |_| {
+            let values: Vec<u32> = Vec::new();
+            Some(values[values.len()])
+        });
+        let Err(TaskError::Panicked(_, message)) = wait_for(&mut task) else {
+            panic!("expected a panic");
+        };
+        assert!(message.contains("index out of bounds"));
+    }
+
+    #[test]
+    fn test_dropping_cancels() {
+        let (sender, receiver) = mpsc::channel();
+        let task = BackgroundTask::spawn("Wait", move |context| {
+            while !context.is_cancelled() {
+                std::thread::yield_now();
+            }
+            sender.send(()).unwrap();
+            Some(())
+        });
+
+        drop(task);
+        assert!(receiver.recv_timeout(Duration::from_secs(10)).is_ok());
+    }
+}
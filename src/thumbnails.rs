@@ -0,0 +1,213 @@
+use macroquad::prelude::*;
+
+use crate::{
+    graph::Graph,
+    graph_drawer::{DrawConfig, Drawable, DrawableGraph},
+    graph_interface::GraphInterface,
+};
+
+pub const THUMBNAIL_WIDTH: f32 = 120.0;
+pub const THUMBNAIL_HEIGHT: f32 = 80.0;
+/// Largest vertex displacement in pixels that keeps the rendered thumbnails.
+const MOVE_TOLERANCE: f32 = 20.0;
+const MARGIN: f32 = 30.0;
+
+/// Miniature renderings of the highlight history, one render target per entry.
+pub struct HistoryThumbnails {
+    targets: Vec<RenderTarget>,
+    rendered_positions: Vec<Vec2>,
+    rendered_edges: Vec<(usize, usize)>,
+    rendered_config: Option<DrawConfig>,
+    /// Id and color of the history entry of every target.
+    rendered_entries: Vec<(usize, Color)>,
+}
+
+impl HistoryThumbnails {
+    pub fn new() -> Self {
+        Self {
+            targets: Vec::new(),
+            rendered_positions: Vec::new(),
+            rendered_edges: Vec::new(),
+            rendered_config: None,
+            rendered_entries: Vec::new(),
+        }
+    }
+
+    pub fn textures(&self) -> impl Iterator<Item = Texture2D> + '_ {
+        self.targets.iter().map(|target| target.texture)
+    }
+
+    fn is_stale(&self, positions: &[Vec2], edges: &[(usize, usize)], config: &DrawConfig) -> bool {
+        self.rendered_config.as_ref() != Some(config)
+            || self.rendered_edges != edges
+            || moved_significantly(&self.rendered_positions, positions, MOVE_TOLERANCE)
+    }
+
+    /// Renders thumbnails for new or moved history entries, or all of them when the drawing is
+    /// stale.
+    pub fn update(&mut self, graph: &Graph, embedding: &GraphInterface, config: &DrawConfig) {
+        let history = embedding.highlight_history();
+
+        for target in self.targets.drain(history.len().min(self.targets.len())..) {
+            target.delete();
+        }
+
+        let positions = embedding.positions();
+        let first_stale = if self.is_stale(&positions, &graph.edges, config) {
+            self.rendered_positions = positions;
+            self.rendered_edges = graph.edges.clone();
+            self.rendered_config = Some(config.clone());
+            0
+        } else {
+            history
+                .iter()
+                .zip(&self.rendered_entries)
+                .take(self.targets.len())
+                .position(|(entry, &rendered)| {
+                    rendered != (entry.id, config.history_entry_color(entry))
+                })
+                .unwrap_or(self.targets.len())
+        };
+        self.rendered_entries = history
+            .iter()
+            .map(|entry| (entry.id, config.history_entry_color(entry)))
+            .collect();
+
+        if first_stale == history.len() {
+            return;
+        }
+
+        let mut thumbnail_config = config.clone();
+        thumbnail_config.vertex_config.draw_index = false;
+        thumbnail_config.edge_config.draw_index = false;
+
+        for (index, entry) in history.iter().enumerate().skip(first_stale) {
+            if index == self.targets.len() {
+                let target = render_target(THUMBNAIL_WIDTH as u32, THUMBNAIL_HEIGHT as u32);
+                target.texture.set_filter(FilterMode::Linear);
+                self.targets.push(target);
+            }
+
+            let drawable = thumbnail_drawable(
+                graph,
+                &self.rendered_positions,
+                &entry.graph,
+                config.history_entry_color(entry),
+                &thumbnail_config,
+            );
+            render_thumbnail(
+                &drawable,
+                &self.rendered_positions,
+                self.targets[index],
+                config,
+            );
+        }
+    }
+}
+
+/// Whether any vertex moved further than `tolerance`, or vertices were added or removed.
+fn moved_significantly(old: &[Vec2], new: &[Vec2], tolerance: f32) -> bool {
+    old.len() != new.len()
+        || old
+            .iter()
+            .zip(new)
+            .any(|(old, new)| old.distance(*new) > tolerance)
+}
+
+/// Drawing of `graph` with the edges of `highlight` in `highlight_color`.
+pub fn thumbnail_drawable(
+    graph: &Graph,
+    positions: &[Vec2],
+    highlight: &Graph,
+    highlight_color: Color,
+    config: &DrawConfig,
+) -> DrawableGraph {
+    let mut drawable = DrawableGraph::from_positions(graph, positions, config);
+
+    for (edge, &(a, b)) in drawable.edges.iter_mut().zip(&graph.edges) {
+        if highlight.has_edge(a, b) {
+            edge.color = highlight_color;
+        }
+    }
+
+    drawable
+}
+
+fn render_thumbnail(
+    drawable: &DrawableGraph,
+    positions: &[Vec2],
+    target: RenderTarget,
+    config: &DrawConfig,
+) {
+    let (min, max) = positions.iter().fold(
+        (Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)),
+        |(min, max), &position| (min.min(position), max.max(position)),
+    );
+    let (min, max) = if positions.is_empty() {
+        (Vec2::ZERO, Vec2::ONE)
+    } else {
+        (min - MARGIN, max + MARGIN)
+    };
+
+    // keep the aspect ratio of the thumbnail, centering the graph
+    let size = max - min;
+    let scale = (size.x / THUMBNAIL_WIDTH).max(size.y / THUMBNAIL_HEIGHT);
+    let display_size = vec2(THUMBNAIL_WIDTH, THUMBNAIL_HEIGHT) * scale;
+    let center = (min + max) / 2.0;
+
+    let mut camera = Camera2D::from_display_rect(Rect::new(
+        center.x - display_size.x / 2.0,
+        center.y - display_size.y / 2.0,
+        display_size.x,
+        display_size.y,
+    ));
+    // render targets are sampled bottom-up
+    camera.zoom.y = -camera.zoom.y;
+    camera.render_target = Some(target);
+
+    set_camera(&camera);
+    clear_background(config.background_color);
+    drawable.draw();
+    set_default_camera();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_moved_significantly() {
+        let old = [vec2(0.0, 0.0), vec2(100.0, 100.0)];
+
+        assert!(!moved_significantly(&old, &old, 5.0));
+        assert!(!moved_significantly(
+            &old,
+            &[vec2(3.0, 0.0), vec2(100.0, 104.0)],
+            5.0
+        ));
+        assert!(moved_significantly(
+            &old,
+            &[vec2(0.0, 0.0), vec2(110.0, 100.0)],
+            5.0
+        ));
+        assert!(moved_significantly(&old, &old[..1], 5.0));
+    }
+
+    #[test]
+    fn test_stale_when_config_or_edges_change() {
+        let mut thumbnails = HistoryThumbnails::new();
+        let config = DrawConfig::default();
+        let positions = [vec2(0.0, 0.0), vec2(50.0, 0.0)];
+        assert!(thumbnails.is_stale(&positions, &[(0, 1)], &config));
+
+        thumbnails.rendered_positions = positions.to_vec();
+        thumbnails.rendered_edges = vec![(0, 1)];
+        thumbnails.rendered_config = Some(config.clone());
+        assert!(!thumbnails.is_stale(&positions, &[(0, 1)], &config));
+        assert!(thumbnails.is_stale(&positions, &[], &config));
+
+        let mut changed_config = config.clone();
+        changed_config.edge_config.highlight_color = BLUE;
+        assert!(thumbnails.is_stale(&positions, &[(0, 1)], &changed_config));
+    }
+}
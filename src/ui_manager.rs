@@ -1,61 +1,378 @@
 use macroquad::prelude::*;
-use macroquad::ui::{hash, root_ui, widgets};
+use macroquad::ui::{hash, root_ui, widgets, Id, Ui};
+use std::{
+    fmt::Display,
+    path::{Path, PathBuf},
+};
 
-use crate::graph::parse_graph6_string;
-use crate::graph_drawer::DrawConfig;
-use crate::graph_interface::GraphInterface;
-use crate::svg_writer::draw_graph_to_file;
+use crate::annotations::{AnnotationLayer, AnnotationTool};
+use crate::edge_labels::parse_edge_labels;
+use crate::file_names::{file_name_or_default, resolve_export_path};
+use crate::force::{ForceConfig, RepulsionModel};
+use crate::generators::Generator;
+use crate::graph::{
+    graph6_degree_sequence, parse_graph6_multi, parse_graph6_string, parse_vertex_list, Graph,
+    SequenceEdges, SubgraphMismatch,
+};
+use crate::graph_drawer::{
+    BackgroundMode, DrawConfig, DrawQuality, DrawableGraph, VertexLabelPlacement,
+};
+use crate::graph_interface::{
+    DrawState, EdgeProperties, EdgeStyle, GraphInterface, HiddenVertexEdges, Selection,
+    VertexProperties, EDGE_HOVER_LIMIT,
+};
+use crate::indexing::Indexing;
+use crate::input::InputConfig;
+use crate::label_placement::LabelPlacement;
+use crate::lanes::{lanes_from_depths, parse_lanes, LaneMode};
+use crate::palette::Palette;
+use crate::positions::{fit_to_rect, format_positions, import_graph_with_positions};
+use crate::recorder::{frame_file_name, FileFrameWriter, Recorder};
+use crate::report::{current_timestamp, format_timestamp, graph_report};
+use crate::rng::SeededRng;
+use crate::scripted_run::{parse_script, PlayerState, ScriptPlayer};
+use crate::svg_writer::{
+    draw_graph_to_file, draw_graphs_to_file, SVGExportOptions, SVGWriterError, SVG_DECIMALS,
+};
+use crate::thumbnails::{thumbnail_drawable, THUMBNAIL_HEIGHT, THUMBNAIL_WIDTH};
+use crate::viewport::ResizeMode;
 use crate::Content;
 
 pub const UI_WIDTH: f32 = 300.0;
+const HISTORY_EXPORT_COLUMNS: usize = 3;
+/// Space kept free around a layout fitted to the view.
+const FIT_MARGIN: f32 = 40.0;
+/// Larger graphs only count their crossings on request, in the background.
+const LIVE_CROSSING_COUNT_LIMIT: usize = 500;
+const PROGRESS_BAR_HEIGHT: f32 = 12.0;
+const SWATCH_SIZE: f32 = 12.0;
+/// Missing edges listed by name when a highlight graph isn't a subgraph.
+const LISTED_MISSING_EDGES: usize = 5;
+pub const EMPTY_GRAPH_MESSAGE: &str =
+    "The graph is empty, load or generate a graph to start drawing";
 
 pub struct UIData {
+    pub graph_name: String,
     pub g6_string: String,
+    /// Graphs of the last import with several graphs, with their g6 strings.
+    pub imported_graphs: Vec<(String, Graph)>,
+    pub imported_graph_index: usize,
     pub highlight_g6_string: String,
+    pub accumulate_highlighting: bool,
+    /// New name for the shown highlight history entry.
+    pub highlight_entry_name: String,
+    pub product_g6_string: String,
+    pub path_source: u32,
+    pub incident_vertex: u32,
+    pub vertex_subset: String,
+    pub vertex_sequence: String,
+    pub edge_endpoints: String,
+    /// New endpoints of the inspected edge.
+    pub reconnect_endpoints: String,
+    pub path_target: u32,
     pub keep_embedding: bool,
+    pub empty_graph_vertices: u32,
+    pub generator: Generator,
+    pub generator_size: u32,
+    pub generator_second_size: u32,
     pub apply_force: bool,
+    pub force_config: ForceConfig,
+    pub lane_root: u32,
+    pub lane_file_name: String,
     pub align_to_square_grid: bool,
     pub align_to_circular_grid: bool,
+    pub align_edge_angles: bool,
+    pub edge_angle_strength: f32,
     pub grid_size: f32,
+    pub snap_size: f32,
     pub svg_file_name: String,
+    /// Where Ctrl+E saves timestamped exports.
+    pub export_directory: String,
+    /// Minutes between autosaves, no autosave when zero.
+    pub autosave_minutes: u32,
+    /// Most layout steps of a local relax.
+    pub local_relax_steps: u32,
+    pub export_fixed_size: bool,
+    pub export_integer_coordinates: bool,
+    /// Decimals of the coordinates in SVG exports.
+    pub export_decimals: u32,
+    /// Export collapsed groups as their meta-vertices instead of the full graph.
+    pub export_collapsed_view: bool,
+    /// Exports create the missing directories of their path instead of failing.
+    pub export_create_directories: bool,
+    pub export_width: u32,
+    pub export_height: u32,
+    /// Set by the UI, the canvas can only be read back once the graph is drawn.
+    pub copy_image_requested: bool,
+    pub png_file_name: String,
+    /// Size of PNG exports relative to the canvas.
+    pub png_scale: f32,
+    /// Set by the UI, exported after the frame is drawn.
+    pub png_export_requested: bool,
+    /// Center vertex of a radial layout on the circular grid, set by the UI.
+    pub radial_layout_center: Option<usize>,
+    pub adjacency_list_file_name: String,
+    pub positions_file_name: String,
+    pub coordinates_text: String,
+    pub report_file_name: String,
+    pub edge_label_file_name: String,
+    pub script_file_name: String,
+    pub recording_base_name: String,
+    pub recording_every_nth_step: u32,
+    pub recording_max_frames: u32,
+    pub recording_max_megabytes: u32,
+    pub messages: Vec<String>,
+    pub show_fps: bool,
+    /// Toggled with Tab, so that the graph gets the whole window.
+    pub panel_hidden: bool,
+    /// Whether the last click went to a UI window. macroquad doesn't tell which widget has the
+    /// input focus, so hotkeys are ignored until the canvas is clicked again.
+    pub keyboard_in_ui: bool,
+    pub shake_magnitude: f32,
+    pub shake_seed: u32,
+    pub auto_shake: bool,
+    pub auto_shake_crossing_target: u32,
+    pub auto_shake_max_restarts: u32,
+    pub auto_shake_restarts: u32,
+    pub resize_mode: ResizeMode,
+    pub input_config: InputConfig,
+    window_generation: usize,
     pub draw_config: DrawConfig,
 }
 
 impl UIData {
     pub fn new() -> Self {
         Self {
+            graph_name: "graph".to_string(),
             g6_string: String::new(),
+            imported_graphs: Vec::new(),
+            imported_graph_index: 0,
             highlight_g6_string: String::new(),
+            accumulate_highlighting: false,
+            highlight_entry_name: String::new(),
+            product_g6_string: String::new(),
+            path_source: 0,
+            incident_vertex: 0,
+            vertex_subset: String::new(),
+            vertex_sequence: String::new(),
+            edge_endpoints: String::new(),
+            reconnect_endpoints: String::new(),
+            path_target: 0,
             keep_embedding: false,
+            empty_graph_vertices: 4,
+            generator: Generator::default(),
+            generator_size: 5,
+            generator_second_size: 3,
             apply_force: false,
+            force_config: ForceConfig::default(),
+            lane_root: 0,
+            lane_file_name: String::new(),
             align_to_square_grid: false,
             align_to_circular_grid: false,
+            align_edge_angles: false,
+            edge_angle_strength: 0.1,
             grid_size: 30.0,
+            snap_size: 30.0,
             svg_file_name: String::new(),
+            export_directory: String::new(),
+            autosave_minutes: 2,
+            local_relax_steps: 200,
+            export_fixed_size: false,
+            export_integer_coordinates: false,
+            export_decimals: SVG_DECIMALS as u32,
+            export_collapsed_view: false,
+            export_create_directories: false,
+            export_width: 800,
+            export_height: 600,
+            copy_image_requested: false,
+            png_file_name: String::new(),
+            png_scale: 2.0,
+            png_export_requested: false,
+            radial_layout_center: None,
+            adjacency_list_file_name: String::new(),
+            positions_file_name: String::new(),
+            coordinates_text: String::new(),
+            report_file_name: String::new(),
+            edge_label_file_name: String::new(),
+            script_file_name: String::new(),
+            recording_base_name: "frame".to_string(),
+            recording_every_nth_step: 5,
+            recording_max_frames: 500,
+            recording_max_megabytes: 100,
+            messages: Vec::new(),
+            show_fps: false,
+            panel_hidden: false,
+            keyboard_in_ui: false,
+            shake_magnitude: 40.0,
+            shake_seed: 0,
+            auto_shake: false,
+            auto_shake_crossing_target: 0,
+            auto_shake_max_restarts: 5,
+            auto_shake_restarts: 0,
+            resize_mode: ResizeMode::Clamp,
+            input_config: InputConfig::default(),
+            window_generation: 0,
             draw_config: DrawConfig::default(),
         }
     }
+
+    /// Options of an SVG export with the current settings.
+    pub fn export_options(&self) -> SVGExportOptions {
+        SVGExportOptions {
+            title: self.graph_name.clone(),
+            target_size: self
+                .export_fixed_size
+                .then(|| vec2(self.export_width as f32, self.export_height as f32)),
+            lanes: self
+                .force_config
+                .lanes
+                .export_separators
+                .then(|| self.force_config.lanes.clone()),
+            integer_coordinates: self.export_integer_coordinates,
+            background: self.draw_config.export_background(),
+            create_directories: self.export_create_directories,
+            decimals: self.export_decimals as usize,
+        }
+    }
+
+    /// Rebuilds the settings window so it picks up the new screen geometry.
+    pub fn handle_viewport_change(&mut self) {
+        self.window_generation += 1;
+    }
+
+    /// Size of the graph canvas, the window without the settings panel when it's shown.
+    pub fn canvas_size(&self) -> Vec2 {
+        vec2(main_screen_width(self.panel_hidden), screen_height())
+    }
+
+    /// Call once per frame, see [`Self::keyboard_in_ui`].
+    pub fn track_keyboard_focus(&mut self) {
+        if is_mouse_button_pressed(MouseButton::Left) {
+            self.keyboard_in_ui = root_ui().is_mouse_over(mouse_position().into());
+        }
+    }
+
+    /// Whether hotkeys should act, rather than go to a text field.
+    pub fn hotkeys_enabled(&self) -> bool {
+        !self.keyboard_in_ui
+    }
+
+    pub fn toggle_panel(&mut self) {
+        self.panel_hidden = !self.panel_hidden;
+    }
 }
 
 pub fn handle_ui(content: &mut Content) {
+    if content.ui_data.panel_hidden {
+        return;
+    }
+
     let data = &mut content.ui_data;
+    let mut stepped_command = None;
+    let mut cycled_edge = None;
+    let mut restored_autosave = None;
+    let mut relax_requested = false;
+    let mut count_crossings_requested = false;
 
-    let id = hash!();
+    let id = hash!("settings", data.window_generation);
 
     widgets::Window::new(
         id,
-        vec2(main_screen_width(), 0.0),
+        vec2(main_screen_width(data.panel_hidden), 0.0),
         vec2(UI_WIDTH, screen_height()),
     )
     .label("Settings")
     .titlebar(true)
     .movable(false)
     .ui(&mut root_ui(), |ui| {
+        if let Some(task) = &content.crossing_task {
+            ui.label(
+                None,
+                &format!("{}: {:.0}%", task.name(), task.progress() * 100.0),
+            );
+            progress_bar(ui, task.progress());
+            if ui.button(None, "Cancel") {
+                task.cancel();
+            }
+            ui.separator();
+        }
+        if let Some(progress) = content.local_relax.as_ref().map(|relax| relax.progress()) {
+            ui.label(None, &format!("Local relax: {:.0}%", progress * 100.0));
+            progress_bar(ui, progress);
+            if ui.button(None, "Cancel") {
+                content.local_relax = None;
+            }
+            ui.separator();
+        }
+        if let Some(snapshot) = &content.restorable_autosave {
+            ui.label(
+                None,
+                &format!("Unsaved work from {}", format_timestamp(snapshot.timestamp)),
+            );
+            if ui.button(None, "Restore") {
+                restored_autosave = content.restorable_autosave.take();
+            }
+            ui.same_line(0.0);
+            if ui.button(None, "Dismiss") {
+                content.restorable_autosave = None;
+            }
+            ui.separator();
+        }
         ui.tree_node(hash!(), "controls", |ui| {
             if ui.button(None, "Reset embedding") {
-                content.embedding = GraphInterface::new(&content.graph);
+                content.embedding =
+                    GraphInterface::with_viewport(&content.graph, content.embedding.viewport);
+                content.action_log.push("Reset embedding".to_string());
             };
+            if ui.button(None, "Fit to view") {
+                let view = Rect::new(
+                    0.0,
+                    0.0,
+                    main_screen_width(data.panel_hidden),
+                    screen_height(),
+                );
+                let positions = fit_to_rect(&content.embedding.positions(), view, FIT_MARGIN);
+                content.embedding.set_positions(&positions);
+                content.action_log.push("Fit layout to view".to_string());
+            }
+            let applied_force = data.apply_force;
             ui.checkbox(hash!(), "Apply force", &mut data.apply_force);
+            if data.apply_force != applied_force {
+                let entry = if data.apply_force {
+                    content.embedding.start_force(&data.force_config);
+                    "Started force layout"
+                } else {
+                    "Stopped force layout"
+                };
+                content.action_log.push(entry.to_string());
+            }
+            let mut repulsion_index = data.force_config.repulsion.index();
+            ui.combo_box(
+                hash!(),
+                "repulsion",
+                &RepulsionModel::NAMES,
+                &mut repulsion_index,
+            );
+            data.force_config.repulsion = RepulsionModel::ALL[repulsion_index];
+            ui.checkbox(
+                hash!(),
+                "Break symmetry on start",
+                &mut data.force_config.break_symmetry,
+            );
+            ui.checkbox(
+                hash!(),
+                "Move selection only",
+                &mut data.force_config.selected_only,
+            );
+            ui.drag(
+                hash!(),
+                "relax steps",
+                (1, 2000),
+                &mut data.local_relax_steps,
+            );
+            if ui.button(None, "Local relax") {
+                relax_requested = true;
+            }
             ui.checkbox(
                 hash!(),
                 "Align to square grid",
@@ -66,40 +383,1208 @@ pub fn handle_ui(content: &mut Content) {
                 "Align to circular grid",
                 &mut data.align_to_circular_grid,
             );
+            if data.align_to_circular_grid && ui.button(None, "Radial layout from vertex") {
+                let center = match content.embedding.selected {
+                    Some(Selection::Vertex(vertex)) => Some(vertex),
+                    _ => content.embedding.hovered_vertex,
+                };
+                match center {
+                    Some(center) => data.radial_layout_center = Some(center),
+                    None => data
+                        .messages
+                        .push("Select the center vertex of the radial layout".to_string()),
+                }
+            }
+            ui.checkbox(
+                hash!(),
+                "Align edges to 45° angles",
+                &mut data.align_edge_angles,
+            );
+            if data.align_edge_angles {
+                ui.slider(
+                    hash!(),
+                    "angle strength",
+                    0.0..1.0,
+                    &mut data.edge_angle_strength,
+                );
+            }
+            ui.checkbox(hash!(), "Show FPS", &mut data.show_fps);
+            ui.drag(
+                hash!(),
+                "autosave minutes",
+                (0, 60),
+                &mut data.autosave_minutes,
+            );
+
+            let mut resize_index = data.resize_mode.index();
+            ui.combo_box(hash!(), "on resize", &ResizeMode::NAMES, &mut resize_index);
+            data.resize_mode = ResizeMode::ALL[resize_index];
+
+            let input_config = &mut data.input_config;
+            ui.slider(
+                hash!(),
+                "drag delay (s)",
+                0.0..0.5,
+                &mut input_config.drag_delay,
+            );
+            ui.slider(
+                hash!(),
+                "drag distance",
+                0.0..30.0,
+                &mut input_config.drag_distance,
+            );
+            ui.slider(
+                hash!(),
+                "double click delay (s)",
+                0.1..1.0,
+                &mut input_config.double_click_delay,
+            );
+            ui.slider(
+                hash!(),
+                "hit tolerance",
+                0.0..30.0,
+                &mut input_config.hit_tolerance,
+            );
+            ui.checkbox(hash!(), "hover edges", &mut input_config.hover_edges);
+            if input_config.hover_edges && !content.embedding.edges_hoverable() {
+                ui.label(
+                    None,
+                    &format!("Edges aren't hovered above {} edges", EDGE_HOVER_LIMIT),
+                );
+            }
+
             ui.slider(hash!(), "grid size", 10.0..50.0, &mut data.grid_size);
+            ui.slider(hash!(), "snap size", 10.0..50.0, &mut data.snap_size);
+            if ui.button(None, "Freeze selection") {
+                content.embedding.freeze_selection();
+            }
+            if ui.button(None, "Unfreeze all") {
+                content.embedding.unfreeze_all();
+            }
+            if ui.button(None, "Collapse selection") {
+                let selection = content.embedding.selected_vertices.clone();
+                match content.embedding.collapse(&selection) {
+                    Some(_) => content
+                        .action_log
+                        .push(format!("Collapsed {} vertices", selection.len())),
+                    None => data.messages.push(
+                        "Select at least two vertices that are not collapsed yet".to_string(),
+                    ),
+                }
+            }
+            let groups = content.embedding.collapsed_groups().len();
+            if groups > 0 && ui.button(None, format!("Expand {} groups", groups).as_str()) {
+                content.embedding.expand_all();
+            }
+
+            ui.separator();
+
+            ui.slider(hash!(), "shake size", 1.0..200.0, &mut data.shake_magnitude);
+            ui.drag(hash!(), "seed", None, &mut data.shake_seed);
+            if ui.button(None, "Reset seed") {
+                content.rng = SeededRng::new(data.shake_seed as u64);
+            }
+            if ui.button(None, "Shake") {
+                content
+                    .embedding
+                    .shake(&content.graph, data.shake_magnitude, &mut content.rng);
+                data.auto_shake_restarts = 0;
+                content
+                    .action_log
+                    .push(format!("Shook layout by {:.0}", data.shake_magnitude));
+            }
+            ui.checkbox(hash!(), "Shake when stuck", &mut data.auto_shake);
+            if data.auto_shake {
+                ui.drag(
+                    hash!(),
+                    "crossing target",
+                    None,
+                    &mut data.auto_shake_crossing_target,
+                );
+                ui.drag(
+                    hash!(),
+                    "max restarts",
+                    (0, 100),
+                    &mut data.auto_shake_max_restarts,
+                );
+                ui.label(
+                    None,
+                    &format!(
+                        "Restarts: {}/{}",
+                        data.auto_shake_restarts, data.auto_shake_max_restarts
+                    ),
+                );
+            }
+        });
+        ui.tree_node(hash!(), "lanes", |ui| {
+            let lanes = &mut data.force_config.lanes;
+            ui.drag(hash!(), "lanes", (0, 32), &mut lanes.count);
+            let mut mode_index = lanes.mode.index();
+            ui.combo_box(hash!(), "mode", &LaneMode::NAMES, &mut mode_index);
+            lanes.mode = LaneMode::ALL[mode_index];
+            if lanes.mode == LaneMode::Attract {
+                ui.slider(hash!(), "attraction", 0.0..1.0, &mut lanes.attraction);
+            }
+            ui.checkbox(hash!(), "draw separators", &mut lanes.draw_separators);
+            ui.checkbox(hash!(), "separators in SVG", &mut lanes.export_separators);
+
+            ui.separator();
+
+            ui.drag(hash!(), "root", (0, 64), &mut data.lane_root);
+            if ui.button(None, "Lanes from BFS depth") {
+                let root = data.draw_config.indexing.internal(data.lane_root as usize);
+                let depths = root
+                    .map(|root| content.graph.depths(root))
+                    .unwrap_or_default();
+                content
+                    .embedding
+                    .set_lanes(&lanes_from_depths(&depths, lanes.count));
+                content
+                    .action_log
+                    .push(format!("Assigned lanes by depth from {}", data.lane_root));
+            }
+
+            ui.label(None, "Lane file (one lane per vertex):");
+            ui.input_text(hash!(), "", &mut data.lane_file_name);
+            if ui.button(None, "Import lanes") {
+                let result = std::fs::read_to_string(&data.lane_file_name)
+                    .map_err(|error| error.to_string())
+                    .and_then(|text| parse_lanes(&text).map_err(|error| error.to_string()));
+
+                match result {
+                    Ok(lanes) => {
+                        content.embedding.set_lanes(&lanes);
+                        content
+                            .action_log
+                            .push(format!("Imported lanes from {}", data.lane_file_name));
+                    }
+                    Err(error) => data.messages.push(error),
+                }
+            }
+            if ui.button(None, "Clear lanes") {
+                content
+                    .embedding
+                    .set_lanes(&vec![None; content.graph.vertices]);
+            }
+        });
+        ui.tree_node(hash!(), "stats", |ui| {
+            ui.label(None, &format!("Vertices: {}", content.graph.vertices));
+            ui.label(None, &format!("Edges: {}", content.graph.edges.len()));
+            ui.label(
+                None,
+                &format!("Frozen vertices: {}", content.embedding.frozen_count()),
+            );
+            // counting is quadratic in the number of edges, large graphs are counted on request
+            if content.graph.edges.len() <= LIVE_CROSSING_COUNT_LIMIT {
+                ui.label(
+                    None,
+                    &format!("Edge crossings: {}", content.embedding.edge_crossings()),
+                );
+            } else {
+                match content.counted_crossings {
+                    Some(crossings) => ui.label(None, &format!("Edge crossings: {}", crossings)),
+                    None => ui.label(None, "Edge crossings: not counted"),
+                }
+                if ui.button(None, "Count crossings") {
+                    count_crossings_requested = true;
+                }
+            }
+
+            let graph = &content.graph;
+            let stats = &content.stats;
+            ui.label(None, &format!("Density: {:.3}", stats.density));
+            ui.label(None, &format!("Components: {}", stats.component_count));
+            ui.label(
+                None,
+                &format!("Bipartite: {}", if stats.bipartite { "yes" } else { "no" }),
+            );
+            match stats.girth {
+                Some(girth) => ui.label(None, &format!("Girth: {}", girth)),
+                None => ui.label(None, "Girth: acyclic"),
+            }
+            match (stats.diameter, stats.radius) {
+                (Some(diameter), Some(radius)) => {
+                    let center: Vec<String> = stats
+                        .center
+                        .iter()
+                        .map(|&vertex| data.draw_config.indexing.display(vertex).to_string())
+                        .collect();
+
+                    ui.label(None, &format!("Diameter: {}", diameter));
+                    ui.label(None, &format!("Radius: {}", radius));
+                    ui.label(None, &format!("Center: {}", center.join(", ")));
+                }
+                _ => ui.label(None, "Diameter: disconnected"),
+            }
+
+            ui.label(None, &format!("Clustering: {:.3}", stats.clustering));
+            if ui.button(None, "Color by local clustering") {
+                let vertex_config = &data.draw_config.vertex_config;
+                let (low, high) = (
+                    vertex_config.main_color.to_vec(),
+                    vertex_config.highlight_color.to_vec(),
+                );
+
+                for (properties, clustering) in content
+                    .embedding
+                    .vertex_properties
+                    .iter_mut()
+                    .zip(graph.local_clustering())
+                {
+                    properties.color_override = Some(Color::from_vec(low.lerp(high, clustering)));
+                }
+                content.embedding.mark_dirty();
+                content
+                    .action_log
+                    .push("Colored vertices by local clustering".to_string());
+            }
+            if ui.button(None, "Color components") {
+                let palette = data.draw_config.palette;
+                for (properties, component) in content
+                    .embedding
+                    .vertex_properties
+                    .iter_mut()
+                    .zip(graph.components())
+                {
+                    properties.color_override = Some(palette.color(component));
+                }
+                content.embedding.mark_dirty();
+                content.action_log.push("Colored components".to_string());
+            }
         });
         ui.tree_node(hash!(), "graph input", |ui| {
             ui.label(None, "Graph g6 string:");
             ui.input_text(hash!(), "", &mut data.g6_string);
             if ui.button(None, "Import graph") {
-                if let Ok(graph) = parse_graph6_string(&data.g6_string) {
-                    if !data.keep_embedding {
-                        content.embedding = GraphInterface::new(&graph);
-                    } else {
-                        content.embedding.update_edges(&graph);
+                let mut imported = Vec::new();
+                for (g6_string, result) in data
+                    .g6_string
+                    .split_whitespace()
+                    .zip(parse_graph6_multi(&data.g6_string))
+                {
+                    match result {
+                        Ok(graph) => imported.push((g6_string.to_string(), graph)),
+                        Err(error) => data.messages.push(format!("{}: {}", g6_string, error)),
                     }
-                    content.graph = graph;
+                }
+
+                if let Some((g6_string, graph)) = imported.first().cloned() {
+                    load_graph(
+                        &mut content.embedding,
+                        &mut content.graph,
+                        data.keep_embedding,
+                        graph,
+                        &mut data.messages,
+                    );
+                    data.graph_name = g6_string;
                     data.g6_string = "".to_string();
+                    content.action_log.push(format!(
+                        "Imported graph {} ({} in input)",
+                        data.graph_name,
+                        imported.len()
+                    ));
+                }
+                data.imported_graph_index = 0;
+                data.imported_graphs = imported;
+            }
+            if ui.button(None, "Degree sequences") {
+                for g6_string in data.g6_string.split_whitespace() {
+                    let message = match graph6_degree_sequence(g6_string) {
+                        Ok(degrees) => {
+                            let degrees: Vec<String> =
+                                degrees.iter().map(|degree| degree.to_string()).collect();
+                            format!("{}: {}", g6_string, degrees.join(", "))
+                        }
+                        Err(error) => format!("{}: {}", g6_string, error),
+                    };
+                    data.messages.push(message);
+                }
+            }
+
+            let imported_count = data.imported_graphs.len();
+            if imported_count > 1 {
+                ui.label(
+                    None,
+                    &format!(
+                        "Imported graph {}/{}",
+                        data.imported_graph_index + 1,
+                        imported_count
+                    ),
+                );
+                let previous = ui.button(None, "Previous graph");
+                let next = ui.button(None, "Next graph");
+                let target = if previous {
+                    data.imported_graph_index.checked_sub(1)
+                } else if next {
+                    Some(data.imported_graph_index + 1).filter(|&index| index < imported_count)
                 } else {
-                    debug!("Error in parsing g6 graph");
+                    None
+                };
+
+                if let Some(index) = target {
+                    let (g6_string, graph) = data.imported_graphs[index].clone();
+                    load_graph(
+                        &mut content.embedding,
+                        &mut content.graph,
+                        data.keep_embedding,
+                        graph,
+                        &mut data.messages,
+                    );
+                    data.imported_graph_index = index;
+                    data.graph_name = g6_string;
+                    content
+                        .action_log
+                        .push(format!("Switched to imported graph {}", data.graph_name));
                 }
             }
 
             ui.checkbox(hash!(), "Keep vertex positions", &mut data.keep_embedding);
+
+            ui.label(None, "Coordinates, one \"x y\" line per vertex:");
+            widgets::Editbox::new(hash!(), vec2(UI_WIDTH - 30.0, 80.0))
+                .multiline(true)
+                .ui(ui, &mut data.coordinates_text);
+            if ui.button(None, "Import graph with coordinates") {
+                match import_graph_with_positions(&data.g6_string, Some(&data.coordinates_text)) {
+                    Ok((graph, positions)) => {
+                        load_graph(
+                            &mut content.embedding,
+                            &mut content.graph,
+                            false,
+                            graph,
+                            &mut data.messages,
+                        );
+                        let view = Rect::new(
+                            0.0,
+                            0.0,
+                            main_screen_width(data.panel_hidden),
+                            screen_height(),
+                        );
+                        content.embedding.set_positions(&fit_to_rect(
+                            &positions.unwrap_or_default(),
+                            view,
+                            FIT_MARGIN,
+                        ));
+                        data.graph_name = data.g6_string.trim().to_string();
+                        data.g6_string = "".to_string();
+                        data.imported_graphs.clear();
+                        content.action_log.push(format!(
+                            "Imported graph {} with pasted coordinates",
+                            data.graph_name
+                        ));
+                    }
+                    Err(error) => data.messages.push(error.to_string()),
+                }
+            }
+
+            ui.label(None, "Positions file:");
+            ui.input_text(hash!(), "", &mut data.positions_file_name);
+            if ui.button(None, "Import graph with positions") {
+                let result = std::fs::read_to_string(&data.positions_file_name)
+                    .map_err(|error| error.to_string())
+                    .and_then(|text| {
+                        import_graph_with_positions(&data.g6_string, Some(&text))
+                            .map_err(|error| error.to_string())
+                    });
+
+                match result {
+                    Ok((graph, positions)) => {
+                        load_graph(
+                            &mut content.embedding,
+                            &mut content.graph,
+                            false,
+                            graph,
+                            &mut data.messages,
+                        );
+                        content
+                            .embedding
+                            .set_positions(&positions.unwrap_or_default());
+                        data.graph_name = data.g6_string.trim().to_string();
+                        data.g6_string = "".to_string();
+                        data.imported_graphs.clear();
+                        content.action_log.push(format!(
+                            "Imported graph {} with positions from {}",
+                            data.graph_name, data.positions_file_name
+                        ));
+                    }
+                    Err(error) => data.messages.push(error),
+                }
+            }
+            if ui.button(None, "Save positions") {
+                let file_name =
+                    file_name_or_default(&data.positions_file_name, &data.graph_name, "txt");
+                let result = write_text_export(
+                    &file_name,
+                    &format_positions(&content.embedding.positions()),
+                    data.export_create_directories,
+                );
+                if result.is_ok() {
+                    // the autosave is stale now
+                    content.saved_state = (
+                        content.graph.clone(),
+                        content.embedding.expanded_positions(),
+                    );
+                    if let Err(error) = content.autosave_paths.mark_saved(current_timestamp()) {
+                        data.messages.push(error.to_string());
+                    }
+                }
+                report_export(
+                    &mut data.messages,
+                    &mut content.action_log,
+                    "positions",
+                    result,
+                );
+            }
+
+            ui.separator();
+
+            ui.label(None, "Product with g6 string:");
+            ui.input_text(hash!(), "", &mut data.product_g6_string);
+            let cartesian = ui.button(None, "Cartesian product");
+            let tensor = ui.button(None, "Tensor product");
+            if cartesian || tensor {
+                match parse_graph6_string(data.product_g6_string.trim()) {
+                    Ok(other) => {
+                        let product = if cartesian {
+                            content.graph.cartesian_product(&other)
+                        } else {
+                            content.graph.tensor_product(&other)
+                        };
+                        let operator = if cartesian { "x" } else { "*" };
+
+                        data.graph_name = format!(
+                            "{} {} {}",
+                            data.graph_name,
+                            operator,
+                            data.product_g6_string.trim()
+                        );
+                        load_graph(
+                            &mut content.embedding,
+                            &mut content.graph,
+                            false,
+                            product,
+                            &mut data.messages,
+                        );
+                        content.action_log.push(format!(
+                            "Took {} product with {}",
+                            if cartesian { "Cartesian" } else { "tensor" },
+                            data.product_g6_string.trim()
+                        ));
+                    }
+                    Err(error) => data.messages.push(error.to_string()),
+                }
+            }
+
+            ui.separator();
+
+            ui.label(None, "Edge label file:");
+            ui.input_text(hash!(), "", &mut data.edge_label_file_name);
+            if ui.button(None, "Import edge labels") {
+                let indexing = data.draw_config.indexing;
+                let result = std::fs::read_to_string(&data.edge_label_file_name)
+                    .map_err(|error| error.to_string())
+                    .and_then(|text| {
+                        parse_edge_labels(&text, indexing).map_err(|error| error.to_string())
+                    });
+
+                match result {
+                    Ok(labels) => {
+                        content.action_log.push(format!(
+                            "Imported edge labels from {}",
+                            data.edge_label_file_name
+                        ));
+                        for (a, b) in content.embedding.set_edge_labels(&labels) {
+                            data.messages.push(format!(
+                                "No edge {} - {} for label",
+                                indexing.display(a),
+                                indexing.display(b)
+                            ));
+                        }
+                    }
+                    Err(error) => data.messages.push(error),
+                }
+            }
+            if ui.button(None, "Clear edge labels") {
+                content.embedding.clear_edge_labels();
+            }
+
+            ui.separator();
+
+            ui.drag(hash!(), "vertices", (0, 64), &mut data.empty_graph_vertices);
+            if ui.button(None, "New empty graph") {
+                let graph = Graph::new(data.empty_graph_vertices as usize);
+                load_graph(
+                    &mut content.embedding,
+                    &mut content.graph,
+                    false,
+                    graph,
+                    &mut data.messages,
+                );
+                data.graph_name = format!("empty_{}", data.empty_graph_vertices);
+                content
+                    .action_log
+                    .push(format!("Created graph {}", data.graph_name));
+            }
+        });
+        ui.tree_node(hash!(), "generate", |ui| {
+            let mut generator_index = data.generator.index();
+            ui.combo_box(hash!(), "family", &Generator::NAMES, &mut generator_index);
+            data.generator = Generator::ALL[generator_index];
+
+            if data.generator.has_size() {
+                ui.drag(hash!(), "size", (0, 64), &mut data.generator_size);
+            }
+            if data.generator.has_second_size() {
+                ui.drag(
+                    hash!(),
+                    "second size",
+                    (0, 64),
+                    &mut data.generator_second_size,
+                );
+            }
+
+            if ui.button(None, "Generate") {
+                let (n, m) = (
+                    data.generator_size as usize,
+                    data.generator_second_size as usize,
+                );
+                let graph = data.generator.generate(n, m);
+                load_graph(
+                    &mut content.embedding,
+                    &mut content.graph,
+                    false,
+                    graph,
+                    &mut data.messages,
+                );
+                data.graph_name = data.generator.graph_name(n, m);
+                content
+                    .action_log
+                    .push(format!("Generated graph {}", data.graph_name));
+            }
         });
         ui.tree_node(hash!(), "graph output", |ui| {
+            ui.label(None, "Graph name");
+            ui.input_text(hash!(), "", &mut data.graph_name);
+
             ui.label(None, "SVG output file");
             ui.input_text(hash!(), "", &mut data.svg_file_name);
+            ui.checkbox(
+                hash!(),
+                "stroke vertex borders",
+                &mut data.draw_config.vertex_config.svg_stroke,
+            );
+            ui.checkbox(
+                hash!(),
+                "outline labels",
+                &mut data.draw_config.vertex_config.label_halo,
+            );
+            ui.checkbox(hash!(), "tooltips", &mut data.draw_config.svg_tooltips);
+            ui.label(None, "Ctrl+E export directory:");
+            ui.input_text(hash!(), "", &mut data.export_directory);
+            ui.checkbox(
+                hash!(),
+                "create missing directories",
+                &mut data.export_create_directories,
+            );
+            ui.checkbox(hash!(), "fixed output size", &mut data.export_fixed_size);
+            if data.export_fixed_size {
+                ui.drag(hash!(), "width", (1, 8192), &mut data.export_width);
+                ui.drag(hash!(), "height", (1, 8192), &mut data.export_height);
+            }
+
+            let mut background_index = data.draw_config.background_mode.index();
+            ui.combo_box(
+                hash!(),
+                "background",
+                &BackgroundMode::NAMES,
+                &mut background_index,
+            );
+            data.draw_config.background_mode = BackgroundMode::ALL[background_index];
+            ui.checkbox(
+                hash!(),
+                "round coordinates to integers",
+                &mut data.export_integer_coordinates,
+            );
+            ui.drag(hash!(), "decimals", (0, 6), &mut data.export_decimals);
+            ui.checkbox(
+                hash!(),
+                "export collapsed view",
+                &mut data.export_collapsed_view,
+            );
+
+            let svg_file_name = file_name_or_default(&data.svg_file_name, &data.graph_name, "svg");
+            let export_options = data.export_options();
             if ui.button(None, "Export to SVG") {
-                draw_graph_to_file(&content.drawable_graph, &data.svg_file_name)
-                    .unwrap_or_else(|error| error!("{}", error));
+                let result = export_svg(
+                    &mut content.embedding,
+                    &content.annotations,
+                    data,
+                    &svg_file_name,
+                );
+                report_export(&mut data.messages, &mut content.action_log, "SVG", result);
+            }
+            if ui.button(None, "Export plain SVG") {
+                let positions = content.embedding.positions();
+                let plain_graph =
+                    DrawableGraph::from_positions(&content.graph, &positions, &data.draw_config);
+
+                let result = draw_graph_to_file(
+                    &plain_graph,
+                    &content.annotations,
+                    data.canvas_size(),
+                    &export_options,
+                    &svg_file_name,
+                );
+                report_export(
+                    &mut data.messages,
+                    &mut content.action_log,
+                    "plain SVG",
+                    result,
+                );
+            }
+            if ui.button(None, "Export history to SVG") {
+                let positions = content.embedding.positions();
+                let drawables: Vec<DrawableGraph> = content
+                    .embedding
+                    .highlight_history()
+                    .iter()
+                    .map(|entry| {
+                        thumbnail_drawable(
+                            &content.graph,
+                            &positions,
+                            &entry.graph,
+                            data.draw_config.history_entry_color(entry),
+                            &data.draw_config,
+                        )
+                    })
+                    .collect();
+                let graphs: Vec<&DrawableGraph> = drawables.iter().collect();
+                let file_name = file_name_or_default(&data.svg_file_name, &data.graph_name, "svg");
+
+                let result = draw_graphs_to_file(
+                    &graphs,
+                    data.canvas_size(),
+                    HISTORY_EXPORT_COLUMNS,
+                    40.0,
+                    &file_name,
+                    data.export_create_directories,
+                );
+                report_export(
+                    &mut data.messages,
+                    &mut content.action_log,
+                    &format!("{} history entries", graphs.len()),
+                    result,
+                );
+            }
+            if ui.button(None, "Copy image to clipboard") {
+                data.copy_image_requested = true;
+            }
+            ui.label(None, "PNG output file");
+            ui.input_text(hash!(), "", &mut data.png_file_name);
+            ui.slider(hash!(), "PNG scale", 1.0..4.0, &mut data.png_scale);
+            if ui.button(None, "Export to PNG") {
+                data.png_export_requested = true;
+            }
+
+            ui.separator();
+
+            ui.label(None, "Adjacency list output file");
+            ui.input_text(hash!(), "", &mut data.adjacency_list_file_name);
+            if ui.button(None, "Export adjacency list") {
+                let adjacency_list = content
+                    .graph
+                    .to_adjacency_list_string(data.draw_config.indexing);
+                let file_name =
+                    file_name_or_default(&data.adjacency_list_file_name, &data.graph_name, "txt");
+                let result =
+                    write_text_export(&file_name, &adjacency_list, data.export_create_directories);
+                report_export(
+                    &mut data.messages,
+                    &mut content.action_log,
+                    "adjacency list",
+                    result,
+                );
+            }
+
+            ui.label(None, "Report output file");
+            ui.input_text(hash!(), "", &mut data.report_file_name);
+            if ui.button(None, "Export report") {
+                let report = graph_report(
+                    &content.graph,
+                    &content.stats,
+                    &data.graph_name,
+                    data.draw_config.indexing,
+                    current_timestamp(),
+                );
+                // positions and adjacency lists default to the graph name already
+                let file_name = file_name_or_default(
+                    &data.report_file_name,
+                    &format!("{}_report", data.graph_name),
+                    "txt",
+                );
+                let result = write_text_export(&file_name, &report, data.export_create_directories);
+                report_export(
+                    &mut data.messages,
+                    &mut content.action_log,
+                    "report",
+                    result,
+                );
+            }
+
+            ui.separator();
+
+            let extract = ui.button(None, "Extract highlighted subgraph");
+            let copy = ui.button(None, "Copy highlighted g6");
+            if extract || copy {
+                let subgraph = content
+                    .graph
+                    .edge_subgraph(&content.embedding.highlighted_edges());
+
+                if copy {
+                    match subgraph.graph.to_graph6_string() {
+                        Some(g6_string) => set_clipboard(&g6_string),
+                        None => data
+                            .messages
+                            .push("Subgraph too large for graph6".to_string()),
+                    }
+                } else {
+                    let positions = content.embedding.subgraph_positions(&subgraph);
+                    load_graph(
+                        &mut content.embedding,
+                        &mut content.graph,
+                        false,
+                        subgraph.graph,
+                        &mut data.messages,
+                    );
+                    content.embedding.set_positions(&positions);
+                    data.graph_name = format!("{}_highlighted", data.graph_name);
+                    content
+                        .action_log
+                        .push("Extracted highlighted subgraph".to_string());
+                }
+            }
+        });
+        ui.tree_node(hash!(), "script", |ui| {
+            ui.label(None, "Script file");
+            ui.input_text(hash!(), "", &mut data.script_file_name);
+            if ui.button(None, "Load script") {
+                let indexing = data.draw_config.indexing;
+                let result = std::fs::read_to_string(&data.script_file_name)
+                    .map_err(|error| error.to_string())
+                    .and_then(|text| {
+                        parse_script(&text, indexing).map_err(|error| error.to_string())
+                    });
+
+                match result {
+                    Ok(commands) => {
+                        content.script_player = Some(ScriptPlayer::new(commands));
+                        content
+                            .action_log
+                            .push(format!("Loaded script {}", data.script_file_name));
+                    }
+                    Err(error) => data.messages.push(error),
+                }
+            }
+
+            if let Some(player) = &mut content.script_player {
+                let (position, length) = player.position();
+                ui.label(
+                    None,
+                    &format!("{:?}: {}/{}", player.state(), position, length),
+                );
+
+                match player.state() {
+                    PlayerState::Paused => {
+                        if ui.button(None, "Play") {
+                            player.play();
+                        }
+                    }
+                    PlayerState::Playing | PlayerState::Waiting { .. } => {
+                        if ui.button(None, "Pause") {
+                            player.pause();
+                        }
+                    }
+                    PlayerState::Finished => {}
+                }
+                if ui.button(None, "Step") {
+                    stepped_command = player.step();
+                }
+            }
+        });
+        ui.tree_node(hash!(), "recording", |ui| {
+            ui.label(None, "Frame base name");
+            ui.input_text(hash!(), "", &mut data.recording_base_name);
+            ui.drag(
+                hash!(),
+                "every nth step",
+                (1, 100),
+                &mut data.recording_every_nth_step,
+            );
+            ui.drag(
+                hash!(),
+                "max frames",
+                (1, 10000),
+                &mut data.recording_max_frames,
+            );
+            ui.drag(
+                hash!(),
+                "max MB",
+                (1, 10000),
+                &mut data.recording_max_megabytes,
+            );
+
+            let is_recording = content
+                .recorder
+                .as_ref()
+                .map(|recorder| recorder.is_recording())
+                .unwrap_or(false);
+
+            if is_recording {
+                if ui.button(None, "Stop recording") {
+                    if let Some(mut recorder) = content.recorder.take() {
+                        recorder.stop();
+                        data.messages.extend(recorder.drain_errors());
+                        data.messages.push(format!(
+                            "Recorded {} frames, dropped {}",
+                            recorder.frames_recorded(),
+                            recorder.frames_dropped()
+                        ));
+                    }
+                }
+            } else if ui.button(None, "Start recording") {
+                let first_frame = frame_file_name(&data.recording_base_name, 1);
+                match resolve_export_path(
+                    Path::new(&first_frame),
+                    "svg",
+                    data.export_create_directories,
+                ) {
+                    Ok(path) => {
+                        content.recorder = Some(Recorder::start(
+                            &data.recording_base_name,
+                            data.recording_every_nth_step as usize,
+                            data.recording_max_frames as usize,
+                            data.recording_max_megabytes as usize * 1_000_000,
+                            FileFrameWriter {
+                                create_directories: data.export_create_directories,
+                            },
+                        ));
+                        data.messages
+                            .push(format!("Recording frames from {}", path.display()));
+                    }
+                    Err(error) => data
+                        .messages
+                        .push(format!("Could not record frames: {}", error)),
+                }
+            }
+
+            if let Some(recorder) = &content.recorder {
+                ui.label(
+                    None,
+                    &format!("Frames recorded: {}", recorder.frames_recorded()),
+                );
+                if recorder.frames_dropped() > 0 {
+                    ui.label(
+                        None,
+                        &format!(
+                            "Frames dropped, the disk is too slow: {}",
+                            recorder.frames_dropped()
+                        ),
+                    );
+                }
+            }
+        });
+        ui.tree_node(hash!(), "messages", |ui| {
+            for message in data.messages.iter().rev().take(10) {
+                ui.label(None, message);
+            }
+            if ui.button(None, "Clear messages") {
+                data.messages.clear();
+            }
+        });
+        ui.tree_node(hash!(), "action log", |ui| {
+            ui.group(hash!(), vec2(UI_WIDTH - 30.0, 150.0), |ui| {
+                for entry in &content.action_log {
+                    ui.label(None, entry);
+                }
+            });
+            if ui.button(None, "Clear log") {
+                content.action_log.clear();
+            }
+        });
+        ui.tree_node(hash!(), "annotations", |ui| {
+            let annotations = &mut content.annotations;
+            ui.checkbox(hash!(), "Edit annotations", &mut annotations.editing);
+
+            let mut tool_index = annotations.tool.index();
+            ui.combo_box(hash!(), "tool", &AnnotationTool::NAMES, &mut tool_index);
+            annotations.tool = AnnotationTool::ALL[tool_index];
+
+            ui.label(None, "Text, click the canvas to place:");
+            ui.input_text(hash!(), "", &mut annotations.text);
+            ui.label(None, "Drag on the canvas to draw an arrow");
+
+            ui.label(
+                None,
+                &format!("Annotations: {}", annotations.annotations.len()),
+            );
+            if ui.button(None, "Remove last annotation") {
+                annotations.annotations.pop();
+            }
+        });
+        ui.tree_node(hash!(), "inspector", |ui| {
+            let indexing = data.draw_config.indexing;
+            // the inspectors edit the properties directly, bypassing the dirty tracking
+            let mut inspected_changed = false;
+
+            if let Some(length) = content
+                .embedding
+                .hovered_edge
+                .and_then(|edge| content.embedding.edge_length(edge))
+            {
+                ui.label(None, &format!("Hovered edge length: {:.2}", length));
+            }
+
+            match content.embedding.selected {
+                Some(Selection::Vertex(index)) => {
+                    if let Some(properties) = content.embedding.vertex_properties.get_mut(index) {
+                        ui.label(None, &format!("Vertex {}", indexing.display(index)));
+                        inspected_changed = vertex_inspector(
+                            ui,
+                            properties,
+                            data.draw_config.vertex_config.main_color,
+                        );
+                    }
+
+                    match content.embedding.collapsed_group_of(index) {
+                        Some(group) => {
+                            if ui.button(None, "Expand group") {
+                                content.embedding.expand(group);
+                            }
+                        }
+                        None => {
+                            if ui.button(None, "Collapse neighbourhood")
+                                && content.embedding.collapse_neighbourhood(index).is_none()
+                            {
+                                data.messages.push(format!(
+                                    "Vertex {} has no neighbours or one is collapsed already",
+                                    indexing.display(index)
+                                ));
+                            }
+                        }
+                    }
+                }
+                Some(Selection::Edge(index)) => {
+                    let length = content.embedding.edge_length(index).unwrap_or(0.0);
+                    if let Some(properties) = content.embedding.edge_properties.get_mut(index) {
+                        let (a, b) = properties.vertices;
+                        ui.label(
+                            None,
+                            &format!("Edge {} - {}", indexing.display(a), indexing.display(b)),
+                        );
+                        inspected_changed = edge_inspector(
+                            ui,
+                            properties,
+                            length,
+                            data.draw_config.edge_config.color,
+                        );
+
+                        ui.label(None, "New endpoints:");
+                        ui.input_text(hash!(), "", &mut data.reconnect_endpoints);
+                        if ui.button(None, "Reconnect") {
+                            match parse_vertex_list(&data.reconnect_endpoints, indexing) {
+                                Ok(endpoints) => match endpoints[..] {
+                                    [c, d] if content.graph.reconnect_edge((a, b), (c, d)) => {
+                                        content.embedding.reconnect_edge((a, b), (c, d));
+                                        content.action_log.push(format!(
+                                            "Reconnected edge {} - {} to {} - {}",
+                                            indexing.display(a),
+                                            indexing.display(b),
+                                            indexing.display(c),
+                                            indexing.display(d)
+                                        ));
+                                    }
+                                    _ => data.messages.push(format!(
+                                        "Cannot reconnect the edge to {}",
+                                        data.reconnect_endpoints.trim()
+                                    )),
+                                },
+                                Err(error) => data.messages.push(error.to_string()),
+                            }
+                        }
+
+                        if ui.button(None, "Cycle direction") {
+                            cycled_edge = Some(index);
+                        }
+                        if ui.button(None, "Subdivide") {
+                            if let Some(c) = content.graph.subdivide_edge(a, b) {
+                                content.embedding.subdivide_edge(a, b, c);
+                            }
+                        }
+                        if ui.button(None, "Add reverse edge") {
+                            if let Some((tail, head)) = content.graph.add_reverse_edge(a, b) {
+                                let orientation = content.graph.orientation(tail, head);
+                                content.embedding.add_edge((tail, head), orientation);
+                                content.action_log.push(format!(
+                                    "Added edge {} -> {}",
+                                    indexing.display(tail),
+                                    indexing.display(head)
+                                ));
+                            }
+                        }
+                    }
+                }
+                None => ui.label(None, "Click a vertex or edge to inspect it"),
+            }
+            if inspected_changed {
+                content.embedding.mark_dirty();
             }
         });
         ui.tree_node(hash!(), "draw config", |ui| {
             ui.label(None, "Highlight g6 string:");
             ui.input_text(hash!(), "", &mut data.highlight_g6_string);
+            ui.checkbox(
+                hash!(),
+                "keep previous highlights",
+                &mut data.accumulate_highlighting,
+            );
             if ui.button(None, "Highlight edges from graph") {
-                parse_and_add_highlighting(&mut data.highlight_g6_string, &mut content.embedding);
+                let highlighted = parse_and_add_highlighting(
+                    &mut data.highlight_g6_string,
+                    &content.graph,
+                    &mut content.embedding,
+                    data.accumulate_highlighting,
+                    data.draw_config.indexing,
+                    &mut data.messages,
+                );
+                if !highlighted.is_empty() {
+                    content
+                        .action_log
+                        .push(format!("Highlighted edges from {}", highlighted.join(" ")));
+                }
+            }
+
+            ui.separator();
+
+            ui.label(None, "Shortest path:");
+            ui.drag(hash!(), "source", (0, 64), &mut data.path_source);
+            ui.drag(hash!(), "target", (0, 64), &mut data.path_target);
+            if ui.button(None, "Highlight shortest path") {
+                let indexing = data.draw_config.indexing;
+                let source = indexing.internal(data.path_source as usize);
+                let target = indexing.internal(data.path_target as usize);
+
+                match source
+                    .zip(target)
+                    .and_then(|(source, target)| content.graph.shortest_path(source, target))
+                {
+                    Some(path) => {
+                        content.embedding.set_path_highlighting(&path);
+                        content.action_log.push(format!(
+                            "Highlighted shortest path {} - {}",
+                            data.path_source, data.path_target
+                        ));
+                    }
+                    None => debug!("No path between the given vertices"),
+                }
+            }
+            ui.checkbox(
+                hash!(),
+                "draw path arrows",
+                &mut data.draw_config.edge_config.draw_path_arrows,
+            );
+
+            ui.separator();
+
+            let edge_config = &mut data.draw_config.edge_config;
+            ui.slider(hash!(), "edge width", 1.0..20.0, &mut edge_config.width);
+            ui.checkbox(hash!(), "width by weight", &mut edge_config.width_by_weight);
+            if edge_config.width_by_weight {
+                ui.slider(
+                    hash!(),
+                    "min weight width",
+                    1.0..20.0,
+                    &mut edge_config.min_weight_width,
+                );
+                ui.slider(
+                    hash!(),
+                    "max weight width",
+                    1.0..20.0,
+                    &mut edge_config.max_weight_width,
+                );
+            }
+            ui.slider(hash!(), "curvature", -0.5..0.5, &mut edge_config.curvature);
+
+            ui.separator();
+
+            ui.drag(hash!(), "vertex", (0, 64), &mut data.incident_vertex);
+            if ui.button(None, "Highlight incident edges") {
+                if let Some(vertex) = data
+                    .draw_config
+                    .indexing
+                    .internal(data.incident_vertex as usize)
+                {
+                    content
+                        .embedding
+                        .highlight_edges_where(|a, b| a == vertex || b == vertex);
+                    content.action_log.push(format!(
+                        "Highlighted edges incident to {}",
+                        data.incident_vertex
+                    ));
+                }
+            }
+
+            ui.label(None, "Vertex subset:");
+            ui.input_text(hash!(), "", &mut data.vertex_subset);
+            if ui.button(None, "Highlight edges within subset") {
+                match parse_vertex_list(&data.vertex_subset, data.draw_config.indexing) {
+                    Ok(subset) => {
+                        content.embedding.highlight_edges_where(|a, b| {
+                            subset.contains(&a) && subset.contains(&b)
+                        });
+                        content.action_log.push(format!(
+                            "Highlighted edges within {}",
+                            data.vertex_subset.trim()
+                        ));
+                    }
+                    Err(error) => data.messages.push(error.to_string()),
+                }
+            }
+
+            ui.separator();
+
+            ui.label(None, "Path or cycle (vertex sequence):");
+            ui.input_text(hash!(), "", &mut data.vertex_sequence);
+            if ui.button(None, "Highlight vertex sequence")
+                && highlight_vertex_sequence(
+                    &data.vertex_sequence,
+                    data.draw_config.indexing,
+                    &content.graph,
+                    &mut content.embedding,
+                    &mut data.messages,
+                )
+            {
+                content.action_log.push(format!(
+                    "Highlighted vertex sequence {}",
+                    data.vertex_sequence.trim()
+                ));
+            }
+
+            ui.label(None, "Edge endpoints:");
+            ui.input_text(hash!(), "", &mut data.edge_endpoints);
+            if ui.button(None, "Toggle edge highlight") {
+                let endpoints = data.edge_endpoints.trim();
+                match content
+                    .graph
+                    .find_edge(endpoints, data.draw_config.indexing)
+                {
+                    Ok(edge) => {
+                        content.embedding.toggle_edge_highlighting(edge);
+                        content
+                            .action_log
+                            .push(format!("Toggled highlight of edge {}", endpoints));
+                    }
+                    Err(error) => data.messages.push(format!("{}: {}", endpoints, error)),
+                }
             }
 
             ui.separator();
@@ -109,11 +1594,69 @@ pub fn handle_ui(content: &mut Content) {
                 None,
                 &format!(
                     "Current: {:?}, History size: {}",
-                    content.embedding.current_highlight_graph,
+                    content.embedding.current_highlight_graph(),
                     content.embedding.get_history_size()
                 ),
             );
 
+            let current = content.embedding.current_highlight_graph();
+            if content.embedding.get_history_size() > 0 {
+                let mut clicked = None;
+                ui.group(
+                    hash!(),
+                    vec2(UI_WIDTH - 30.0, 2.0 * THUMBNAIL_HEIGHT),
+                    |ui| {
+                        let entries = content.embedding.highlight_history();
+                        for (index, (texture, entry)) in
+                            content.thumbnails.textures().zip(entries).enumerate()
+                        {
+                            let marker = if current == Some(index) { "> " } else { "" };
+                            color_swatch(ui, data.draw_config.history_entry_color(entry));
+                            ui.same_line(0.0);
+                            ui.label(None, &format!("{}#{} {}", marker, index, entry.name));
+                            if ui.texture(texture, THUMBNAIL_WIDTH, THUMBNAIL_HEIGHT) {
+                                clicked = Some(index);
+                            }
+                        }
+                    },
+                );
+                if let Some(index) = clicked {
+                    content.embedding.jump_to_highlighting(index);
+                }
+            }
+
+            if let Some(index) = current {
+                ui.input_text(hash!(), "entry name", &mut data.highlight_entry_name);
+                if ui.button(None, "Rename") {
+                    let name = data.highlight_entry_name.trim();
+                    if !name.is_empty() {
+                        content.embedding.rename_highlight(index, name);
+                        data.highlight_entry_name.clear();
+                    }
+                }
+                if ui.button(None, "Move up") && index > 0 {
+                    content.embedding.move_highlight(index, index - 1);
+                }
+                if ui.button(None, "Move down") {
+                    content.embedding.move_highlight(index, index + 1);
+                }
+                if ui.button(None, "Duplicate") {
+                    content.embedding.duplicate_highlight(index);
+                }
+                if ui.button(None, "Delete") {
+                    content.embedding.delete_highlight(index);
+                }
+
+                if let Some(entry) = content.embedding.highlight_history().get(index) {
+                    let palette_color = data.draw_config.edge_highlight_color(entry.color_index);
+                    let mut color_override = entry.color_override;
+                    color_override_inspector(ui, hash!(), &mut color_override, palette_color);
+                    if color_override != entry.color_override {
+                        content.embedding.set_highlight_color(index, color_override);
+                    }
+                }
+            }
+
             if ui.button(None, "Next highlighting") {
                 content.embedding.set_next_highlighting();
             }
@@ -134,11 +1677,76 @@ pub fn handle_ui(content: &mut Content) {
                 "draw vertex index",
                 &mut data.draw_config.vertex_config.draw_index,
             );
-            if data.draw_config.vertex_config.draw_index {
+            let vertex_config = &mut data.draw_config.vertex_config;
+            let mut placement_index = vertex_config.label_placement.index();
+            ui.combo_box(
+                hash!(),
+                "label placement",
+                &VertexLabelPlacement::NAMES,
+                &mut placement_index,
+            );
+            vertex_config.label_placement = VertexLabelPlacement::ALL[placement_index];
+            if vertex_config.label_placement == VertexLabelPlacement::Inside {
                 ui.checkbox(
                     hash!(),
-                    "zero-indexed vertices",
-                    &mut data.draw_config.vertex_config.zero_indexed,
+                    "shrink labels to fit",
+                    &mut vertex_config.fit_labels,
+                );
+            } else {
+                ui.slider(
+                    hash!(),
+                    "label angle",
+                    -180.0..180.0,
+                    &mut vertex_config.outside_label_angle,
+                );
+                ui.slider(
+                    hash!(),
+                    "label offset",
+                    0.0..40.0,
+                    &mut vertex_config.outside_label_offset,
+                );
+            }
+            ui.checkbox(
+                hash!(),
+                "dash isolated vertices",
+                &mut data.draw_config.vertex_config.dash_isolated,
+            );
+            ui.checkbox(
+                hash!(),
+                "avoid label overlap",
+                &mut data.draw_config.vertex_config.avoid_label_overlap,
+            );
+            ui.checkbox(
+                hash!(),
+                "auto label contrast",
+                &mut data.draw_config.vertex_config.auto_label_contrast,
+            );
+
+            let edge_config = &mut data.draw_config.edge_config;
+            let mut hidden_edges_index = edge_config.hidden_vertex_edges.index();
+            ui.combo_box(
+                hash!(),
+                "edges of hidden vertices",
+                &HiddenVertexEdges::NAMES,
+                &mut hidden_edges_index,
+            );
+            edge_config.hidden_vertex_edges = HiddenVertexEdges::ALL[hidden_edges_index];
+            ui.checkbox(
+                hash!(),
+                "emphasize edges of highlighted vertices",
+                &mut edge_config.emphasize_highlighted_vertex_edges,
+            );
+            ui.checkbox(
+                hash!(),
+                "warn about off-target edge lengths",
+                &mut edge_config.warn_length_deviation,
+            );
+            if edge_config.warn_length_deviation {
+                ui.slider(
+                    hash!(),
+                    "length tolerance",
+                    0.1..20.0,
+                    &mut edge_config.length_tolerance,
                 );
             }
             ui.checkbox(
@@ -146,31 +1754,511 @@ pub fn handle_ui(content: &mut Content) {
                 "draw edge index",
                 &mut data.draw_config.edge_config.draw_index,
             );
-            if data.draw_config.edge_config.draw_index {
+
+            let mut quality_index = data.draw_config.quality.index();
+            ui.combo_box(hash!(), "quality", &DrawQuality::NAMES, &mut quality_index);
+            data.draw_config.quality = DrawQuality::ALL[quality_index];
+            if data.draw_config.quality != DrawQuality::Full {
+                ui.drag(
+                    hash!(),
+                    "detail edge limit",
+                    (100, 1000000),
+                    &mut data.draw_config.lod_edge_threshold,
+                );
                 ui.checkbox(
                     hash!(),
-                    "zero-indexed edges",
-                    &mut data.draw_config.edge_config.zero_indexed,
+                    "subsample edges while moving",
+                    &mut data.draw_config.lod_subsample,
                 );
             }
+
+            let mut indexing_index = data.draw_config.indexing.index();
+            ui.combo_box(hash!(), "indexing", &Indexing::NAMES, &mut indexing_index);
+            data.draw_config.indexing = Indexing::ALL[indexing_index];
+
+            let mut palette_index = data.draw_config.palette.index();
+            ui.combo_box(hash!(), "palette", &Palette::NAMES, &mut palette_index);
+            if Palette::ALL[palette_index] != data.draw_config.palette {
+                Palette::ALL[palette_index].apply(&mut data.draw_config);
+            }
         });
     });
 
-    root_ui().move_window(id, Vec2::new(main_screen_width(), 0.0));
+    root_ui().move_window(id, Vec2::new(main_screen_width(data.panel_hidden), 0.0));
+
+    if let Some(command) = stepped_command {
+        content.execute_script_command(command);
+    }
+    if let Some(edge) = cycled_edge {
+        content.cycle_edge_orientation(edge);
+    }
+    if let Some(snapshot) = restored_autosave {
+        content.restore_snapshot(snapshot);
+    }
+    if relax_requested {
+        content.relax_selection();
+    }
+    if count_crossings_requested {
+        content.count_crossings_in_background();
+    }
 }
 
-pub fn main_screen_width() -> f32 {
-    screen_width() - UI_WIDTH
+fn progress_bar(ui: &mut Ui, fraction: f32) {
+    let mut canvas = ui.canvas();
+    let width = UI_WIDTH - 30.0;
+    let position = canvas.request_space(vec2(width, PROGRESS_BAR_HEIGHT));
+
+    canvas.rect(
+        Rect::new(position.x, position.y, width, PROGRESS_BAR_HEIGHT),
+        GRAY,
+        None,
+    );
+    canvas.rect(
+        Rect::new(
+            position.x,
+            position.y,
+            width * fraction,
+            PROGRESS_BAR_HEIGHT,
+        ),
+        None,
+        SKYBLUE,
+    );
 }
 
-fn parse_and_add_highlighting(highlighting_string: &mut String, embedding: &mut GraphInterface) {
-    let graphs = highlighting_string.lines();
+pub fn main_screen_width(panel_hidden: bool) -> f32 {
+    canvas_width(screen_width(), panel_hidden)
+}
+
+fn canvas_width(screen_width: f32, panel_hidden: bool) -> f32 {
+    if panel_hidden {
+        screen_width
+    } else {
+        screen_width - UI_WIDTH
+    }
+}
 
-    for graph in graphs {
-        if let Ok(graph) = parse_graph6_string(graph) {
-            embedding.set_edge_highlighting_and_add_to_history(graph);
+/// Returns whether any property changed.
+fn vertex_inspector(ui: &mut Ui, properties: &mut VertexProperties, default_color: Color) -> bool {
+    let before = properties.clone();
+    ui.drag(hash!(), "x", None, &mut properties.position.x);
+    ui.drag(hash!(), "y", None, &mut properties.position.y);
+    let mut has_radius_override = properties.radius_override.is_some();
+    ui.checkbox(hash!(), "override radius", &mut has_radius_override);
+    if has_radius_override {
+        let radius = properties.radius_override.get_or_insert(properties.radius);
+        ui.slider(hash!(), "radius", 5.0..50.0, radius);
+    } else {
+        properties.radius_override = None;
+    }
+
+    let mut state_index = properties.draw_state.index();
+    ui.combo_box(hash!(), "draw state", &DrawState::NAMES, &mut state_index);
+    properties.draw_state = DrawState::ALL[state_index];
+
+    let mut label = properties.label.clone().unwrap_or_default();
+    ui.input_text(hash!(), "label", &mut label);
+    properties.label = if label.is_empty() { None } else { Some(label) };
+
+    ui.checkbox(hash!(), "pinned", &mut properties.pinned);
+
+    let mut in_lane = properties.lane.is_some();
+    ui.checkbox(hash!(), "in lane", &mut in_lane);
+    if in_lane {
+        let mut lane = properties.lane.unwrap_or(0) as u32;
+        ui.drag(hash!(), "lane (0 = top)", (0, 32), &mut lane);
+        properties.lane = Some(lane as usize);
+    } else {
+        properties.lane = None;
+    }
+
+    color_override_inspector(ui, hash!(), &mut properties.color_override, default_color);
+
+    *properties != before
+}
+
+/// Returns whether any property changed.
+fn edge_inspector(
+    ui: &mut Ui,
+    properties: &mut EdgeProperties,
+    length: f32,
+    default_color: Color,
+) -> bool {
+    let before = properties.clone();
+    ui.label(None, &format!("length: {:.2}", length));
+    let mut has_target_length = properties.target_length.is_some();
+    ui.checkbox(hash!(), "fix length", &mut has_target_length);
+    if has_target_length {
+        let target = properties.target_length.get_or_insert(length);
+        ui.drag(hash!(), "target length", (1.0, 1000.0), target);
+    } else {
+        properties.target_length = None;
+    }
+
+    let mut has_width_override = properties.width_override.is_some();
+    ui.checkbox(hash!(), "override width", &mut has_width_override);
+    if has_width_override {
+        let width = properties.width_override.get_or_insert(properties.width);
+        ui.slider(hash!(), "width", 1.0..20.0, width);
+    } else {
+        properties.width_override = None;
+    }
+
+    if let Some(weight) = properties.weight {
+        ui.label(None, &format!("weight: {}", weight));
+    }
+
+    let mut state_index = properties.draw_state.index();
+    ui.combo_box(hash!(), "draw state", &DrawState::NAMES, &mut state_index);
+    properties.draw_state = DrawState::ALL[state_index];
+
+    let mut style_index = properties.style.index();
+    ui.combo_box(hash!(), "style", &EdgeStyle::NAMES, &mut style_index);
+    properties.style = EdgeStyle::ALL[style_index];
+
+    color_override_inspector(ui, hash!(), &mut properties.color_override, default_color);
+
+    *properties != before
+}
+
+/// Widgets ids are derived from `id`, so that several of these can be shown at once.
+fn color_override_inspector(
+    ui: &mut Ui,
+    id: Id,
+    color_override: &mut Option<Color>,
+    initial: Color,
+) {
+    let mut has_override = color_override.is_some();
+    ui.checkbox(hash!(id, "override"), "override color", &mut has_override);
+
+    if !has_override {
+        *color_override = None;
+        return;
+    }
+
+    let color = color_override.get_or_insert(initial);
+    ui.slider(hash!(id, "red"), "red", 0.0..1.0, &mut color.r);
+    ui.slider(hash!(id, "green"), "green", 0.0..1.0, &mut color.g);
+    ui.slider(hash!(id, "blue"), "blue", 0.0..1.0, &mut color.b);
+}
+
+fn color_swatch(ui: &mut Ui, color: Color) {
+    let mut canvas = ui.canvas();
+    let position = canvas.request_space(Vec2::splat(SWATCH_SIZE));
+    canvas.rect(
+        Rect::new(position.x, position.y, SWATCH_SIZE, SWATCH_SIZE),
+        GRAY,
+        color,
+    );
+}
+
+fn highlight_vertex_sequence(
+    sequence: &str,
+    indexing: Indexing,
+    graph: &Graph,
+    embedding: &mut GraphInterface,
+    messages: &mut Vec<String>,
+) -> bool {
+    let sequence = match parse_vertex_list(sequence, indexing) {
+        Ok(sequence) => sequence,
+        Err(error) => {
+            messages.push(error.to_string());
+            return false;
         }
+    };
+
+    let SequenceEdges { present, missing } = graph.sequence_edges(&sequence);
+
+    for (a, b) in missing {
+        messages.push(format!(
+            "No edge {} - {}",
+            indexing.display(a),
+            indexing.display(b)
+        ));
+    }
+
+    let mut highlight_graph = Graph::new(graph.vertices);
+    highlight_graph.edges = present;
+    embedding.set_edge_highlighting_and_add_to_history(highlight_graph);
+    embedding.set_vertex_highlighting(&sequence);
+    true
+}
+
+/// Highlights the edges of every graph in `highlighting_string` that `graph` has, warning
+/// about the ones it doesn't have. Returns the graph6 strings that were highlighted.
+fn parse_and_add_highlighting(
+    highlighting_string: &mut String,
+    graph: &Graph,
+    embedding: &mut GraphInterface,
+    accumulate: bool,
+    indexing: Indexing,
+    messages: &mut Vec<String>,
+) -> Vec<String> {
+    let mut highlighted = Vec::new();
+    let tokens = highlighting_string.split_whitespace();
+    for (g6_string, result) in tokens.zip(parse_graph6_multi(highlighting_string)) {
+        let highlight = match result {
+            Ok(highlight) => highlight,
+            Err(error) => {
+                messages.push(format!("{}: {}", g6_string, error));
+                continue;
+            }
+        };
+
+        messages.extend(subgraph_warnings(
+            &highlight.subgraph_mismatch(graph),
+            indexing,
+        ));
+        if accumulate {
+            embedding.add_edge_highlighting_and_add_to_history(highlight);
+        } else {
+            embedding.set_edge_highlighting_and_add_to_history(highlight);
+        }
+        highlighted.push(g6_string.to_string());
     }
 
     *highlighting_string = "".to_string();
+    highlighted
+}
+
+/// The graph in full detail, with the collapsed groups expanded unless the collapsed view is
+/// exported, and with its labels placed like on screen.
+pub fn export_drawable(embedding: &mut GraphInterface, data: &UIData) -> DrawableGraph {
+    let compose = |embedding: &GraphInterface| DrawableGraph::compose(embedding, &data.draw_config);
+
+    let mut drawable = if data.export_collapsed_view {
+        compose(embedding)
+    } else {
+        embedding.with_groups_expanded(compose)
+    };
+    if data.draw_config.vertex_config.avoid_label_overlap {
+        let config = &data.draw_config;
+        LabelPlacement::new().apply(
+            &mut drawable,
+            &config.vertex_config,
+            config.outside_label_color(),
+        );
+    }
+
+    drawable
+}
+
+/// Exports the graph in full detail, the screen may show it in reduced detail.
+pub fn export_svg<P: AsRef<Path>>(
+    embedding: &mut GraphInterface,
+    annotations: &AnnotationLayer,
+    data: &UIData,
+    path: P,
+) -> Result<PathBuf, SVGWriterError> {
+    let drawable = export_drawable(embedding, data);
+    draw_graph_to_file(
+        &drawable,
+        annotations,
+        data.canvas_size(),
+        &data.export_options(),
+        path,
+    )
+}
+
+/// Writes a text export, see [`resolve_export_path`] for where it ends up.
+fn write_text_export(
+    path: &str,
+    contents: &str,
+    create_directories: bool,
+) -> Result<PathBuf, String> {
+    let path = resolve_export_path(Path::new(path), "txt", create_directories)
+        .map_err(|error| error.to_string())?;
+    std::fs::write(&path, contents).map_err(|error| error.to_string())?;
+
+    Ok(path)
+}
+
+/// Tells where an export was written, or why it wasn't, in the message area.
+pub fn report_export<E: Display>(
+    messages: &mut Vec<String>,
+    action_log: &mut Vec<String>,
+    description: &str,
+    result: Result<PathBuf, E>,
+) {
+    match result {
+        Ok(path) => {
+            let message = format!("Exported {} to {}", description, path.display());
+            messages.push(message.clone());
+            action_log.push(message);
+        }
+        Err(error) => messages.push(format!("Could not export {}: {}", description, error)),
+    }
+}
+
+/// Warnings for a highlight graph that isn't a subgraph of the shown graph, of which only the
+/// common edges get highlighted.
+pub fn subgraph_warnings(mismatch: &SubgraphMismatch, indexing: Indexing) -> Vec<String> {
+    let mut warnings = Vec::new();
+
+    if let Some((highlight_vertices, vertices)) = mismatch.vertex_counts {
+        warnings.push(format!(
+            "The highlight graph has {} vertices, the graph has {}",
+            highlight_vertices, vertices
+        ));
+    }
+
+    let missing = &mismatch.missing_edges;
+    if !missing.is_empty() {
+        let mut listed: Vec<String> = missing
+            .iter()
+            .take(LISTED_MISSING_EDGES)
+            .map(|&(a, b)| format!("{} - {}", indexing.display(a), indexing.display(b)))
+            .collect();
+        if missing.len() > LISTED_MISSING_EDGES {
+            listed.push("...".to_string());
+        }
+        warnings.push(format!(
+            "{} of the highlighted edges are not in the graph: {}",
+            missing.len(),
+            listed.join(", ")
+        ));
+    }
+
+    warnings
+}
+
+/// Replaces the graph, keeping the vertex positions when `keep_embedding` is set. Duplicate
+/// edges are removed and reported in `messages`.
+pub fn load_graph(
+    embedding: &mut GraphInterface,
+    current: &mut Graph,
+    keep_embedding: bool,
+    mut graph: Graph,
+    messages: &mut Vec<String>,
+) {
+    let removed = graph.dedup_edges();
+    if removed > 0 {
+        messages.push(format!("Removed {} duplicate edges", removed));
+    }
+    if graph.vertices == 0 {
+        messages.push(EMPTY_GRAPH_MESSAGE.to_string());
+    }
+
+    if keep_embedding {
+        embedding.update_edges(&graph);
+    } else {
+        *embedding = GraphInterface::with_viewport(&graph, embedding.viewport);
+    }
+    *current = graph;
+}
+
+fn set_clipboard(text: &str) {
+    let InternalGlContext { quad_context, .. } = unsafe { get_internal_gl() };
+    quad_context.clipboard_set(text);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subgraph_warnings_list_a_few_missing_edges() {
+        let mismatch = SubgraphMismatch {
+            vertex_counts: Some((7, 6)),
+            missing_edges: (0..7).map(|vertex| (vertex, vertex + 1)).collect(),
+        };
+
+        assert_eq!(
+            subgraph_warnings(&mismatch, Indexing::One),
+            vec![
+                "The highlight graph has 7 vertices, the graph has 6",
+                "7 of the highlighted edges are not in the graph: \
+                 1 - 2, 2 - 3, 3 - 4, 4 - 5, 5 - 6, ...",
+            ]
+        );
+
+        let mismatch = SubgraphMismatch {
+            vertex_counts: None,
+            missing_edges: vec![(0, 2)],
+        };
+        assert_eq!(
+            subgraph_warnings(&mismatch, Indexing::Zero),
+            vec!["1 of the highlighted edges are not in the graph: 0 - 2"]
+        );
+        assert!(subgraph_warnings(
+            &SubgraphMismatch {
+                vertex_counts: None,
+                missing_edges: Vec::new(),
+            },
+            Indexing::Zero
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn test_failed_highlights_report_nothing_to_log() {
+        let graph = Graph::cycle(4);
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+        let mut messages = Vec::new();
+
+        let mut g6_strings = "not-g6 Cr".to_string();
+        let highlighted = parse_and_add_highlighting(
+            &mut g6_strings,
+            &graph,
+            &mut embedding,
+            false,
+            Indexing::Zero,
+            &mut messages,
+        );
+        assert_eq!(highlighted, vec!["Cr"]);
+        assert!(messages[0].starts_with("not-g6: "));
+
+        let mut g6_strings = "not-g6".to_string();
+        let highlighted = parse_and_add_highlighting(
+            &mut g6_strings,
+            &graph,
+            &mut embedding,
+            false,
+            Indexing::Zero,
+            &mut messages,
+        );
+        assert!(highlighted.is_empty());
+
+        let sequence = |sequence: &str, embedding: &mut GraphInterface| {
+            highlight_vertex_sequence(sequence, Indexing::Zero, &graph, embedding, &mut Vec::new())
+        };
+        assert!(sequence("0 1 2", &mut embedding));
+        assert!(!sequence("0 x", &mut embedding));
+    }
+
+    #[test]
+    fn test_exports_keep_labels_apart() {
+        let graph = Graph::new(2);
+        let mut embedding = GraphInterface::with_viewport(&graph, vec2(400.0, 400.0));
+        embedding.set_positions(&[vec2(100.0, 100.0), vec2(104.0, 100.0)]);
+        let mut data = UIData::new();
+        data.draw_config.vertex_config.draw_index = true;
+        data.draw_config.vertex_config.label_placement = VertexLabelPlacement::Outside;
+        let label_position = |drawable: &DrawableGraph, vertex: usize| {
+            drawable.vertices[vertex].label.as_ref().unwrap().position
+        };
+
+        data.draw_config.vertex_config.avoid_label_overlap = false;
+        let overlapping = export_drawable(&mut embedding, &data);
+        data.draw_config.vertex_config.avoid_label_overlap = true;
+        let placed = export_drawable(&mut embedding, &data);
+
+        assert!(
+            label_position(&overlapping, 0) != label_position(&placed, 0)
+                || label_position(&overlapping, 1) != label_position(&placed, 1)
+        );
+    }
+
+    #[test]
+    fn test_hidden_panel_frees_the_canvas() {
+        let mut data = UIData::new();
+        assert!(!data.panel_hidden);
+        assert_eq!(canvas_width(1000.0, data.panel_hidden), 1000.0 - UI_WIDTH);
+
+        data.toggle_panel();
+        assert!(data.panel_hidden);
+        assert_eq!(canvas_width(1000.0, data.panel_hidden), 1000.0);
+
+        data.toggle_panel();
+        assert_eq!(canvas_width(1000.0, data.panel_hidden), 1000.0 - UI_WIDTH);
+    }
 }
@@ -0,0 +1,110 @@
+use macroquad::prelude::Vec2;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ViewportChange {
+    pub old: Vec2,
+    pub new: Vec2,
+}
+
+/// Detects changes of the main drawing area between frames.
+pub struct ViewportTracker {
+    current: Vec2,
+}
+
+impl ViewportTracker {
+    pub fn new(viewport: Vec2) -> Self {
+        Self { current: viewport }
+    }
+
+    /// Returns a change exactly once for every new viewport size.
+    pub fn update(&mut self, viewport: Vec2) -> Option<ViewportChange> {
+        if viewport == self.current {
+            return None;
+        }
+
+        let change = ViewportChange {
+            old: self.current,
+            new: viewport,
+        };
+        self.current = viewport;
+
+        Some(change)
+    }
+}
+
+/// How vertex positions follow a change of the viewport.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ResizeMode {
+    Clamp,
+    Rescale,
+}
+
+impl ResizeMode {
+    pub const ALL: [ResizeMode; 2] = [ResizeMode::Clamp, ResizeMode::Rescale];
+    pub const NAMES: [&'static str; 2] = ["Clamp", "Rescale"];
+
+    pub fn index(&self) -> usize {
+        Self::ALL.iter().position(|mode| mode == self).unwrap()
+    }
+
+    pub fn apply(&self, position: Vec2, change: ViewportChange) -> Vec2 {
+        match self {
+            ResizeMode::Rescale if change.old.x > 0.0 && change.old.y > 0.0 => {
+                (position * change.new / change.old).clamp(Vec2::ZERO, change.new)
+            }
+            _ => position.clamp(Vec2::ZERO, change.new),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use macroquad::prelude::vec2;
+
+    use super::*;
+
+    #[test]
+    fn test_tracker_reports_each_change_once() {
+        let mut tracker = ViewportTracker::new(vec2(800.0, 600.0));
+
+        assert_eq!(tracker.update(vec2(800.0, 600.0)), None);
+        assert_eq!(
+            tracker.update(vec2(400.0, 600.0)),
+            Some(ViewportChange {
+                old: vec2(800.0, 600.0),
+                new: vec2(400.0, 600.0)
+            })
+        );
+        assert_eq!(tracker.update(vec2(400.0, 600.0)), None);
+    }
+
+    #[test]
+    fn test_rescale_is_proportional() {
+        let change = ViewportChange {
+            old: vec2(800.0, 600.0),
+            new: vec2(400.0, 900.0),
+        };
+
+        assert_eq!(
+            ResizeMode::Rescale.apply(vec2(200.0, 300.0), change),
+            vec2(100.0, 450.0)
+        );
+        assert_eq!(
+            ResizeMode::Clamp.apply(vec2(700.0, 300.0), change),
+            vec2(400.0, 300.0)
+        );
+    }
+
+    #[test]
+    fn test_rescale_from_empty_viewport_clamps() {
+        let change = ViewportChange {
+            old: Vec2::ZERO,
+            new: vec2(100.0, 100.0),
+        };
+
+        assert_eq!(
+            ResizeMode::Rescale.apply(vec2(150.0, 50.0), change),
+            vec2(100.0, 50.0)
+        );
+    }
+}